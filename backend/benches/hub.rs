@@ -0,0 +1,164 @@
+//! Hub throughput under many concurrent rooms
+//!
+//! Runs the same mix of broadcasts and connection churn over 1k rooms
+//! against the hub as it is and against the hub behind one global
+//! `RwLock`, the way it used to be shared. Joins and leaves there take the
+//! write lock, stalling every other room's broadcasts while they run.
+//!
+//! ```sh
+//! cargo bench --bench hub
+//! ```
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use mo_de_backend::game::ServerEvent;
+use mo_de_backend::ws::Hub;
+use rand::Rng;
+use tokio::sync::{mpsc, RwLock};
+use uuid::Uuid;
+
+const ROOMS: usize = 1000;
+const PLAYERS_PER_ROOM: usize = 4;
+const OPS_PER_WORKER: usize = 20_000;
+/// One operation in this many is a join and leave rather than a broadcast
+const CHURN_EVERY: usize = 10;
+
+/// How the workers reach the hub
+#[derive(Clone)]
+enum Shared {
+    Sharded(Arc<Hub>),
+    GlobalLock(Arc<RwLock<Hub>>),
+}
+
+impl Shared {
+    async fn broadcast(&self, room_id: &str, event: ServerEvent) {
+        match self {
+            Shared::Sharded(hub) => hub.broadcast(room_id, event),
+            Shared::GlobalLock(hub) => hub.read().await.broadcast(room_id, event),
+        }
+    }
+
+    async fn churn(&self, room_id: &str) {
+        let (tx, _rx) = mpsc::channel(1);
+        let player_id = Uuid::new_v4();
+        match self {
+            Shared::Sharded(hub) => {
+                let conn_id = hub.join(
+                    room_id,
+                    player_id,
+                    None,
+                    Default::default(),
+                    tx,
+                    Default::default(),
+                );
+                hub.leave(room_id, player_id, conn_id);
+            }
+            Shared::GlobalLock(hub) => {
+                let conn_id = hub.write().await.join(
+                    room_id,
+                    player_id,
+                    None,
+                    Default::default(),
+                    tx,
+                    Default::default(),
+                );
+                hub.write().await.leave(room_id, player_id, conn_id);
+            }
+        }
+    }
+}
+
+/// Seat every room and drain the players' queues so they never fill up
+fn populate(hub: &Hub) -> Vec<String> {
+    (0..ROOMS)
+        .map(|i| {
+            let room_id = format!("bench{:04}", i);
+            for _ in 0..PLAYERS_PER_ROOM {
+                let (tx, mut rx) = mpsc::channel(64);
+                hub.join(
+                    &room_id,
+                    Uuid::new_v4(),
+                    None,
+                    Default::default(),
+                    tx,
+                    Default::default(),
+                );
+                tokio::spawn(async move { while rx.recv().await.is_some() {} });
+            }
+            room_id
+        })
+        .collect()
+}
+
+async fn run(shared: Shared, rooms: Arc<Vec<String>>, workers: usize) -> Duration {
+    let started = Instant::now();
+    let tasks: Vec<_> = (0..workers)
+        .map(|_| {
+            let shared = shared.clone();
+            let rooms = rooms.clone();
+            tokio::spawn(async move {
+                for op in 0..OPS_PER_WORKER {
+                    let room_id = &rooms[rand::thread_rng().gen_range(0..rooms.len())];
+                    if op % CHURN_EVERY == 0 {
+                        shared.churn(room_id).await;
+                    } else {
+                        let event = ServerEvent::PlayerConnected {
+                            player_id: Uuid::new_v4(),
+                            reconnected: false,
+                        };
+                        shared.broadcast(room_id, event).await;
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.expect("bench worker panicked");
+    }
+    started.elapsed()
+}
+
+fn main() {
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .max(2);
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(workers)
+        .enable_all()
+        .build()
+        .expect("failed to build runtime");
+
+    runtime.block_on(async {
+        let total = (workers * OPS_PER_WORKER) as f64;
+        println!(
+            "{} rooms, {} workers, {} ops each, 1 in {} a join and leave",
+            ROOMS, workers, OPS_PER_WORKER, CHURN_EVERY
+        );
+
+        let hub = Hub::new();
+        let rooms = Arc::new(populate(&hub));
+        let elapsed = run(
+            Shared::GlobalLock(Arc::new(RwLock::new(hub))),
+            rooms,
+            workers,
+        )
+        .await;
+        println!(
+            "  global lock  {:>8.1} ms  {:>10.0} ops/s",
+            elapsed.as_secs_f64() * 1000.0,
+            total / elapsed.as_secs_f64()
+        );
+
+        let hub = Hub::new();
+        let rooms = Arc::new(populate(&hub));
+        let elapsed = run(Shared::Sharded(Arc::new(hub)), rooms, workers).await;
+        println!(
+            "  sharded      {:>8.1} ms  {:>10.0} ops/s",
+            elapsed.as_secs_f64() * 1000.0,
+            total / elapsed.as_secs_f64()
+        );
+    });
+}
@@ -0,0 +1,19 @@
+//! Generates the gRPC service from `proto/` when the `grpc` feature is on
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto");
+        // A protoc on the PATH is not needed unless PROTOC names one
+        if std::env::var_os("PROTOC").is_none() {
+            std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+        }
+        tonic_build::configure()
+            .build_client(false)
+            .compile_protos(&["proto/mo_de.proto"], &["proto"])?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+-- Self-play calibration runs
+CREATE TABLE IF NOT EXISTS bot_calibration_runs (
+    id UUID PRIMARY KEY,
+    seed BIGINT NOT NULL,
+    games_per_candidate INT NOT NULL,
+    max_turns INT NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    error TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+-- Win rate of each parameter set under each personality
+CREATE TABLE IF NOT EXISTS bot_calibration_results (
+    run_id UUID REFERENCES bot_calibration_runs(id) ON DELETE CASCADE,
+    personality VARCHAR(20) NOT NULL,
+    params JSONB NOT NULL,
+    games INT NOT NULL,
+    wins INT NOT NULL,
+    win_rate DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS bot_calibration_results_run_idx
+    ON bot_calibration_results (run_id);
+
+-- Parameter sets currently backing each difficulty
+CREATE TABLE IF NOT EXISTS bot_difficulty_params (
+    difficulty VARCHAR(20) PRIMARY KEY,
+    params JSONB NOT NULL,
+    win_rate DOUBLE PRECISION NOT NULL,
+    run_id UUID REFERENCES bot_calibration_runs(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
@@ -0,0 +1,10 @@
+-- Chat of finished games
+CREATE TABLE IF NOT EXISTS chat_messages (
+    room_id VARCHAR(16) NOT NULL,
+    id BIGINT NOT NULL,
+    player_id UUID NOT NULL,
+    player_name VARCHAR(50) NOT NULL,
+    message TEXT NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (room_id, id)
+);
@@ -0,0 +1,5 @@
+-- Password login; accounts created another way have no password
+ALTER TABLE users ADD COLUMN IF NOT EXISTS password_hash TEXT;
+
+-- Usernames are unique whatever their case
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_lower ON users (LOWER(username));
@@ -0,0 +1,11 @@
+-- Accounts at OAuth providers that sign in as a user
+CREATE TABLE IF NOT EXISTS user_identities (
+    provider TEXT NOT NULL,
+    -- The provider's stable id for the account
+    subject TEXT NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE INDEX IF NOT EXISTS user_identities_user ON user_identities (user_id);
@@ -0,0 +1,9 @@
+-- How a registered user shows up in rooms; every column is optional
+CREATE TABLE IF NOT EXISTS profiles (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    display_name VARCHAR(24),
+    avatar TEXT,
+    color TEXT,
+    locale TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
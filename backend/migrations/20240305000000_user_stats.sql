@@ -0,0 +1,13 @@
+-- Lifetime totals of registered users over finished games
+CREATE TABLE IF NOT EXISTS user_stats (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    games_played INT NOT NULL DEFAULT 0,
+    wins INT NOT NULL DEFAULT 0,
+    rent_collected BIGINT NOT NULL DEFAULT 0,
+    rent_paid BIGINT NOT NULL DEFAULT 0,
+    properties_bought INT NOT NULL DEFAULT 0,
+    bankruptcies INT NOT NULL DEFAULT 0,
+    -- Summed over games, for the average length
+    total_game_secs BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,23 @@
+-- Friend requests; a friendship is a request that was accepted
+CREATE TABLE IF NOT EXISTS friendships (
+    requester_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    addressee_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    accepted_at TIMESTAMPTZ,
+    PRIMARY KEY (requester_id, addressee_id),
+    CHECK (requester_id <> addressee_id)
+);
+
+CREATE INDEX IF NOT EXISTS friendships_addressee ON friendships (addressee_id);
+
+-- Invitations to a room, until accepted, declined or expired
+CREATE TABLE IF NOT EXISTS room_invites (
+    id UUID PRIMARY KEY,
+    room_id VARCHAR(16) NOT NULL,
+    from_user UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    to_user UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS room_invites_to_user ON room_invites (to_user);
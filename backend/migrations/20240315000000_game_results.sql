@@ -0,0 +1,14 @@
+-- Finished games are kept here once their room leaves Redis. Seats of
+-- guests and bots have no user, so seats are keyed by player id.
+ALTER TABLE games ADD COLUMN IF NOT EXISTS room_id VARCHAR(16);
+
+ALTER TABLE game_players ADD COLUMN IF NOT EXISTS player_id UUID;
+ALTER TABLE game_players ADD COLUMN IF NOT EXISTS net_worth INT;
+UPDATE game_players SET player_id = user_id WHERE player_id IS NULL;
+
+ALTER TABLE game_players DROP CONSTRAINT IF EXISTS game_players_pkey;
+ALTER TABLE game_players ALTER COLUMN player_id SET NOT NULL;
+ALTER TABLE game_players ALTER COLUMN user_id DROP NOT NULL;
+ALTER TABLE game_players ADD PRIMARY KEY (game_id, player_id);
+
+CREATE INDEX IF NOT EXISTS game_players_user ON game_players (user_id);
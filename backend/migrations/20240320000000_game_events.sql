@@ -0,0 +1,15 @@
+-- Append-only journal of everything the engine broadcast in a room. Room
+-- ids are reused once a room is gone, so read a game back by room and time.
+CREATE TABLE IF NOT EXISTS game_events (
+    id BIGSERIAL PRIMARY KEY,
+    room_id VARCHAR(16) NOT NULL,
+    seq BIGINT NOT NULL,
+    -- Player whose action caused the event, if any
+    player_id UUID,
+    kind TEXT NOT NULL,
+    event JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS game_events_room ON game_events (room_id, created_at, seq);
+CREATE INDEX IF NOT EXISTS game_events_kind ON game_events (kind);
@@ -0,0 +1,8 @@
+-- Latest saved state of idle games, restored when their Redis key is gone
+CREATE TABLE IF NOT EXISTS game_snapshots (
+    room_id VARCHAR(16) PRIMARY KEY,
+    state JSONB NOT NULL,
+    -- Journal position of the state, to skip saving it twice
+    journal_seq BIGINT NOT NULL,
+    saved_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
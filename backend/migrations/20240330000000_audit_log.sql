@@ -0,0 +1,16 @@
+-- Moderation and operator actions: who did what to whom in which room.
+-- Admins act through a shared token, so they have no actor id.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    action TEXT NOT NULL,
+    room_id VARCHAR(16) NOT NULL,
+    -- Acting player, or NULL for an administrator
+    actor_id UUID,
+    actor_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    target_id UUID,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_room ON audit_log (room_id, id);
+CREATE INDEX IF NOT EXISTS audit_log_action ON audit_log (action, id);
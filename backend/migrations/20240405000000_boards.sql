@@ -0,0 +1,56 @@
+-- Versioned board definitions. Games keep the version they were created
+-- on, so add a new version rather than editing one in use. Versions must
+-- keep the kind of tile on each square; the server skips those that don't.
+CREATE TABLE IF NOT EXISTS boards (
+    id VARCHAR(32) NOT NULL,
+    version INT NOT NULL,
+    name TEXT NOT NULL,
+    tiles JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (id, version)
+);
+
+-- The built-in board as version 1
+INSERT INTO boards (id, version, name, tiles) VALUES ('world', 1, 'World Cities', '[
+  {"index": 0, "name": "START", "tile_type": "Go", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 1, "name": "Salvador", "tile_type": "Property", "group": "Brown", "price": 60, "rent_base": 2, "rent_schedule": [10, 30, 90, 160, 250], "mortgage_value": 30, "build_cost": 50, "country_code": "BR"},
+  {"index": 2, "name": "Treasure", "tile_type": "CommunityChest", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 3, "name": "Rio", "tile_type": "Property", "group": "Brown", "price": 60, "rent_base": 4, "rent_schedule": [20, 60, 180, 320, 450], "mortgage_value": 30, "build_cost": 50, "country_code": "BR"},
+  {"index": 4, "name": "Income Tax 10%", "tile_type": "Tax", "group": null, "price": 0, "rent_base": 200, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 5, "name": "TLV Airport", "tile_type": "Railroad", "group": "Railroad", "price": 200, "rent_base": 25, "rent_schedule": [25, 50, 100, 200], "mortgage_value": 100, "build_cost": 0, "country_code": "IL"},
+  {"index": 6, "name": "Tel Aviv", "tile_type": "Property", "group": "LightBlue", "price": 100, "rent_base": 6, "rent_schedule": [30, 90, 270, 400, 550], "mortgage_value": 50, "build_cost": 50, "country_code": "IL"},
+  {"index": 7, "name": "Surprise", "tile_type": "Chance", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 8, "name": "Haifa", "tile_type": "Property", "group": "LightBlue", "price": 100, "rent_base": 6, "rent_schedule": [30, 90, 270, 400, 550], "mortgage_value": 50, "build_cost": 50, "country_code": "IL"},
+  {"index": 9, "name": "Jerusalem", "tile_type": "Property", "group": "LightBlue", "price": 120, "rent_base": 8, "rent_schedule": [40, 100, 300, 450, 600], "mortgage_value": 60, "build_cost": 50, "country_code": "IL"},
+  {"index": 10, "name": "In Prison", "tile_type": "Jail", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 11, "name": "Venice", "tile_type": "Property", "group": "Pink", "price": 140, "rent_base": 10, "rent_schedule": [50, 150, 450, 625, 750], "mortgage_value": 70, "build_cost": 100, "country_code": "IT"},
+  {"index": 12, "name": "Electric Company", "tile_type": "Utility", "group": "Utility", "price": 150, "rent_base": 4, "rent_schedule": [4, 10], "mortgage_value": 75, "build_cost": 0, "country_code": null},
+  {"index": 13, "name": "Milan", "tile_type": "Property", "group": "Pink", "price": 140, "rent_base": 10, "rent_schedule": [50, 150, 450, 625, 750], "mortgage_value": 70, "build_cost": 100, "country_code": "IT"},
+  {"index": 14, "name": "Rome", "tile_type": "Property", "group": "Pink", "price": 160, "rent_base": 12, "rent_schedule": [60, 180, 500, 700, 900], "mortgage_value": 80, "build_cost": 100, "country_code": "IT"},
+  {"index": 15, "name": "MUC Airport", "tile_type": "Railroad", "group": "Railroad", "price": 200, "rent_base": 25, "rent_schedule": [25, 50, 100, 200], "mortgage_value": 100, "build_cost": 0, "country_code": "DE"},
+  {"index": 16, "name": "Frankfurt", "tile_type": "Property", "group": "Orange", "price": 180, "rent_base": 14, "rent_schedule": [70, 200, 550, 750, 950], "mortgage_value": 90, "build_cost": 100, "country_code": "DE"},
+  {"index": 17, "name": "Treasure", "tile_type": "CommunityChest", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 18, "name": "Treasure", "tile_type": "Property", "group": "Orange", "price": 180, "rent_base": 14, "rent_schedule": [70, 200, 550, 750, 950], "mortgage_value": 90, "build_cost": 100, "country_code": "DE"},
+  {"index": 19, "name": "Munich", "tile_type": "Property", "group": "Orange", "price": 200, "rent_base": 16, "rent_schedule": [80, 220, 600, 800, 1000], "mortgage_value": 100, "build_cost": 100, "country_code": "DE"},
+  {"index": 20, "name": "Vacation", "tile_type": "FreeParking", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 21, "name": "Berlin", "tile_type": "Property", "group": "Red", "price": 220, "rent_base": 18, "rent_schedule": [90, 250, 700, 875, 1050], "mortgage_value": 110, "build_cost": 150, "country_code": "DE"},
+  {"index": 22, "name": "Surprise", "tile_type": "Chance", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 23, "name": "Manchester", "tile_type": "Property", "group": "Red", "price": 220, "rent_base": 18, "rent_schedule": [90, 250, 700, 875, 1050], "mortgage_value": 110, "build_cost": 150, "country_code": "GB"},
+  {"index": 24, "name": "Liverpool", "tile_type": "Property", "group": "Red", "price": 240, "rent_base": 20, "rent_schedule": [100, 300, 750, 925, 1100], "mortgage_value": 120, "build_cost": 150, "country_code": "GB"},
+  {"index": 25, "name": "JFK Airport", "tile_type": "Railroad", "group": "Railroad", "price": 200, "rent_base": 25, "rent_schedule": [25, 50, 100, 200], "mortgage_value": 100, "build_cost": 0, "country_code": "US"},
+  {"index": 26, "name": "Paris", "tile_type": "Property", "group": "Yellow", "price": 260, "rent_base": 22, "rent_schedule": [110, 330, 800, 975, 1150], "mortgage_value": 130, "build_cost": 150, "country_code": "FR"},
+  {"index": 27, "name": "Toulouse", "tile_type": "Property", "group": "Yellow", "price": 260, "rent_base": 22, "rent_schedule": [110, 330, 800, 975, 1150], "mortgage_value": 130, "build_cost": 150, "country_code": "FR"},
+  {"index": 28, "name": "Water Company", "tile_type": "Utility", "group": "Utility", "price": 150, "rent_base": 4, "rent_schedule": [4, 10], "mortgage_value": 75, "build_cost": 0, "country_code": null},
+  {"index": 29, "name": "Lyon", "tile_type": "Property", "group": "Yellow", "price": 280, "rent_base": 24, "rent_schedule": [120, 360, 850, 1025, 1200], "mortgage_value": 140, "build_cost": 150, "country_code": "FR"},
+  {"index": 30, "name": "Go to prison", "tile_type": "GoToJail", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 31, "name": "CDG Airport", "tile_type": "Property", "group": "Green", "price": 300, "rent_base": 26, "rent_schedule": [130, 390, 900, 1100, 1275], "mortgage_value": 150, "build_cost": 200, "country_code": "FR"},
+  {"index": 32, "name": "Shanghai", "tile_type": "Property", "group": "Green", "price": 300, "rent_base": 26, "rent_schedule": [130, 390, 900, 1100, 1275], "mortgage_value": 150, "build_cost": 200, "country_code": "CN"},
+  {"index": 33, "name": "Treasure", "tile_type": "CommunityChest", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 34, "name": "Beijing", "tile_type": "Property", "group": "Green", "price": 320, "rent_base": 28, "rent_schedule": [150, 450, 1000, 1200, 1400], "mortgage_value": 160, "build_cost": 200, "country_code": "CN"},
+  {"index": 35, "name": "Shenzhen", "tile_type": "Railroad", "group": "Railroad", "price": 200, "rent_base": 25, "rent_schedule": [25, 50, 100, 200], "mortgage_value": 100, "build_cost": 0, "country_code": "CN"},
+  {"index": 36, "name": "Surprise", "tile_type": "Chance", "group": null, "price": 0, "rent_base": 0, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 37, "name": "New York", "tile_type": "Property", "group": "DarkBlue", "price": 350, "rent_base": 35, "rent_schedule": [175, 500, 1100, 1300, 1500], "mortgage_value": 175, "build_cost": 200, "country_code": "US"},
+  {"index": 38, "name": "Luxury Tax", "tile_type": "Tax", "group": null, "price": 0, "rent_base": 100, "rent_schedule": [], "mortgage_value": 0, "build_cost": 0, "country_code": null},
+  {"index": 39, "name": "Tokyo", "tile_type": "Property", "group": "DarkBlue", "price": 400, "rent_base": 50, "rent_schedule": [200, 600, 1400, 1700, 2000], "mortgage_value": 200, "build_cost": 200, "country_code": "JP"}
+]')
+ON CONFLICT (id, version) DO NOTHING;
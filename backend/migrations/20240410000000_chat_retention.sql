@@ -0,0 +1,3 @@
+-- Chat is now written as it is sent, not only when a game ends, and
+-- deleted once past CHAT_RETENTION_DAYS
+CREATE INDEX IF NOT EXISTS chat_messages_sent_at ON chat_messages (sent_at);
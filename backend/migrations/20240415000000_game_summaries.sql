@@ -0,0 +1,3 @@
+-- Post-game summary of each finished game: per-player counters and the
+-- round-by-round timeline, as sent to players when the game ends
+ALTER TABLE games ADD COLUMN IF NOT EXISTS summary JSONB;
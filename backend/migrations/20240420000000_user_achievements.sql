@@ -0,0 +1,9 @@
+-- Achievements of registered users, each stored the first time it is earned
+CREATE TABLE IF NOT EXISTS user_achievements (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    achievement VARCHAR(32) NOT NULL,
+    -- Game it was first earned in
+    game_id UUID REFERENCES games(id) ON DELETE SET NULL,
+    unlocked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, achievement)
+);
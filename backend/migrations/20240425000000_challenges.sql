@@ -0,0 +1,35 @@
+-- Daily and weekly challenges, one row per slot of each period
+CREATE TABLE IF NOT EXISTS challenges (
+    id UUID PRIMARY KEY,
+    period VARCHAR(8) NOT NULL,
+    slot INT NOT NULL,
+    goal VARCHAR(32) NOT NULL,
+    target BIGINT NOT NULL,
+    -- Only games on this board count, when set
+    board_id VARCHAR(64),
+    reward_kind VARCHAR(16) NOT NULL,
+    reward VARCHAR(64) NOT NULL,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (period, starts_at, slot)
+);
+
+CREATE INDEX IF NOT EXISTS idx_challenges_ends_at ON challenges(ends_at);
+
+-- How far each registered user got, and when they claimed the reward
+CREATE TABLE IF NOT EXISTS challenge_progress (
+    challenge_id UUID NOT NULL REFERENCES challenges(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    progress BIGINT NOT NULL DEFAULT 0,
+    claimed_at TIMESTAMPTZ,
+    PRIMARY KEY (challenge_id, user_id)
+);
+
+-- Titles and cosmetics users have earned
+CREATE TABLE IF NOT EXISTS user_rewards (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(16) NOT NULL,
+    reward VARCHAR(64) NOT NULL,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, kind, reward)
+);
@@ -0,0 +1,8 @@
+-- Ranked games, and whether they count towards ratings: a ranked game where
+-- seats shared a device is stored as unrated, with the flags raised
+ALTER TABLE games ADD COLUMN IF NOT EXISTS ranked BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS rated BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS integrity_flags JSONB;
+
+CREATE INDEX IF NOT EXISTS idx_games_flagged ON games(ended_at DESC)
+    WHERE integrity_flags IS NOT NULL;
@@ -0,0 +1,14 @@
+-- Users and IP addresses kept out of rooms, sockets and sign-ins
+CREATE TABLE IF NOT EXISTS bans (
+    id UUID PRIMARY KEY,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    ip VARCHAR(64),
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Permanent when unset
+    expires_at TIMESTAMPTZ,
+    CHECK (user_id IS NOT NULL OR ip IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_bans_user ON bans(user_id) WHERE user_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_bans_ip ON bans(ip) WHERE ip IS NOT NULL;
@@ -0,0 +1,26 @@
+-- Players reported by other players, for moderators to review
+CREATE TABLE IF NOT EXISTS reports (
+    id UUID PRIMARY KEY,
+    room_id VARCHAR(16) NOT NULL,
+    reporter_id UUID NOT NULL,
+    reporter_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    reporter_name VARCHAR(50) NOT NULL,
+    target_id UUID NOT NULL,
+    target_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    target_name VARCHAR(50) NOT NULL,
+    category VARCHAR(32) NOT NULL,
+    comment TEXT,
+    -- The target's latest chat messages in the room, when asked for
+    chat_excerpt JSONB,
+    -- open, resolved or dismissed
+    status VARCHAR(16) NOT NULL DEFAULT 'open',
+    resolution_note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_reports_status ON reports(status, created_at DESC);
+
+-- One open report per reporter and target
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reports_open_pair ON reports(reporter_id, target_id)
+    WHERE status = 'open';
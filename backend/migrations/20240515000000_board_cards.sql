@@ -0,0 +1,37 @@
+-- Surprise (chance) and Treasure (community_chest) decks of each board
+-- version. Like the tiles, a version's cards are fixed once games use it;
+-- revise them in a new board version.
+CREATE TABLE IF NOT EXISTS board_cards (
+    board_id VARCHAR(32) NOT NULL,
+    version INT NOT NULL,
+    deck VARCHAR(16) NOT NULL CHECK (deck IN ('chance', 'community_chest')),
+    position INT NOT NULL,
+    text TEXT NOT NULL,
+    effect JSONB NOT NULL,
+    PRIMARY KEY (board_id, version, deck, position),
+    FOREIGN KEY (board_id, version) REFERENCES boards (id, version)
+);
+
+-- Decks of the built-in board
+INSERT INTO board_cards (board_id, version, deck, position, text, effect) VALUES
+    ('world', 1, 'chance', 0, 'Advance to START', '{"type": "advance_to", "tile": 0}'),
+    ('world', 1, 'chance', 1, 'Take a flight to Tokyo', '{"type": "advance_to", "tile": 39}'),
+    ('world', 1, 'chance', 2, 'Advance to Berlin', '{"type": "advance_to", "tile": 21}'),
+    ('world', 1, 'chance', 3, 'Fly out of JFK Airport', '{"type": "advance_to", "tile": 25}'),
+    ('world', 1, 'chance', 4, 'Go back 3 spaces', '{"type": "move_back", "spaces": 3}'),
+    ('world', 1, 'chance', 5, 'Go directly to prison', '{"type": "go_to_jail"}'),
+    ('world', 1, 'chance', 6, 'Get out of prison free', '{"type": "get_out_of_jail"}'),
+    ('world', 1, 'chance', 7, 'Speeding fine: pay $15', '{"type": "pay", "amount": 15}'),
+    ('world', 1, 'chance', 8, 'Your building loan matures: collect $150', '{"type": "collect", "amount": 150}'),
+    ('world', 1, 'chance', 9, 'You are elected chair of the board: pay each player $50', '{"type": "pay_each", "amount": 50}'),
+    ('world', 1, 'community_chest', 0, 'Advance to START', '{"type": "advance_to", "tile": 0}'),
+    ('world', 1, 'community_chest', 1, 'Bank error in your favor: collect $200', '{"type": "collect", "amount": 200}'),
+    ('world', 1, 'community_chest', 2, 'Doctor''s fee: pay $50', '{"type": "pay", "amount": 50}'),
+    ('world', 1, 'community_chest', 3, 'Get out of prison free', '{"type": "get_out_of_jail"}'),
+    ('world', 1, 'community_chest', 4, 'Go directly to prison', '{"type": "go_to_jail"}'),
+    ('world', 1, 'community_chest', 5, 'It is your birthday: collect $10 from each player', '{"type": "collect_from_each", "amount": 10}'),
+    ('world', 1, 'community_chest', 6, 'Income tax refund: collect $20', '{"type": "collect", "amount": 20}'),
+    ('world', 1, 'community_chest', 7, 'Hospital fees: pay $100', '{"type": "pay", "amount": 100}'),
+    ('world', 1, 'community_chest', 8, 'You inherit $100', '{"type": "collect", "amount": 100}'),
+    ('world', 1, 'community_chest', 9, 'School fees: pay $50', '{"type": "pay", "amount": 50}')
+ON CONFLICT (board_id, version, deck, position) DO NOTHING;
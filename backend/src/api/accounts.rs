@@ -0,0 +1,315 @@
+//! Registration, password login and sessions
+//!
+//! Accounts are optional: rooms can still be played with just a display
+//! name. Hashing runs on the blocking pool since argon2 is slow on purpose.
+//!
+//! Signing in starts a session and hands out a short-lived access token and
+//! a refresh token. Every refresh replaces the refresh token, and logging out
+//! revokes the session along with access tokens already issued for it and
+//! the player tokens of seats taken while signed in.
+
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, SaltString};
+use argon2::{Argon2, PasswordHasher, PasswordVerifier};
+use axum::{extract::State, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{
+    auth::{self, AuthUser},
+    bans,
+    openapi::ErrorResponse,
+    session_store, AppState, ClientIp,
+};
+use crate::db::{
+    sessions,
+    users::{self, User},
+};
+use crate::error::{AppError, AppResult};
+
+const MIN_USERNAME_LEN: usize = 3;
+const MAX_USERNAME_LEN: usize = 24;
+const MIN_PASSWORD_LEN: usize = 8;
+const MAX_PASSWORD_LEN: usize = 128;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CredentialsRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionResponse {
+    pub user: User,
+    /// Sent as `Authorization: Bearer` to endpoints that need an account
+    pub token: String,
+    /// Seconds until `token` expires
+    pub expires_in: i64,
+    /// Trades for a new token pair at `/api/auth/refresh`; single use
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct LogoutRequest {
+    /// End every session of the account, not just this one
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// Create an account and sign in
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    tag = "auth",
+    request_body = CredentialsRequest,
+    responses(
+        (status = 200, description = "Account created", body = SessionResponse),
+        (status = 400, description = "Invalid or taken username, or weak password", body = ErrorResponse),
+    )
+)]
+pub async fn register(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<CredentialsRequest>,
+) -> AppResult<Json<SessionResponse>> {
+    let username = validate_username(&req.username)?;
+    validate_password(&req.password)?;
+    bans::ensure_not_banned(&state, None, ip.as_deref()).await?;
+
+    let hash = tokio::task::spawn_blocking(move || hash_password(&req.password))
+        .await
+        .map_err(|e| AppError::Internal(e.into()))??;
+
+    let user = users::create_user(&state.db, &username, &hash).await?;
+    tracing::info!("Registered user {}", user.username);
+
+    start_session(&state, user, ip.as_deref()).await.map(Json)
+}
+
+/// Sign in with a username and password
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    request_body = CredentialsRequest,
+    responses(
+        (status = 200, description = "Signed in", body = SessionResponse),
+        (status = 401, description = "Wrong username or password", body = ErrorResponse),
+        (status = 403, description = "The user or address is banned", body = ErrorResponse),
+    )
+)]
+pub async fn login(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<CredentialsRequest>,
+) -> AppResult<Json<SessionResponse>> {
+    let Some((user, Some(hash))) = users::find_by_username(&state.db, req.username.trim()).await?
+    else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let matches = tokio::task::spawn_blocking(move || verify_password(&req.password, &hash))
+        .await
+        .map_err(|e| AppError::Internal(e.into()))?;
+    if !matches {
+        return Err(AppError::Unauthorized);
+    }
+
+    start_session(&state, user, ip.as_deref()).await.map(Json)
+}
+
+/// Trade a refresh token for a new access and refresh token
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New token pair; the old refresh token is spent", body = SessionResponse),
+        (status = 401, description = "Unknown, used, expired or revoked refresh token", body = ErrorResponse),
+    )
+)]
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(req): Json<RefreshRequest>,
+) -> AppResult<Json<SessionResponse>> {
+    let (refresh_token, new_hash) = auth::new_refresh_token();
+    let old_hash = auth::hash_refresh_token(&req.refresh_token);
+
+    let (session_id, user_id) = sessions::rotate(&state.db, &old_hash, &new_hash)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+    let user = users::get_user(&state.db, user_id)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+    session_store::open(
+        &state.redis,
+        session_id,
+        Some(user.id),
+        auth::REFRESH_TOKEN_TTL_SECS,
+    )
+    .await?;
+
+    let token = auth::issue_user_token(&state.config, user.id, &user.username, session_id)?;
+    Ok(Json(SessionResponse {
+        user,
+        token,
+        expires_in: auth::USER_TOKEN_TTL_SECS,
+        refresh_token,
+    }))
+}
+
+/// End this session, or every session of the account
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "auth",
+    security(("user_token" = [])),
+    request_body(content = Option<LogoutRequest>),
+    responses(
+        (status = 204, description = "Signed out; the refresh, access and player tokens of the session no longer work"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+    )
+)]
+pub async fn logout(
+    user: AuthUser,
+    State(state): State<AppState>,
+    body: Option<Json<LogoutRequest>>,
+) -> AppResult<StatusCode> {
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+
+    let revoked = if req.all {
+        sessions::revoke_all(&state.db, user.id).await?
+    } else {
+        sessions::revoke(&state.db, user.id, user.session_id).await?;
+        vec![user.session_id]
+    };
+
+    for session_id in revoked {
+        session_store::end(&state.redis, session_id).await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The signed-in user
+#[utoipa::path(
+    get,
+    path = "/api/auth/me",
+    tag = "auth",
+    security(("user_token" = [])),
+    responses(
+        (status = 200, description = "The account behind the token", body = User),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+    )
+)]
+pub async fn me(user: AuthUser, State(state): State<AppState>) -> AppResult<Json<User>> {
+    let user = users::get_user(&state.db, user.id)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+
+    Ok(Json(user))
+}
+
+/// Open a session for a user who just proved who they are, unless they or
+/// their address are banned
+pub(super) async fn start_session(
+    state: &AppState,
+    user: User,
+    ip: Option<&str>,
+) -> AppResult<SessionResponse> {
+    bans::ensure_not_banned(state, Some(user.id), ip).await?;
+    let (refresh_token, refresh_hash) = auth::new_refresh_token();
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(auth::REFRESH_TOKEN_TTL_SECS);
+    let session_id =
+        sessions::create_session(&state.db, user.id, &refresh_hash, expires_at).await?;
+    session_store::open(
+        &state.redis,
+        session_id,
+        Some(user.id),
+        auth::REFRESH_TOKEN_TTL_SECS,
+    )
+    .await?;
+
+    let token = auth::issue_user_token(&state.config, user.id, &user.username, session_id)?;
+    Ok(SessionResponse {
+        user,
+        token,
+        expires_in: auth::USER_TOKEN_TTL_SECS,
+        refresh_token,
+    })
+}
+
+fn validate_username(username: &str) -> AppResult<String> {
+    let username = username.trim();
+    let len = username.chars().count();
+
+    if !(MIN_USERNAME_LEN..=MAX_USERNAME_LEN).contains(&len) {
+        return Err(AppError::BadRequest(format!(
+            "Username must be {} to {} characters",
+            MIN_USERNAME_LEN, MAX_USERNAME_LEN
+        )));
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(AppError::BadRequest(
+            "Username may only use letters, digits, '_' and '-'".into(),
+        ));
+    }
+
+    Ok(username.to_string())
+}
+
+/// A valid username close to a display name, leaving room for a suffix
+pub(super) fn username_from(display_name: &str) -> String {
+    let mut username: String = display_name
+        .trim()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '-' => Some(c),
+            ' ' | '.' => Some('_'),
+            _ => None,
+        })
+        .take(MAX_USERNAME_LEN - 5)
+        .collect();
+
+    if username.len() < MIN_USERNAME_LEN {
+        username = "player".into();
+    }
+    username
+}
+
+fn validate_password(password: &str) -> AppResult<()> {
+    let len = password.chars().count();
+    if !(MIN_PASSWORD_LEN..=MAX_PASSWORD_LEN).contains(&len) {
+        return Err(AppError::BadRequest(format!(
+            "Password must be {} to {} characters",
+            MIN_PASSWORD_LEN, MAX_PASSWORD_LEN
+        )));
+    }
+    Ok(())
+}
+
+fn hash_password(password: &str) -> AppResult<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to hash password: {}", e)))
+}
+
+fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
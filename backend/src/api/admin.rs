@@ -0,0 +1,348 @@
+//! Operator endpoints for inspecting and closing rooms and for reloading
+//! tunables
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{auth::AdminAuth, AppState};
+use crate::config::Tunables;
+use crate::db::audit::{self, AuditAction, AuditEntry, AuditFilter, AuditRecord};
+use crate::db::games::{self, FlaggedGame};
+use crate::error::{AppError, AppResult};
+use crate::game::commands::AdminCommand;
+use crate::game::integrity::IntegrityFlag;
+use crate::game::maintenance::{self, MaintenanceStats};
+use crate::game::{GameEngine, GameState, LOG_LEN};
+
+#[derive(Debug, Serialize)]
+pub struct RoomSummary {
+    pub room_id: String,
+    pub phase: String,
+    pub players: usize,
+    pub bots: usize,
+    /// Players connected to this instance
+    pub connected: usize,
+    /// Spectators connected to this instance
+    pub spectators: usize,
+    pub ranked: bool,
+    /// Seats of this ranked room share a device
+    pub flagged: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoomDetail {
+    pub event_seq: u64,
+    /// Unredacted
+    pub state: GameState,
+    /// Connections to this instance
+    pub connections: Vec<ConnectionInfo>,
+    pub spectators: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConnectionInfo {
+    pub player_id: Uuid,
+    pub connected_secs: u64,
+    pub latency_ms: Option<u64>,
+    pub last_acked_seq: u64,
+    pub queue_depth: usize,
+    pub dropped_events: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShadowMuteRequest {
+    pub player_id: Uuid,
+    /// `false` lifts the shadow mute
+    #[serde(default = "shadow_mute_default")]
+    pub muted: bool,
+}
+
+fn shadow_mute_default() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloseRoomRequest {
+    /// Shown to the room's players
+    pub reason: Option<String>,
+}
+
+const DEFAULT_CLOSE_REASON: &str = "Closed by an administrator";
+
+const DEFAULT_AUDIT_PAGE: usize = 50;
+const MAX_AUDIT_PAGE: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub room_id: Option<String>,
+    /// `kick`, `mute`, `unmute`, `shadow_mute`, `shadow_unmute`, `command`,
+    /// `end_room` or `delete_room`
+    pub action: Option<String>,
+    /// Entries where this player acted or was acted on
+    pub player_id: Option<Uuid>,
+    /// Only entries with a lower id
+    pub before: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+/// Every room held in Redis
+pub async fn list_rooms(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<RoomSummary>>> {
+    let games = GameEngine::list_games(&state.redis).await?;
+    let hub = &state.hub;
+
+    let mut rooms: Vec<RoomSummary> = games
+        .iter()
+        .map(|game| RoomSummary {
+            room_id: game.id.clone(),
+            phase: format!("{:?}", game.phase),
+            players: game.players.len(),
+            bots: game.players.iter().filter(|p| p.is_bot).count(),
+            connected: hub.connections(&game.id).len(),
+            spectators: hub.spectator_count(&game.id),
+            ranked: game.config.ranked,
+            flagged: game.integrity.is_flagged(),
+        })
+        .collect();
+    rooms.sort_by(|a, b| a.room_id.cmp(&b.room_id));
+
+    Ok(Json(rooms))
+}
+
+/// A room's full state and its connections
+pub async fn get_room(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+) -> AppResult<Json<RoomDetail>> {
+    let game = GameEngine::get_game(&state.redis, &room_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+    let hub = &state.hub;
+
+    let connections = hub
+        .connections(&room_id)
+        .into_iter()
+        .map(|c| ConnectionInfo {
+            player_id: c.player_id,
+            connected_secs: c.connected_for.as_secs(),
+            latency_ms: c.latency_ms,
+            last_acked_seq: c.last_acked_seq,
+            queue_depth: c.queue_depth,
+            dropped_events: c.dropped_events,
+        })
+        .collect();
+
+    Ok(Json(RoomDetail {
+        event_seq: hub.current_seq(&room_id),
+        state: game,
+        connections,
+        spectators: hub.spectator_count(&room_id),
+    }))
+}
+
+/// End a room's game, keeping the room so players can see the final board
+pub async fn end_room(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    req: Option<Json<CloseRoomRequest>>,
+) -> AppResult<Json<serde_json::Value>> {
+    let reason = req
+        .and_then(|Json(req)| req.reason)
+        .unwrap_or_else(|| DEFAULT_CLOSE_REASON.into());
+    GameEngine::close_room(&state.redis, &state.hub, &room_id, reason.clone(), false).await?;
+    audit_close(AuditAction::EndRoom, room_id.clone(), reason);
+
+    tracing::info!("Admin ended room {}", room_id);
+    Ok(Json(serde_json::json!({ "status": "ended" })))
+}
+
+/// Delete a room and disconnect everyone in it
+pub async fn delete_room(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    req: Option<Json<CloseRoomRequest>>,
+) -> AppResult<Json<serde_json::Value>> {
+    let reason = req
+        .and_then(|Json(req)| req.reason)
+        .unwrap_or_else(|| DEFAULT_CLOSE_REASON.into());
+    GameEngine::close_room(&state.redis, &state.hub, &room_id, reason.clone(), true).await?;
+    audit_close(AuditAction::DeleteRoom, room_id.clone(), reason);
+
+    tracing::info!("Admin deleted room {}", room_id);
+    Ok(Json(serde_json::json!({ "status": "deleted" })))
+}
+
+/// Change a running game outside its rules: adjust a balance, end the turn,
+/// cancel the auction or release a player from jail
+pub async fn run_command(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Json(command): Json<AdminCommand>,
+) -> AppResult<StatusCode> {
+    GameEngine::admin_command(&state.redis, &state.hub, &room_id, command.clone()).await?;
+
+    tracing::info!("Admin ran {:?} in room {}", command, room_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A room's full state with its log, to load into another server with
+/// `import_room`
+pub async fn export_room(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+) -> AppResult<Json<GameState>> {
+    let mut game = GameEngine::get_game(&state.redis, &room_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+    game.logs = GameEngine::get_logs(&state.redis, &room_id, LOG_LEN).await?;
+
+    tracing::info!("Admin exported room {}", room_id);
+    Ok(Json(game))
+}
+
+/// Overwrite a room's state with an exported one, or create the room from
+/// it, and send it to everyone connected
+pub async fn import_room(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Json(game): Json<GameState>,
+) -> AppResult<Json<GameState>> {
+    let game = GameEngine::import_game(&state.redis, &state.hub, &room_id, game).await?;
+
+    tracing::warn!("Admin imported a state into room {}", room_id);
+    Ok(Json(game))
+}
+
+/// Shadow-mute a player in a room, or lift their shadow mute
+pub async fn shadow_mute(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Json(req): Json<ShadowMuteRequest>,
+) -> AppResult<StatusCode> {
+    GameEngine::shadow_mute(&state.redis, &state.hub, &room_id, req.player_id, req.muted).await?;
+
+    tracing::info!(
+        "Admin set shadow mute of {} in room {} to {}",
+        req.player_id,
+        room_id,
+        req.muted
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn audit_close(action: AuditAction, room_id: String, reason: String) {
+    audit::record(AuditEntry {
+        action,
+        room_id,
+        actor_id: None,
+        actor_user_id: None,
+        target_id: None,
+        details: serde_json::json!({ "reason": reason }),
+    });
+}
+
+/// Host and administrator actions, newest first
+pub async fn list_audit(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> AppResult<Json<Vec<AuditRecord>>> {
+    let filter = AuditFilter {
+        room_id: query.room_id.as_deref(),
+        action: query.action.as_deref(),
+        player_id: query.player_id,
+        before: query.before,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_PAGE)
+        .clamp(1, MAX_AUDIT_PAGE);
+
+    Ok(Json(audit::list(&state.db, &filter, limit).await?))
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlaggedRoom {
+    pub room_id: String,
+    pub phase: String,
+    pub flags: Vec<IntegrityFlag>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    /// Rooms held in Redis, by id
+    pub rooms: Vec<FlaggedRoom>,
+    /// Stored games, newest first; these were not rated
+    pub games: Vec<FlaggedGame>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IntegrityQuery {
+    /// Stored games to list
+    pub limit: Option<usize>,
+}
+
+/// Ranked rooms and games where seats shared an IP address or fingerprint
+pub async fn list_integrity_flags(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<IntegrityQuery>,
+) -> AppResult<Json<IntegrityReport>> {
+    let mut rooms: Vec<FlaggedRoom> = GameEngine::list_games(&state.redis)
+        .await?
+        .into_iter()
+        .filter(|game| game.integrity.is_flagged())
+        .map(|game| FlaggedRoom {
+            room_id: game.id,
+            phase: format!("{:?}", game.phase),
+            flags: game.integrity.flags,
+        })
+        .collect();
+    rooms.sort_by(|a, b| a.room_id.cmp(&b.room_id));
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_PAGE)
+        .clamp(1, MAX_AUDIT_PAGE);
+    let games = games::flagged(&state.db, limit).await?;
+
+    Ok(Json(IntegrityReport { rooms, games }))
+}
+
+/// What the maintenance job has cleared out on this instance
+pub async fn get_maintenance(_admin: AdminAuth) -> Json<MaintenanceStats> {
+    Json(maintenance::stats())
+}
+
+/// Tunables in effect
+pub async fn get_tunables(_admin: AdminAuth, State(state): State<AppState>) -> Json<Arc<Tunables>> {
+    Json(state.tunables.current())
+}
+
+/// Read the tunables again from the environment and `.env`, like SIGHUP
+pub async fn reload_tunables(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+) -> AppResult<Json<Arc<Tunables>>> {
+    state
+        .tunables
+        .reload()
+        .map(Json)
+        .map_err(|e| AppError::BadRequest(format!("Reload failed, nothing changed: {}", e)))
+}
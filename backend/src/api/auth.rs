@@ -0,0 +1,359 @@
+//! Request authentication extractors
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use super::{session_store, AppState};
+use crate::config::Config;
+use crate::error::{AppError, AppResult};
+
+/// Player tokens stay valid for a day, and are renewed for as long as
+/// their session holds the seat
+const PLAYER_TOKEN_TTL_SECS: i64 = 86400;
+
+/// Guest sessions, and the record of whom each room seated under which
+/// session, last a month without use like those of users, so players can
+/// come back to rooms kept for a week or restored from a snapshot
+const GUEST_SESSION_TTL_SECS: i64 = REFRESH_TOKEN_TTL_SECS;
+
+/// User access tokens are short-lived; clients renew them with the refresh
+/// token, which is where revocation is enforced for good
+pub const USER_TOKEN_TTL_SECS: i64 = 15 * 60;
+
+/// Refresh tokens, and so sessions of users, last a month without use
+pub const REFRESH_TOKEN_TTL_SECS: i64 = 30 * 86400;
+
+/// Audience of user session tokens, so they cannot pass for player tokens
+/// or the other way round
+const USER_AUDIENCE: &str = "user";
+
+/// Operator access via `Authorization: Bearer <ADMIN_TOKEN>`
+///
+/// Admin endpoints are disabled entirely when no token is configured.
+pub struct AdminAuth;
+
+#[async_trait]
+impl FromRequestParts<AppState> for AdminAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        let Some(expected) = state.config.admin_token.as_deref() else {
+            return Err(AppError::Forbidden("Admin API is disabled".into()));
+        };
+
+        let TypedHeader(Authorization(bearer)) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| AppError::Unauthorized)?;
+
+        // Digests are compared so neither the contents nor the length of the
+        // token leak through timing
+        let given = Sha256::digest(bearer.token().as_bytes());
+        let expected = Sha256::digest(expected.as_bytes());
+        if !bool::from(given.ct_eq(&expected)) {
+            return Err(AppError::Unauthorized);
+        }
+
+        Ok(AdminAuth)
+    }
+}
+
+/// Claims of the token handed to a player when they create or join a room
+#[derive(Debug, Serialize, Deserialize)]
+struct PlayerClaims {
+    /// Player id
+    sub: Uuid,
+    room: String,
+    /// Session the seat was taken under
+    sid: Uuid,
+    exp: i64,
+}
+
+/// Seat `player_id` in `room_id` under a session and sign a token proving
+/// the bearer is that player
+///
+/// Signed-in users seat under their own session, so the token stops
+/// working when it ends, like the session's access tokens; guests get a
+/// session of their own.
+pub async fn seat_token(
+    state: &AppState,
+    room_id: &str,
+    player_id: Uuid,
+    session_id: Option<Uuid>,
+) -> AppResult<String> {
+    let session_id = match session_id {
+        Some(session_id) => session_id,
+        None => {
+            let session_id = Uuid::new_v4();
+            session_store::open(&state.redis, session_id, None, GUEST_SESSION_TTL_SECS).await?;
+            session_id
+        }
+    };
+    session_store::add_seat(
+        &state.redis,
+        session_id,
+        room_id,
+        player_id,
+        GUEST_SESSION_TTL_SECS,
+    )
+    .await?;
+
+    sign_player_token(&state.config, room_id, player_id, session_id)
+}
+
+/// A fresh token for the seat `auth` proves, under the same session
+///
+/// Rooms can outlive a token, so clients trade theirs in before it runs
+/// out, or after, as long as the session still holds the seat.
+pub fn renew_seat_token(config: &Config, auth: &PlayerAuth) -> AppResult<String> {
+    sign_player_token(config, &auth.room_id, auth.player_id, auth.session_id)
+}
+
+fn sign_player_token(
+    config: &Config,
+    room_id: &str,
+    player_id: Uuid,
+    session_id: Uuid,
+) -> AppResult<String> {
+    let claims = PlayerClaims {
+        sub: player_id,
+        room: room_id.to_string(),
+        sid: session_id,
+        exp: chrono::Utc::now().timestamp() + PLAYER_TOKEN_TTL_SECS,
+    };
+
+    jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+    )
+    .map_err(|e| AppError::Internal(e.into()))
+}
+
+/// Check that a token was issued for this player in this room, under a
+/// session that is still live
+pub async fn verify_player_token(
+    state: &AppState,
+    token: &str,
+    room_id: &str,
+    player_id: Uuid,
+) -> AppResult<PlayerAuth> {
+    let claims = decode_player_token(&state.config, token)?;
+
+    if claims.sub != player_id || claims.room != room_id {
+        return Err(AppError::Unauthorized);
+    }
+
+    check_seat(state, claims).await
+}
+
+/// Renew the session a player token names, refusing it once the session
+/// has ended or no longer holds the seat
+async fn check_seat(state: &AppState, claims: PlayerClaims) -> AppResult<PlayerAuth> {
+    let session = session_store::renew(&state.redis, claims.sid)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+    if session.seats.get(&claims.room) != Some(&claims.sub) {
+        return Err(AppError::Unauthorized);
+    }
+
+    Ok(PlayerAuth {
+        room_id: claims.room,
+        player_id: claims.sub,
+        session_id: claims.sid,
+    })
+}
+
+/// Player a valid token was issued to, whatever the room
+pub fn player_in_token(config: &Config, token: &str) -> Option<Uuid> {
+    decode_player_token(config, token)
+        .ok()
+        .map(|claims| claims.sub)
+}
+
+fn decode_player_token(config: &Config, token: &str) -> AppResult<PlayerClaims> {
+    decode_player_claims(config, token, &Validation::default())
+}
+
+fn decode_player_claims(
+    config: &Config,
+    token: &str,
+    validation: &Validation,
+) -> AppResult<PlayerClaims> {
+    jsonwebtoken::decode::<PlayerClaims>(
+        token,
+        &DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+        validation,
+    )
+    .map(|data| data.claims)
+    .map_err(|_| AppError::Unauthorized)
+}
+
+/// A player proven by `Authorization: Bearer <player token>`
+///
+/// Handlers must still check `room_id` against the room they serve.
+pub struct PlayerAuth {
+    pub room_id: String,
+    pub player_id: Uuid,
+    /// Session the seat was taken under
+    pub session_id: Uuid,
+}
+
+impl PlayerAuth {
+    /// Reject the token unless it was issued for `room_id`
+    pub fn for_room(&self, room_id: &str) -> AppResult<Uuid> {
+        if self.room_id != room_id {
+            return Err(AppError::Unauthorized);
+        }
+        Ok(self.player_id)
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for PlayerAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        let TypedHeader(Authorization(bearer)) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| AppError::Unauthorized)?;
+
+        let claims = decode_player_token(&state.config, bearer.token())?;
+        check_seat(state, claims).await
+    }
+}
+
+/// A player proven by a player token that may have run out
+///
+/// Only for renewing the token: the signature is still checked, and so is
+/// the session, which must be live and hold the seat.
+pub struct LapsedPlayerAuth(pub PlayerAuth);
+
+#[async_trait]
+impl FromRequestParts<AppState> for LapsedPlayerAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        let TypedHeader(Authorization(bearer)) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| AppError::Unauthorized)?;
+
+        let mut validation = Validation::default();
+        validation.validate_exp = false;
+        let claims = decode_player_claims(&state.config, bearer.token(), &validation)?;
+        check_seat(state, claims).await.map(LapsedPlayerAuth)
+    }
+}
+
+/// Claims of a registered user's access token
+#[derive(Debug, Serialize, Deserialize)]
+struct UserClaims {
+    /// User id
+    sub: Uuid,
+    username: String,
+    /// Session the token was issued for
+    sid: Uuid,
+    aud: String,
+    exp: i64,
+}
+
+/// Sign an access token for a registered user's session
+pub fn issue_user_token(
+    config: &Config,
+    user_id: Uuid,
+    username: &str,
+    session_id: Uuid,
+) -> AppResult<String> {
+    let claims = UserClaims {
+        sub: user_id,
+        username: username.to_string(),
+        sid: session_id,
+        aud: USER_AUDIENCE.to_string(),
+        exp: chrono::Utc::now().timestamp() + USER_TOKEN_TTL_SECS,
+    };
+
+    jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+    )
+    .map_err(|e| AppError::Internal(e.into()))
+}
+
+/// User a valid session token was issued to
+pub fn user_in_token(config: &Config, token: &str) -> Option<Uuid> {
+    decode_user_token(config, token)
+        .ok()
+        .map(|claims| claims.sub)
+}
+
+fn decode_user_token(config: &Config, token: &str) -> AppResult<UserClaims> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[USER_AUDIENCE]);
+
+    jsonwebtoken::decode::<UserClaims>(
+        token,
+        &DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
+    .map_err(|_| AppError::Unauthorized)
+}
+
+/// A fresh opaque refresh token and the hash to store for it
+pub fn new_refresh_token() -> (String, String) {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+    let hash = hash_refresh_token(&token);
+    (token, hash)
+}
+
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// A registered user proven by `Authorization: Bearer <access token>`
+///
+/// The token's session is looked up and renewed, so this needs Redis.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub session_id: Uuid,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        let TypedHeader(Authorization(bearer)) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| AppError::Unauthorized)?;
+
+        let claims = decode_user_token(&state.config, bearer.token())?;
+        let session = session_store::renew(&state.redis, claims.sid)
+            .await?
+            .ok_or(AppError::Unauthorized)?;
+        if session.user_id != Some(claims.sub) {
+            return Err(AppError::Unauthorized);
+        }
+
+        Ok(AuthUser {
+            id: claims.sub,
+            session_id: claims.sid,
+        })
+    }
+}
@@ -0,0 +1,139 @@
+//! Bans of users and IP addresses
+//!
+//! A ban keeps its user or address from creating and joining rooms, opening
+//! game sockets and signing in. Banning a user also ends their sessions and
+//! closes the sockets they have open.
+
+use std::net::IpAddr;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::{auth::AdminAuth, session_store, AppState};
+use crate::db::{self, bans::Ban, sessions};
+use crate::error::{AppError, AppResult, FieldErrors};
+use crate::game::ServerEvent;
+
+/// Longest reason kept with a ban
+const MAX_REASON_LEN: usize = 500;
+
+/// Refuse a banned user or address
+pub(crate) async fn ensure_not_banned(
+    state: &AppState,
+    user_id: Option<Uuid>,
+    ip: Option<&str>,
+) -> AppResult<()> {
+    match db::bans::find_active(&state.db, user_id, ip).await? {
+        Some(_) => Err(AppError::Forbidden("You are banned".into())),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBanRequest {
+    pub user_id: Option<Uuid>,
+    pub ip: Option<String>,
+    /// Shown to the banned user
+    pub reason: Option<String>,
+    /// Permanent when unset
+    pub duration_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BansQuery {
+    /// List expired bans too
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// Bans in force, or every ban with `?all=true`, newest first
+pub async fn list_bans(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<BansQuery>,
+) -> AppResult<Json<Vec<Ban>>> {
+    Ok(Json(db::bans::list(&state.db, query.all).await?))
+}
+
+/// Ban a user, an IP address or both
+pub async fn create_ban(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Json(req): Json<CreateBanRequest>,
+) -> AppResult<Json<Ban>> {
+    let mut errors = FieldErrors::new();
+    if req.user_id.is_none() && req.ip.is_none() {
+        errors.add("user_id", "required", "A user_id or an ip is required");
+    }
+    let ip = match req.ip.as_deref().map(str::parse::<IpAddr>) {
+        Some(Ok(ip)) => Some(ip.to_string()),
+        Some(Err(_)) => {
+            errors.add("ip", "invalid", "Not an IP address");
+            None
+        }
+        None => None,
+    };
+    let reason = req
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty());
+    if reason.is_some_and(|r| r.chars().count() > MAX_REASON_LEN) {
+        errors.add(
+            "reason",
+            "too_long",
+            format!("reason must be at most {} characters", MAX_REASON_LEN),
+        );
+    }
+    errors.into_result()?;
+
+    if let Some(user_id) = req.user_id {
+        db::users::get_user(&state.db, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+    }
+
+    let expires_at = req.duration_secs.map(|secs| {
+        chrono::Utc::now() + chrono::Duration::seconds(secs.min(i64::MAX as u64) as i64)
+    });
+    let ban = db::bans::create(&state.db, req.user_id, ip.as_deref(), reason, expires_at).await?;
+
+    if let Some(user_id) = ban.user_id {
+        // Their tokens stop working, player tokens of their seats included
+        for session_id in sessions::revoke_all(&state.db, user_id).await? {
+            session_store::end(&state.redis, session_id).await?;
+        }
+        state.hub.notify_user(
+            user_id,
+            ServerEvent::Banned {
+                reason: ban.reason.clone(),
+                expires_at: ban.expires_at.map(|at| at.timestamp()),
+            },
+        );
+    }
+
+    tracing::info!(
+        "Admin banned user {:?} and address {:?}",
+        ban.user_id,
+        ban.ip
+    );
+    Ok(Json(ban))
+}
+
+/// Lift a ban
+pub async fn delete_ban(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(ban_id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    if !db::bans::delete(&state.db, ban_id).await? {
+        return Err(AppError::NotFound("Ban not found".into()));
+    }
+    tracing::info!("Admin lifted ban {}", ban_id);
+    Ok(StatusCode::NO_CONTENT)
+}
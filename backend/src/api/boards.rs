@@ -0,0 +1,130 @@
+//! Board definitions, so clients render exactly what the server charges
+
+use axum::{
+    extract::{Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::openapi::ErrorResponse;
+use crate::error::{AppError, AppResult};
+use crate::game::board::{self, ColorGroup, Tile, TileType};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BoardSummary {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Newest version, which new rooms are created on
+    pub version: i32,
+    pub tile_count: usize,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct BoardQuery {
+    /// A room's `board_version`; the newest when left out
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BoardResponse {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub version: i32,
+    pub tiles: Vec<TileInfo>,
+    pub groups: Vec<GroupInfo>,
+}
+
+/// A tile with what clients need to draw it
+///
+/// For tax tiles `rent_base` is the amount due; for utilities the rent
+/// schedule holds dice multipliers.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TileInfo {
+    #[serde(flatten)]
+    #[schema(inline)]
+    pub tile: &'static Tile,
+    pub color: Option<&'static str>,
+    pub is_corner: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GroupInfo {
+    pub group: ColorGroup,
+    pub color: &'static str,
+    /// Tiles needed for a full set
+    pub size: u8,
+}
+
+/// Boards the server can play on
+#[utoipa::path(
+    get,
+    path = "/api/boards",
+    tag = "boards",
+    responses((status = 200, description = "Available boards", body = [BoardSummary]))
+)]
+pub async fn list_boards() -> Json<Vec<BoardSummary>> {
+    let boards = board::latest_boards()
+        .into_iter()
+        .map(|def| BoardSummary {
+            id: &def.id,
+            name: &def.name,
+            version: def.version,
+            tile_count: def.tiles.len(),
+        })
+        .collect();
+
+    Json(boards)
+}
+
+/// Every tile and color group of a board
+#[utoipa::path(
+    get,
+    path = "/api/boards/{board_id}",
+    tag = "boards",
+    params(("board_id" = String, Path, description = "Board id"), BoardQuery),
+    responses(
+        (status = 200, description = "The board's tiles and groups", body = BoardResponse),
+        (status = 404, description = "Unknown board or version", body = ErrorResponse),
+    )
+)]
+pub async fn get_board(
+    Path(board_id): Path<String>,
+    Query(query): Query<BoardQuery>,
+) -> AppResult<Json<BoardResponse>> {
+    let def = match query.version {
+        Some(version) => board::definition(&board_id, version),
+        None => board::latest(&board_id),
+    }
+    .ok_or_else(|| AppError::NotFound("Board not found".into()))?;
+
+    let tiles = def
+        .tiles
+        .iter()
+        .map(|tile| TileInfo {
+            tile,
+            color: tile.group.map(|g| g.color_hex()),
+            is_corner: matches!(
+                tile.tile_type,
+                TileType::Go | TileType::Jail | TileType::FreeParking | TileType::GoToJail
+            ),
+        })
+        .collect();
+
+    let groups = ColorGroup::ALL
+        .iter()
+        .map(|&group| GroupInfo {
+            group,
+            color: group.color_hex(),
+            size: group.property_count(),
+        })
+        .collect();
+
+    Ok(Json(BoardResponse {
+        id: &def.id,
+        name: &def.name,
+        version: def.version,
+        tiles,
+        groups,
+    }))
+}
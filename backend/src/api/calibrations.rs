@@ -0,0 +1,156 @@
+//! Bot difficulty calibration
+//!
+//! Runs are played on the blocking pool and recorded in Postgres. A finished
+//! run can replace the parameters behind each difficulty, both in the
+//! database and in this process.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{auth::AdminAuth, AppState};
+use crate::bot::{BotDifficulty, BotParams};
+use crate::db::calibration::{self as store, CalibrationRun};
+use crate::error::{AppError, AppResult};
+use crate::game::calibration::{self, CalibrationParams};
+
+/// Most games a single candidate may play
+const MAX_GAMES_PER_CANDIDATE: u32 = 500;
+
+const DEFAULT_GAMES_PER_CANDIDATE: u32 = 40;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCalibrationRequest {
+    pub games_per_candidate: Option<u32>,
+    /// Random when omitted
+    pub seed: Option<u64>,
+    pub max_turns: Option<u32>,
+    /// Switch difficulties to the measured parameters when the run finishes
+    #[serde(default = "default_apply")]
+    pub apply: bool,
+}
+
+fn default_apply() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateCalibrationResponse {
+    pub run_id: Uuid,
+    /// Games the run will play in total
+    pub games: u32,
+}
+
+/// Parameters a difficulty currently plays with
+#[derive(Debug, Serialize)]
+pub struct DifficultyInfo {
+    pub difficulty: BotDifficulty,
+    pub params: BotParams,
+    /// Whether the parameters came from a calibration run
+    pub calibrated: bool,
+    /// Win rate measured against the baseline table
+    pub win_rate: Option<f64>,
+    pub run_id: Option<Uuid>,
+}
+
+/// Start a calibration run
+pub async fn create_calibration(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Json(req): Json<CreateCalibrationRequest>,
+) -> AppResult<Json<CreateCalibrationResponse>> {
+    let games_per_candidate = req
+        .games_per_candidate
+        .unwrap_or(DEFAULT_GAMES_PER_CANDIDATE);
+    if games_per_candidate == 0 || games_per_candidate > MAX_GAMES_PER_CANDIDATE {
+        return Err(AppError::BadRequest(format!(
+            "games_per_candidate must be between 1 and {}",
+            MAX_GAMES_PER_CANDIDATE
+        )));
+    }
+
+    let params = CalibrationParams {
+        seed: req.seed.unwrap_or_else(|| rand::thread_rng().gen()),
+        games_per_candidate,
+        max_turns: req.max_turns.unwrap_or(calibration::DEFAULT_MAX_TURNS),
+    };
+    let run_id = Uuid::new_v4();
+
+    store::create_run(&state.db, run_id, &params).await?;
+
+    let db = state.db.clone();
+    tokio::spawn(async move {
+        if let Err(e) = run_calibration(&db, run_id, params, req.apply).await {
+            tracing::error!("Calibration run {} failed: {}", run_id, e);
+        }
+    });
+
+    Ok(Json(CreateCalibrationResponse {
+        run_id,
+        games: calibration::candidates().len() as u32 * games_per_candidate,
+    }))
+}
+
+/// Fetch a run's status and, once done, its results
+pub async fn get_calibration(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(run_id): Path<Uuid>,
+) -> AppResult<Json<CalibrationRun>> {
+    let run = store::get_run(&state.db, run_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Calibration run not found".into()))?;
+
+    Ok(Json(run))
+}
+
+/// Parameters behind each difficulty, with their measured strength
+pub async fn get_difficulties(
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<DifficultyInfo>>> {
+    let stored = store::load_params(&state.db).await?;
+
+    let difficulties = BotDifficulty::ALL
+        .iter()
+        .map(|&difficulty| {
+            let entry = stored.iter().find(|s| s.difficulty == difficulty);
+            DifficultyInfo {
+                difficulty,
+                params: difficulty.params(),
+                calibrated: entry.is_some(),
+                win_rate: entry.map(|s| s.win_rate),
+                run_id: entry.and_then(|s| s.run_id),
+            }
+        })
+        .collect();
+
+    Ok(Json(difficulties))
+}
+
+async fn run_calibration(
+    db: &PgPool,
+    run_id: Uuid,
+    params: CalibrationParams,
+    apply: bool,
+) -> AppResult<()> {
+    let report = match tokio::task::spawn_blocking(move || calibration::calibrate(&params)).await {
+        Ok(report) => report,
+        Err(e) => return store::fail_run(db, run_id, &e.to_string()).await,
+    };
+
+    store::complete_run(db, run_id, &report, apply).await?;
+
+    if apply {
+        for tuned in &report.tuned {
+            tuned.difficulty.set_params(tuned.params);
+        }
+        tracing::info!("Applied bot parameters from calibration run {}", run_id);
+    }
+
+    Ok(())
+}
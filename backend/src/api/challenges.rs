@@ -0,0 +1,78 @@
+//! Daily and weekly challenges and the rewards they earn
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{auth::AuthUser, openapi::ErrorResponse, AppState};
+use crate::db::challenges::{self, ChallengeStatus, OwnedReward};
+use crate::error::AppResult;
+use crate::game::challenges::Reward;
+
+/// Challenges of the current day and week
+#[utoipa::path(
+    get,
+    path = "/api/challenges",
+    tag = "challenges",
+    security((), ("user_token" = [])),
+    responses(
+        (status = 200, description = "Running challenges, daily ones first, with the signed-in user's progress", body = [ChallengeStatus]),
+    )
+)]
+pub async fn list_challenges(
+    user: Option<AuthUser>,
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<ChallengeStatus>>> {
+    Ok(Json(
+        challenges::current(&state.db, user.map(|u| u.id)).await?,
+    ))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClaimResponse {
+    pub reward: Reward,
+}
+
+/// Claim the reward of a completed challenge
+#[utoipa::path(
+    post,
+    path = "/api/challenges/{challenge_id}/claim",
+    tag = "challenges",
+    security(("user_token" = [])),
+    params(("challenge_id" = Uuid, Path, description = "Challenge id")),
+    responses(
+        (status = 200, description = "Reward granted", body = ClaimResponse),
+        (status = 400, description = "Challenge is not complete yet", body = ErrorResponse),
+        (status = 404, description = "Challenge not found", body = ErrorResponse),
+        (status = 409, description = "Reward already claimed", body = ErrorResponse),
+    )
+)]
+pub async fn claim(
+    user: AuthUser,
+    State(state): State<AppState>,
+    Path(challenge_id): Path<Uuid>,
+) -> AppResult<Json<ClaimResponse>> {
+    let reward = challenges::claim(&state.db, user.id, challenge_id).await?;
+    Ok(Json(ClaimResponse { reward }))
+}
+
+/// Titles and cosmetics the signed-in user has earned
+#[utoipa::path(
+    get,
+    path = "/api/users/me/rewards",
+    tag = "challenges",
+    security(("user_token" = [])),
+    responses(
+        (status = 200, description = "Earned rewards, oldest first", body = [OwnedReward]),
+    )
+)]
+pub async fn list_rewards(
+    user: AuthUser,
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<OwnedReward>>> {
+    Ok(Json(challenges::rewards(&state.db, user.id).await?))
+}
@@ -0,0 +1,49 @@
+//! Which browser origins may call the API
+//!
+//! With `ALLOWED_ORIGINS` unset any origin may call, without credentials,
+//! which suits local development. Once set, only those origins get CORS
+//! headers, and they may send cookies. WebSocket upgrades are not covered
+//! by CORS, so their `Origin` is checked against the same list. The list is
+//! read on every request, so reloading it takes effect at once.
+
+use axum::http::{header::ORIGIN, HeaderMap, HeaderValue};
+use tower_http::cors::{AllowCredentials, AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+use crate::config::{LiveTunables, Tunables};
+use crate::error::{AppError, AppResult};
+
+/// CORS layer for every route
+pub fn cors_layer(tunables: &LiveTunables) -> CorsLayer {
+    let origins = tunables.clone();
+    let credentials = tunables.clone();
+
+    // Credentials rule out wildcards, so echo what the browser asks for
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            allows(&origins.current(), origin)
+        }))
+        .allow_methods(AllowMethods::mirror_request())
+        .allow_headers(AllowHeaders::mirror_request())
+        .allow_credentials(AllowCredentials::predicate(move |_, _| {
+            credentials.current().allowed_origins.is_some()
+        }))
+}
+
+/// Refuse a WebSocket upgrade from a page on an origin not allowed
+///
+/// Clients outside a browser send no `Origin` and are let through.
+pub(crate) fn check_origin(tunables: &Tunables, headers: &HeaderMap) -> AppResult<()> {
+    match headers.get(ORIGIN) {
+        Some(origin) if !allows(tunables, origin) => {
+            Err(AppError::Forbidden("Origin not allowed".into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn allows(tunables: &Tunables, origin: &HeaderValue) -> bool {
+    match &tunables.allowed_origins {
+        Some(allowed) => allowed.iter().any(|allowed| origin == allowed.as_str()),
+        None => true,
+    }
+}
@@ -0,0 +1,284 @@
+//! Friends and room invites
+//!
+//! Invites go to friends only. The invitee sees them through `GET
+//! /api/invites`, and right away on any room connection they have open.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    auth::{self, AuthUser},
+    bans,
+    handlers::JoinRoomResponse,
+    openapi::ErrorResponse,
+    users, AppState, ClientIp,
+};
+use crate::db::{
+    self,
+    friends::{self, FriendList},
+    invites::{self, Invite},
+};
+use crate::error::{AppError, AppResult};
+use crate::game::{GameEngine, GamePhase, ServerEvent};
+
+/// Invites are for a lobby that is open now, not for later
+const INVITE_TTL_SECS: i64 = 3600;
+
+/// The signed-in user's friends and pending requests
+#[utoipa::path(
+    get,
+    path = "/api/friends",
+    tag = "friends",
+    security(("user_token" = [])),
+    responses(
+        (status = 200, description = "Friends and pending requests, by username", body = FriendList),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+    )
+)]
+pub async fn list_friends(
+    user: AuthUser,
+    State(state): State<AppState>,
+) -> AppResult<Json<FriendList>> {
+    Ok(Json(friends::list(&state.db, user.id).await?))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FriendRequest {
+    pub username: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FriendRequestResponse {
+    pub user_id: Uuid,
+    /// True when they had already asked, so this made you friends
+    pub accepted: bool,
+}
+
+/// Ask another user to be friends
+#[utoipa::path(
+    post,
+    path = "/api/friends/requests",
+    tag = "friends",
+    security(("user_token" = [])),
+    request_body = FriendRequest,
+    responses(
+        (status = 200, description = "Request sent, or theirs accepted", body = FriendRequestResponse),
+        (status = 400, description = "Yourself, already friends or already asked", body = ErrorResponse),
+        (status = 404, description = "No such user", body = ErrorResponse),
+    )
+)]
+pub async fn request_friend(
+    user: AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<FriendRequest>,
+) -> AppResult<Json<FriendRequestResponse>> {
+    let (other, _) = db::users::find_by_username(&state.db, req.username.trim())
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    if other.id == user.id {
+        return Err(AppError::BadRequest("You cannot befriend yourself".into()));
+    }
+
+    let accepted = friends::request(&state.db, user.id, other.id).await?;
+
+    Ok(Json(FriendRequestResponse {
+        user_id: other.id,
+        accepted,
+    }))
+}
+
+/// Accept a friend request
+#[utoipa::path(
+    post,
+    path = "/api/friends/{user_id}/accept",
+    tag = "friends",
+    security(("user_token" = [])),
+    params(("user_id" = Uuid, Path, description = "User who sent the request")),
+    responses(
+        (status = 204, description = "You are now friends"),
+        (status = 404, description = "No pending request from that user", body = ErrorResponse),
+    )
+)]
+pub async fn accept_friend(
+    user: AuthUser,
+    State(state): State<AppState>,
+    Path(from): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    if !friends::accept(&state.db, user.id, from).await? {
+        return Err(AppError::NotFound("No pending friend request".into()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Unfriend someone, or decline or withdraw a request
+#[utoipa::path(
+    delete,
+    path = "/api/friends/{user_id}",
+    tag = "friends",
+    security(("user_token" = [])),
+    params(("user_id" = Uuid, Path, description = "The other user")),
+    responses(
+        (status = 204, description = "Removed"),
+        (status = 404, description = "Neither friends nor a pending request", body = ErrorResponse),
+    )
+)]
+pub async fn remove_friend(
+    user: AuthUser,
+    State(state): State<AppState>,
+    Path(other): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    if !friends::remove(&state.db, user.id, other).await? {
+        return Err(AppError::NotFound("Not friends".into()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InviteRequest {
+    pub user_id: Uuid,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InviteResponse {
+    pub invite_id: Uuid,
+}
+
+/// Invite a friend into a room you are seated in
+#[utoipa::path(
+    post,
+    path = "/api/rooms/{room_id}/invites",
+    tag = "friends",
+    security(("user_token" = [])),
+    params(("room_id" = String, Path, description = "Room id")),
+    request_body = InviteRequest,
+    responses(
+        (status = 200, description = "Invite sent", body = InviteResponse),
+        (status = 400, description = "Game already started", body = ErrorResponse),
+        (status = 403, description = "Not friends, or not seated in the room", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
+pub async fn invite(
+    user: AuthUser,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Json(req): Json<InviteRequest>,
+) -> AppResult<Json<InviteResponse>> {
+    let game = GameEngine::get_game(&state.redis, &room_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+    if game.phase != GamePhase::Lobby {
+        return Err(AppError::BadRequest("Game already started".into()));
+    }
+    if !game.players.iter().any(|p| p.user_id == Some(user.id)) {
+        return Err(AppError::Forbidden(
+            "Only players in the room can invite".into(),
+        ));
+    }
+    if !friends::are_friends(&state.db, user.id, req.user_id).await? {
+        return Err(AppError::Forbidden("You can only invite friends".into()));
+    }
+
+    let inviter = db::users::get_user(&state.db, user.id)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(INVITE_TTL_SECS);
+    let invite_id = invites::create(&state.db, &room_id, user.id, req.user_id, expires_at).await?;
+
+    state.hub.notify_user(
+        req.user_id,
+        ServerEvent::Invited {
+            invite_id,
+            room_id,
+            from_user_id: user.id,
+            from_name: inviter.username,
+        },
+    );
+
+    Ok(Json(InviteResponse { invite_id }))
+}
+
+/// Room invites waiting for the signed-in user
+#[utoipa::path(
+    get,
+    path = "/api/invites",
+    tag = "friends",
+    security(("user_token" = [])),
+    responses(
+        (status = 200, description = "Unexpired invites, newest first", body = [Invite]),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+    )
+)]
+pub async fn list_invites(
+    user: AuthUser,
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<Invite>>> {
+    Ok(Json(invites::pending_for(&state.db, user.id).await?))
+}
+
+/// Accept an invite and take a seat in its room
+///
+/// The seat uses the profile's name and looks, as when joining signed in.
+#[utoipa::path(
+    post,
+    path = "/api/invites/{invite_id}/accept",
+    tag = "friends",
+    security(("user_token" = [])),
+    params(("invite_id" = Uuid, Path, description = "Invite id")),
+    responses(
+        (status = 200, description = "Joined, with the player's token", body = JoinRoomResponse),
+        (status = 400, description = "Room full, game started or name taken", body = ErrorResponse),
+        (status = 404, description = "Invite or room not found, or invite expired", body = ErrorResponse),
+    )
+)]
+pub async fn accept_invite(
+    user: AuthUser,
+    State(state): State<AppState>,
+    Path(invite_id): Path<Uuid>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+) -> AppResult<Json<JoinRoomResponse>> {
+    bans::ensure_not_banned(&state, Some(user.id), ip.as_deref()).await?;
+    let room_id = invites::take(&state.db, invite_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Invite not found or expired".into()))?;
+
+    let (player_name, prefs) =
+        users::seat_for(&state, Some(&user), None, "player_name", &headers).await?;
+    let player_id =
+        GameEngine::join_room(&state.redis, &state.hub, &room_id, &player_name, &prefs).await?;
+    let token = auth::seat_token(&state, &room_id, player_id, Some(user.session_id)).await?;
+
+    Ok(Json(JoinRoomResponse { player_id, token }))
+}
+
+/// Decline an invite
+#[utoipa::path(
+    delete,
+    path = "/api/invites/{invite_id}",
+    tag = "friends",
+    security(("user_token" = [])),
+    params(("invite_id" = Uuid, Path, description = "Invite id")),
+    responses(
+        (status = 204, description = "Declined"),
+        (status = 404, description = "Invite not found", body = ErrorResponse),
+    )
+)]
+pub async fn decline_invite(
+    user: AuthUser,
+    State(state): State<AppState>,
+    Path(invite_id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    invites::take(&state.db, invite_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Invite not found".into()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
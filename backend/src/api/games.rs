@@ -0,0 +1,33 @@
+//! Finished games kept in Postgres
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use uuid::Uuid;
+
+use super::{openapi::ErrorResponse, AppState};
+use crate::db::games;
+use crate::error::{AppError, AppResult};
+use crate::game::summary::GameSummary;
+
+/// Counters and net-worth timeline of a finished game
+#[utoipa::path(
+    get,
+    path = "/api/games/{game_id}/summary",
+    tag = "users",
+    params(("game_id" = Uuid, Path, description = "Game id, as listed in a user's games")),
+    responses(
+        (status = 200, description = "Summary stored when the game ended", body = GameSummary),
+        (status = 404, description = "Game not found or stored without a summary", body = ErrorResponse),
+    )
+)]
+pub async fn get_summary(
+    State(state): State<AppState>,
+    Path(game_id): Path<Uuid>,
+) -> AppResult<Json<GameSummary>> {
+    let summary = games::summary(&state.db, game_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Game not found".into()))?;
+    Ok(Json(summary))
+}
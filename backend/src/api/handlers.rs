@@ -1,79 +1,215 @@
 //! HTTP handlers for REST API
 
+use std::future::Future;
+use std::time::{Duration, Instant};
+
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-use super::AppState;
-use crate::error::{AppError, AppResult};
-use crate::game::{GameConfig, GameEngine};
+use super::{
+    auth::{self, AuthUser},
+    bans,
+    openapi::ErrorResponse,
+    session_store, users, AppState, ClientIp,
+};
+use crate::bot::BotDifficulty;
+use crate::db;
+use crate::error::{AppError, AppResult, FieldErrors};
+use crate::game::chat::{self, ChatMessage};
+use crate::game::house_rules;
+use crate::game::presets::Preset;
+use crate::game::summary::GameSummary;
+use crate::game::{
+    ClientEvent, ClientMessage, GameConfig, GameEngine, GamePhase, GameState, ServerEvent,
+    TimerOverrides, TradeRecord, LOG_LEN,
+};
+use crate::game::{PLAYER_COLORS, PLAYER_PIECES};
+use crate::i18n::Locale;
+use crate::shutdown;
+use crate::ws;
 
 /// Create a new game room
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateRoomRequest {
-    pub host_name: String,
+    /// Optional when signed in; the profile's name is used by default
+    pub host_name: Option<String>,
     pub config: Option<GameConfig>,
+    /// Bundle of options from `/api/presets`; replaces the matching fields
+    /// of `config` and keeps the others
+    pub preset: Option<Preset>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CreateRoomResponse {
     pub room_id: String,
     pub player_id: Uuid,
+    /// Presented when opening the game WebSocket
+    pub token: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/rooms",
+    tag = "rooms",
+    request_body = CreateRoomRequest,
+    security((), ("user_token" = [])),
+    responses(
+        (status = 200, description = "Room created, with the host's token", body = CreateRoomResponse),
+        (status = 400, description = "Invalid config or host name; `fields` lists each problem", body = ErrorResponse),
+        (status = 403, description = "The user or address is banned", body = ErrorResponse),
+        (status = 503, description = "Server is shutting down", body = ErrorResponse),
+    )
+)]
 pub async fn create_room(
     State(state): State<AppState>,
+    user: Option<AuthUser>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     Json(req): Json<CreateRoomRequest>,
 ) -> AppResult<Json<CreateRoomResponse>> {
-    let config = req.config.unwrap_or_default();
+    shutdown::ensure_accepting()?;
+    bans::ensure_not_banned(&state, user.as_ref().map(|u| u.id), ip.as_deref()).await?;
+    let mut config = req.config.unwrap_or_default();
+    if let Some(preset) = req.preset {
+        preset.apply(&mut config);
+    }
+    let tunables = state.tunables.current();
+    let timers = tunables.timers.with(&config.timers);
+
+    let mut errors = match &req.host_name {
+        Some(name) => GameEngine::check_name(name, "host_name"),
+        None => FieldErrors::new(),
+    };
+    errors.nest("config", config.validate());
+    errors.nest(
+        "config",
+        house_rules::check(&config, &tunables.disabled_house_rules),
+    );
+    errors.nest("config.timers", timers.validate());
+    errors.into_result()?;
+    config.timers = TimerOverrides::pinned(timers);
+
+    let (host_name, prefs) =
+        users::seat_for(&state, user.as_ref(), req.host_name, "host_name", &headers).await?;
     let (room_id, player_id) =
-        GameEngine::create_room(&state.redis, &req.host_name, config).await?;
+        GameEngine::create_room(&state.redis, &state.hub, &host_name, &prefs, config).await?;
+
+    let session_id = user.map(|user| user.session_id);
+    let token = auth::seat_token(&state, &room_id, player_id, session_id).await?;
 
-    Ok(Json(CreateRoomResponse { room_id, player_id }))
+    Ok(Json(CreateRoomResponse {
+        room_id,
+        player_id,
+        token,
+    }))
 }
 
 /// Join an existing room
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct JoinRoomRequest {
-    pub player_name: String,
+    /// Optional when signed in; the profile's name is used by default
+    pub player_name: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct JoinRoomResponse {
     pub player_id: Uuid,
+    /// Presented when opening the game WebSocket
+    pub token: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/rooms/{room_id}/join",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    request_body = JoinRoomRequest,
+    security((), ("user_token" = [])),
+    responses(
+        (status = 200, description = "Joined, with the player's token", body = JoinRoomResponse),
+        (status = 400, description = "Room full, game started or invalid name", body = ErrorResponse),
+        (status = 403, description = "The user or address is banned", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 503, description = "Server is shutting down", body = ErrorResponse),
+    )
+)]
 pub async fn join_room(
     State(state): State<AppState>,
     Path(room_id): Path<String>,
+    user: Option<AuthUser>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     Json(req): Json<JoinRoomRequest>,
 ) -> AppResult<Json<JoinRoomResponse>> {
-    let player_id = GameEngine::join_room(&state.redis, &room_id, &req.player_name).await?;
+    shutdown::ensure_accepting()?;
+    bans::ensure_not_banned(&state, user.as_ref().map(|u| u.id), ip.as_deref()).await?;
+    let (player_name, prefs) = users::seat_for(
+        &state,
+        user.as_ref(),
+        req.player_name,
+        "player_name",
+        &headers,
+    )
+    .await?;
+    let player_id =
+        GameEngine::join_room(&state.redis, &state.hub, &room_id, &player_name, &prefs).await?;
+
+    let session_id = user.map(|user| user.session_id);
+    let token = auth::seat_token(&state, &room_id, player_id, session_id).await?;
 
-    Ok(Json(JoinRoomResponse { player_id }))
+    Ok(Json(JoinRoomResponse { player_id, token }))
 }
 
 /// Get room state
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RoomStateResponse {
     pub room_id: String,
     pub players: Vec<PlayerInfo>,
     pub phase: String,
     pub config: GameConfig,
+    /// Spectators connected to this instance
+    pub spectators: usize,
+    /// Colors no player has yet, for `PUT /api/rooms/{room_id}/color`
+    pub available_colors: Vec<&'static str>,
+    /// Pieces no player has yet, for `PUT /api/rooms/{room_id}/piece`
+    pub available_pieces: Vec<&'static str>,
+    /// Unix time a closed room is deleted; it is read-only until then
+    pub closed_until: Option<i64>,
+    /// Seconds the room is kept after its last activity, if the host set it
+    pub lifetime_secs: Option<u64>,
+    /// Seconds until the room expires unless something happens in it
+    pub expires_in_secs: Option<u64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PlayerInfo {
     pub id: Uuid,
     pub name: String,
     pub color: String,
     pub is_host: bool,
     pub is_bot: bool,
+    pub avatar: Option<String>,
+    pub piece: Option<String>,
+    /// Bots are always ready, and the host says so by starting
+    pub ready: bool,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/rooms/{room_id}",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    responses(
+        (status = 200, description = "Lobby view of the room", body = RoomStateResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
 pub async fn get_room(
     State(state): State<AppState>,
     Path(room_id): Path<String>,
@@ -91,6 +227,27 @@ pub async fn get_room(
             color: p.color.clone(),
             is_host: p.is_host,
             is_bot: p.is_bot,
+            avatar: p.avatar.clone(),
+            piece: p.piece.clone(),
+            ready: p.ready || p.is_bot || p.is_host,
+        })
+        .collect();
+
+    let spectators = state.hub.spectator_count(&room_id);
+    let expires_in_secs = GameEngine::expires_in(&state.redis, &room_id).await?;
+    let available_colors = PLAYER_COLORS
+        .iter()
+        .copied()
+        .filter(|color| !game.players.iter().any(|p| p.color == *color))
+        .collect();
+    let available_pieces = PLAYER_PIECES
+        .iter()
+        .copied()
+        .filter(|piece| {
+            !game
+                .players
+                .iter()
+                .any(|p| p.piece.as_deref() == Some(piece))
         })
         .collect();
 
@@ -99,34 +256,797 @@ pub async fn get_room(
         players,
         phase: format!("{:?}", game.phase),
         config: game.config,
+        spectators,
+        available_colors,
+        available_pieces,
+        closed_until: game.closed_until,
+        lifetime_secs: game.lifetime_secs,
+        expires_in_secs,
+    }))
+}
+
+/// Counters and net-worth timeline of a room's game so far
+#[utoipa::path(
+    get,
+    path = "/api/rooms/{room_id}/summary",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    responses(
+        (status = 200, description = "Summary as it would read if the game ended now", body = GameSummary),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_summary(
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+) -> AppResult<Json<GameSummary>> {
+    let game = GameEngine::get_game(&state.redis, &room_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+    Ok(Json(GameSummary::of(&game)))
+}
+
+/// Lobby for the same players after a finished game
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RematchResponse {
+    pub room_id: String,
+    /// Same as in the finished game
+    pub player_id: Uuid,
+    /// Presented when opening the new room's WebSocket
+    pub token: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/rooms/{room_id}/rematch",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id of the finished game")),
+    security(("player_token" = [])),
+    responses(
+        (status = 200, description = "The rematch lobby, new or already opened", body = RematchResponse),
+        (status = 400, description = "Game is not over yet", body = ErrorResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 403, description = "Not a player in this game", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 503, description = "Server is shutting down", body = ErrorResponse),
+    )
+)]
+pub async fn rematch(
+    auth: auth::PlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+) -> AppResult<Json<RematchResponse>> {
+    shutdown::ensure_accepting()?;
+    let session_id = auth.session_id;
+    let player_id = auth.for_room(&room_id)?;
+    let (next, created) = GameEngine::rematch(&state.redis, &room_id, player_id).await?;
+
+    // Everyone else still on the old room's socket moves over with their
+    // own token, under the session they sat down with
+    if created {
+        for player in next
+            .players
+            .iter()
+            .filter(|p| !p.is_bot && p.id != player_id)
+        {
+            let seated_under =
+                session_store::seated_under(&state.redis, &room_id, player.id).await?;
+            let token = auth::seat_token(&state, &next.id, player.id, seated_under).await?;
+            let event = ServerEvent::Rematch {
+                room_id: next.id.clone(),
+                token: Some(token),
+            };
+            state.hub.send_to(&room_id, player.id, event);
+        }
+        let event = ServerEvent::Rematch {
+            room_id: next.id.clone(),
+            token: None,
+        };
+        state.hub.broadcast_spectators(&room_id, event);
+    }
+
+    let token = auth::seat_token(&state, &next.id, player_id, Some(session_id)).await?;
+    Ok(Json(RematchResponse {
+        room_id: next.id,
+        player_id,
+        token,
+    }))
+}
+
+/// Trade a player token in for a fresh one for the same seat, before or
+/// after it runs out, while the session it was issued under holds the seat
+#[utoipa::path(
+    post,
+    path = "/api/rooms/{room_id}/token",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    security(("player_token" = [])),
+    responses(
+        (status = 200, description = "A fresh token for the seat", body = JoinRoomResponse),
+        (status = 401, description = "Missing token, token for another room, or its session has ended or lost the seat", body = ErrorResponse),
+    )
+)]
+pub async fn renew_token(
+    auth: auth::LapsedPlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+) -> AppResult<Json<JoinRoomResponse>> {
+    let auth::LapsedPlayerAuth(auth) = auth;
+    let player_id = auth.for_room(&room_id)?;
+    let token = auth::renew_seat_token(&state.config, &auth)?;
+    Ok(Json(JoinRoomResponse { player_id, token }))
+}
+
+/// Close the room; its state and chat stay viewable, read-only, until the
+/// close grace period runs out and it is deleted
+#[utoipa::path(
+    post,
+    path = "/api/rooms/{room_id}/close",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    security(("player_token" = [])),
+    responses(
+        (status = 204, description = "Room closed; everyone gets `ROOM_CLOSED` and the final state"),
+        (status = 400, description = "Room is already closed", body = ErrorResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 403, description = "Not the host", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
+pub async fn close_room(
+    auth: auth::PlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+) -> AppResult<StatusCode> {
+    let player_id = auth.for_room(&room_id)?;
+    GameEngine::close_by_host(&state.redis, &state.hub, &room_id, player_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetLifetimeRequest {
+    /// Seconds the room is kept after its last activity, 600 to 604800;
+    /// `null` goes back to the server's default
+    pub lifetime_secs: Option<u64>,
+}
+
+/// Extend or shorten how long the room is kept after its last activity
+#[utoipa::path(
+    put,
+    path = "/api/rooms/{room_id}/lifetime",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    request_body = SetLifetimeRequest,
+    security(("player_token" = [])),
+    responses(
+        (status = 204, description = "Lifetime set and the expiry renewed with it; the room gets the updated state"),
+        (status = 400, description = "Lifetime out of range, or room closed", body = ErrorResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 403, description = "Not the host", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
+pub async fn set_lifetime(
+    auth: auth::PlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Json(req): Json<SetLifetimeRequest>,
+) -> AppResult<StatusCode> {
+    let player_id = auth.for_room(&room_id)?;
+    GameEngine::set_lifetime(
+        &state.redis,
+        &state.hub,
+        &room_id,
+        player_id,
+        req.lifetime_secs,
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Change color in the lobby
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PickColorRequest {
+    /// One of the room's `available_colors`
+    pub color: String,
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/rooms/{room_id}/color",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    request_body = PickColorRequest,
+    security(("player_token" = [])),
+    responses(
+        (status = 204, description = "Color taken; the room gets the updated roster"),
+        (status = 400, description = "Unknown color, color taken or game started", body = ErrorResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
+pub async fn pick_color(
+    auth: auth::PlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Json(req): Json<PickColorRequest>,
+) -> AppResult<StatusCode> {
+    let player_id = auth.for_room(&room_id)?;
+    let message = ClientMessage {
+        turn_id: None,
+        request_id: None,
+        event: ClientEvent::PickColor { color: req.color },
+    };
+    GameEngine::handle_event(&state.redis, &state.hub, &room_id, player_id, message).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Change playing piece in the lobby
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PickPieceRequest {
+    /// One of the room's `available_pieces`
+    pub piece: String,
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/rooms/{room_id}/piece",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    request_body = PickPieceRequest,
+    security(("player_token" = [])),
+    responses(
+        (status = 204, description = "Piece taken; the room gets the updated roster"),
+        (status = 400, description = "Unknown piece, piece taken or game started", body = ErrorResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
+pub async fn pick_piece(
+    auth: auth::PlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Json(req): Json<PickPieceRequest>,
+) -> AppResult<StatusCode> {
+    let player_id = auth.for_room(&room_id)?;
+    let message = ClientMessage {
+        turn_id: None,
+        request_id: None,
+        event: ClientEvent::PickPiece { piece: req.piece },
+    };
+    GameEngine::handle_event(&state.redis, &state.hub, &room_id, player_id, message).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActionResponse {
+    /// Type of the event applied, e.g. `ROLL_DICE`
+    pub event: Option<String>,
+    /// The client's `request_id`, echoed back
+    pub request_id: Option<String>,
+    /// Sequence number of the last event reflected in `state`
+    pub seq: u64,
+    /// The game after the action, as the player may see it
+    #[schema(value_type = Object)]
+    pub state: GameState,
+}
+
+/// Take any game action without a WebSocket
+///
+/// The body is a `ClientMessage` exactly as sent over the socket, with
+/// `turn_id` for turn-scoped actions and an optional `request_id`. Its
+/// events reach the room like any other; a rejected action is an error
+/// response rather than an `ERROR` event.
+#[utoipa::path(
+    post,
+    path = "/api/rooms/{room_id}/actions",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    request_body(content = Object, description = "A client event such as `{\"type\": \"ROLL_DICE\", \"turn_id\": \"...\"}`"),
+    security(("player_token" = [])),
+    responses(
+        (status = 200, description = "Action applied", body = ActionResponse),
+        (status = 400, description = "Malformed event", body = ErrorResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 403, description = "The player may not take this action", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 422, description = "Action not allowed by the rules right now", body = ErrorResponse),
+    )
+)]
+pub async fn submit_action(
+    auth: auth::PlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Json(message): Json<ClientMessage>,
+) -> AppResult<Json<ActionResponse>> {
+    let player_id = auth.for_room(&room_id)?;
+    if matches!(message.event, ClientEvent::Ack { .. }) {
+        return Err(AppError::BadRequest(
+            "Acknowledgements are only sent over a WebSocket".into(),
+        ));
+    }
+
+    let event = message.event.type_name();
+    let request_id = message.request_id();
+    GameEngine::handle_event(&state.redis, &state.hub, &room_id, player_id, message).await?;
+
+    let local_seq = state.hub.current_seq(&room_id);
+    let seq = local_seq.max(ws::room_seq(&state.redis, &room_id).await?);
+    let game = GameEngine::get_game(&state.redis, &room_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+    Ok(Json(ActionResponse {
+        event,
+        request_id,
+        seq,
+        state: game.view_for(player_id),
+    }))
+}
+
+/// Authoritative snapshot for a player who missed events
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GameStateResponse {
+    /// Sequence number of the last event reflected in `state`
+    pub seq: u64,
+    #[schema(value_type = Object)]
+    pub state: GameState,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rooms/{room_id}/state",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    security(("player_token" = [])),
+    responses(
+        (status = 200, description = "Game state as the player may see it", body = GameStateResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_state(
+    auth: auth::PlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+) -> AppResult<Json<GameStateResponse>> {
+    let player_id = auth.for_room(&room_id)?;
+
+    // Read the sequence first, so an event landing in between is delivered
+    // again rather than lost
+    let local_seq = state.hub.current_seq(&room_id);
+    let seq = local_seq.max(ws::room_seq(&state.redis, &room_id).await?);
+    let game = GameEngine::get_game(&state.redis, &room_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+    Ok(Json(GameStateResponse {
+        seq,
+        state: game.view_for(player_id),
+    }))
+}
+
+/// Page backwards through a room's chat
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ChatQuery {
+    /// Only messages with a lower id
+    pub before: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChatPageResponse {
+    /// Oldest first
+    pub messages: Vec<ChatMessage>,
+    /// `before` for the previous page, if there may be one
+    pub next_before: Option<u64>,
+}
+
+const DEFAULT_CHAT_PAGE: usize = 50;
+const MAX_CHAT_PAGE: usize = 100;
+
+/// Live rooms are read from Redis; once their history runs out or the room
+/// has expired, messages are read from Postgres
+#[utoipa::path(
+    get,
+    path = "/api/rooms/{room_id}/chat",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id"), ChatQuery),
+    responses(
+        (status = 200, description = "A page of chat history", body = ChatPageResponse),
+    )
+)]
+pub async fn get_chat(
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Query(query): Query<ChatQuery>,
+) -> AppResult<Json<ChatPageResponse>> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_CHAT_PAGE)
+        .clamp(1, MAX_CHAT_PAGE);
+
+    let mut messages = chat::history(&state.redis, &room_id, query.before, limit).await?;
+    if messages.is_empty() {
+        messages = db::chat::history(&state.db, &room_id, query.before, limit).await?;
+    }
+
+    let next_before = messages
+        .first()
+        .filter(|m| messages.len() == limit && m.id > 1)
+        .map(|m| m.id);
+
+    Ok(Json(ChatPageResponse {
+        messages,
+        next_before,
+    }))
+}
+
+/// How much of a room's log to read
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct LogsQuery {
+    /// Most recent lines to return, at most the 100 a room keeps
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LogsResponse {
+    /// Oldest first
+    pub entries: Vec<String>,
+}
+
+/// A room's game log
+///
+/// State events only carry the lines their change added, so clients read
+/// what came before from here. Lines are in the language of
+/// `Accept-Language` where translated.
+#[utoipa::path(
+    get,
+    path = "/api/rooms/{room_id}/logs",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id"), LogsQuery),
+    responses(
+        (status = 200, description = "The room's latest log lines", body = LogsResponse),
+    )
+)]
+pub async fn get_logs(
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Query(query): Query<LogsQuery>,
+    headers: HeaderMap,
+) -> AppResult<Json<LogsResponse>> {
+    let limit = query.limit.unwrap_or(LOG_LEN).clamp(1, LOG_LEN);
+    let locale = Locale::negotiate(None, &headers);
+    let entries = GameEngine::get_logs(&state.redis, &room_id, limit)
+        .await?
+        .iter()
+        .map(|line| line.render(locale))
+        .collect();
+
+    Ok(Json(LogsResponse { entries }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TradesResponse {
+    /// Oldest first
+    pub trades: Vec<TradeRecord>,
+}
+
+/// Every settled trade offer of a room
+///
+/// Accepted trades are listed for everyone; rejected, countered and expired
+/// offers only for the two players they were between.
+#[utoipa::path(
+    get,
+    path = "/api/rooms/{room_id}/trades",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    security(("player_token" = [])),
+    responses(
+        (status = 200, description = "Trade history as the player may see it", body = TradesResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_trades(
+    auth: auth::PlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+) -> AppResult<Json<TradesResponse>> {
+    let player_id = auth.for_room(&room_id)?;
+    let game = GameEngine::get_game(&state.redis, &room_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+    Ok(Json(TradesResponse {
+        trades: game.view_for(player_id).trades,
     }))
 }
 
 /// Add a bot to the room
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct AddBotRequest {
-    pub difficulty: Option<String>,
+    pub difficulty: Option<BotDifficulty>,
+    pub name: Option<String>,
+    /// One of the curated avatar ids
+    pub avatar: Option<String>,
+}
+
+/// Bots are played by the server, so their seats come without a token
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddBotResponse {
+    pub player_id: Uuid,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/rooms/{room_id}/bot",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    request_body = AddBotRequest,
+    security(("player_token" = [])),
+    responses(
+        (status = 200, description = "Bot added", body = AddBotResponse),
+        (status = 400, description = "Room full, game started or invalid name", body = ErrorResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 403, description = "Not the host", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 503, description = "Server is shutting down", body = ErrorResponse),
+    )
+)]
 pub async fn add_bot(
+    auth: auth::PlayerAuth,
     State(state): State<AppState>,
     Path(room_id): Path<String>,
-    Json(_req): Json<AddBotRequest>,
-) -> AppResult<Json<JoinRoomResponse>> {
-    let player_id = GameEngine::add_bot(&state.redis, &room_id).await?;
-    Ok(Json(JoinRoomResponse { player_id }))
+    Json(req): Json<AddBotRequest>,
+) -> AppResult<Json<AddBotResponse>> {
+    shutdown::ensure_accepting()?;
+    let host_id = auth.for_room(&room_id)?;
+    let difficulty = req.difficulty.unwrap_or_default();
+    let player_id = GameEngine::add_bot(
+        &state.redis,
+        &state.hub,
+        &room_id,
+        host_id,
+        difficulty,
+        req.name.as_deref(),
+        req.avatar.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(AddBotResponse { player_id }))
 }
 
 /// Start the game
+#[utoipa::path(
+    post,
+    path = "/api/rooms/{room_id}/start",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    security(("player_token" = [])),
+    responses(
+        (status = 200, description = "Game started", body = Object, example = json!({ "status": "started" })),
+        (status = 400, description = "Too few players, players not ready or game already started", body = ErrorResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 403, description = "Not the host", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
 pub async fn start_game(
+    auth: auth::PlayerAuth,
     State(state): State<AppState>,
     Path(room_id): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
-    GameEngine::start_game(&state.redis, &state.hub, &room_id).await?;
+    let host_id = auth.for_room(&room_id)?;
+    GameEngine::start_game(&state.redis, &state.hub, &room_id, host_id).await?;
     Ok(Json(serde_json::json!({ "status": "started" })))
 }
 
+/// Room diagnostics for the host
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoomDiagnosticsResponse {
+    pub room_id: String,
+    pub phase: String,
+    pub event_seq: u64,
+    pub turn: Option<TurnDiagnostics>,
+    pub bot_task_running: bool,
+    pub players: Vec<PlayerDiagnostics>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TurnDiagnostics {
+    pub player_id: Uuid,
+    pub phase: String,
+    pub waiting_on_bot: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PlayerDiagnostics {
+    pub player_id: Uuid,
+    pub name: String,
+    pub is_bot: bool,
+    pub bot_controlled: bool,
+    pub connected: bool,
+    pub latency_ms: Option<u64>,
+    pub last_acked_seq: Option<u64>,
+    /// Messages queued for the client's socket
+    pub queue_depth: Option<usize>,
+    /// Events dropped because the client fell behind
+    pub dropped_events: Option<u64>,
+    pub connected_secs: Option<u64>,
+    pub disconnected_secs: Option<u64>,
+    /// Seconds left before a bot takes over for a disconnected player
+    pub takeover_in_secs: Option<u64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rooms/{room_id}/diagnostics",
+    tag = "rooms",
+    params(("room_id" = String, Path, description = "Room id")),
+    security(("player_token" = [])),
+    responses(
+        (status = 200, description = "Connection and bot health of the room", body = RoomDiagnosticsResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 403, description = "Not the host", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_diagnostics(
+    auth: auth::PlayerAuth,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+) -> AppResult<Json<RoomDiagnosticsResponse>> {
+    let player_id = auth.for_room(&room_id)?;
+    let game = GameEngine::get_game(&state.redis, &room_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+    let is_host = game
+        .get_player(player_id)
+        .map(|p| p.is_host)
+        .unwrap_or(false);
+    if !is_host {
+        return Err(AppError::Forbidden(
+            "Only the host can view diagnostics".into(),
+        ));
+    }
+
+    let hub = &state.hub;
+    let connections = hub.connections(&room_id);
+    let grace = game.config.timers().reconnect_grace_secs as u64;
+    let playing = game.phase == GamePhase::Playing;
+
+    let players = game
+        .players
+        .iter()
+        .map(|p| {
+            let conn = connections.iter().find(|c| c.player_id == p.id);
+            let disconnected_secs = hub.disconnected_for(&room_id, p.id).map(|d| d.as_secs());
+            let takeover_in_secs = disconnected_secs
+                .filter(|_| playing && !p.controlled_by_bot())
+                .map(|secs| grace.saturating_sub(secs));
+
+            PlayerDiagnostics {
+                player_id: p.id,
+                name: p.name.clone(),
+                is_bot: p.is_bot,
+                bot_controlled: p.bot_controlled,
+                connected: conn.is_some(),
+                latency_ms: conn.and_then(|c| c.latency_ms),
+                last_acked_seq: conn.map(|c| c.last_acked_seq),
+                queue_depth: conn.map(|c| c.queue_depth),
+                dropped_events: conn.map(|c| c.dropped_events),
+                connected_secs: conn.map(|c| c.connected_for.as_secs()),
+                disconnected_secs,
+                takeover_in_secs,
+            }
+        })
+        .collect();
+
+    let turn = game.turn.as_ref().map(|t| TurnDiagnostics {
+        player_id: t.player_id,
+        phase: format!("{:?}", t.phase),
+        waiting_on_bot: game
+            .get_player(t.player_id)
+            .map(|p| p.controlled_by_bot())
+            .unwrap_or(false),
+    });
+
+    Ok(Json(RoomDiagnosticsResponse {
+        event_seq: hub.current_seq(&room_id),
+        room_id: room_id.clone(),
+        phase: format!("{:?}", game.phase),
+        turn,
+        bot_task_running: GameEngine::bot_task_running(&room_id),
+        players,
+    }))
+}
+
 /// Health check
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "Server is up", body = String, content_type = "text/plain"))
+)]
 pub async fn health() -> &'static str {
     "OK"
 }
+
+/// How long a datastore may take to answer a readiness probe
+const READY_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    /// The server is shutting down and takes no new work
+    pub draining: bool,
+    pub postgres: DependencyStatus,
+    pub redis: DependencyStatus,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependencyStatus {
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Readiness check: answers 503 unless Postgres and Redis both respond and
+/// the server is not shutting down
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Both datastores respond", body = ReadinessResponse),
+        (status = 503, description = "A datastore is down or slow, or the server is shutting down", body = ReadinessResponse),
+    )
+)]
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let mut redis = state.redis.clone();
+    let ping = redis::cmd("PING");
+    let (postgres, redis) = tokio::join!(
+        probe(sqlx::query("SELECT 1").execute(&state.db)),
+        probe(ping.query_async::<_, String>(&mut redis)),
+    );
+
+    let draining = shutdown::is_draining();
+    let ready = postgres.ok && redis.ok && !draining;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(ReadinessResponse {
+            ready,
+            draining,
+            postgres,
+            redis,
+        }),
+    )
+}
+
+/// Time a datastore round trip, giving up after `READY_TIMEOUT`
+async fn probe<T, E: std::fmt::Display>(
+    check: impl Future<Output = Result<T, E>>,
+) -> DependencyStatus {
+    let started = Instant::now();
+    let error = match tokio::time::timeout(READY_TIMEOUT, check).await {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some("Timed out".to_string()),
+    };
+
+    DependencyStatus {
+        ok: error.is_none(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
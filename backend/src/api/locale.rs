@@ -0,0 +1,30 @@
+//! Error responses in the client's language
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body, extract::Request, http::header::CONTENT_LENGTH, middleware::Next,
+    response::Response,
+};
+
+use crate::error::AppError;
+use crate::i18n::Locale;
+
+/// Rewrite error bodies in the language of `Accept-Language`
+///
+/// Errors render in English; the error itself travels with the response, so
+/// its body is rebuilt here for other languages.
+pub async fn localize_errors(request: Request, next: Next) -> Response {
+    let locale = Locale::negotiate(None, request.headers());
+    let response = next.run(request).await;
+    if locale == Locale::En {
+        return response;
+    }
+    let Some(error) = response.extensions().get::<Arc<AppError>>().cloned() else {
+        return response;
+    };
+
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(error.body(locale).to_string()))
+}
@@ -1,16 +1,43 @@
 //! API module - HTTP handlers and routes
 
-mod handlers;
+mod accounts;
+mod admin;
+pub(crate) mod auth;
+mod bans;
+mod boards;
+mod calibrations;
+mod challenges;
+mod cors;
+mod friends;
+mod games;
+pub(crate) mod handlers;
+mod locale;
+mod oauth;
+mod openapi;
+mod presets;
+mod rate_limit;
+mod reports;
 mod routes;
+mod session_store;
+mod simulations;
+mod users;
 
 use std::sync::Arc;
 
 use redis::aio::ConnectionManager;
 use sqlx::PgPool;
-use tokio::sync::RwLock;
 
-use crate::{config::Config, ws::Hub};
+use crate::{
+    config::{Config, LiveTunables},
+    ws::Hub,
+};
 
+pub(crate) use bans::ensure_not_banned;
+pub(crate) use cors::check_origin;
+pub use cors::cors_layer;
+#[cfg(feature = "grpc")]
+pub(crate) use rate_limit::client_ip;
+pub(crate) use rate_limit::ClientIp;
 pub use routes::routes;
 
 /// Shared application state
@@ -18,6 +45,8 @@ pub use routes::routes;
 pub struct AppState {
     pub db: PgPool,
     pub redis: ConnectionManager,
-    pub hub: Arc<RwLock<Hub>>,
+    pub hub: Arc<Hub>,
     pub config: Config,
+    /// Settings that may be reloaded while running
+    pub tunables: LiveTunables,
 }
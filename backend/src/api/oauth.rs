@@ -0,0 +1,333 @@
+//! Sign-in through Google and Discord
+//!
+//! `start` hands the client the provider's consent page; the provider sends
+//! the browser back to `callback`, which signs in the user owning the
+//! provider account, creating one on first sight. Starting while signed in
+//! links the provider account to the current user instead.
+//!
+//! The CSRF state and PKCE verifier wait in Redis between the two steps, so
+//! any instance can finish a flow another one started.
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use oauth2::{
+    basic::BasicClient, reqwest::async_http_client, AuthUrl, AuthorizationCode, ClientId,
+    ClientSecret, CsrfToken, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope,
+    TokenResponse, TokenUrl,
+};
+use once_cell::sync::Lazy;
+use rand::Rng;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::{
+    accounts::{self, SessionResponse},
+    auth::AuthUser,
+    openapi::ErrorResponse,
+    AppState, ClientIp,
+};
+use crate::config::{Config, OAuthClient};
+use crate::db::{
+    identities,
+    users::{self, User},
+};
+use crate::error::{AppError, AppResult};
+use crate::game::store;
+
+/// Time the user has to get through the provider's consent page
+const STATE_TTL_SECS: u64 = 600;
+
+/// Attempts at a free username before giving up
+const USERNAME_ATTEMPTS: usize = 5;
+
+static HTTP: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Provider {
+    Google,
+    Discord,
+}
+
+impl Provider {
+    fn parse(name: &str) -> AppResult<Self> {
+        match name {
+            "google" => Ok(Provider::Google),
+            "discord" => Ok(Provider::Discord),
+            _ => Err(AppError::NotFound(format!("Unknown provider {}", name))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Provider::Google => "google",
+            Provider::Discord => "discord",
+        }
+    }
+
+    fn credentials(self, config: &Config) -> Option<&OAuthClient> {
+        match self {
+            Provider::Google => config.google_oauth.as_ref(),
+            Provider::Discord => config.discord_oauth.as_ref(),
+        }
+    }
+
+    fn auth_url(self) -> &'static str {
+        match self {
+            Provider::Google => "https://accounts.google.com/o/oauth2/v2/auth",
+            Provider::Discord => "https://discord.com/oauth2/authorize",
+        }
+    }
+
+    fn token_url(self) -> &'static str {
+        match self {
+            Provider::Google => "https://oauth2.googleapis.com/token",
+            Provider::Discord => "https://discord.com/api/oauth2/token",
+        }
+    }
+
+    fn scopes(self) -> &'static [&'static str] {
+        match self {
+            Provider::Google => &["openid", "profile"],
+            Provider::Discord => &["identify"],
+        }
+    }
+
+    fn profile_url(self) -> &'static str {
+        match self {
+            Provider::Google => "https://openidconnect.googleapis.com/v1/userinfo",
+            Provider::Discord => "https://discord.com/api/users/@me",
+        }
+    }
+
+    fn client(self, config: &Config) -> AppResult<BasicClient> {
+        let credentials = self.credentials(config).ok_or_else(|| {
+            AppError::NotFound(format!("Sign-in with {} is not enabled", self.name()))
+        })?;
+        let redirect = format!(
+            "{}/api/auth/oauth/{}/callback",
+            config.public_url,
+            self.name()
+        );
+
+        let url_error = |e: oauth2::url::ParseError| AppError::Internal(e.into());
+        Ok(BasicClient::new(
+            ClientId::new(credentials.client_id.clone()),
+            Some(ClientSecret::new(credentials.client_secret.clone())),
+            AuthUrl::new(self.auth_url().into()).map_err(url_error)?,
+            Some(TokenUrl::new(self.token_url().into()).map_err(url_error)?),
+        )
+        .set_redirect_uri(RedirectUrl::new(redirect).map_err(url_error)?))
+    }
+
+    /// Stable account id and display name from the profile endpoint
+    fn identity(self, profile: &serde_json::Value) -> Option<(String, String)> {
+        let field = |name: &str| profile.get(name).and_then(|v| v.as_str());
+        let (subject, name) = match self {
+            Provider::Google => (field("sub")?, field("name")),
+            Provider::Discord => (field("id")?, field("global_name").or(field("username"))),
+        };
+        Some((subject.to_string(), name.unwrap_or_default().to_string()))
+    }
+}
+
+/// What `callback` needs to finish a flow
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingLogin {
+    provider: Provider,
+    pkce_verifier: String,
+    /// Link to this user rather than sign in
+    link_user: Option<Uuid>,
+}
+
+fn state_key(csrf: &str) -> String {
+    store::layout().oauth_state_key(csrf)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OAuthStartResponse {
+    /// Send the browser here
+    pub authorize_url: String,
+}
+
+/// Begin signing in with a provider
+#[utoipa::path(
+    get,
+    path = "/api/auth/oauth/{provider}/start",
+    tag = "auth",
+    params(("provider" = String, Path, description = "`google` or `discord`")),
+    security((), ("user_token" = [])),
+    responses(
+        (status = 200, description = "Consent page of the provider; with an access token the provider account is linked to that user", body = OAuthStartResponse),
+        (status = 404, description = "Unknown or disabled provider", body = ErrorResponse),
+    )
+)]
+pub async fn start(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    user: Option<AuthUser>,
+) -> AppResult<Json<OAuthStartResponse>> {
+    let provider = Provider::parse(&provider)?;
+    let client = provider.client(&state.config)?;
+
+    let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+    let (authorize_url, csrf) = client
+        .authorize_url(CsrfToken::new_random)
+        .add_scopes(provider.scopes().iter().map(|s| Scope::new(s.to_string())))
+        .set_pkce_challenge(challenge)
+        .url();
+
+    let pending = PendingLogin {
+        provider,
+        pkce_verifier: verifier.secret().clone(),
+        link_user: user.map(|user| user.id),
+    };
+    let json = serde_json::to_string(&pending).map_err(|e| AppError::Internal(e.into()))?;
+    let mut conn = state.redis.clone();
+    let _: () = conn
+        .set_ex(state_key(csrf.secret()), json, STATE_TTL_SECS)
+        .await?;
+
+    Ok(Json(OAuthStartResponse {
+        authorize_url: authorize_url.to_string(),
+    }))
+}
+
+/// Where the provider sends the browser back
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: String,
+    /// Set by the provider when the user declined
+    pub error: Option<String>,
+}
+
+/// Finish signing in with a provider
+#[utoipa::path(
+    get,
+    path = "/api/auth/oauth/{provider}/callback",
+    tag = "auth",
+    params(("provider" = String, Path, description = "`google` or `discord`"), CallbackQuery),
+    responses(
+        (status = 200, description = "Signed in, creating an account on first use", body = SessionResponse),
+        (status = 400, description = "Unknown or expired state, declined consent, or provider account linked elsewhere", body = ErrorResponse),
+    )
+)]
+pub async fn callback(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    Query(query): Query<CallbackQuery>,
+    ClientIp(ip): ClientIp,
+) -> AppResult<Json<SessionResponse>> {
+    let provider = Provider::parse(&provider)?;
+
+    // Taken whatever happens next, so a state is only ever good once
+    let mut conn = state.redis.clone();
+    let pending: Option<String> = conn.get_del(state_key(&query.state)).await?;
+    let pending: PendingLogin = pending
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .filter(|pending: &PendingLogin| pending.provider == provider)
+        .ok_or_else(|| AppError::BadRequest("Sign-in expired, please start again".into()))?;
+
+    if let Some(error) = query.error {
+        return Err(AppError::BadRequest(format!(
+            "Sign-in was not completed: {}",
+            error
+        )));
+    }
+    let code = query
+        .code
+        .ok_or_else(|| AppError::BadRequest("Missing authorization code".into()))?;
+
+    let token = provider
+        .client(&state.config)?
+        .exchange_code(AuthorizationCode::new(code))
+        .set_pkce_verifier(PkceCodeVerifier::new(pending.pkce_verifier))
+        .request_async(async_http_client)
+        .await
+        .map_err(|e| {
+            tracing::warn!("{} code exchange failed: {}", provider.name(), e);
+            AppError::BadRequest("The provider rejected the sign-in".into())
+        })?;
+
+    let (subject, display_name) = fetch_identity(provider, token.access_token().secret()).await?;
+
+    let user = match (
+        identities::find_user(&state.db, provider.name(), &subject).await?,
+        pending.link_user,
+    ) {
+        (Some(user), Some(link_user)) if user.id != link_user => {
+            return Err(AppError::BadRequest(
+                "This provider account is already linked to another user".into(),
+            ));
+        }
+        (Some(user), _) => user,
+        (None, Some(link_user)) => {
+            identities::link(&state.db, provider.name(), &subject, link_user).await?;
+            tracing::info!("Linked {} account to user {}", provider.name(), link_user);
+            users::get_user(&state.db, link_user)
+                .await?
+                .ok_or(AppError::Unauthorized)?
+        }
+        (None, None) => create_user(&state, provider, &subject, &display_name).await?,
+    };
+
+    accounts::start_session(&state, user, ip.as_deref())
+        .await
+        .map(Json)
+}
+
+async fn fetch_identity(provider: Provider, access_token: &str) -> AppResult<(String, String)> {
+    let unavailable = |e: reqwest::Error| {
+        tracing::warn!("{} profile request failed: {}", provider.name(), e);
+        AppError::BadRequest("Could not read the provider profile".into())
+    };
+
+    let profile: serde_json::Value = HTTP
+        .get(provider.profile_url())
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(unavailable)?
+        .json()
+        .await
+        .map_err(unavailable)?;
+
+    provider
+        .identity(&profile)
+        .ok_or_else(|| AppError::BadRequest("Could not read the provider profile".into()))
+}
+
+/// New account named after the provider profile, with a suffix if taken
+async fn create_user(
+    state: &AppState,
+    provider: Provider,
+    subject: &str,
+    display_name: &str,
+) -> AppResult<User> {
+    let base = accounts::username_from(display_name);
+
+    for attempt in 0..USERNAME_ATTEMPTS {
+        let username = if attempt == 0 {
+            base.clone()
+        } else {
+            format!("{}_{}", base, rand::thread_rng().gen_range(1000..10000))
+        };
+
+        if let Some(user) =
+            identities::create_user(&state.db, &username, provider.name(), subject).await?
+        {
+            tracing::info!("Registered user {} via {}", user.username, provider.name());
+            return Ok(user);
+        }
+    }
+
+    Err(AppError::BadRequest(
+        "Could not find a free username, please register instead".into(),
+    ))
+}
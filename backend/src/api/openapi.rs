@@ -0,0 +1,112 @@
+//! OpenAPI description of the public REST API
+//!
+//! Served as `/api/openapi.json` with Swagger UI at `/api/docs`. Admin and
+//! WebSocket endpoints are documented in the README only.
+
+use serde::Serialize;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi, ToSchema};
+
+use super::{
+    accounts, boards, challenges, friends, games, handlers, oauth, presets, reports, users,
+};
+use crate::error::FieldError;
+
+/// Body of every error response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    /// Whether the same request may succeed if sent again; such responses
+    /// also carry `Retry-After`
+    pub retryable: bool,
+    /// Each offending field of the request body, when it failed validation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<FieldError>>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Mo-De API", description = "Rooms, lobby and game state for Mo-De"),
+    paths(
+        handlers::health,
+        handlers::ready,
+        handlers::create_room,
+        handlers::get_room,
+        handlers::join_room,
+        handlers::add_bot,
+        handlers::start_game,
+        handlers::rematch,
+        handlers::renew_token,
+        handlers::close_room,
+        handlers::set_lifetime,
+        handlers::pick_color,
+        handlers::pick_piece,
+        handlers::get_state,
+        handlers::submit_action,
+        handlers::get_chat,
+        handlers::get_logs,
+        handlers::get_trades,
+        handlers::get_summary,
+        handlers::get_diagnostics,
+        reports::create_report,
+        accounts::register,
+        accounts::login,
+        accounts::refresh,
+        accounts::logout,
+        accounts::me,
+        oauth::start,
+        oauth::callback,
+        users::get_me,
+        users::update_me,
+        users::get_stats,
+        users::get_games,
+        users::get_achievements,
+        games::get_summary,
+        challenges::list_challenges,
+        challenges::claim,
+        challenges::list_rewards,
+        friends::list_friends,
+        friends::request_friend,
+        friends::accept_friend,
+        friends::remove_friend,
+        friends::invite,
+        friends::list_invites,
+        friends::accept_invite,
+        friends::decline_invite,
+        boards::list_boards,
+        boards::get_board,
+        presets::list_presets,
+    ),
+    components(schemas(ErrorResponse, FieldError)),
+    modifiers(&BearerTokens),
+    tags(
+        (name = "rooms", description = "Creating, joining and following rooms"),
+        (name = "auth", description = "Registered accounts"),
+        (name = "users", description = "Profiles, statistics and achievements of registered users"),
+        (name = "friends", description = "Friends and room invites"),
+        (name = "challenges", description = "Daily and weekly challenges and their rewards"),
+        (name = "boards", description = "Board definitions"),
+        (name = "health", description = "Liveness and readiness"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Player tokens from create and join, and user session tokens from login
+struct BearerTokens;
+
+impl Modify for BearerTokens {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        for name in ["player_token", "user_token"] {
+            components.add_security_scheme(
+                name,
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            );
+        }
+    }
+}
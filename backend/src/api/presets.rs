@@ -0,0 +1,37 @@
+//! Room presets, so clients can offer them by name
+
+use axum::Json;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::game::{presets::Preset, GameConfig};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PresetInfo {
+    /// Sent as `preset` when creating a room
+    pub id: Preset,
+    pub name: &'static str,
+    /// The default config with the preset applied; a turn limit left unset
+    /// takes the server's default
+    pub config: GameConfig,
+}
+
+/// Presets a room can be created with
+#[utoipa::path(
+    get,
+    path = "/api/presets",
+    tag = "rooms",
+    responses((status = 200, description = "Available presets", body = [PresetInfo]))
+)]
+pub async fn list_presets() -> Json<Vec<PresetInfo>> {
+    let presets = Preset::ALL
+        .into_iter()
+        .map(|preset| PresetInfo {
+            id: preset,
+            name: preset.name(),
+            config: preset.config(),
+        })
+        .collect();
+
+    Json(presets)
+}
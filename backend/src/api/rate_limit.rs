@@ -0,0 +1,134 @@
+//! Per-client rate limiting for REST routes
+//!
+//! Fixed windows counted in Redis, so every instance enforces the same
+//! budget. Clients are told apart by player, user (or admin) token when they send
+//! a valid one and by address otherwise, so made-up tokens buy nothing. If
+//! Redis is unreachable requests are let through.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::request::Parts,
+    http::{header::AUTHORIZATION, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use redis::aio::ConnectionManager;
+
+use super::{auth, AppState};
+use crate::config::{Config, LiveTunables, RateLimit, Tunables};
+use crate::error::AppError;
+use crate::game::store;
+
+/// Limit applied to one group of routes
+#[derive(Clone)]
+pub struct Limiter {
+    pub redis: ConnectionManager,
+    /// Keeps the counters of different groups apart
+    pub group: &'static str,
+    /// Picks the group's limit out of the current tunables
+    pub limit: fn(&Tunables) -> Option<RateLimit>,
+    pub tunables: LiveTunables,
+    pub config: Arc<Config>,
+}
+
+/// Middleware counting the request against the client's budget
+pub async fn limit(State(limiter): State<Limiter>, req: Request, next: Next) -> Response {
+    let Some(limit) = (limiter.limit)(&limiter.tunables.current()) else {
+        return next.run(req).await;
+    };
+    let client = client_key(&req, &limiter.config);
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let window = now / limit.window_secs;
+    let key = store::layout().rate_limit_key(limiter.group, &client, window);
+
+    let mut conn = limiter.redis.clone();
+    let counted: redis::RedisResult<(u64,)> = redis::pipe()
+        .atomic()
+        .incr(&key, 1)
+        .expire(&key, limit.window_secs as i64)
+        .ignore()
+        .query_async(&mut conn)
+        .await;
+
+    match counted {
+        Ok((count,)) if count > limit.max_requests => {
+            let retry_after = (window + 1) * limit.window_secs - now;
+            AppError::RateLimited(retry_after.max(1)).into_response()
+        }
+        Ok(_) => next.run(req).await,
+        Err(e) => {
+            tracing::warn!("Rate limit check failed, allowing request: {}", e);
+            next.run(req).await
+        }
+    }
+}
+
+/// Who the request counts against
+fn client_key(req: &Request, config: &Config) -> String {
+    if let Some(token) = bearer(req.headers()) {
+        if config.admin_token.as_deref() == Some(token) {
+            return "admin".into();
+        }
+        if let Some(player_id) = auth::player_in_token(config, token) {
+            return format!("player:{}", player_id);
+        }
+        if let Some(user_id) = auth::user_in_token(config, token) {
+            return format!("user:{}", user_id);
+        }
+    }
+
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr);
+    let ip = client_ip(config, req.headers(), peer);
+
+    format!("ip:{}", ip.unwrap_or_else(|| "unknown".into()))
+}
+
+/// IP address of the client, as `client_ip` tells it; `None` when unknown
+pub struct ClientIp(pub Option<String>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Infallible> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| *addr);
+        Ok(ClientIp(client_ip(&state.config, &parts.headers, peer)))
+    }
+}
+
+/// IP address of the client: the first `X-Forwarded-For` entry behind a
+/// trusted proxy, the peer address otherwise
+pub(crate) fn client_ip(
+    config: &Config,
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+) -> Option<String> {
+    let forwarded = config
+        .trust_proxy
+        .then(|| headers.get("x-forwarded-for"))
+        .flatten()
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(|ip| ip.trim().to_string());
+
+    forwarded.or_else(|| peer.map(|addr| addr.ip().to_string()))
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
@@ -0,0 +1,205 @@
+//! Player reports and their review by moderators
+//!
+//! Any seated player may report another human in the same room. The report
+//! keeps both names as they were, and on request the target's latest chat
+//! messages from the archive, so it can still be judged once the room is
+//! gone.
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    auth::{AdminAuth, PlayerAuth},
+    openapi::ErrorResponse,
+    AppState,
+};
+use crate::db::{
+    self,
+    reports::{NewReport, Report, ReportCategory, ReportStatus, ReportedPlayer},
+};
+use crate::error::{AppError, AppResult, FieldErrors};
+use crate::game::{GameEngine, Player};
+
+/// Longest comment kept with a report or its resolution
+const MAX_COMMENT_LEN: usize = 1000;
+/// Messages of the target kept as the chat excerpt
+const CHAT_EXCERPT_LEN: usize = 20;
+
+const DEFAULT_REPORT_PAGE: usize = 50;
+const MAX_REPORT_PAGE: usize = 500;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateReportRequest {
+    /// Room both players are seated in; must match the player token
+    pub room_id: String,
+    /// Player id of the reported player
+    pub target_id: Uuid,
+    pub category: ReportCategory,
+    pub comment: Option<String>,
+    /// Attach the target's latest messages in the room
+    #[serde(default)]
+    pub include_chat: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateReportResponse {
+    pub report_id: Uuid,
+}
+
+fn trimmed_comment<'a>(
+    errors: &mut FieldErrors,
+    field: &str,
+    comment: Option<&'a str>,
+) -> Option<&'a str> {
+    let comment = comment.map(str::trim).filter(|c| !c.is_empty());
+    if comment.is_some_and(|c| c.chars().count() > MAX_COMMENT_LEN) {
+        errors.add(
+            field,
+            "too_long",
+            format!("{} must be at most {} characters", field, MAX_COMMENT_LEN),
+        );
+    }
+    comment
+}
+
+fn reported(player: &Player) -> ReportedPlayer {
+    ReportedPlayer {
+        player_id: player.id,
+        user_id: player.user_id,
+        name: player.name.clone(),
+    }
+}
+
+/// Report another player in the room for moderators to review
+#[utoipa::path(
+    post,
+    path = "/api/reports",
+    tag = "rooms",
+    request_body = CreateReportRequest,
+    security(("player_token" = [])),
+    responses(
+        (status = 200, description = "Report filed", body = CreateReportResponse),
+        (status = 400, description = "Reporting yourself or a bot, or comment too long", body = ErrorResponse),
+        (status = 401, description = "Missing token or token for another room", body = ErrorResponse),
+        (status = 403, description = "Not a player in this game", body = ErrorResponse),
+        (status = 404, description = "Room or player not found", body = ErrorResponse),
+        (status = 409, description = "An open report on this player already exists", body = ErrorResponse),
+    )
+)]
+pub async fn create_report(
+    auth: PlayerAuth,
+    State(state): State<AppState>,
+    Json(req): Json<CreateReportRequest>,
+) -> AppResult<Json<CreateReportResponse>> {
+    let player_id = auth.for_room(&req.room_id)?;
+
+    let mut errors = FieldErrors::new();
+    let comment = trimmed_comment(&mut errors, "comment", req.comment.as_deref());
+    errors.into_result()?;
+
+    let game = GameEngine::get_game(&state.redis, &req.room_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+    let reporter = game
+        .get_player(player_id)
+        .ok_or_else(|| AppError::Forbidden("Not a player in this game".into()))?;
+    let target = game
+        .get_player(req.target_id)
+        .ok_or_else(|| AppError::NotFound("Player not found".into()))?;
+    if target.id == reporter.id {
+        return Err(AppError::BadRequest("You cannot report yourself".into()));
+    }
+    if target.is_bot {
+        return Err(AppError::BadRequest("Bots cannot be reported".into()));
+    }
+
+    let chat_excerpt = if req.include_chat {
+        Some(db::chat::by_player(&state.db, &req.room_id, target.id, CHAT_EXCERPT_LEN).await?)
+    } else {
+        None
+    };
+
+    let report = db::reports::create(
+        &state.db,
+        NewReport {
+            room_id: &req.room_id,
+            reporter: reported(reporter),
+            target: reported(target),
+            category: req.category,
+            comment,
+            chat_excerpt: chat_excerpt.as_deref(),
+        },
+    )
+    .await?;
+
+    tracing::info!(
+        "Player {} reported {} in room {} for {}",
+        reporter.id,
+        target.id,
+        req.room_id,
+        req.category.name()
+    );
+    Ok(Json(CreateReportResponse {
+        report_id: report.id,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportsQuery {
+    /// `open`, `resolved` or `dismissed`; every report when unset
+    pub status: Option<ReportStatus>,
+    pub limit: Option<usize>,
+}
+
+/// Reports, newest first
+pub async fn list_reports(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<ReportsQuery>,
+) -> AppResult<Json<Vec<Report>>> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_REPORT_PAGE)
+        .clamp(1, MAX_REPORT_PAGE);
+    Ok(Json(
+        db::reports::list(&state.db, query.status, limit).await?,
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveReportRequest {
+    /// `resolved` when acted upon, `dismissed` otherwise
+    pub status: ReportStatus,
+    pub note: Option<String>,
+}
+
+/// Close an open report
+pub async fn resolve_report(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(report_id): Path<Uuid>,
+    Json(req): Json<ResolveReportRequest>,
+) -> AppResult<Json<Report>> {
+    let mut errors = FieldErrors::new();
+    if req.status == ReportStatus::Open {
+        errors.add("status", "invalid", "status must be resolved or dismissed");
+    }
+    let note = trimmed_comment(&mut errors, "note", req.note.as_deref());
+    errors.into_result()?;
+
+    match db::reports::resolve(&state.db, report_id, req.status, note).await? {
+        Some(report) => {
+            tracing::info!("Admin {} report {}", req.status.name(), report_id);
+            Ok(Json(report))
+        }
+        None if db::reports::exists(&state.db, report_id).await? => {
+            Err(AppError::Conflict("Report is already closed".into()))
+        }
+        None => Err(AppError::NotFound("Report not found".into())),
+    }
+}
@@ -1,23 +1,168 @@
 //! Route definitions
 
+use std::sync::Arc;
+
 use axum::{
-    routing::{get, post},
+    middleware,
+    routing::{delete, get, post, put},
     Router,
 };
 
-use super::{handlers, AppState};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use super::locale;
+use super::rate_limit::{self, Limiter};
+use super::{
+    accounts, admin, bans, boards, calibrations, challenges, friends, games, handlers, oauth,
+    openapi::ApiDoc, presets, reports, simulations, users, AppState,
+};
+use crate::config::{Config, RateLimit, Tunables};
 use crate::ws;
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        // Health check
-        .route("/health", get(handlers::health))
-        // Room management
+pub fn routes(state: &AppState) -> Router<AppState> {
+    let config = Arc::new(state.config.clone());
+
+    // Unauthenticated and the cheapest way to flood the server or guess
+    // passwords
+    let rooms = Router::new()
         .route("/api/rooms", post(handlers::create_room))
-        .route("/api/rooms/:room_id", get(handlers::get_room))
         .route("/api/rooms/:room_id/join", post(handlers::join_room))
         .route("/api/rooms/:room_id/bot", post(handlers::add_bot))
+        .route("/api/rooms/:room_id/rematch", post(handlers::rematch))
+        .route("/api/auth/register", post(accounts::register))
+        .route("/api/auth/login", post(accounts::login))
+        .route("/api/auth/oauth/:provider/start", get(oauth::start))
+        .route("/api/auth/oauth/:provider/callback", get(oauth::callback));
+
+    let api = Router::new()
+        // Accounts
+        .route("/api/auth/refresh", post(accounts::refresh))
+        .route("/api/auth/me", get(accounts::me))
+        .route("/api/auth/logout", post(accounts::logout))
+        .route("/api/users/me", get(users::get_me).patch(users::update_me))
+        .route("/api/users/:user_id/stats", get(users::get_stats))
+        .route("/api/users/:user_id/games", get(users::get_games))
+        .route(
+            "/api/users/:user_id/achievements",
+            get(users::get_achievements),
+        )
+        .route("/api/games/:game_id/summary", get(games::get_summary))
+        // Challenges
+        .route("/api/challenges", get(challenges::list_challenges))
+        .route(
+            "/api/challenges/:challenge_id/claim",
+            post(challenges::claim),
+        )
+        .route("/api/users/me/rewards", get(challenges::list_rewards))
+        // Friends and invites
+        .route("/api/friends", get(friends::list_friends))
+        .route("/api/friends/requests", post(friends::request_friend))
+        .route("/api/friends/:user_id", delete(friends::remove_friend))
+        .route("/api/friends/:user_id/accept", post(friends::accept_friend))
+        .route("/api/rooms/:room_id/invites", post(friends::invite))
+        .route("/api/invites", get(friends::list_invites))
+        .route("/api/invites/:invite_id", delete(friends::decline_invite))
+        .route(
+            "/api/invites/:invite_id/accept",
+            post(friends::accept_invite),
+        )
+        // Room management
+        .route("/api/rooms/:room_id", get(handlers::get_room))
         .route("/api/rooms/:room_id/start", post(handlers::start_game))
+        .route("/api/rooms/:room_id/close", post(handlers::close_room))
+        .route("/api/rooms/:room_id/token", post(handlers::renew_token))
+        .route("/api/rooms/:room_id/lifetime", put(handlers::set_lifetime))
+        .route("/api/rooms/:room_id/color", put(handlers::pick_color))
+        .route("/api/rooms/:room_id/piece", put(handlers::pick_piece))
+        .route("/api/rooms/:room_id/state", get(handlers::get_state))
+        .route("/api/rooms/:room_id/actions", post(handlers::submit_action))
+        .route("/api/rooms/:room_id/chat", get(handlers::get_chat))
+        .route("/api/rooms/:room_id/logs", get(handlers::get_logs))
+        .route("/api/rooms/:room_id/trades", get(handlers::get_trades))
+        .route("/api/rooms/:room_id/summary", get(handlers::get_summary))
+        .route(
+            "/api/rooms/:room_id/diagnostics",
+            get(handlers::get_diagnostics),
+        )
+        .route("/api/reports", post(reports::create_report))
+        // Board definitions
+        .route("/api/boards", get(boards::list_boards))
+        .route("/api/boards/:board_id", get(boards::get_board))
+        .route("/api/presets", get(presets::list_presets))
+        // Room inspection and shutdown (admin)
+        .route("/admin/rooms", get(admin::list_rooms))
+        .route(
+            "/admin/rooms/:room_id",
+            get(admin::get_room).delete(admin::delete_room),
+        )
+        .route("/admin/rooms/:room_id/end", post(admin::end_room))
+        .route("/admin/rooms/:room_id/command", post(admin::run_command))
+        .route("/admin/rooms/:room_id/export", get(admin::export_room))
+        .route("/admin/rooms/:room_id/state", put(admin::import_room))
+        .route(
+            "/admin/rooms/:room_id/shadow-mute",
+            post(admin::shadow_mute),
+        )
+        .route("/admin/audit", get(admin::list_audit))
+        .route("/admin/integrity", get(admin::list_integrity_flags))
+        .route("/admin/bans", get(bans::list_bans).post(bans::create_ban))
+        .route("/admin/bans/:ban_id", delete(bans::delete_ban))
+        .route("/admin/reports", get(reports::list_reports))
+        .route(
+            "/admin/reports/:report_id/resolve",
+            post(reports::resolve_report),
+        )
+        .route("/admin/maintenance", get(admin::get_maintenance))
+        .route("/admin/config", get(admin::get_tunables))
+        .route("/admin/config/reload", post(admin::reload_tunables))
+        // Offline bot simulations (admin)
+        .route("/api/simulations", post(simulations::create_simulation))
+        .route("/api/simulations/:job_id", get(simulations::get_simulation))
+        // Bot difficulty calibration
+        .route(
+            "/api/bots/difficulties",
+            get(calibrations::get_difficulties),
+        )
+        .route("/api/calibrations", post(calibrations::create_calibration))
+        .route(
+            "/api/calibrations/:run_id",
+            get(calibrations::get_calibration),
+        );
+
+    Router::new()
+        // Health check
+        .route("/health", get(handlers::health))
+        .route("/health/ready", get(handlers::ready))
+        .merge(limited(rooms, state, &config, "rooms", |t| {
+            t.rate_limit_rooms
+        }))
+        .merge(limited(api, state, &config, "api", |t| t.rate_limit_api))
         // WebSocket
+        .route("/ws/:room_id/spectate", get(ws::spectate_handler))
         .route("/ws/:room_id/:player_id", get(ws::handler))
+        // Server-Sent Events where WebSockets are blocked
+        .route("/api/rooms/:room_id/events", get(ws::events_handler))
+        // API docs
+        .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", ApiDoc::openapi()))
+        .layer(middleware::from_fn(locale::localize_errors))
+}
+
+/// Put a group of routes behind its own rate limit, checked on every request
+/// since a reload may turn it on or off
+fn limited(
+    router: Router<AppState>,
+    state: &AppState,
+    config: &Arc<Config>,
+    group: &'static str,
+    limit: fn(&Tunables) -> Option<RateLimit>,
+) -> Router<AppState> {
+    let limiter = Limiter {
+        redis: state.redis.clone(),
+        group,
+        limit,
+        tunables: state.tunables.clone(),
+        config: config.clone(),
+    };
+    router.route_layer(middleware::from_fn_with_state(limiter, rate_limit::limit))
 }
@@ -0,0 +1,172 @@
+//! Server-side sessions behind every token
+//!
+//! Each access and player token names a session kept in Redis: who it
+//! belongs to (a registered user, or a guest), the seat taken under it in
+//! each room, and when it lapses. The REST extractors, the game WebSocket,
+//! the event stream and gRPC all look the session up on every use, which
+//! renews it, so one revocation covers every transport: ending a session
+//! deletes it.
+//!
+//! Signed-in users share the id of their `user_sessions` row, so the
+//! session lives as long as its refresh token is used. Guests get a session
+//! of their own when they first take a seat.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::game::store;
+
+/// Renews a live session and reads it in one step, so an expired session is
+/// never brought back
+static RENEW: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r"
+        local ttl = redis.call('HGET', KEYS[1], 'ttl')
+        if not ttl then
+            return {}
+        end
+        redis.call('EXPIRE', KEYS[1], ttl)
+        redis.call('HSET', KEYS[1], 'expires_at', tonumber(ARGV[1]) + tonumber(ttl))
+        return redis.call('HGETALL', KEYS[1])
+        ",
+    )
+});
+
+/// Seats are recorded under `room:<room_id>` in the session's hash
+const SEAT_FIELD: &str = "room:";
+
+/// A live session
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Registered user the session belongs to; guests have none
+    pub user_id: Option<Uuid>,
+    /// Player seated under the session, by room
+    pub seats: HashMap<String, Uuid>,
+    /// Unix time the session lapses unless it is used again
+    pub expires_at: i64,
+}
+
+/// Start or refresh the session `session_id`, lapsing `ttl_secs` after its
+/// last use
+pub async fn open(
+    redis: &ConnectionManager,
+    session_id: Uuid,
+    user_id: Option<Uuid>,
+    ttl_secs: i64,
+) -> AppResult<()> {
+    let key = store::layout().session_key(session_id);
+    let expires_at = chrono::Utc::now().timestamp() + ttl_secs;
+    let mut fields = vec![
+        ("ttl", ttl_secs.to_string()),
+        ("expires_at", expires_at.to_string()),
+    ];
+    if let Some(user_id) = user_id {
+        fields.push(("user_id", user_id.to_string()));
+    }
+
+    let mut conn = redis.clone();
+    let _: () = redis::pipe()
+        .atomic()
+        .hset_multiple(&key, &fields)
+        .ignore()
+        .expire(&key, ttl_secs)
+        .ignore()
+        .query_async(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Record the seat taken under a session
+///
+/// The room also remembers which session each of its players sits under,
+/// so tokens handed out on their behalf stay tied to it.
+pub async fn add_seat(
+    redis: &ConnectionManager,
+    session_id: Uuid,
+    room_id: &str,
+    player_id: Uuid,
+    room_ttl_secs: i64,
+) -> AppResult<()> {
+    let layout = store::layout();
+    let room_key = layout.room_sessions_key(room_id);
+
+    let mut conn = redis.clone();
+    let _: () = redis::pipe()
+        .atomic()
+        .hset(
+            layout.session_key(session_id),
+            format!("{}{}", SEAT_FIELD, room_id),
+            player_id.to_string(),
+        )
+        .ignore()
+        .hset(&room_key, player_id.to_string(), session_id.to_string())
+        .ignore()
+        .expire(&room_key, room_ttl_secs)
+        .ignore()
+        .query_async(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Session a player of a room took their seat under
+pub async fn seated_under(
+    redis: &ConnectionManager,
+    room_id: &str,
+    player_id: Uuid,
+) -> AppResult<Option<Uuid>> {
+    let mut conn = redis.clone();
+    let session_id: Option<String> = conn
+        .hget(
+            store::layout().room_sessions_key(room_id),
+            player_id.to_string(),
+        )
+        .await?;
+    Ok(session_id.and_then(|id| id.parse().ok()))
+}
+
+/// Look up a session and push its expiry back; `None` once it has lapsed
+/// or was ended
+pub async fn renew(redis: &ConnectionManager, session_id: Uuid) -> AppResult<Option<Session>> {
+    let mut conn = redis.clone();
+    let fields: Vec<String> = RENEW
+        .key(store::layout().session_key(session_id))
+        .arg(chrono::Utc::now().timestamp())
+        .invoke_async(&mut conn)
+        .await?;
+    if fields.is_empty() {
+        return Ok(None);
+    }
+
+    let mut session = Session {
+        user_id: None,
+        seats: HashMap::new(),
+        expires_at: 0,
+    };
+    for pair in fields.chunks_exact(2) {
+        let (field, value) = (pair[0].as_str(), pair[1].as_str());
+        match field {
+            "user_id" => session.user_id = value.parse().ok(),
+            "expires_at" => session.expires_at = value.parse().unwrap_or_default(),
+            _ => {
+                if let (Some(room_id), Ok(player_id)) =
+                    (field.strip_prefix(SEAT_FIELD), value.parse())
+                {
+                    session.seats.insert(room_id.to_string(), player_id);
+                }
+            }
+        }
+    }
+    Ok(Some(session))
+}
+
+/// End a session; every token issued under it stops working at once
+pub async fn end(redis: &ConnectionManager, session_id: Uuid) -> AppResult<()> {
+    let mut conn = redis.clone();
+    let _: () = conn.del(store::layout().session_key(session_id)).await?;
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Offline bot-vs-bot simulation jobs
+//!
+//! Jobs run on the blocking pool and keep their state in Redis, so results
+//! can be fetched from any instance until they expire.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use rand::Rng;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{auth::AdminAuth, AppState};
+use crate::bot::BotDifficulty;
+use crate::error::{AppError, AppResult, FieldErrors};
+use crate::game::simulation::{
+    self, SimulationParams, SimulationResult, SimulationStats, DEFAULT_MAX_TURNS,
+};
+use crate::game::{store, GameConfig};
+
+/// Largest batch accepted in one job
+const MAX_GAMES: u32 = 1000;
+
+/// Games run between progress updates
+const CHUNK_SIZE: u32 = 50;
+
+/// Jobs and their results expire after 24 hours
+const JOB_TTL_SECS: u64 = 86400;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSimulationRequest {
+    pub games: u32,
+    pub bots: u8,
+    pub config: Option<GameConfig>,
+    /// Base seed; game `i` uses `seed + i`. Random when omitted.
+    pub seed: Option<u64>,
+    pub max_turns: Option<u32>,
+    #[serde(default)]
+    pub include_replays: bool,
+    /// Difficulty per seat; unlisted seats play `normal`
+    #[serde(default)]
+    pub difficulties: Vec<BotDifficulty>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateSimulationResponse {
+    pub job_id: Uuid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A batch of simulations as stored in Redis
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationJob {
+    pub id: Uuid,
+    pub status: JobStatus,
+    pub games: u32,
+    pub completed: u32,
+    pub bots: u8,
+    pub seed: u64,
+    pub max_turns: u32,
+    pub config: GameConfig,
+    pub include_replays: bool,
+    #[serde(default)]
+    pub difficulties: Vec<BotDifficulty>,
+    pub error: Option<String>,
+    pub summary: Option<SimulationStats>,
+    /// Serialized results, kept as raw JSON so replays round-trip untouched
+    #[serde(default)]
+    pub results: Vec<serde_json::Value>,
+}
+
+/// Enqueue a batch of bot-only games
+pub async fn create_simulation(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Json(req): Json<CreateSimulationRequest>,
+) -> AppResult<Json<CreateSimulationResponse>> {
+    if req.games == 0 || req.games > MAX_GAMES {
+        return Err(AppError::BadRequest(format!(
+            "games must be between 1 and {}",
+            MAX_GAMES
+        )));
+    }
+    if !(2..=8).contains(&req.bots) {
+        return Err(AppError::BadRequest("bots must be between 2 and 8".into()));
+    }
+    if req.difficulties.len() > req.bots as usize {
+        return Err(AppError::BadRequest("More difficulties than bots".into()));
+    }
+
+    let mut config = req.config.unwrap_or_default();
+    config.max_players = config.max_players.max(req.bots);
+    let mut errors = FieldErrors::new();
+    errors.nest("config", config.validate());
+    errors.into_result()?;
+
+    let job = SimulationJob {
+        id: Uuid::new_v4(),
+        status: JobStatus::Queued,
+        games: req.games,
+        completed: 0,
+        bots: req.bots,
+        seed: req.seed.unwrap_or_else(|| rand::thread_rng().gen()),
+        max_turns: req.max_turns.unwrap_or(DEFAULT_MAX_TURNS),
+        config,
+        include_replays: req.include_replays,
+        difficulties: req.difficulties,
+        error: None,
+        summary: None,
+        results: Vec::new(),
+    };
+    let job_id = job.id;
+
+    save_job(&state.redis, &job).await?;
+
+    let redis = state.redis.clone();
+    tokio::spawn(async move {
+        if let Err(e) = run_job(&redis, job).await {
+            tracing::error!("Simulation job {} failed: {}", job_id, e);
+        }
+    });
+
+    Ok(Json(CreateSimulationResponse { job_id }))
+}
+
+/// Fetch a job's status and, once done, its results
+pub async fn get_simulation(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+) -> AppResult<Json<SimulationJob>> {
+    let job = get_job(&state.redis, job_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Simulation job not found".into()))?;
+
+    Ok(Json(job))
+}
+
+async fn run_job(redis: &ConnectionManager, mut job: SimulationJob) -> AppResult<()> {
+    job.status = JobStatus::Running;
+    save_job(redis, &job).await?;
+
+    let mut results: Vec<SimulationResult> = Vec::with_capacity(job.games as usize);
+
+    while job.completed < job.games {
+        let start = job.completed;
+        let end = (start + CHUNK_SIZE).min(job.games);
+        let params: Vec<SimulationParams> = (start..end)
+            .map(|i| SimulationParams {
+                seed: job.seed.wrapping_add(i as u64),
+                bots: job.bots,
+                config: job.config.clone(),
+                max_turns: job.max_turns,
+                record_replay: job.include_replays,
+                difficulties: job.difficulties.clone(),
+                seats: Vec::new(),
+            })
+            .collect();
+
+        let chunk = tokio::task::spawn_blocking(move || {
+            params.iter().map(simulation::run).collect::<Vec<_>>()
+        })
+        .await;
+
+        match chunk {
+            Ok(chunk) => results.extend(chunk),
+            Err(e) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(e.to_string());
+                return save_job(redis, &job).await;
+            }
+        }
+
+        job.completed = end;
+        save_job(redis, &job).await?;
+    }
+
+    job.summary = Some(simulation::aggregate(&results));
+    job.results = results
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<_, _>>()
+        .map_err(|e| AppError::Internal(e.into()))?;
+    job.status = JobStatus::Completed;
+
+    save_job(redis, &job).await
+}
+
+async fn get_job(redis: &ConnectionManager, job_id: Uuid) -> AppResult<Option<SimulationJob>> {
+    let mut conn = redis.clone();
+    let data: Option<String> = conn.get(store::layout().simulation_key(job_id)).await?;
+
+    data.map(|json| serde_json::from_str(&json).map_err(|e| AppError::Internal(e.into())))
+        .transpose()
+}
+
+async fn save_job(redis: &ConnectionManager, job: &SimulationJob) -> AppResult<()> {
+    let mut conn = redis.clone();
+    let json = serde_json::to_string(job).map_err(|e| AppError::Internal(e.into()))?;
+
+    let _: () = conn
+        .set_ex(store::layout().simulation_key(job.id), json, JOB_TTL_SECS)
+        .await?;
+
+    Ok(())
+}
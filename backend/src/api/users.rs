@@ -0,0 +1,302 @@
+//! Profiles, statistics and achievements of registered users
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::{auth::AuthUser, openapi::ErrorResponse, AppState};
+use crate::db::{
+    self,
+    achievements::{self, UnlockedAchievement},
+    games::{self, GameRecord},
+    profiles::{self, Profile},
+    stats::{self, UserStats},
+};
+use crate::error::{AppError, AppResult, FieldErrors};
+use crate::game::{SeatPrefs, MAX_NAME_LEN, PLAYER_AVATARS, PLAYER_COLORS};
+use crate::i18n::Locale;
+
+const MAX_LOCALE_LEN: usize = 35;
+
+const DEFAULT_GAMES_PAGE: usize = 20;
+const MAX_GAMES_PAGE: usize = 100;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProfileResponse {
+    pub id: Uuid,
+    pub username: String,
+    #[serde(flatten)]
+    pub profile: Profile,
+}
+
+/// Fields left out stay as they are; an empty string clears one
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateProfileRequest {
+    pub display_name: Option<String>,
+    /// One of the curated avatar ids
+    pub avatar: Option<String>,
+    /// One of the player colors
+    pub color: Option<String>,
+    pub locale: Option<String>,
+}
+
+/// The signed-in user's profile
+#[utoipa::path(
+    get,
+    path = "/api/users/me",
+    tag = "users",
+    security(("user_token" = [])),
+    responses(
+        (status = 200, description = "Profile, with unset fields as null", body = ProfileResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+    )
+)]
+pub async fn get_me(
+    user: AuthUser,
+    State(state): State<AppState>,
+) -> AppResult<Json<ProfileResponse>> {
+    let account = db::users::get_user(&state.db, user.id)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+    let profile = profiles::get_profile(&state.db, user.id).await?;
+
+    Ok(Json(ProfileResponse {
+        id: account.id,
+        username: account.username,
+        profile,
+    }))
+}
+
+/// Change the signed-in user's profile
+#[utoipa::path(
+    patch,
+    path = "/api/users/me",
+    tag = "users",
+    security(("user_token" = [])),
+    request_body = UpdateProfileRequest,
+    responses(
+        (status = 200, description = "The updated profile", body = ProfileResponse),
+        (status = 400, description = "Invalid name, avatar, color or locale", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+    )
+)]
+pub async fn update_me(
+    user: AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<UpdateProfileRequest>,
+) -> AppResult<Json<ProfileResponse>> {
+    let account = db::users::get_user(&state.db, user.id)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+    let mut profile = profiles::get_profile(&state.db, user.id).await?;
+
+    if let Some(name) = req.display_name {
+        profile.display_name = cleared(name).map(validate_display_name).transpose()?;
+    }
+    if let Some(avatar) = req.avatar {
+        profile.avatar = cleared(avatar)
+            .map(|avatar| one_of(avatar, PLAYER_AVATARS, "avatar"))
+            .transpose()?;
+    }
+    if let Some(color) = req.color {
+        profile.color = cleared(color)
+            .map(|color| one_of(color.to_uppercase(), PLAYER_COLORS, "color"))
+            .transpose()?;
+    }
+    if let Some(locale) = req.locale {
+        profile.locale = cleared(locale).map(validate_locale).transpose()?;
+    }
+
+    profiles::save_profile(&state.db, user.id, &profile).await?;
+
+    Ok(Json(ProfileResponse {
+        id: account.id,
+        username: account.username,
+        profile,
+    }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserStatsResponse {
+    pub user_id: Uuid,
+    pub username: String,
+    #[serde(flatten)]
+    pub stats: UserStats,
+}
+
+/// Lifetime statistics of a user over the games they finished
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/stats",
+    tag = "users",
+    params(("user_id" = Uuid, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Totals over finished games", body = UserStatsResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_stats(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<Json<UserStatsResponse>> {
+    let user = db::users::get_user(&state.db, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+    let stats = stats::get_stats(&state.db, user_id).await?;
+
+    Ok(Json(UserStatsResponse {
+        user_id: user.id,
+        username: user.username,
+        stats,
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GamesQuery {
+    pub limit: Option<usize>,
+}
+
+/// Finished games a user played in
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/games",
+    tag = "users",
+    params(("user_id" = Uuid, Path, description = "User id"), GamesQuery),
+    responses(
+        (status = 200, description = "Latest finished games with every seat's placement, newest first", body = [GameRecord]),
+        (status = 404, description = "User not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_games(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<GamesQuery>,
+) -> AppResult<Json<Vec<GameRecord>>> {
+    db::users::get_user(&state.db, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_GAMES_PAGE)
+        .clamp(1, MAX_GAMES_PAGE);
+    Ok(Json(
+        games::recent_for_user(&state.db, user_id, limit).await?,
+    ))
+}
+
+/// Achievements a user has unlocked
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/achievements",
+    tag = "users",
+    params(("user_id" = Uuid, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Unlocked achievements, oldest first", body = [UnlockedAchievement]),
+        (status = 404, description = "User not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_achievements(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<Json<Vec<UnlockedAchievement>>> {
+    db::users::get_user(&state.db, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    Ok(Json(achievements::for_user(&state.db, user_id).await?))
+}
+
+/// Name and looks a signed-in user takes a seat with
+///
+/// `name` wins over the profile; without either the username is used. The
+/// profile's locale wins over the request's `Accept-Language`.
+pub(super) async fn seat_for(
+    state: &AppState,
+    user: Option<&AuthUser>,
+    name: Option<String>,
+    field: &str,
+    headers: &HeaderMap,
+) -> AppResult<(String, SeatPrefs)> {
+    let Some(user) = user else {
+        let Some(name) = name else {
+            let mut errors = FieldErrors::new();
+            errors.add(field, "required", "A name is required");
+            return Err(AppError::Validation(errors));
+        };
+        let prefs = SeatPrefs {
+            locale: Locale::negotiate(None, headers),
+            ..SeatPrefs::default()
+        };
+        return Ok((name, prefs));
+    };
+
+    let profile = profiles::get_profile(&state.db, user.id).await?;
+    let name = match name.or(profile.display_name) {
+        Some(name) => name,
+        None => {
+            db::users::get_user(&state.db, user.id)
+                .await?
+                .ok_or(AppError::Unauthorized)?
+                .username
+        }
+    };
+
+    Ok((
+        name,
+        SeatPrefs {
+            user_id: Some(user.id),
+            locale: Locale::negotiate(profile.locale.as_deref(), headers),
+            avatar: profile.avatar,
+            color: profile.color,
+        },
+    ))
+}
+
+fn cleared(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn validate_display_name(name: String) -> AppResult<String> {
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Name cannot be longer than {} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(AppError::BadRequest(
+            "Name contains invalid characters".into(),
+        ));
+    }
+    Ok(name)
+}
+
+fn one_of(value: String, options: &[&str], what: &str) -> AppResult<String> {
+    if !options.contains(&value.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "Unknown {}, expected one of {}",
+            what,
+            options.join(", ")
+        )));
+    }
+    Ok(value)
+}
+
+/// Loose BCP 47 check: alphanumeric subtags joined by `-`
+fn validate_locale(locale: String) -> AppResult<String> {
+    let valid = locale.len() <= MAX_LOCALE_LEN
+        && locale.split('-').all(|tag| {
+            (1..=8).contains(&tag.len()) && tag.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    if !valid {
+        return Err(AppError::BadRequest("Invalid locale".into()));
+    }
+    Ok(locale)
+}
@@ -0,0 +1,165 @@
+//! Play bot-only games in memory and print aggregate statistics
+//!
+//! ```text
+//! cargo run --release --bin simulate -- --games 500 --bots 4 --seed 42
+//! ```
+
+use std::time::Instant;
+
+use anyhow::{bail, Context};
+
+use mo_de_backend::bot::BotDifficulty;
+use mo_de_backend::game::simulation::{self, SimulationParams, DEFAULT_MAX_TURNS};
+use mo_de_backend::game::GameConfig;
+
+const USAGE: &str = "\
+Usage: simulate [OPTIONS]
+
+Options:
+  --games <N>      Games to play (default 100)
+  --bots <N>       Bots per game, 2-8 (default 4)
+  --seed <N>       Seed of the first game; game i uses seed + i (default 0)
+  --max-turns <N>  Turns before a game counts as unfinished (default 1000)
+  --hard <N>       Seats played by hard bots, starting from the first (default 0)
+  --json           Print statistics as JSON
+  -h, --help       Show this help";
+
+struct Args {
+    games: u32,
+    bots: u8,
+    seed: u64,
+    max_turns: u32,
+    hard: u8,
+    json: bool,
+}
+
+fn parse_args() -> anyhow::Result<Option<Args>> {
+    let mut args = Args {
+        games: 100,
+        bots: 4,
+        seed: 0,
+        max_turns: DEFAULT_MAX_TURNS,
+        hard: 0,
+        json: false,
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(flag) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .with_context(|| format!("{} needs a value", flag))
+        };
+
+        match flag.as_str() {
+            "--games" => args.games = value()?.parse()?,
+            "--bots" => args.bots = value()?.parse()?,
+            "--seed" => args.seed = value()?.parse()?,
+            "--max-turns" => args.max_turns = value()?.parse()?,
+            "--hard" => args.hard = value()?.parse()?,
+            "--json" => args.json = true,
+            "-h" | "--help" => return Ok(None),
+            other => bail!("Unknown option {}\n\n{}", other, USAGE),
+        }
+    }
+
+    if !(2..=8).contains(&args.bots) {
+        bail!("--bots must be between 2 and 8");
+    }
+    if args.hard > args.bots {
+        bail!("--hard cannot exceed --bots");
+    }
+
+    Ok(Some(args))
+}
+
+fn main() -> anyhow::Result<()> {
+    let Some(args) = parse_args()? else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+
+    let config = GameConfig {
+        max_players: args.bots,
+        ..GameConfig::default()
+    };
+    let difficulties = vec![BotDifficulty::Hard; args.hard as usize];
+
+    let started = Instant::now();
+    let results: Vec<_> = (0..args.games)
+        .map(|i| {
+            simulation::run(&SimulationParams {
+                seed: args.seed.wrapping_add(i as u64),
+                bots: args.bots,
+                config: config.clone(),
+                max_turns: args.max_turns,
+                record_replay: false,
+                difficulties: difficulties.clone(),
+                seats: Vec::new(),
+            })
+        })
+        .collect();
+    let elapsed = started.elapsed();
+
+    let stats = simulation::aggregate(&results);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} games with {} bots in {:.2?} (seeds {}..{})",
+        stats.games,
+        args.bots,
+        elapsed,
+        args.seed,
+        args.seed.wrapping_add(args.games as u64)
+    );
+    println!(
+        "Finished: {}  Unfinished: {}  Errors: {}",
+        stats.finished,
+        stats.games - stats.finished,
+        stats.errored
+    );
+    println!("Average length: {:.1} turns", stats.average_turns);
+    if let Some(range) = &stats.finished_turns {
+        println!(
+            "Finished games: {:.1} turns on average ({}-{})",
+            range.average, range.min, range.max
+        );
+    }
+
+    println!("\nWin rate by personality");
+    for p in &stats.personalities {
+        println!(
+            "  {:<14} {:>6.1}%  ({} wins / {} seats)",
+            format!("{:?}", p.personality),
+            p.win_rate * 100.0,
+            p.wins,
+            p.seats
+        );
+    }
+
+    println!("\nLanding frequency");
+    let mut tiles = stats.tiles.clone();
+    tiles.sort_by_key(|t| std::cmp::Reverse(t.landings));
+    for t in &tiles {
+        println!(
+            "  {:>2} {:<20} {:>5.2}%  ({})",
+            t.tile_idx,
+            t.name,
+            t.frequency * 100.0,
+            t.landings
+        );
+    }
+
+    println!("\nBankruptcy causes");
+    if stats.bankruptcies.is_empty() {
+        println!("  none");
+    }
+    for b in &stats.bankruptcies {
+        println!("  {:<8} {}", b.cause, b.count);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Canned chat lines that make bot games feel livelier
+
+/// Moments a bot may comment on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatTrigger {
+    WonAuction,
+    Jailed,
+    CompletedSet,
+}
+
+impl ChatTrigger {
+    fn lines(&self) -> &'static [&'static str] {
+        match self {
+            ChatTrigger::WonAuction => &[
+                "Sold! To me.",
+                "A bargain, if I say so myself.",
+                "Thanks for letting that one go.",
+            ],
+            ChatTrigger::Jailed => &[
+                "I demand a lawyer!",
+                "This is a misunderstanding.",
+                "Guess I'll sit this one out.",
+            ],
+            ChatTrigger::CompletedSet => &[
+                "The full set is mine. Time to build.",
+                "Mind your step around my streets.",
+                "That's a monopoly. Rents are going up.",
+            ],
+        }
+    }
+
+    /// Pick a line; `salt` varies the choice without needing an RNG
+    pub fn line(&self, salt: usize) -> &'static str {
+        let lines = self.lines();
+        lines[salt % lines.len()]
+    }
+}
@@ -2,8 +2,17 @@
 
 use uuid::Uuid;
 
-use crate::game::board::{get_tile, ColorGroup, TileType, BOARD};
-use crate::game::state::GameState;
+use super::rollout;
+use super::strategies::BotDifficulty;
+use crate::game::board::{ColorGroup, TileType};
+use crate::game::state::{GamePhase, GameState, Player, TurnPhase};
+use crate::game::{ClientEvent, GameEngine};
+
+/// Cash kept in hand even with no rent threats ahead
+const BASE_CASH_RESERVE: i32 = 50;
+
+/// Tiles ahead that a single roll can reach
+const ROLL_REACH: u8 = 12;
 
 /// Bot AI decision engine
 pub struct BotAI;
@@ -64,35 +73,29 @@ impl BotAI {
 
     /// Decide whether to buy a property
     pub fn should_buy(game: &GameState, bot_id: Uuid, tile_idx: u8) -> bool {
-        let bot = match game.get_player(bot_id) {
-            Some(p) => p,
-            None => return false,
+        let (balance, price) = match (game.get_player(bot_id), game.tile(tile_idx)) {
+            (Some(bot), Some(tile)) => (bot.balance, tile.price),
+            _ => return false,
         };
 
-        let tile = match get_tile(tile_idx) {
-            Some(t) => t,
-            None => return false,
+        price <= Self::buy_budget(game, bot_id, tile_idx)
+            && balance - price as i32 >= Self::cash_reserve(game, bot_id)
+    }
+
+    /// Most the bot is willing to pay for a tile at list price
+    pub fn buy_budget(game: &GameState, bot_id: Uuid, tile_idx: u8) -> u32 {
+        let bot = match game.get_player(bot_id) {
+            Some(p) => p,
+            None => return 0,
         };
 
-        let group = match tile.group {
+        let group = match game.tile(tile_idx).and_then(|t| t.group) {
             Some(g) => g,
-            None => return false,
+            None => return 0,
         };
 
-        let priority = Self::get_priorities()
-            .iter()
-            .find(|p| p.group == group)
-            .map(|p| p.priority)
-            .unwrap_or(1);
-
-        let owned_in_group: usize = game
-            .properties
-            .iter()
-            .filter(|(idx, state)| {
-                state.owner == Some(bot_id) && get_tile(**idx).and_then(|t| t.group) == Some(group)
-            })
-            .count();
-
+        let priority = Self::group_priority(group);
+        let owned_in_group = Self::owned_in_group(game, bot_id, group);
         let group_size = group.property_count() as usize;
 
         let max_percent: u32 = match (priority, owned_in_group) {
@@ -106,9 +109,28 @@ impl BotAI {
             (_, _) => 30,
         };
 
-        let max_spend = (bot.balance as u32 * max_percent) / 100;
+        let budget = (bot.balance.max(0) as u32 * max_percent) / 100;
+        (budget as f32 * bot.policy_params().spend_scale) as u32
+    }
 
-        tile.price <= max_spend
+    /// Priority of a color group, 1 (lowest) to 5
+    pub fn group_priority(group: ColorGroup) -> u8 {
+        Self::get_priorities()
+            .iter()
+            .find(|p| p.group == group)
+            .map(|p| p.priority)
+            .unwrap_or(1)
+    }
+
+    /// Number of tiles in a group a player owns
+    pub fn owned_in_group(game: &GameState, player_id: Uuid, group: ColorGroup) -> usize {
+        game.properties
+            .iter()
+            .filter(|(idx, state)| {
+                state.owner == Some(player_id)
+                    && game.tile(**idx).and_then(|t| t.group) == Some(group)
+            })
+            .count()
     }
 
     /// Calculate max bid for auction
@@ -118,7 +140,7 @@ impl BotAI {
             None => return 0,
         };
 
-        let tile = match get_tile(tile_idx) {
+        let tile = match game.tile(tile_idx) {
             Some(t) => t,
             None => return 0,
         };
@@ -128,20 +150,8 @@ impl BotAI {
             None => return 0,
         };
 
-        let priority = Self::get_priorities()
-            .iter()
-            .find(|p| p.group == group)
-            .map(|p| p.priority)
-            .unwrap_or(1);
-
-        let owned_in_group: usize = game
-            .properties
-            .iter()
-            .filter(|(idx, state)| {
-                state.owner == Some(bot_id) && get_tile(**idx).and_then(|t| t.group) == Some(group)
-            })
-            .count();
-
+        let priority = Self::group_priority(group);
+        let owned_in_group = Self::owned_in_group(game, bot_id, group);
         let group_size = group.property_count() as usize;
         let would_complete_set = owned_in_group >= group_size - 1;
 
@@ -149,31 +159,79 @@ impl BotAI {
             .players
             .iter()
             .filter(|p| p.id != bot_id && !p.is_bankrupt)
-            .any(|p| {
-                let their_count: usize = game
-                    .properties
-                    .iter()
-                    .filter(|(idx, state)| {
-                        state.owner == Some(p.id)
-                            && get_tile(**idx).and_then(|t| t.group) == Some(group)
-                    })
-                    .count();
-                their_count >= group_size - 1
-            });
+            .any(|p| Self::owned_in_group(game, p.id, group) >= group_size - 1);
 
         let mut value = tile.price as f32;
 
         if would_complete_set {
             value *= 1.8;
         }
+        value *= 1.0 + (priority as f32 * 0.1);
+        value *= bot.policy_params().bid_scale;
+
+        // Denying a set is worth a premium on top of what the tile is worth
         if blocks_opponent {
-            value *= 1.5;
+            value *= 1.0 + bot.bot_personality.blocking_premium();
         }
-        value *= 1.0 + (priority as f32 * 0.1);
+
+        // Never bid past what the richest remaining rival could answer
+        let passed = game
+            .auction
+            .as_ref()
+            .map(|a| a.passed_players.as_slice())
+            .unwrap_or_default();
+        let rival_cash = game
+            .players
+            .iter()
+            .filter(|p| p.id != bot_id && !p.is_bankrupt && !passed.contains(&p.id))
+            .map(|p| p.balance.max(0) as u32)
+            .max()
+            .unwrap_or(0);
+        let outbids_rivals = rival_cash + Self::bid_step(game, tile_idx);
 
         let max_spend = (bot.balance as f32 * 0.5) as u32;
+        let above_reserve = (bot.balance - Self::cash_reserve(game, bot_id)).max(0) as u32;
+
+        (value as u32)
+            .min(outbids_rivals)
+            .min(max_spend)
+            .min(above_reserve)
+    }
 
-        (value as u32).min(max_spend)
+    /// Smallest raise a bot makes over the current bid
+    pub fn bid_step(game: &GameState, tile_idx: u8) -> u32 {
+        game.tile(tile_idx)
+            .map(|t| (t.price / 10).max(10))
+            .unwrap_or(10)
+    }
+
+    /// Cash to keep back for rent that may come due on the next roll
+    ///
+    /// Looks at opponents' developed properties (buildings or a full set)
+    /// within reach of one roll and keeps the largest of their rents, so a
+    /// single unlucky landing cannot bankrupt the bot.
+    pub fn cash_reserve(game: &GameState, bot_id: Uuid) -> i32 {
+        let bot = match game.get_player(bot_id) {
+            Some(p) => p,
+            None => return 0,
+        };
+
+        let exposure = (1..=ROLL_REACH)
+            .filter_map(|step| {
+                let idx = (bot.position + step) % game.board().tiles.len() as u8;
+                let prop = game.properties.get(&idx)?;
+                let owner = prop.owner.filter(|owner| *owner != bot_id)?;
+                let group = game.tile(idx)?.group?;
+
+                let developed =
+                    prop.houses > 0 || GameEngine::player_has_full_set(game, owner, group);
+                developed.then(|| GameEngine::calculate_rent(game, idx))
+            })
+            .max()
+            .unwrap_or(0);
+
+        let reserve = (BASE_CASH_RESERVE + exposure as i32) as f32;
+        (reserve * bot.policy_params().reserve_scale) as i32
     }
 
     /// Get properties the bot should build on
@@ -187,7 +245,9 @@ impl BotAI {
 
         for priority in Self::get_priorities() {
             let group = priority.group;
-            let group_tiles: Vec<u8> = BOARD
+            let group_tiles: Vec<u8> = game
+                .board()
+                .tiles
                 .iter()
                 .filter(|t| t.group == Some(group) && t.tile_type == TileType::Property)
                 .map(|t| t.index)
@@ -200,17 +260,18 @@ impl BotAI {
                     .unwrap_or(false)
             });
 
-            if !owns_all {
+            // Railroads and utilities have no buildable tiles
+            if group_tiles.is_empty() || !owns_all {
                 continue;
             }
 
-            let tile = match get_tile(group_tiles[0]) {
+            let tile = match game.tile(group_tiles[0]) {
                 Some(t) => t,
                 None => continue,
             };
             let build_cost = tile.build_cost;
 
-            if bot.balance < build_cost as i32 {
+            if bot.balance - (build_cost as i32) < Self::cash_reserve(game, bot_id) {
                 continue;
             }
 
@@ -238,13 +299,8 @@ impl BotAI {
         targets
     }
 
-    /// Decide whether to pay to leave jail
-    pub fn should_pay_jail(game: &GameState, bot_id: Uuid) -> bool {
-        let bot = match game.get_player(bot_id) {
-            Some(p) => p,
-            None => return false,
-        };
-
+    /// Fraction of ownable properties already bought (0.0 = fresh board)
+    pub fn game_progress(game: &GameState) -> f32 {
         let unowned_properties: usize = game
             .properties
             .iter()
@@ -252,7 +308,17 @@ impl BotAI {
             .count();
 
         let total_properties = game.properties.len();
-        let game_progress = 1.0 - (unowned_properties as f32 / total_properties as f32);
+        1.0 - (unowned_properties as f32 / total_properties as f32)
+    }
+
+    /// Decide whether to pay to leave jail
+    pub fn should_pay_jail(game: &GameState, bot_id: Uuid) -> bool {
+        let bot = match game.get_player(bot_id) {
+            Some(p) => p,
+            None => return false,
+        };
+
+        let game_progress = Self::game_progress(game);
 
         if game_progress < 0.5 && bot.balance >= 50 {
             return true;
@@ -269,6 +335,171 @@ impl BotAI {
         bot.balance >= 100
     }
 
+    /// Decide how to handle the start of a turn spent in jail
+    pub fn jail_action(game: &GameState, bot_id: Uuid) -> JailAction {
+        let bot = match game.get_player(bot_id) {
+            Some(p) => p,
+            None => return JailAction::Roll,
+        };
+
+        if !bot.in_jail {
+            return JailAction::Roll;
+        }
+
+        // Early on a free exit is always worth taking; later the card is kept
+        // unless the bot would have paid anyway
+        if bot.get_out_cards > 0 && (Self::game_progress(game) < 0.5 || bot.balance >= 200) {
+            return JailAction::UseCard;
+        }
+
+        if Self::should_pay_jail(game, bot_id) {
+            JailAction::PayBail
+        } else {
+            JailAction::Roll
+        }
+    }
+
+    /// Work off a debt: pay it once the cash is there, otherwise mortgage the
+    /// property worth least, and give up when nothing is left to mortgage
+    pub fn debt_action(game: &GameState, bot_id: Uuid) -> Option<ClientEvent> {
+        let debt = game.debt.as_ref().filter(|d| d.player_id == bot_id)?;
+        if game.get_player(bot_id)?.balance >= debt.amount {
+            return Some(ClientEvent::PayDebt);
+        }
+
+        let cheapest = game
+            .properties
+            .iter()
+            .filter(|(_, p)| p.owner == Some(bot_id) && !p.is_mortgaged && p.houses == 0)
+            .filter_map(|(&idx, _)| game.tile(idx).map(|t| (t.mortgage_value, idx)))
+            .min();
+
+        Some(match cheapest {
+            Some((_, tile_idx)) => ClientEvent::Mortgage { tile_idx },
+            None => ClientEvent::DeclareBankruptcy,
+        })
+    }
+
+    /// Pick the next action for whichever bot has to act, if any
+    ///
+    /// Returns `None` when the game is waiting on a human.
+    pub fn next_action(game: &GameState) -> Option<(Uuid, ClientEvent)> {
+        let (bot_id, action) = Self::policy_action(game, Player::controlled_by_bot)?;
+
+        let is_hard = game
+            .get_player(bot_id)
+            .is_some_and(|p| p.bot_difficulty == BotDifficulty::Hard);
+        if !is_hard {
+            return Some((bot_id, action));
+        }
+
+        let candidates = Self::hard_candidates(game, bot_id, action);
+        Some((bot_id, rollout::best_action(game, bot_id, candidates)))
+    }
+
+    /// Alternatives a hard bot weighs against the normal choice, which
+    /// always comes first
+    fn hard_candidates(game: &GameState, bot_id: Uuid, action: ClientEvent) -> Vec<ClientEvent> {
+        // Cash the bot may spend without dipping into its reserve
+        let spendable = game.get_player(bot_id).map(|p| p.balance).unwrap_or(0)
+            - Self::cash_reserve(game, bot_id);
+
+        let alternative = match &action {
+            ClientEvent::BuyProperty | ClientEvent::PassProperty => {
+                let price = game
+                    .get_player(bot_id)
+                    .and_then(|p| game.tile(p.position))
+                    .map(|t| t.price as i32)
+                    .unwrap_or(i32::MAX);
+
+                match action {
+                    ClientEvent::PassProperty if spendable >= price => {
+                        Some(ClientEvent::BuyProperty)
+                    }
+                    ClientEvent::BuyProperty => Some(ClientEvent::PassProperty),
+                    _ => None,
+                }
+            }
+            ClientEvent::PassBid => game.auction.as_ref().and_then(|auction| {
+                let amount = auction.current_bid + Self::bid_step(game, auction.tile_idx);
+                (spendable >= amount as i32).then_some(ClientEvent::Bid { amount })
+            }),
+            ClientEvent::Bid { .. } => Some(ClientEvent::PassBid),
+            // Build targets already leave the reserve untouched
+            ClientEvent::EndTurn => Self::get_build_targets(game, bot_id)
+                .first()
+                .map(|&tile_idx| ClientEvent::Build { tile_idx }),
+            _ => None,
+        };
+
+        match alternative {
+            Some(alternative) => vec![action, alternative],
+            None => vec![action],
+        }
+    }
+
+    /// The normal policy for whichever seat `controls` hands to a bot
+    ///
+    /// Rollouts pass `|_| true` to play every seat, humans included.
+    pub(crate) fn policy_action(
+        game: &GameState,
+        controls: impl Fn(&Player) -> bool,
+    ) -> Option<(Uuid, ClientEvent)> {
+        if game.phase != GamePhase::Playing {
+            return None;
+        }
+
+        let turn = game.turn.as_ref()?;
+
+        // Auctions involve every player, not just the one whose turn it is
+        if turn.phase == TurnPhase::Auction {
+            let auction = game.auction.as_ref()?;
+            let bidder = game.players.iter().find(|p| {
+                controls(p)
+                    && !p.is_bankrupt
+                    && !auction.passed_players.contains(&p.id)
+                    && auction.highest_bidder != Some(p.id)
+            })?;
+
+            let amount = auction.current_bid + Self::bid_step(game, auction.tile_idx);
+            let action = if amount <= Self::calculate_max_bid(game, bidder.id, auction.tile_idx) {
+                ClientEvent::Bid { amount }
+            } else {
+                ClientEvent::PassBid
+            };
+            return Some((bidder.id, action));
+        }
+
+        // Check if current player is controlled by a bot
+        let bot = game.get_player(turn.player_id)?;
+        if !controls(bot) {
+            return None;
+        }
+
+        let action = match turn.phase {
+            // Leaving jail is decided before rolling; paying or using a card
+            // keeps the phase at WaitingForRoll for the next action
+            TurnPhase::WaitingForRoll => match Self::jail_action(game, bot.id) {
+                JailAction::PayBail => ClientEvent::PayJail,
+                JailAction::UseCard => ClientEvent::UseCard,
+                JailAction::Roll => ClientEvent::RollDice,
+            },
+            TurnPhase::BuyDecision => {
+                if Self::should_buy(game, bot.id, bot.position) {
+                    ClientEvent::BuyProperty
+                } else {
+                    ClientEvent::PassProperty
+                }
+            }
+            TurnPhase::Bankruptcy => Self::debt_action(game, bot.id)?,
+            TurnPhase::TurnEnd => ClientEvent::EndTurn,
+            // For other phases, wait
+            _ => return None,
+        };
+
+        Some((bot.id, action))
+    }
+
     /// Evaluate a trade offer
     pub fn evaluate_trade(offering_value: i32, requesting_value: i32) -> TradeDecision {
         if offering_value as f32 > requesting_value as f32 * 1.2 {
@@ -282,7 +513,7 @@ impl BotAI {
 
     /// Calculate value of a property for trade evaluation
     pub fn calculate_property_value(game: &GameState, player_id: Uuid, tile_idx: u8) -> i32 {
-        let tile = match get_tile(tile_idx) {
+        let tile = match game.tile(tile_idx) {
             Some(t) => t,
             None => return 0,
         };
@@ -299,7 +530,7 @@ impl BotAI {
             .iter()
             .filter(|(idx, state)| {
                 state.owner == Some(player_id)
-                    && get_tile(**idx).and_then(|t| t.group) == Some(group)
+                    && game.tile(**idx).and_then(|t| t.group) == Some(group)
             })
             .count();
 
@@ -316,6 +547,13 @@ impl BotAI {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JailAction {
+    PayBail,
+    UseCard,
+    Roll,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TradeDecision {
     Accept,
@@ -0,0 +1,115 @@
+//! Human-readable reasons behind bot decisions, for tuning and debugging
+
+use uuid::Uuid;
+
+use super::{BotAI, BotDifficulty};
+use crate::game::{ClientEvent, GameState};
+
+impl BotAI {
+    /// Describe the inputs behind `action`, e.g.
+    /// "bought Paris: priority 4, owns 2/3 Yellow, max_spend=520, reserve=50"
+    pub fn explain(game: &GameState, bot_id: Uuid, action: &ClientEvent) -> String {
+        let bot = match game.get_player(bot_id) {
+            Some(p) => p,
+            None => return "unknown bot".into(),
+        };
+
+        let reason = match action {
+            ClientEvent::BuyProperty | ClientEvent::PassProperty => {
+                let verb = if matches!(action, ClientEvent::BuyProperty) {
+                    "bought"
+                } else {
+                    "passed on"
+                };
+                match game.tile(bot.position) {
+                    Some(tile) => {
+                        let group = tile
+                            .group
+                            .map(|g| {
+                                format!(
+                                    "priority {}, owns {}/{} {:?}, ",
+                                    Self::group_priority(g),
+                                    Self::owned_in_group(game, bot_id, g),
+                                    g.property_count(),
+                                    g
+                                )
+                            })
+                            .unwrap_or_default();
+                        format!(
+                            "{} {}: {}price={}, max_spend={}, reserve={}, balance={}",
+                            verb,
+                            tile.name,
+                            group,
+                            tile.price,
+                            Self::buy_budget(game, bot_id, bot.position),
+                            Self::cash_reserve(game, bot_id),
+                            bot.balance
+                        )
+                    }
+                    None => verb.to_string(),
+                }
+            }
+            ClientEvent::Bid { .. } | ClientEvent::PassBid => match &game.auction {
+                Some(auction) => {
+                    let tile_name = game
+                        .tile(auction.tile_idx)
+                        .map(|t| t.name.as_str())
+                        .unwrap_or("?");
+                    let what = match action {
+                        ClientEvent::Bid { amount } => format!("bid {} on", amount),
+                        _ => "passed on".to_string(),
+                    };
+                    format!(
+                        "{} {}: current_bid={}, max_bid={}, reserve={}, balance={}",
+                        what,
+                        tile_name,
+                        auction.current_bid,
+                        Self::calculate_max_bid(game, bot_id, auction.tile_idx),
+                        Self::cash_reserve(game, bot_id),
+                        bot.balance
+                    )
+                }
+                None => "no auction".into(),
+            },
+            ClientEvent::PayJail | ClientEvent::UseCard | ClientEvent::RollDice if bot.in_jail => {
+                let what = match action {
+                    ClientEvent::PayJail => "paid bail",
+                    ClientEvent::UseCard => "used a jail card",
+                    _ => "rolled for doubles",
+                };
+                format!(
+                    "{}: progress={:.0}%, cards={}, jail_turns={}, balance={}",
+                    what,
+                    Self::game_progress(game) * 100.0,
+                    bot.get_out_cards,
+                    bot.jail_turns,
+                    bot.balance
+                )
+            }
+            ClientEvent::RollDice => "rolled".into(),
+            ClientEvent::Build { tile_idx } => {
+                let (name, cost) = game
+                    .tile(*tile_idx)
+                    .map(|t| (t.name.as_str(), t.build_cost))
+                    .unwrap_or(("?", 0));
+                let houses = game.properties.get(tile_idx).map(|p| p.houses).unwrap_or(0);
+                format!(
+                    "built on {}: houses={}, cost={}, reserve={}, balance={}",
+                    name,
+                    houses,
+                    cost,
+                    Self::cash_reserve(game, bot_id),
+                    bot.balance
+                )
+            }
+            ClientEvent::EndTurn => "ended turn: nothing left worth doing".into(),
+            other => format!("{:?}", other),
+        };
+
+        if bot.bot_difficulty == BotDifficulty::Hard {
+            format!("{} (chosen by rollouts)", reason)
+        } else {
+            reason
+        }
+    }
+}
@@ -1,7 +1,13 @@
 //! Bot module - Deterministic AI for computer players
 
+mod chat;
 mod decision;
+mod explain;
+mod rollout;
 mod strategies;
+mod tuning;
 
-pub use decision::BotAI;
+pub use chat::ChatTrigger;
+pub use decision::{BotAI, JailAction};
 pub use strategies::*;
+pub use tuning::BotParams;
@@ -0,0 +1,135 @@
+//! Monte Carlo evaluation for hard bots
+//!
+//! Candidate actions are scored by playing the game forward a few turns with
+//! every seat on the normal policy, then comparing the bot's net worth with
+//! the rest of the table. Sampling stops at the rollout count or the time
+//! budget, whichever comes first.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use uuid::Uuid;
+
+use super::BotAI;
+use crate::game::{ClientEvent, GameEngine, GamePhase, GameState, ServerEvent};
+
+/// Rollouts per candidate action
+const ROLLOUTS: u32 = 24;
+
+/// Turns played forward in each rollout
+const HORIZON_TURNS: u32 = 12;
+
+/// Actions allowed in one rollout before it is cut short
+const MAX_ROLLOUT_ACTIONS: u32 = 400;
+
+/// Wall-clock budget for a whole decision
+const DECISION_BUDGET: Duration = Duration::from_millis(250);
+
+/// Pick the candidate with the best average outcome for `bot_id`
+///
+/// The first candidate wins ties and is returned if no rollout finishes in
+/// time, so callers should put the default choice first.
+pub fn best_action(game: &GameState, bot_id: Uuid, candidates: Vec<ClientEvent>) -> ClientEvent {
+    let deadline = Instant::now() + DECISION_BUDGET;
+    let mut rng = StdRng::seed_from_u64(decision_seed(game, bot_id));
+    let mut totals = vec![(0.0f64, 0u32); candidates.len()];
+
+    // Candidates share each round's seed so they face the same dice
+    'rounds: for _ in 0..ROLLOUTS {
+        let seed: u64 = rng.gen();
+        for (total, action) in totals.iter_mut().zip(&candidates) {
+            if Instant::now() >= deadline {
+                break 'rounds;
+            }
+            if let Some(score) = rollout(game, bot_id, action.clone(), seed) {
+                total.0 += score;
+                total.1 += 1;
+            }
+        }
+    }
+
+    let mut best = 0;
+    let mut best_score = f64::MIN;
+    for (i, (total, samples)) in totals.iter().enumerate() {
+        if *samples == 0 {
+            continue;
+        }
+        let mean = total / *samples as f64;
+        if mean > best_score {
+            best = i;
+            best_score = mean;
+        }
+    }
+
+    candidates[best].clone()
+}
+
+/// Play one action and a short future, returning the bot's standing
+fn rollout(game: &GameState, bot_id: Uuid, action: ClientEvent, seed: u64) -> Option<f64> {
+    let mut sim = game.clone();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut events = Vec::new();
+
+    GameEngine::apply_event(&mut sim, bot_id, action, &mut rng, &mut events).ok()?;
+
+    let mut turns = 0;
+    for _ in 0..MAX_ROLLOUT_ACTIONS {
+        if sim.phase != GamePhase::Playing || turns >= HORIZON_TURNS {
+            break;
+        }
+
+        let Some((player_id, action)) = BotAI::policy_action(&sim, |_| true) else {
+            break;
+        };
+
+        events.clear();
+        if GameEngine::apply_event(&mut sim, player_id, action, &mut rng, &mut events).is_err() {
+            break;
+        }
+
+        turns += events
+            .iter()
+            .filter(|e| matches!(e, ServerEvent::TurnChanged { .. }))
+            .count() as u32;
+    }
+
+    Some(standing(&sim, bot_id))
+}
+
+/// Net worth relative to the average surviving opponent
+fn standing(game: &GameState, bot_id: Uuid) -> f64 {
+    let opponents: Vec<i32> = game
+        .players
+        .iter()
+        .filter(|p| p.id != bot_id && !p.is_bankrupt)
+        .map(|p| game.net_worth(p.id))
+        .collect();
+
+    let field = if opponents.is_empty() {
+        0.0
+    } else {
+        opponents.iter().sum::<i32>() as f64 / opponents.len() as f64
+    };
+
+    game.net_worth(bot_id) as f64 - field
+}
+
+/// Seed derived from the visible state so the same position gets the same
+/// decision, in live rooms and seeded simulations alike
+fn decision_seed(game: &GameState, bot_id: Uuid) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bot_id.hash(&mut hasher);
+    for player in &game.players {
+        (player.id, player.balance, player.position).hash(&mut hasher);
+    }
+    if let Some(turn) = &game.turn {
+        (&turn.dice, turn.doubles_count).hash(&mut hasher);
+    }
+    if let Some(auction) = &game.auction {
+        (auction.tile_idx, auction.current_bid).hash(&mut hasher);
+    }
+    hasher.finish()
+}
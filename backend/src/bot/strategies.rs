@@ -1,17 +1,96 @@
 //! Bot strategy constants and helpers
 
+use std::time::Duration;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::game::ClientEvent;
+
+/// How hard a bot plays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BotDifficulty {
+    /// Rule-of-thumb decisions with the weakest measured parameters
+    Easy,
+    /// Rule-of-thumb decisions
+    #[default]
+    Normal,
+    /// Buy, bid and build decisions backed by short rollouts
+    Hard,
+}
+
+/// How long bots pause before acting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BotPace {
+    /// No pauses, for bot-only games
+    Instant,
+    /// Short pauses that still let humans follow along
+    Fast,
+    /// Pauses close to how long a person takes
+    #[default]
+    HumanLike,
+}
+
+impl BotPace {
+    /// Jittered pause before a bot takes `action`
+    pub fn think_time(&self, action: &ClientEvent, rng: &mut impl Rng) -> Duration {
+        // Base range in milliseconds at human-like pace
+        let (min, max): (u64, u64) = match action {
+            ClientEvent::RollDice | ClientEvent::PayJail | ClientEvent::UseCard => (600, 1100),
+            ClientEvent::BuyProperty | ClientEvent::PassProperty => (900, 1800),
+            ClientEvent::Bid { .. } | ClientEvent::PassBid => (500, 1300),
+            ClientEvent::Build { .. } => (700, 1400),
+            ClientEvent::EndTurn => (300, 700),
+            _ => (500, 1000),
+        };
+
+        let divisor = match self {
+            BotPace::Instant => return Duration::ZERO,
+            BotPace::Fast => 4,
+            BotPace::HumanLike => 1,
+        };
+
+        Duration::from_millis(rng.gen_range(min..=max) / divisor)
+    }
+}
+
 /// Strategy profile for bots
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BotPersonality {
     /// Aggressive - buys everything, bids high
     Aggressive,
     /// Conservative - only buys good deals
     Conservative,
     /// Balanced - standard strategy
+    #[default]
     Balanced,
 }
 
 impl BotPersonality {
+    /// Personality for the n-th bot in a room, so tables get a mix
+    pub fn for_seat(bot_idx: usize) -> Self {
+        const ROTATION: [BotPersonality; 3] = [
+            BotPersonality::Balanced,
+            BotPersonality::Aggressive,
+            BotPersonality::Conservative,
+        ];
+        ROTATION[bot_idx % ROTATION.len()]
+    }
+
+    /// Share of intrinsic value a bot will overpay to keep an opponent from
+    /// completing a set
+    pub fn blocking_premium(&self) -> f32 {
+        match self {
+            BotPersonality::Aggressive => 0.3,
+            BotPersonality::Conservative => 0.05,
+            BotPersonality::Balanced => 0.15,
+        }
+    }
+
     /// Get buy threshold multiplier (higher = more willing to spend)
     pub fn buy_threshold(&self) -> f32 {
         match self {
@@ -39,9 +118,3 @@ impl BotPersonality {
         }
     }
 }
-
-impl Default for BotPersonality {
-    fn default() -> Self {
-        BotPersonality::Balanced
-    }
-}
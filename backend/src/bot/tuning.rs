@@ -0,0 +1,77 @@
+//! Heuristic parameters behind each difficulty
+//!
+//! Every difficulty maps to a [`BotParams`] set. The built-in sets are
+//! hand-picked guesses; a calibration run replaces them with sets whose
+//! strength was measured in self-play, and the server loads the stored sets
+//! at startup.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use super::strategies::BotDifficulty;
+
+/// Multipliers on the rule-of-thumb heuristics; `1.0` leaves them as written
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BotParams {
+    /// Scales the share of cash a bot will spend at list price
+    pub spend_scale: f32,
+    /// Scales the value a bot puts on a tile at auction
+    pub bid_scale: f32,
+    /// Scales the cash kept back for rent within reach
+    pub reserve_scale: f32,
+}
+
+impl Default for BotParams {
+    fn default() -> Self {
+        Self {
+            spend_scale: 1.0,
+            bid_scale: 1.0,
+            reserve_scale: 1.0,
+        }
+    }
+}
+
+/// Parameter sets currently in effect, keyed by difficulty
+static TUNED: Lazy<RwLock<HashMap<BotDifficulty, BotParams>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+impl BotDifficulty {
+    /// All difficulties, weakest first
+    pub const ALL: [BotDifficulty; 3] = [
+        BotDifficulty::Easy,
+        BotDifficulty::Normal,
+        BotDifficulty::Hard,
+    ];
+
+    /// Parameters this difficulty plays with
+    pub fn params(&self) -> BotParams {
+        TUNED
+            .read()
+            .ok()
+            .and_then(|tuned| tuned.get(self).copied())
+            .unwrap_or_else(|| self.default_params())
+    }
+
+    /// Built-in parameters used until a calibration has been applied
+    pub fn default_params(&self) -> BotParams {
+        match self {
+            // Timid buyer that overpays at auction and hoards cash
+            BotDifficulty::Easy => BotParams {
+                spend_scale: 0.7,
+                bid_scale: 1.2,
+                reserve_scale: 2.0,
+            },
+            BotDifficulty::Normal | BotDifficulty::Hard => BotParams::default(),
+        }
+    }
+
+    /// Replace the parameters for this difficulty
+    pub fn set_params(&self, params: BotParams) {
+        if let Ok(mut tuned) = TUNED.write() {
+            tuned.insert(*self, params);
+        }
+    }
+}
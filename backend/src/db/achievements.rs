@@ -0,0 +1,87 @@
+//! Achievements unlocked by registered users
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::game::achievements::{self, Achievement};
+use crate::game::GameState;
+
+/// An achievement as stored for a user
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UnlockedAchievement {
+    pub achievement: Achievement,
+    /// Game it was first earned in, unless that game is gone
+    pub game_id: Option<Uuid>,
+    pub unlocked_at: DateTime<Utc>,
+}
+
+/// An achievement a user earned for the first time
+#[derive(Debug, Clone, Copy)]
+pub struct Unlocked {
+    pub user_id: Uuid,
+    pub achievement: Achievement,
+}
+
+/// Achievements of a user, oldest first
+pub async fn for_user(pool: &PgPool, user_id: Uuid) -> AppResult<Vec<UnlockedAchievement>> {
+    let rows = sqlx::query(
+        "SELECT achievement, game_id, unlocked_at FROM user_achievements
+         WHERE user_id = $1
+         ORDER BY unlocked_at, achievement",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let name: String = row.try_get("achievement")?;
+            Ok(UnlockedAchievement {
+                achievement: name
+                    .parse()
+                    .map_err(|e: String| AppError::Internal(anyhow::anyhow!(e)))?,
+                game_id: row.try_get("game_id")?,
+                unlocked_at: row.try_get("unlocked_at")?,
+            })
+        })
+        .collect()
+}
+
+/// Store what every registered user earned in a finished game, returning
+/// the achievements unlocked for the first time
+///
+/// Users are handled in id order, like their statistics.
+pub(super) async fn record_game(
+    conn: &mut PgConnection,
+    game_id: Uuid,
+    game: &GameState,
+) -> AppResult<Vec<Unlocked>> {
+    let mut unlocked = Vec::new();
+    for (user_id, player) in game.registered_players() {
+        for achievement in achievements::earned(game, player) {
+            let inserted = sqlx::query(
+                "INSERT INTO user_achievements (user_id, achievement, game_id)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (user_id, achievement) DO NOTHING",
+            )
+            .bind(user_id)
+            .bind(achievement.name())
+            .bind(game_id)
+            .execute(&mut *conn)
+            .await?;
+
+            if inserted.rows_affected() > 0 {
+                unlocked.push(Unlocked {
+                    user_id,
+                    achievement,
+                });
+            }
+        }
+    }
+
+    Ok(unlocked)
+}
@@ -0,0 +1,102 @@
+//! Background archiving of finished games
+//!
+//! The engine only knows Redis, so it hands finished rooms over here and
+//! carries on; the writes happen on their own task. Without a pool (offline
+//! simulations) nothing is archived and rooms expire as usual.
+
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+use redis::aio::ConnectionManager;
+use sqlx::PgPool;
+
+use super::achievements::Unlocked;
+use crate::error::AppResult;
+use crate::game::{store, GameState, ServerEvent};
+use crate::ws::Hub;
+
+static POOL: OnceCell<PgPool> = OnceCell::new();
+
+/// Finished rooms stay in Redis this long once stored, so players can look
+/// at the final board
+const FINISHED_ROOM_TTL_SECS: i64 = 600;
+
+/// Give the archive a pool to write with; called once at startup
+pub fn init(pool: PgPool) {
+    if POOL.set(pool).is_err() {
+        tracing::warn!("Archive pool already set");
+    }
+}
+
+/// Store a finished game and its players' statistics in the background
+///
+/// The room is left to expire from Redis only once it is safely stored.
+/// Players who unlocked an achievement hear about it over their sockets.
+pub fn archive_game(redis: ConnectionManager, hub: Arc<Hub>, game: GameState) {
+    let Some(pool) = POOL.get().cloned() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let room_id = game.id.clone();
+        // A finished game must not come back from an older snapshot
+        if let Err(e) = super::snapshots::delete(&pool, &room_id).await {
+            tracing::warn!("Failed to drop snapshot of room {}: {}", room_id, e);
+        }
+        let unlocked = match super::finalize_game(&pool, &game).await {
+            Ok((_, unlocked)) => unlocked,
+            Err(e) => {
+                tracing::error!("Failed to archive game of room {}: {}", room_id, e);
+                return;
+            }
+        };
+        for Unlocked {
+            user_id,
+            achievement,
+        } in unlocked
+        {
+            hub.notify_user(
+                user_id,
+                ServerEvent::AchievementUnlocked {
+                    achievement,
+                    room_id: room_id.clone(),
+                },
+            );
+        }
+
+        let mut conn = redis;
+        let expired: redis::RedisResult<()> = redis::pipe()
+            .expire(store::layout().game_key(&room_id), FINISHED_ROOM_TTL_SECS)
+            .ignore()
+            .expire(store::layout().log_key(&room_id), FINISHED_ROOM_TTL_SECS)
+            .ignore()
+            .expire(store::layout().chat_key(&room_id), FINISHED_ROOM_TTL_SECS)
+            .ignore()
+            .expire(
+                store::layout().chat_seq_key(&room_id),
+                FINISHED_ROOM_TTL_SECS,
+            )
+            .ignore()
+            .query_async(&mut conn)
+            .await;
+        if let Err(e) = expired {
+            tracing::warn!("Failed to expire finished room {}: {}", room_id, e);
+        }
+    });
+}
+
+/// Saved state of a room whose Redis key is gone, if it was snapshotted
+pub async fn restore_snapshot(room_id: &str) -> AppResult<Option<GameState>> {
+    let Some(pool) = POOL.get() else {
+        return Ok(None);
+    };
+    super::snapshots::load(pool, room_id).await
+}
+
+/// Forget a room's snapshot so it cannot be restored
+pub async fn delete_snapshot(room_id: &str) -> AppResult<()> {
+    let Some(pool) = POOL.get() else {
+        return Ok(());
+    };
+    super::snapshots::delete(pool, room_id).await
+}
@@ -0,0 +1,159 @@
+//! Audit log of host and administrator actions
+//!
+//! Entries are written on their own task so moderation never waits on
+//! Postgres. Without a pool (offline simulations) nothing is recorded.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::error::AppResult;
+
+static POOL: OnceCell<PgPool> = OnceCell::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Kick,
+    Mute,
+    Unmute,
+    ShadowMute,
+    ShadowUnmute,
+    Command,
+    EndRoom,
+    DeleteRoom,
+}
+
+impl AuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Kick => "kick",
+            AuditAction::Mute => "mute",
+            AuditAction::Unmute => "unmute",
+            AuditAction::ShadowMute => "shadow_mute",
+            AuditAction::ShadowUnmute => "shadow_unmute",
+            AuditAction::Command => "command",
+            AuditAction::EndRoom => "end_room",
+            AuditAction::DeleteRoom => "delete_room",
+        }
+    }
+}
+
+/// An action about to be recorded
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub action: AuditAction,
+    pub room_id: String,
+    /// Acting player; `None` for an administrator
+    pub actor_id: Option<Uuid>,
+    pub actor_user_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    /// Names, reasons and whatever else explains the action
+    pub details: serde_json::Value,
+}
+
+/// An action as recorded
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    pub id: i64,
+    pub action: String,
+    pub room_id: String,
+    pub actor_id: Option<Uuid>,
+    pub actor_user_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What to look for in the log; unset fields match anything
+#[derive(Debug, Default)]
+pub struct AuditFilter<'a> {
+    pub room_id: Option<&'a str>,
+    pub action: Option<&'a str>,
+    /// Acting or targeted player
+    pub player_id: Option<Uuid>,
+    /// Only entries with a lower id
+    pub before: Option<i64>,
+}
+
+/// Give the audit log a pool to write with; called once at startup
+pub fn init(pool: PgPool) {
+    if POOL.set(pool).is_err() {
+        tracing::warn!("Audit log pool already set");
+    }
+}
+
+/// Record an action in the background
+pub fn record(entry: AuditEntry) {
+    let Some(pool) = POOL.get().cloned() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        if let Err(e) = insert(&pool, &entry).await {
+            tracing::error!(
+                "Failed to audit {} in room {}: {}",
+                entry.action.as_str(),
+                entry.room_id,
+                e
+            );
+        }
+    });
+}
+
+async fn insert(pool: &PgPool, entry: &AuditEntry) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO audit_log (action, room_id, actor_id, actor_user_id, target_id, details)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(entry.action.as_str())
+    .bind(&entry.room_id)
+    .bind(entry.actor_id)
+    .bind(entry.actor_user_id)
+    .bind(entry.target_id)
+    .bind(&entry.details)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Up to `limit` of the latest matching entries, newest first
+pub async fn list(
+    pool: &PgPool,
+    filter: &AuditFilter<'_>,
+    limit: usize,
+) -> AppResult<Vec<AuditRecord>> {
+    let rows = sqlx::query(
+        "SELECT id, action, room_id, actor_id, actor_user_id, target_id, details, created_at
+         FROM audit_log
+         WHERE ($1::TEXT IS NULL OR room_id = $1)
+           AND ($2::TEXT IS NULL OR action = $2)
+           AND ($3::UUID IS NULL OR actor_id = $3 OR target_id = $3)
+           AND ($4::BIGINT IS NULL OR id < $4)
+         ORDER BY id DESC LIMIT $5",
+    )
+    .bind(filter.room_id)
+    .bind(filter.action)
+    .bind(filter.player_id)
+    .bind(filter.before)
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(AuditRecord {
+                id: row.try_get("id")?,
+                action: row.try_get("action")?,
+                room_id: row.try_get("room_id")?,
+                actor_id: row.try_get("actor_id")?,
+                actor_user_id: row.try_get("actor_user_id")?,
+                target_id: row.try_get("target_id")?,
+                details: row.try_get("details")?,
+                created_at: row.try_get("created_at")?,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,95 @@
+//! Bans of users and IP addresses
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use uuid::Uuid;
+
+use crate::error::AppResult;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Ban {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Permanent when unset
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+fn from_row(row: &PgRow) -> AppResult<Ban> {
+    Ok(Ban {
+        id: row.try_get("id")?,
+        user_id: row.try_get("user_id")?,
+        ip: row.try_get("ip")?,
+        reason: row.try_get("reason")?,
+        created_at: row.try_get("created_at")?,
+        expires_at: row.try_get("expires_at")?,
+    })
+}
+
+pub async fn create(
+    pool: &PgPool,
+    user_id: Option<Uuid>,
+    ip: Option<&str>,
+    reason: Option<&str>,
+    expires_at: Option<DateTime<Utc>>,
+) -> AppResult<Ban> {
+    let row = sqlx::query(
+        "INSERT INTO bans (id, user_id, ip, reason, expires_at) VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, user_id, ip, reason, created_at, expires_at",
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(ip)
+    .bind(reason)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await?;
+    from_row(&row)
+}
+
+/// Bans, newest first; expired ones only with `include_expired`
+pub async fn list(pool: &PgPool, include_expired: bool) -> AppResult<Vec<Ban>> {
+    let rows = sqlx::query(
+        "SELECT id, user_id, ip, reason, created_at, expires_at FROM bans
+         WHERE $1 OR expires_at IS NULL OR expires_at > NOW()
+         ORDER BY created_at DESC",
+    )
+    .bind(include_expired)
+    .fetch_all(pool)
+    .await?;
+    rows.iter().map(from_row).collect()
+}
+
+/// Lift a ban; returns whether it existed
+pub async fn delete(pool: &PgPool, ban_id: Uuid) -> AppResult<bool> {
+    let result = sqlx::query("DELETE FROM bans WHERE id = $1")
+        .bind(ban_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// A ban in force on the user or the address, the longest-lasting first
+pub async fn find_active(
+    pool: &PgPool,
+    user_id: Option<Uuid>,
+    ip: Option<&str>,
+) -> AppResult<Option<Ban>> {
+    if user_id.is_none() && ip.is_none() {
+        return Ok(None);
+    }
+    let row = sqlx::query(
+        "SELECT id, user_id, ip, reason, created_at, expires_at FROM bans
+         WHERE (user_id = $1 OR ip = $2) AND (expires_at IS NULL OR expires_at > NOW())
+         ORDER BY expires_at DESC NULLS FIRST
+         LIMIT 1",
+    )
+    .bind(user_id)
+    .bind(ip)
+    .fetch_optional(pool)
+    .await?;
+    row.as_ref().map(from_row).transpose()
+}
@@ -0,0 +1,68 @@
+//! Stored versions of the boards and their card decks
+
+use sqlx::{PgPool, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::game::board::{BoardDef, Tile};
+use crate::game::cards::{Card, CardEffect, Deck};
+
+/// Every stored version of every board
+pub async fn load_all(pool: &PgPool) -> AppResult<Vec<BoardDef>> {
+    let rows = sqlx::query("SELECT id, version, name, tiles FROM boards ORDER BY id, version")
+        .fetch_all(pool)
+        .await?;
+
+    let mut defs = rows
+        .iter()
+        .map(|row| {
+            let tiles: serde_json::Value = row.try_get("tiles")?;
+            let tiles: Vec<Tile> =
+                serde_json::from_value(tiles).map_err(|e| AppError::Internal(e.into()))?;
+            Ok(BoardDef {
+                id: row.try_get("id")?,
+                name: row.try_get("name")?,
+                version: row.try_get("version")?,
+                tiles,
+                chance: Vec::new(),
+                community_chest: Vec::new(),
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let cards = sqlx::query(
+        r#"
+        SELECT board_id, version, deck, text, effect
+        FROM board_cards
+        ORDER BY board_id, version, deck, position
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for row in cards {
+        let board_id: String = row.try_get("board_id")?;
+        let version: i32 = row.try_get("version")?;
+        let deck: Deck = parse(serde_json::Value::String(row.try_get("deck")?))?;
+        let card = Card {
+            text: row.try_get("text")?,
+            effect: parse::<CardEffect>(row.try_get("effect")?)?,
+        };
+
+        let Some(def) = defs
+            .iter_mut()
+            .find(|d| d.id == board_id && d.version == version)
+        else {
+            continue;
+        };
+        match deck {
+            Deck::Chance => def.chance.push(card),
+            Deck::CommunityChest => def.community_chest.push(card),
+        }
+    }
+
+    Ok(defs)
+}
+
+fn parse<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> AppResult<T> {
+    serde_json::from_value(value).map_err(|e| AppError::Internal(e.into()))
+}
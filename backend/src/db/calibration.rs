@@ -0,0 +1,229 @@
+//! Stored bot calibration runs and tuned difficulty parameters
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::{types::Json, PgPool, Row};
+use uuid::Uuid;
+
+use crate::bot::{BotDifficulty, BotParams};
+use crate::error::{AppError, AppResult};
+use crate::game::calibration::{self, CalibrationParams, CalibrationReport, CandidateResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A calibration run with its per-candidate results
+#[derive(Debug, Serialize)]
+pub struct CalibrationRun {
+    pub id: Uuid,
+    pub status: RunStatus,
+    pub seed: u64,
+    pub games_per_candidate: u32,
+    pub max_turns: u32,
+    pub error: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub report: Option<CalibrationReport>,
+}
+
+/// Parameters stored for a difficulty by the last applied run
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredParams {
+    pub difficulty: BotDifficulty,
+    pub params: BotParams,
+    pub win_rate: f64,
+    pub run_id: Option<Uuid>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Record a run that is about to start
+pub async fn create_run(pool: &PgPool, id: Uuid, params: &CalibrationParams) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO bot_calibration_runs (id, seed, games_per_candidate, max_turns, status)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(id)
+    .bind(params.seed as i64)
+    .bind(params.games_per_candidate as i32)
+    .bind(params.max_turns as i32)
+    .bind(label(&RunStatus::Running)?)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Mark a run as failed
+pub async fn fail_run(pool: &PgPool, id: Uuid, error: &str) -> AppResult<()> {
+    sqlx::query(
+        "UPDATE bot_calibration_runs SET status = $2, error = $3, completed_at = NOW()
+         WHERE id = $1",
+    )
+    .bind(id)
+    .bind(label(&RunStatus::Failed)?)
+    .bind(error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Store a finished run's results, and its picks when `apply` is set
+pub async fn complete_run(
+    pool: &PgPool,
+    id: Uuid,
+    report: &CalibrationReport,
+    apply: bool,
+) -> AppResult<()> {
+    let mut tx = pool.begin().await?;
+
+    for candidate in &report.candidates {
+        sqlx::query(
+            "INSERT INTO bot_calibration_results
+                 (run_id, personality, params, games, wins, win_rate)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(id)
+        .bind(label(&candidate.personality)?)
+        .bind(Json(candidate.params))
+        .bind(candidate.games as i32)
+        .bind(candidate.wins as i32)
+        .bind(candidate.win_rate)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if apply {
+        for tuned in &report.tuned {
+            sqlx::query(
+                "INSERT INTO bot_difficulty_params (difficulty, params, win_rate, run_id)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (difficulty) DO UPDATE
+                 SET params = $2, win_rate = $3, run_id = $4, updated_at = NOW()",
+            )
+            .bind(label(&tuned.difficulty)?)
+            .bind(Json(tuned.params))
+            .bind(tuned.win_rate)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    sqlx::query("UPDATE bot_calibration_runs SET status = $2, completed_at = NOW() WHERE id = $1")
+        .bind(id)
+        .bind(label(&RunStatus::Completed)?)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Fetch a run and, once it has completed, its results
+pub async fn get_run(pool: &PgPool, id: Uuid) -> AppResult<Option<CalibrationRun>> {
+    let Some(row) = sqlx::query(
+        "SELECT id, seed, games_per_candidate, max_turns, status, error, created_at, completed_at
+         FROM bot_calibration_runs WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let status: RunStatus = parse(row.try_get("status")?)?;
+
+    let report = if status == RunStatus::Completed {
+        let rows = sqlx::query(
+            "SELECT personality, params, games, wins, win_rate
+             FROM bot_calibration_results WHERE run_id = $1",
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+
+        let candidates = rows
+            .iter()
+            .map(|row| {
+                Ok(CandidateResult {
+                    personality: parse(row.try_get("personality")?)?,
+                    params: row.try_get::<Json<BotParams>, _>("params")?.0,
+                    games: row.try_get::<i32, _>("games")? as u32,
+                    wins: row.try_get::<i32, _>("wins")? as u32,
+                    win_rate: row.try_get("win_rate")?,
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        let tuned = calibration::tune(&candidates);
+
+        Some(CalibrationReport { candidates, tuned })
+    } else {
+        None
+    };
+
+    Ok(Some(CalibrationRun {
+        id: row.try_get("id")?,
+        status,
+        seed: row.try_get::<i64, _>("seed")? as u64,
+        games_per_candidate: row.try_get::<i32, _>("games_per_candidate")? as u32,
+        max_turns: row.try_get::<i32, _>("max_turns")? as u32,
+        error: row.try_get("error")?,
+        created_at: row.try_get("created_at")?,
+        completed_at: row.try_get("completed_at")?,
+        report,
+    }))
+}
+
+/// Parameters stored for each calibrated difficulty
+pub async fn load_params(pool: &PgPool) -> AppResult<Vec<StoredParams>> {
+    let rows = sqlx::query(
+        "SELECT difficulty, params, win_rate, run_id, updated_at FROM bot_difficulty_params",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(StoredParams {
+                difficulty: parse(row.try_get("difficulty")?)?,
+                params: row.try_get::<Json<BotParams>, _>("params")?.0,
+                win_rate: row.try_get("win_rate")?,
+                run_id: row.try_get("run_id")?,
+                updated_at: row.try_get("updated_at")?,
+            })
+        })
+        .collect()
+}
+
+/// Make the stored parameters the ones bots play with
+pub async fn apply_stored_params(pool: &PgPool) -> AppResult<usize> {
+    let stored = load_params(pool).await?;
+    for entry in &stored {
+        entry.difficulty.set_params(entry.params);
+    }
+
+    Ok(stored.len())
+}
+
+/// Name a unit enum the way it is serialized
+fn label<T: Serialize>(value: &T) -> AppResult<String> {
+    match serde_json::to_value(value).map_err(|e| AppError::Internal(e.into()))? {
+        serde_json::Value::String(s) => Ok(s),
+        other => Err(AppError::Internal(anyhow::anyhow!(
+            "Expected a string label, got {}",
+            other
+        ))),
+    }
+}
+
+fn parse<T: DeserializeOwned>(label: String) -> AppResult<T> {
+    serde_json::from_value(serde_json::Value::String(label))
+        .map_err(|e| AppError::Internal(e.into()))
+}
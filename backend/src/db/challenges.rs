@@ -0,0 +1,251 @@
+//! Challenges of the current day and week, and what users made of them
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{postgres::PgRow, PgConnection, PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::game::challenges::{Goal, Period, Reward};
+use crate::game::GameState;
+
+/// A running challenge with the signed-in user's progress
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChallengeStatus {
+    pub id: Uuid,
+    pub period: Period,
+    pub goal: Goal,
+    pub target: i64,
+    /// Only games on this board count, when set
+    pub board_id: Option<String>,
+    pub reward: Reward,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// 0 without a signed-in user
+    pub progress: i64,
+    pub claimed: bool,
+}
+
+/// A title or cosmetic a user has earned
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct OwnedReward {
+    #[serde(flatten)]
+    pub reward: Reward,
+    pub granted_at: DateTime<Utc>,
+}
+
+fn parse<T: std::str::FromStr<Err = String>>(row: &PgRow, column: &str) -> AppResult<T> {
+    let value: String = row.try_get(column)?;
+    value
+        .parse()
+        .map_err(|e: String| AppError::Internal(anyhow::anyhow!(e)))
+}
+
+fn reward(row: &PgRow) -> AppResult<Reward> {
+    Ok(Reward {
+        kind: parse(row, "reward_kind")?,
+        id: row.try_get("reward")?,
+    })
+}
+
+/// Store the challenges of the periods `now` falls in, unless another
+/// request already did
+async fn ensure_current(conn: &mut PgConnection, now: DateTime<Utc>) -> AppResult<()> {
+    for period in Period::ALL {
+        let (starts_at, ends_at) = period.bounds(now);
+        for (slot, template) in period.rotation(starts_at).into_iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO challenges (id, period, slot, goal, target, board_id, reward_kind,
+                                         reward, starts_at, ends_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                 ON CONFLICT (period, starts_at, slot) DO NOTHING",
+            )
+            .bind(Uuid::new_v4())
+            .bind(period.name())
+            .bind(slot as i32)
+            .bind(template.goal.name())
+            .bind(template.target)
+            .bind(template.board_id)
+            .bind(template.reward_kind.name())
+            .bind(template.reward)
+            .bind(starts_at)
+            .bind(ends_at)
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Challenges running now, daily ones first, with a user's progress
+pub async fn current(pool: &PgPool, user_id: Option<Uuid>) -> AppResult<Vec<ChallengeStatus>> {
+    let now = Utc::now();
+    let mut conn = pool.acquire().await?;
+    ensure_current(&mut conn, now).await?;
+
+    let rows = sqlx::query(
+        "SELECT c.id, c.period, c.goal, c.target, c.board_id, c.reward_kind, c.reward,
+                c.starts_at, c.ends_at, COALESCE(p.progress, 0) AS progress,
+                p.claimed_at IS NOT NULL AS claimed
+         FROM challenges c
+         LEFT JOIN challenge_progress p ON p.challenge_id = c.id AND p.user_id = $1
+         WHERE c.starts_at <= $2 AND c.ends_at > $2
+         ORDER BY c.ends_at, c.slot",
+    )
+    .bind(user_id)
+    .bind(now)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(ChallengeStatus {
+                id: row.try_get("id")?,
+                period: parse(row, "period")?,
+                goal: parse(row, "goal")?,
+                target: row.try_get("target")?,
+                board_id: row.try_get("board_id")?,
+                reward: reward(row)?,
+                starts_at: row.try_get("starts_at")?,
+                ends_at: row.try_get("ends_at")?,
+                progress: row.try_get("progress")?,
+                claimed: row.try_get("claimed")?,
+            })
+        })
+        .collect()
+}
+
+/// Add a finished game to the progress of every registered user who played
+/// it, on every challenge running now
+///
+/// Users are handled in id order, like their statistics.
+pub(super) async fn record_game(conn: &mut PgConnection, game: &GameState) -> AppResult<()> {
+    let players = game.registered_players();
+    if players.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    ensure_current(conn, now).await?;
+    let challenges = sqlx::query(
+        "SELECT id, goal, board_id FROM challenges WHERE starts_at <= $1 AND ends_at > $1",
+    )
+    .bind(now)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (user_id, player) in players {
+        for challenge in &challenges {
+            let board_id: Option<String> = challenge.try_get("board_id")?;
+            if board_id.is_some_and(|id| id != game.board_id) {
+                continue;
+            }
+            let goal: Goal = parse(challenge, "goal")?;
+            let progress = goal.progress(game, player);
+            if progress <= 0 {
+                continue;
+            }
+
+            let challenge_id: Uuid = challenge.try_get("id")?;
+            sqlx::query(
+                "INSERT INTO challenge_progress (challenge_id, user_id, progress)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (challenge_id, user_id) DO UPDATE SET
+                     progress = challenge_progress.progress + EXCLUDED.progress",
+            )
+            .bind(challenge_id)
+            .bind(user_id)
+            .bind(progress)
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Grant the reward of a challenge a user completed, once
+///
+/// Rewards can still be claimed after the challenge has ended.
+pub async fn claim(pool: &PgPool, user_id: Uuid, challenge_id: Uuid) -> AppResult<Reward> {
+    let mut tx = pool.begin().await?;
+
+    // Concurrent claims wait on the progress row and then find it claimed
+    let row = sqlx::query(
+        "UPDATE challenge_progress p SET claimed_at = NOW()
+         FROM challenges c
+         WHERE c.id = p.challenge_id AND p.challenge_id = $1 AND p.user_id = $2
+           AND p.claimed_at IS NULL AND p.progress >= c.target
+         RETURNING c.reward_kind, c.reward",
+    )
+    .bind(challenge_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(row) = row else {
+        return Err(claim_error(&mut tx, user_id, challenge_id).await);
+    };
+    let reward = reward(&row)?;
+
+    // The same reward may come from another challenge too
+    sqlx::query(
+        "INSERT INTO user_rewards (user_id, kind, reward) VALUES ($1, $2, $3)
+         ON CONFLICT (user_id, kind, reward) DO NOTHING",
+    )
+    .bind(user_id)
+    .bind(reward.kind.name())
+    .bind(&reward.id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(reward)
+}
+
+/// Why a challenge could not be claimed
+async fn claim_error(conn: &mut PgConnection, user_id: Uuid, challenge_id: Uuid) -> AppError {
+    let found = sqlx::query(
+        "SELECT p.claimed_at IS NOT NULL AS claimed
+         FROM challenges c
+         LEFT JOIN challenge_progress p ON p.challenge_id = c.id AND p.user_id = $2
+         WHERE c.id = $1",
+    )
+    .bind(challenge_id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await;
+
+    let row = match found {
+        Ok(Some(row)) => row,
+        Ok(None) => return AppError::NotFound("Challenge not found".into()),
+        Err(e) => return e.into(),
+    };
+    match row.try_get::<Option<bool>, _>("claimed") {
+        Ok(Some(true)) => AppError::Conflict("Reward already claimed".into()),
+        Ok(_) => AppError::BadRequest("Challenge is not complete yet".into()),
+        Err(e) => e.into(),
+    }
+}
+
+/// Rewards a user has earned, oldest first
+pub async fn rewards(pool: &PgPool, user_id: Uuid) -> AppResult<Vec<OwnedReward>> {
+    let rows = sqlx::query(
+        "SELECT kind AS reward_kind, reward, granted_at FROM user_rewards
+         WHERE user_id = $1
+         ORDER BY granted_at, kind, reward",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(OwnedReward {
+                reward: reward(row)?,
+                granted_at: row.try_get("granted_at")?,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,218 @@
+//! Chat messages in Postgres
+//!
+//! Messages are queued as they are sent and written in batches by a writer
+//! task, so chat never waits on Postgres; they stay after the room is gone,
+//! for moderation reviews and for reading back finished games. When the
+//! queue fills, messages are dropped from Postgres but still reach the room.
+//! Without a pool (offline simulations) nothing is queued.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use once_cell::sync::OnceCell;
+use sqlx::{postgres::PgRow, PgPool, QueryBuilder, Row};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::game::chat::ChatMessage;
+
+/// Messages waiting to be written before new ones are dropped
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Most messages written in one insert
+const BATCH_SIZE: usize = 500;
+
+/// Longest a message waits for its batch to fill
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often messages past their retention are deleted
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// How often `flush` checks whether the writer caught up
+const FLUSH_POLL: Duration = Duration::from_millis(20);
+
+static QUEUE: OnceCell<mpsc::Sender<(String, ChatMessage)>> = OnceCell::new();
+
+/// Messages queued or being written
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+
+/// Start the writer task, and deleting messages older than
+/// `retention_days` unless it is 0; called once at startup
+pub fn init(pool: PgPool, retention_days: u32) {
+    let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+    if QUEUE.set(tx).is_err() {
+        tracing::warn!("Chat writer already started");
+        return;
+    }
+    tokio::spawn(write(pool.clone(), rx));
+    if retention_days > 0 {
+        tokio::spawn(purge(pool, retention_days));
+    }
+}
+
+/// Queue a message sent in a room
+pub fn record(room_id: &str, message: &ChatMessage) {
+    let Some(queue) = QUEUE.get() else {
+        return;
+    };
+
+    // Counted before sending so the writer never takes it below zero
+    PENDING.fetch_add(1, Ordering::AcqRel);
+    match queue.try_send((room_id.to_string(), message.clone())) {
+        Ok(()) => {}
+        Err(TrySendError::Closed(_)) => {
+            PENDING.fetch_sub(1, Ordering::AcqRel);
+        }
+        Err(TrySendError::Full(_)) => {
+            PENDING.fetch_sub(1, Ordering::AcqRel);
+            tracing::warn!(
+                "Chat queue full, dropping message {} of room {}",
+                message.id,
+                room_id
+            );
+        }
+    }
+}
+
+async fn write(pool: PgPool, mut rx: mpsc::Receiver<(String, ChatMessage)>) {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    while let Some(first) = rx.recv().await {
+        batch.push(first);
+
+        // Give the batch a moment to fill before writing it
+        let deadline = tokio::time::sleep(FLUSH_INTERVAL);
+        tokio::pin!(deadline);
+        while batch.len() < BATCH_SIZE {
+            tokio::select! {
+                message = rx.recv() => match message {
+                    Some(message) => batch.push(message),
+                    None => break,
+                },
+                _ = &mut deadline => break,
+            }
+        }
+
+        if let Err(e) = insert(&pool, &batch).await {
+            tracing::error!("Failed to write {} chat messages: {}", batch.len(), e);
+        }
+        PENDING.fetch_sub(batch.len(), Ordering::AcqRel);
+        batch.clear();
+    }
+}
+
+/// Wait until every queued message is written, giving up after `timeout`
+///
+/// Returns whether the writer caught up in time.
+pub async fn flush(timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while PENDING.load(Ordering::Acquire) > 0 {
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(FLUSH_POLL).await;
+    }
+    true
+}
+
+async fn insert(pool: &PgPool, batch: &[(String, ChatMessage)]) -> sqlx::Result<()> {
+    let mut query = QueryBuilder::new(
+        "INSERT INTO chat_messages (room_id, id, player_id, player_name, message, sent_at) ",
+    );
+    query.push_values(batch, |mut row, (room_id, message)| {
+        row.push_bind(room_id)
+            .push_bind(message.id as i64)
+            .push_bind(message.from)
+            .push_bind(&message.from_name)
+            .push_bind(&message.message)
+            .push_bind(message.sent_at);
+    });
+    query.push(" ON CONFLICT (room_id, id) DO NOTHING");
+
+    query.build().execute(pool).await?;
+    Ok(())
+}
+
+async fn purge(pool: PgPool, retention_days: u32) {
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match purge_older_than(&pool, retention_days).await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!("Deleted {} chat messages past retention", n),
+            Err(e) => tracing::warn!("Deleting old chat messages failed: {}", e),
+        }
+    }
+}
+
+/// Delete messages sent more than `days` ago, returning how many
+pub async fn purge_older_than(pool: &PgPool, days: u32) -> AppResult<u64> {
+    let purged =
+        sqlx::query("DELETE FROM chat_messages WHERE sent_at < NOW() - make_interval(days => $1)")
+            .bind(days as i32)
+            .execute(pool)
+            .await?
+            .rows_affected();
+
+    Ok(purged)
+}
+
+/// Up to `limit` of a player's latest archived messages in a room, oldest
+/// first
+pub async fn by_player(
+    pool: &PgPool,
+    room_id: &str,
+    player_id: Uuid,
+    limit: usize,
+) -> AppResult<Vec<ChatMessage>> {
+    let rows = sqlx::query(
+        "SELECT id, player_id, player_name, message, sent_at FROM chat_messages
+         WHERE room_id = $1 AND player_id = $2
+         ORDER BY id DESC LIMIT $3",
+    )
+    .bind(room_id)
+    .bind(player_id)
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+
+    let mut messages = rows.iter().map(from_row).collect::<AppResult<Vec<_>>>()?;
+    messages.reverse();
+    Ok(messages)
+}
+
+fn from_row(row: &PgRow) -> AppResult<ChatMessage> {
+    Ok(ChatMessage {
+        id: row.try_get::<i64, _>("id")? as u64,
+        from: row.try_get("player_id")?,
+        from_name: row.try_get("player_name")?,
+        message: row.try_get("message")?,
+        sent_at: row.try_get("sent_at")?,
+    })
+}
+
+/// Up to `limit` of the latest archived messages older than `before`,
+/// oldest first
+pub async fn history(
+    pool: &PgPool,
+    room_id: &str,
+    before: Option<u64>,
+    limit: usize,
+) -> AppResult<Vec<ChatMessage>> {
+    let rows = sqlx::query(
+        "SELECT id, player_id, player_name, message, sent_at FROM chat_messages
+         WHERE room_id = $1 AND ($2::BIGINT IS NULL OR id < $2)
+         ORDER BY id DESC LIMIT $3",
+    )
+    .bind(room_id)
+    .bind(before.map(|b| b as i64))
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+
+    let mut messages = rows.iter().map(from_row).collect::<AppResult<Vec<_>>>()?;
+    messages.reverse();
+
+    Ok(messages)
+}
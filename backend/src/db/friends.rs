@@ -0,0 +1,135 @@
+//! Friendships between registered users
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Friend {
+    pub user_id: Uuid,
+    pub username: String,
+    /// When the friendship, or for pending requests the request, started
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct FriendList {
+    pub friends: Vec<Friend>,
+    /// Requests waiting for this user's answer
+    pub incoming: Vec<Friend>,
+    /// Requests this user sent that were not answered yet
+    pub outgoing: Vec<Friend>,
+}
+
+pub async fn list(pool: &PgPool, user_id: Uuid) -> AppResult<FriendList> {
+    let rows = sqlx::query(
+        "SELECT f.requester_id, f.created_at, f.accepted_at, u.id AS other_id, u.username
+         FROM friendships f
+         JOIN users u ON u.id = CASE WHEN f.requester_id = $1 THEN f.addressee_id
+                                     ELSE f.requester_id END
+         WHERE f.requester_id = $1 OR f.addressee_id = $1
+         ORDER BY u.username",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut list = FriendList::default();
+    for row in rows {
+        let requester: Uuid = row.try_get("requester_id")?;
+        let accepted_at: Option<DateTime<Utc>> = row.try_get("accepted_at")?;
+        let friend = Friend {
+            user_id: row.try_get("other_id")?,
+            username: row.try_get("username")?,
+            since: accepted_at.unwrap_or(row.try_get("created_at")?),
+        };
+
+        match (accepted_at, requester == user_id) {
+            (Some(_), _) => list.friends.push(friend),
+            (None, true) => list.outgoing.push(friend),
+            (None, false) => list.incoming.push(friend),
+        }
+    }
+
+    Ok(list)
+}
+
+/// Ask `to` to be friends, returning whether that made them friends
+///
+/// A request to someone who already asked this user accepts theirs.
+pub async fn request(pool: &PgPool, from: Uuid, to: Uuid) -> AppResult<bool> {
+    if accept(pool, from, to).await? {
+        return Ok(true);
+    }
+
+    let inserted = sqlx::query(
+        "INSERT INTO friendships (requester_id, addressee_id)
+         SELECT $1, $2
+         WHERE NOT EXISTS (
+             SELECT 1 FROM friendships WHERE requester_id = $2 AND addressee_id = $1
+         )
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(from)
+    .bind(to)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if inserted == 0 {
+        return Err(AppError::BadRequest(
+            "Already friends or a request is pending".into(),
+        ));
+    }
+    Ok(false)
+}
+
+/// Accept the pending request `from` sent to `user_id`, if there is one
+pub async fn accept(pool: &PgPool, user_id: Uuid, from: Uuid) -> AppResult<bool> {
+    let accepted = sqlx::query(
+        "UPDATE friendships SET accepted_at = NOW()
+         WHERE requester_id = $1 AND addressee_id = $2 AND accepted_at IS NULL",
+    )
+    .bind(from)
+    .bind(user_id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(accepted > 0)
+}
+
+/// End a friendship, or withdraw or decline a request, either way round
+pub async fn remove(pool: &PgPool, user_id: Uuid, other: Uuid) -> AppResult<bool> {
+    let removed = sqlx::query(
+        "DELETE FROM friendships
+         WHERE (requester_id = $1 AND addressee_id = $2)
+            OR (requester_id = $2 AND addressee_id = $1)",
+    )
+    .bind(user_id)
+    .bind(other)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(removed > 0)
+}
+
+pub async fn are_friends(pool: &PgPool, a: Uuid, b: Uuid) -> AppResult<bool> {
+    let row = sqlx::query(
+        "SELECT 1 FROM friendships
+         WHERE accepted_at IS NOT NULL
+           AND ((requester_id = $1 AND addressee_id = $2)
+             OR (requester_id = $2 AND addressee_id = $1))",
+    )
+    .bind(a)
+    .bind(b)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.is_some())
+}
@@ -0,0 +1,259 @@
+//! Finished games and how each seat ended up
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{postgres::PgRow, PgConnection, PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::achievements::Unlocked;
+use crate::error::{AppError, AppResult};
+use crate::game::integrity::IntegrityFlag;
+use crate::game::summary::GameSummary;
+use crate::game::GameState;
+
+/// A finished game as stored
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GameRecord {
+    pub id: Uuid,
+    pub room_id: Option<String>,
+    /// Winner's account, if they had one
+    pub winner_id: Option<Uuid>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Seats from first to last place
+    pub players: Vec<GamePlayerRecord>,
+}
+
+/// How one seat of a finished game ended up
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GamePlayerRecord {
+    pub player_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub player_name: Option<String>,
+    /// Placement, 1 for the winner
+    pub final_position: Option<i32>,
+    pub final_balance: Option<i32>,
+    pub net_worth: Option<i32>,
+    pub is_bot: bool,
+}
+
+/// Attempts at finalizing a game before giving up
+const FINALIZE_ATTEMPTS: u32 = 3;
+
+/// Store a finished game, its seats, and its players' statistics,
+/// achievements and challenge progress in one transaction, returning the game's id and the
+/// achievements unlocked for the first time
+///
+/// Concurrent games ending with the same players can collide on the
+/// statistics rows; the transaction is retried when Postgres aborts it for
+/// that.
+pub async fn finalize_game(pool: &PgPool, game: &GameState) -> AppResult<(Uuid, Vec<Unlocked>)> {
+    let mut attempt = 1;
+    loop {
+        match try_finalize(pool, game).await {
+            Err(AppError::Database(e)) if attempt < FINALIZE_ATTEMPTS && is_retryable(&e) => {
+                tracing::debug!("Retrying results of room {}: {}", game.id, e);
+                tokio::time::sleep(Duration::from_millis(50 * attempt as u64)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn try_finalize(pool: &PgPool, game: &GameState) -> AppResult<(Uuid, Vec<Unlocked>)> {
+    let mut tx = pool.begin().await?;
+    let id = record_game(&mut tx, game).await?;
+    super::stats::record_game(&mut tx, game).await?;
+    let unlocked = super::achievements::record_game(&mut tx, id, game).await?;
+    super::challenges::record_game(&mut tx, game).await?;
+    tx.commit().await?;
+    Ok((id, unlocked))
+}
+
+/// Serialization failures and deadlocks
+pub(crate) fn is_retryable(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Database(db) => matches!(db.code().as_deref(), Some("40001" | "40P01")),
+        _ => false,
+    }
+}
+
+/// Store a finished game with a row per seat, returning the game's id
+///
+/// `final_position` of a seat is its placement, 1 for the winner.
+async fn record_game(conn: &mut PgConnection, game: &GameState) -> AppResult<Uuid> {
+    let id = Uuid::new_v4();
+    let standings = game.standings();
+    let winner = standings.first().and_then(|id| game.get_player(*id));
+    let started_at = game
+        .started_at
+        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0));
+    let config = serde_json::to_value(&game.config).map_err(|e| AppError::Internal(e.into()))?;
+    let final_state = serde_json::to_value(game).map_err(|e| AppError::Internal(e.into()))?;
+    let summary =
+        serde_json::to_value(GameSummary::of(game)).map_err(|e| AppError::Internal(e.into()))?;
+    let flags = game
+        .integrity
+        .is_flagged()
+        .then(|| serde_json::to_value(&game.integrity.flags))
+        .transpose()
+        .map_err(|e| AppError::Internal(e.into()))?;
+
+    sqlx::query(
+        "INSERT INTO games (id, room_id, winner_id, config, started_at, ended_at, final_state,
+                            summary, ranked, rated, integrity_flags)
+         VALUES ($1, $2, $3, $4, $5, NOW(), $6, $7, $8, $9, $10)",
+    )
+    .bind(id)
+    .bind(&game.id)
+    .bind(winner.and_then(|p| p.user_id))
+    .bind(config)
+    .bind(started_at)
+    .bind(final_state)
+    .bind(summary)
+    .bind(game.config.ranked)
+    .bind(game.is_rated())
+    .bind(flags)
+    .execute(&mut *conn)
+    .await?;
+
+    for (place, player_id) in standings.iter().enumerate() {
+        let Some(player) = game.get_player(*player_id) else {
+            continue;
+        };
+
+        sqlx::query(
+            "INSERT INTO game_players (game_id, player_id, user_id, player_name,
+                                       final_position, final_balance, net_worth, is_bot)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(id)
+        .bind(player.id)
+        .bind(player.user_id)
+        .bind(&player.name)
+        .bind(place as i32 + 1)
+        .bind(player.balance)
+        .bind(game.net_worth(player.id))
+        .bind(player.is_bot)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(id)
+}
+
+/// Summary stored with a finished game; `None` when the game is unknown or
+/// was stored before summaries were kept
+pub async fn summary(pool: &PgPool, game_id: Uuid) -> AppResult<Option<GameSummary>> {
+    let summary: Option<Option<serde_json::Value>> =
+        sqlx::query_scalar("SELECT summary FROM games WHERE id = $1")
+            .bind(game_id)
+            .fetch_optional(pool)
+            .await?;
+
+    summary
+        .flatten()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| AppError::Internal(e.into()))
+}
+
+/// A stored ranked game whose seats shared a device
+#[derive(Debug, Clone, Serialize)]
+pub struct FlaggedGame {
+    pub id: Uuid,
+    pub room_id: Option<String>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub flags: Vec<IntegrityFlag>,
+}
+
+/// Latest flagged games, newest first
+pub async fn flagged(pool: &PgPool, limit: usize) -> AppResult<Vec<FlaggedGame>> {
+    let rows = sqlx::query(
+        "SELECT id, room_id, ended_at, integrity_flags FROM games
+         WHERE integrity_flags IS NOT NULL
+         ORDER BY ended_at DESC LIMIT $1",
+    )
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let flags: serde_json::Value = row.try_get("integrity_flags")?;
+            Ok(FlaggedGame {
+                id: row.try_get("id")?,
+                room_id: row.try_get("room_id")?,
+                ended_at: row.try_get("ended_at")?,
+                flags: serde_json::from_value(flags).map_err(|e| AppError::Internal(e.into()))?,
+            })
+        })
+        .collect()
+}
+
+/// Latest finished games a user played in, newest first
+pub async fn recent_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+    limit: usize,
+) -> AppResult<Vec<GameRecord>> {
+    let rows = sqlx::query(
+        "SELECT g.id, g.room_id, g.winner_id, g.started_at, g.ended_at FROM games g
+         WHERE EXISTS (SELECT 1 FROM game_players gp WHERE gp.game_id = g.id AND gp.user_id = $1)
+         ORDER BY g.ended_at DESC NULLS LAST LIMIT $2",
+    )
+    .bind(user_id)
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+
+    let mut games = rows
+        .iter()
+        .map(|row| {
+            Ok(GameRecord {
+                id: row.try_get("id")?,
+                room_id: row.try_get("room_id")?,
+                winner_id: row.try_get("winner_id")?,
+                started_at: row.try_get("started_at")?,
+                ended_at: row.try_get("ended_at")?,
+                players: Vec::new(),
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let ids: Vec<Uuid> = games.iter().map(|game| game.id).collect();
+    let seats = sqlx::query(
+        "SELECT game_id, player_id, user_id, player_name, final_position, final_balance,
+                net_worth, is_bot
+         FROM game_players WHERE game_id = ANY($1)
+         ORDER BY final_position NULLS LAST",
+    )
+    .bind(&ids)
+    .fetch_all(pool)
+    .await?;
+
+    for row in &seats {
+        let game_id: Uuid = row.try_get("game_id")?;
+        if let Some(game) = games.iter_mut().find(|game| game.id == game_id) {
+            game.players.push(player_record(row)?);
+        }
+    }
+
+    Ok(games)
+}
+
+fn player_record(row: &PgRow) -> AppResult<GamePlayerRecord> {
+    Ok(GamePlayerRecord {
+        player_id: row.try_get("player_id")?,
+        user_id: row.try_get("user_id")?,
+        player_name: row.try_get("player_name")?,
+        final_position: row.try_get("final_position")?,
+        final_balance: row.try_get("final_balance")?,
+        net_worth: row.try_get("net_worth")?,
+        is_bot: row.try_get::<Option<bool>, _>("is_bot")?.unwrap_or(false),
+    })
+}
@@ -0,0 +1,93 @@
+//! Provider accounts linked to users, for OAuth login
+
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use super::users::User;
+use crate::error::{AppError, AppResult};
+
+fn already_linked(e: sqlx::Error) -> AppError {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            AppError::BadRequest("This provider account is already linked to a user".into())
+        }
+        _ => AppError::Database(e),
+    }
+}
+
+/// User the provider account signs in as, if it was seen before
+pub async fn find_user(pool: &PgPool, provider: &str, subject: &str) -> AppResult<Option<User>> {
+    let row = sqlx::query(
+        "SELECT u.id, u.username, u.created_at FROM user_identities i
+         JOIN users u ON u.id = i.user_id
+         WHERE i.provider = $1 AND i.subject = $2",
+    )
+    .bind(provider)
+    .bind(subject)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|row| {
+        Ok(User {
+            id: row.try_get("id")?,
+            username: row.try_get("username")?,
+            created_at: row.try_get("created_at")?,
+        })
+    })
+    .transpose()
+}
+
+/// Let a provider account sign in as an existing user
+pub async fn link(pool: &PgPool, provider: &str, subject: &str, user_id: Uuid) -> AppResult<()> {
+    sqlx::query("INSERT INTO user_identities (provider, subject, user_id) VALUES ($1, $2, $3)")
+        .bind(provider)
+        .bind(subject)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(already_linked)?;
+
+    Ok(())
+}
+
+/// Create a passwordless user signed in by a provider account
+///
+/// Returns `None` if the username is taken, so the caller can try another.
+pub async fn create_user(
+    pool: &PgPool,
+    username: &str,
+    provider: &str,
+    subject: &str,
+) -> AppResult<Option<User>> {
+    let mut tx = pool.begin().await?;
+
+    let inserted = sqlx::query(
+        "INSERT INTO users (id, username) VALUES ($1, $2) RETURNING id, username, created_at",
+    )
+    .bind(Uuid::new_v4())
+    .bind(username)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let row = match inserted {
+        Ok(row) => row,
+        Err(sqlx::Error::Database(db)) if db.is_unique_violation() => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let user = User {
+        id: row.try_get("id")?,
+        username: row.try_get("username")?,
+        created_at: row.try_get("created_at")?,
+    };
+
+    sqlx::query("INSERT INTO user_identities (provider, subject, user_id) VALUES ($1, $2, $3)")
+        .bind(provider)
+        .bind(subject)
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(already_linked)?;
+
+    tx.commit().await?;
+    Ok(Some(user))
+}
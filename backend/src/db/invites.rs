@@ -0,0 +1,97 @@
+//! Invitations to join a room
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Invite {
+    pub id: Uuid,
+    pub room_id: String,
+    pub from_user_id: Uuid,
+    pub from_username: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Invite `to` into a room, replacing an earlier invite to the same room
+pub async fn create(
+    pool: &PgPool,
+    room_id: &str,
+    from: Uuid,
+    to: Uuid,
+    expires_at: DateTime<Utc>,
+) -> AppResult<Uuid> {
+    let id = Uuid::new_v4();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM room_invites WHERE room_id = $1 AND to_user = $2")
+        .bind(room_id)
+        .bind(to)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO room_invites (id, room_id, from_user, to_user, expires_at)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(id)
+    .bind(room_id)
+    .bind(from)
+    .bind(to)
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(id)
+}
+
+/// Invites waiting for a user's answer, newest first
+pub async fn pending_for(pool: &PgPool, user_id: Uuid) -> AppResult<Vec<Invite>> {
+    let rows = sqlx::query(
+        "SELECT i.id, i.room_id, i.from_user, u.username, i.created_at, i.expires_at
+         FROM room_invites i
+         JOIN users u ON u.id = i.from_user
+         WHERE i.to_user = $1 AND i.expires_at > NOW()
+         ORDER BY i.created_at DESC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(Invite {
+                id: row.try_get("id")?,
+                room_id: row.try_get("room_id")?,
+                from_user_id: row.try_get("from_user")?,
+                from_username: row.try_get("username")?,
+                created_at: row.try_get("created_at")?,
+                expires_at: row.try_get("expires_at")?,
+            })
+        })
+        .collect()
+}
+
+/// Remove an invite addressed to `user_id`, returning its room if it was
+/// still valid
+pub async fn take(pool: &PgPool, id: Uuid, user_id: Uuid) -> AppResult<Option<String>> {
+    let row = sqlx::query(
+        "DELETE FROM room_invites WHERE id = $1 AND to_user = $2
+         RETURNING room_id, expires_at > NOW() AS live",
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let live: bool = row.try_get("live")?;
+    Ok(live.then(|| row.try_get("room_id")).transpose()?)
+}
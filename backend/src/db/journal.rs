@@ -0,0 +1,148 @@
+//! Append-only journal of game events
+//!
+//! The engine queues events without waiting; a writer task inserts them in
+//! batches. When Postgres falls behind and the queue fills, events are
+//! dropped rather than slowing down play. Without a pool (offline
+//! simulations) nothing is queued.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use sqlx::{PgPool, QueryBuilder};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use uuid::Uuid;
+
+use crate::game::ServerEvent;
+
+/// Events waiting to be written before new ones are dropped
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Most events written in one insert
+const BATCH_SIZE: usize = 500;
+
+/// Longest an event waits for its batch to fill
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often `flush` checks whether the writer caught up
+const FLUSH_POLL: Duration = Duration::from_millis(20);
+
+static QUEUE: OnceCell<mpsc::Sender<JournalEntry>> = OnceCell::new();
+
+/// Events queued or being written
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+
+/// One event as it went out to a room
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    pub room_id: String,
+    /// Position in the room's journal, from 1
+    pub seq: u64,
+    /// Player whose action caused the event
+    pub player_id: Option<Uuid>,
+    pub event: ServerEvent,
+    pub at: DateTime<Utc>,
+}
+
+/// Start the writer task; called once at startup
+pub fn init(pool: PgPool) {
+    let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+    if QUEUE.set(tx).is_err() {
+        tracing::warn!("Event journal already started");
+        return;
+    }
+    tokio::spawn(write(pool, rx));
+}
+
+/// Queue events for the journal
+pub fn record(entries: Vec<JournalEntry>) {
+    let Some(queue) = QUEUE.get() else {
+        return;
+    };
+
+    for entry in entries {
+        // Counted before sending so the writer never takes it below zero
+        PENDING.fetch_add(1, Ordering::AcqRel);
+        match queue.try_send(entry) {
+            Ok(()) => {}
+            Err(TrySendError::Full(entry)) => {
+                PENDING.fetch_sub(1, Ordering::AcqRel);
+                tracing::warn!(
+                    "Event journal queue full, dropping event {} of room {}",
+                    entry.seq,
+                    entry.room_id
+                );
+            }
+            Err(TrySendError::Closed(_)) => {
+                PENDING.fetch_sub(1, Ordering::AcqRel);
+                return;
+            }
+        }
+    }
+}
+
+async fn write(pool: PgPool, mut rx: mpsc::Receiver<JournalEntry>) {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    while let Some(first) = rx.recv().await {
+        batch.push(first);
+
+        // Give the batch a moment to fill before writing it
+        let deadline = tokio::time::sleep(FLUSH_INTERVAL);
+        tokio::pin!(deadline);
+        while batch.len() < BATCH_SIZE {
+            tokio::select! {
+                entry = rx.recv() => match entry {
+                    Some(entry) => batch.push(entry),
+                    None => break,
+                },
+                _ = &mut deadline => break,
+            }
+        }
+
+        if let Err(e) = insert(&pool, &batch).await {
+            tracing::error!("Failed to write {} journal events: {}", batch.len(), e);
+        }
+        PENDING.fetch_sub(batch.len(), Ordering::AcqRel);
+        batch.clear();
+    }
+}
+
+/// Wait until every queued event is written, giving up after `timeout`
+///
+/// Returns whether the writer caught up in time.
+pub async fn flush(timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while PENDING.load(Ordering::Acquire) > 0 {
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(FLUSH_POLL).await;
+    }
+    true
+}
+
+async fn insert(pool: &PgPool, batch: &[JournalEntry]) -> sqlx::Result<()> {
+    let mut query = QueryBuilder::new(
+        "INSERT INTO game_events (room_id, seq, player_id, kind, event, created_at) ",
+    );
+    query.push_values(batch, |mut row, entry| {
+        let event = serde_json::to_value(&entry.event).unwrap_or_default();
+        let kind = event
+            .get("type")
+            .and_then(|kind| kind.as_str())
+            .unwrap_or("UNKNOWN")
+            .to_string();
+
+        row.push_bind(&entry.room_id)
+            .push_bind(entry.seq as i64)
+            .push_bind(entry.player_id)
+            .push_bind(kind)
+            .push_bind(event)
+            .push_bind(entry.at);
+    });
+
+    query.build().execute(pool).await?;
+    Ok(())
+}
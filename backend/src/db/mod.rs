@@ -1,5 +1,25 @@
 //! Database module
 
+pub mod achievements;
+pub mod archive;
+pub mod audit;
+pub mod bans;
+pub mod boards;
+pub mod calibration;
+pub mod challenges;
+pub mod chat;
+pub mod friends;
+pub mod games;
+pub mod identities;
+pub mod invites;
+pub mod journal;
 mod pool;
+pub mod profiles;
+pub mod reports;
+pub mod sessions;
+pub mod snapshots;
+pub mod stats;
+pub mod users;
 
+pub use games::finalize_game;
 pub use pool::create_pool;
@@ -0,0 +1,61 @@
+//! Display preferences of registered users
+
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct Profile {
+    /// Name used in rooms; the username when unset
+    pub display_name: Option<String>,
+    pub avatar: Option<String>,
+    /// Preferred player color, as `#RRGGBB`
+    pub color: Option<String>,
+    /// BCP 47 language tag such as `en` or `pt-BR`
+    pub locale: Option<String>,
+}
+
+/// A user's profile; all defaults if they never saved one
+pub async fn get_profile(pool: &PgPool, user_id: Uuid) -> AppResult<Profile> {
+    let row =
+        sqlx::query("SELECT display_name, avatar, color, locale FROM profiles WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+
+    let Some(row) = row else {
+        return Ok(Profile::default());
+    };
+
+    Ok(Profile {
+        display_name: row.try_get("display_name")?,
+        avatar: row.try_get("avatar")?,
+        color: row.try_get("color")?,
+        locale: row.try_get("locale")?,
+    })
+}
+
+pub async fn save_profile(pool: &PgPool, user_id: Uuid, profile: &Profile) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO profiles (user_id, display_name, avatar, color, locale)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (user_id) DO UPDATE SET
+             display_name = EXCLUDED.display_name,
+             avatar = EXCLUDED.avatar,
+             color = EXCLUDED.color,
+             locale = EXCLUDED.locale,
+             updated_at = NOW()",
+    )
+    .bind(user_id)
+    .bind(&profile.display_name)
+    .bind(&profile.avatar)
+    .bind(&profile.color)
+    .bind(&profile.locale)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,253 @@
+//! Players reported by other players
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::game::chat::ChatMessage;
+
+/// What a player is reported for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportCategory {
+    Cheating,
+    Harassment,
+    Spam,
+    OffensiveName,
+    Other,
+}
+
+impl ReportCategory {
+    pub const ALL: [ReportCategory; 5] = [
+        ReportCategory::Cheating,
+        ReportCategory::Harassment,
+        ReportCategory::Spam,
+        ReportCategory::OffensiveName,
+        ReportCategory::Other,
+    ];
+
+    /// Name as stored and sent to clients
+    pub fn name(self) -> &'static str {
+        match self {
+            ReportCategory::Cheating => "cheating",
+            ReportCategory::Harassment => "harassment",
+            ReportCategory::Spam => "spam",
+            ReportCategory::OffensiveName => "offensive_name",
+            ReportCategory::Other => "other",
+        }
+    }
+}
+
+impl FromStr for ReportCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        ReportCategory::ALL
+            .into_iter()
+            .find(|category| category.name() == s)
+            .ok_or_else(|| format!("Unknown report category {}", s))
+    }
+}
+
+/// Where a report stands with the moderators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    Open,
+    /// Acted upon
+    Resolved,
+    /// Looked at and nothing done
+    Dismissed,
+}
+
+impl ReportStatus {
+    pub const ALL: [ReportStatus; 3] = [
+        ReportStatus::Open,
+        ReportStatus::Resolved,
+        ReportStatus::Dismissed,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReportStatus::Open => "open",
+            ReportStatus::Resolved => "resolved",
+            ReportStatus::Dismissed => "dismissed",
+        }
+    }
+}
+
+impl FromStr for ReportStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        ReportStatus::ALL
+            .into_iter()
+            .find(|status| status.name() == s)
+            .ok_or_else(|| format!("Unknown report status {}", s))
+    }
+}
+
+/// One side of a report: a seat in the room and its account, if any
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportedPlayer {
+    pub player_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub id: Uuid,
+    pub room_id: String,
+    pub reporter: ReportedPlayer,
+    pub target: ReportedPlayer,
+    pub category: ReportCategory,
+    pub comment: Option<String>,
+    /// The target's latest messages in the room when the report was made
+    pub chat_excerpt: Option<Vec<ChatMessage>>,
+    pub status: ReportStatus,
+    pub resolution_note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// A report as filed, before it is stored
+pub struct NewReport<'a> {
+    pub room_id: &'a str,
+    pub reporter: ReportedPlayer,
+    pub target: ReportedPlayer,
+    pub category: ReportCategory,
+    pub comment: Option<&'a str>,
+    pub chat_excerpt: Option<&'a [ChatMessage]>,
+}
+
+const COLUMNS: &str = "id, room_id, reporter_id, reporter_user_id, reporter_name, target_id, \
+     target_user_id, target_name, category, comment, chat_excerpt, status, resolution_note, \
+     created_at, resolved_at";
+
+fn internal(e: impl std::fmt::Display) -> AppError {
+    AppError::Internal(anyhow::anyhow!("{}", e))
+}
+
+fn from_row(row: &PgRow) -> AppResult<Report> {
+    let category: String = row.try_get("category")?;
+    let status: String = row.try_get("status")?;
+    let chat_excerpt: Option<serde_json::Value> = row.try_get("chat_excerpt")?;
+    Ok(Report {
+        id: row.try_get("id")?,
+        room_id: row.try_get("room_id")?,
+        reporter: ReportedPlayer {
+            player_id: row.try_get("reporter_id")?,
+            user_id: row.try_get("reporter_user_id")?,
+            name: row.try_get("reporter_name")?,
+        },
+        target: ReportedPlayer {
+            player_id: row.try_get("target_id")?,
+            user_id: row.try_get("target_user_id")?,
+            name: row.try_get("target_name")?,
+        },
+        category: category.parse().map_err(internal)?,
+        comment: row.try_get("comment")?,
+        chat_excerpt: chat_excerpt
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(internal)?,
+        status: status.parse().map_err(internal)?,
+        resolution_note: row.try_get("resolution_note")?,
+        created_at: row.try_get("created_at")?,
+        resolved_at: row.try_get("resolved_at")?,
+    })
+}
+
+/// Store a report; refused while the reporter has one open on the target
+pub async fn create(pool: &PgPool, report: NewReport<'_>) -> AppResult<Report> {
+    let chat_excerpt = report
+        .chat_excerpt
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(internal)?;
+
+    let result = sqlx::query(&format!(
+        "INSERT INTO reports (id, room_id, reporter_id, reporter_user_id, reporter_name,
+             target_id, target_user_id, target_name, category, comment, chat_excerpt)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+         RETURNING {}",
+        COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(report.room_id)
+    .bind(report.reporter.player_id)
+    .bind(report.reporter.user_id)
+    .bind(&report.reporter.name)
+    .bind(report.target.player_id)
+    .bind(report.target.user_id)
+    .bind(&report.target.name)
+    .bind(report.category.name())
+    .bind(report.comment)
+    .bind(chat_excerpt)
+    .fetch_one(pool)
+    .await;
+
+    match result {
+        Ok(row) => from_row(&row),
+        Err(sqlx::Error::Database(db)) if db.is_unique_violation() => Err(AppError::Conflict(
+            "You already reported this player".into(),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Up to `limit` reports, optionally in one status, newest first
+pub async fn list(
+    pool: &PgPool,
+    status: Option<ReportStatus>,
+    limit: usize,
+) -> AppResult<Vec<Report>> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM reports
+         WHERE $1::TEXT IS NULL OR status = $1
+         ORDER BY created_at DESC
+         LIMIT $2",
+        COLUMNS
+    ))
+    .bind(status.map(ReportStatus::name))
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+    rows.iter().map(from_row).collect()
+}
+
+/// Close an open report; `None` when it does not exist or is closed already
+pub async fn resolve(
+    pool: &PgPool,
+    report_id: Uuid,
+    status: ReportStatus,
+    note: Option<&str>,
+) -> AppResult<Option<Report>> {
+    let row = sqlx::query(&format!(
+        "UPDATE reports SET status = $2, resolution_note = $3, resolved_at = NOW()
+         WHERE id = $1 AND status = 'open'
+         RETURNING {}",
+        COLUMNS
+    ))
+    .bind(report_id)
+    .bind(status.name())
+    .bind(note)
+    .fetch_optional(pool)
+    .await?;
+    row.as_ref().map(from_row).transpose()
+}
+
+/// Whether a report exists at all
+pub async fn exists(pool: &PgPool, report_id: Uuid) -> AppResult<bool> {
+    let found: Option<i32> = sqlx::query_scalar("SELECT 1 FROM reports WHERE id = $1")
+        .bind(report_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(found.is_some())
+}
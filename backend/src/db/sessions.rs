@@ -0,0 +1,83 @@
+//! Sign-in sessions backing refresh tokens
+//!
+//! Only a hash of the refresh token is stored, so a leaked table cannot be
+//! replayed.
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::error::AppResult;
+
+/// Start a session for `user_id` holding `refresh_hash`
+pub async fn create_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    refresh_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> AppResult<Uuid> {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO user_sessions (id, user_id, refresh_hash, expires_at) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(refresh_hash)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(id)
+}
+
+/// Swap a live session's refresh token for a new one
+///
+/// Returns the session and user ids, or `None` if `old_hash` belongs to no
+/// live session. The swap is a single statement, so a token can only be
+/// used once even when two refreshes race.
+pub async fn rotate(
+    pool: &PgPool,
+    old_hash: &str,
+    new_hash: &str,
+) -> AppResult<Option<(Uuid, Uuid)>> {
+    let row = sqlx::query(
+        "UPDATE user_sessions SET refresh_hash = $2, last_used_at = NOW()
+         WHERE refresh_hash = $1 AND revoked_at IS NULL AND expires_at > NOW()
+         RETURNING id, user_id",
+    )
+    .bind(old_hash)
+    .bind(new_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|row| Ok((row.try_get("id")?, row.try_get("user_id")?)))
+        .transpose()
+}
+
+/// End one session of a user
+pub async fn revoke(pool: &PgPool, user_id: Uuid, session_id: Uuid) -> AppResult<()> {
+    sqlx::query(
+        "UPDATE user_sessions SET revoked_at = NOW()
+         WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// End every live session of a user, returning their ids
+pub async fn revoke_all(pool: &PgPool, user_id: Uuid) -> AppResult<Vec<Uuid>> {
+    let rows = sqlx::query(
+        "UPDATE user_sessions SET revoked_at = NOW()
+         WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+         RETURNING id",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(|row| Ok(row.try_get("id")?)).collect()
+}
@@ -0,0 +1,63 @@
+//! Saved states of idle games
+
+use sqlx::{PgPool, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::game::GameState;
+
+/// Save a game's state unless the same state is already saved
+pub async fn save(pool: &PgPool, game: &GameState) -> AppResult<bool> {
+    let state = serde_json::to_value(game).map_err(|e| AppError::Internal(e.into()))?;
+
+    let saved = sqlx::query(
+        "INSERT INTO game_snapshots (room_id, state, journal_seq) VALUES ($1, $2, $3)
+         ON CONFLICT (room_id) DO UPDATE SET
+             state = EXCLUDED.state,
+             journal_seq = EXCLUDED.journal_seq,
+             saved_at = NOW()
+         WHERE game_snapshots.journal_seq <> EXCLUDED.journal_seq",
+    )
+    .bind(&game.id)
+    .bind(state)
+    .bind(game.journal_seq as i64)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(saved > 0)
+}
+
+pub async fn load(pool: &PgPool, room_id: &str) -> AppResult<Option<GameState>> {
+    let row = sqlx::query("SELECT state FROM game_snapshots WHERE room_id = $1")
+        .bind(room_id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.map(|row| {
+        let state: serde_json::Value = row.try_get("state")?;
+        serde_json::from_value(state).map_err(|e| AppError::Internal(e.into()))
+    })
+    .transpose()
+}
+
+pub async fn delete(pool: &PgPool, room_id: &str) -> AppResult<()> {
+    sqlx::query("DELETE FROM game_snapshots WHERE room_id = $1")
+        .bind(room_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Drop snapshots nobody came back to, returning how many
+pub async fn purge_older_than(pool: &PgPool, days: i32) -> AppResult<u64> {
+    let purged = sqlx::query(
+        "DELETE FROM game_snapshots WHERE saved_at < NOW() - make_interval(days => $1)",
+    )
+    .bind(days)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(purged)
+}
@@ -0,0 +1,96 @@
+//! Lifetime statistics of registered users
+
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::game::GameState;
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct UserStats {
+    pub games_played: i32,
+    pub wins: i32,
+    pub rent_collected: i64,
+    pub rent_paid: i64,
+    pub properties_bought: i32,
+    pub bankruptcies: i32,
+    /// Mean length of a game in seconds, once one has been played
+    pub average_game_secs: Option<i64>,
+}
+
+/// Totals of a user; all zero if they never finished a game
+pub async fn get_stats(pool: &PgPool, user_id: Uuid) -> AppResult<UserStats> {
+    let row = sqlx::query(
+        "SELECT games_played, wins, rent_collected, rent_paid, properties_bought,
+                bankruptcies, total_game_secs
+         FROM user_stats WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(UserStats::default());
+    };
+
+    let games_played: i32 = row.try_get("games_played")?;
+    let total_game_secs: i64 = row.try_get("total_game_secs")?;
+    Ok(UserStats {
+        games_played,
+        wins: row.try_get("wins")?,
+        rent_collected: row.try_get("rent_collected")?,
+        rent_paid: row.try_get("rent_paid")?,
+        properties_bought: row.try_get("properties_bought")?,
+        bankruptcies: row.try_get("bankruptcies")?,
+        average_game_secs: (games_played > 0).then(|| total_game_secs / games_played as i64),
+    })
+}
+
+/// Add a finished game to the totals of every registered user who played it
+///
+/// Users are updated in id order, so concurrent games lock rows in the same
+/// order.
+pub(super) async fn record_game(conn: &mut PgConnection, game: &GameState) -> AppResult<()> {
+    let players = game.registered_players();
+    if players.is_empty() {
+        return Ok(());
+    }
+
+    let length = game
+        .started_at
+        .map(|started| (chrono::Utc::now().timestamp() - started).max(0))
+        .unwrap_or(0);
+    let winner = game.winner();
+
+    for (user_id, player) in players {
+        let won = winner == Some(player.id);
+
+        sqlx::query(
+            "INSERT INTO user_stats (user_id, games_played, wins, rent_collected, rent_paid,
+                                     properties_bought, bankruptcies, total_game_secs)
+             VALUES ($1, 1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (user_id) DO UPDATE SET
+                 games_played = user_stats.games_played + 1,
+                 wins = user_stats.wins + EXCLUDED.wins,
+                 rent_collected = user_stats.rent_collected + EXCLUDED.rent_collected,
+                 rent_paid = user_stats.rent_paid + EXCLUDED.rent_paid,
+                 properties_bought = user_stats.properties_bought + EXCLUDED.properties_bought,
+                 bankruptcies = user_stats.bankruptcies + EXCLUDED.bankruptcies,
+                 total_game_secs = user_stats.total_game_secs + EXCLUDED.total_game_secs,
+                 updated_at = NOW()",
+        )
+        .bind(user_id)
+        .bind(won as i32)
+        .bind(player.tally.rent_collected)
+        .bind(player.tally.rent_paid)
+        .bind(player.tally.properties_bought as i32)
+        .bind(player.is_bankrupt as i32)
+        .bind(length)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Registered user accounts
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct User {
+    pub id: Uuid,
+    pub username: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Create an account, failing if the username is taken
+pub async fn create_user(pool: &PgPool, username: &str, password_hash: &str) -> AppResult<User> {
+    let row = sqlx::query(
+        "INSERT INTO users (id, username, password_hash) VALUES ($1, $2, $3)
+         RETURNING id, username, created_at",
+    )
+    .bind(Uuid::new_v4())
+    .bind(username)
+    .bind(password_hash)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            AppError::BadRequest("Username is already taken".into())
+        }
+        _ => AppError::Database(e),
+    })?;
+
+    Ok(User {
+        id: row.try_get("id")?,
+        username: row.try_get("username")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+/// Look up an account by username, with its password hash if it has one
+pub async fn find_by_username(
+    pool: &PgPool,
+    username: &str,
+) -> AppResult<Option<(User, Option<String>)>> {
+    let Some(row) = sqlx::query(
+        "SELECT id, username, created_at, password_hash FROM users WHERE LOWER(username) = LOWER($1)",
+    )
+    .bind(username)
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let user = User {
+        id: row.try_get("id")?,
+        username: row.try_get("username")?,
+        created_at: row.try_get("created_at")?,
+    };
+    Ok(Some((user, row.try_get("password_hash")?)))
+}
+
+pub async fn get_user(pool: &PgPool, id: Uuid) -> AppResult<Option<User>> {
+    let row = sqlx::query("SELECT id, username, created_at FROM users WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.map(|row| {
+        Ok(User {
+            id: row.try_get("id")?,
+            username: row.try_get("username")?,
+            created_at: row.try_get("created_at")?,
+        })
+    })
+    .transpose()
+}
@@ -1,12 +1,18 @@
 //! Application error types
 
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use std::sync::Arc;
+
+use serde::Serialize;
 use serde_json::json;
 use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::i18n::{self, Locale};
 
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -16,6 +22,9 @@ pub enum AppError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Invalid request: {0}")]
+    Validation(FieldErrors),
+
     #[error("Unauthorized")]
     Unauthorized,
 
@@ -25,6 +34,15 @@ pub enum AppError {
     #[error("Game error: {0}")]
     GameError(String),
 
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Rate limited, retry in {0}s")]
+    RateLimited(u64),
+
+    #[error("Unavailable: {0}")]
+    Unavailable(String),
+
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
@@ -35,30 +53,215 @@ pub enum AppError {
     Internal(#[from] anyhow::Error),
 }
 
+impl AppError {
+    /// Stable code clients can match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Validation(_) => "validation_failed",
+            AppError::Unauthorized => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::GameError(_) => "game_error",
+            AppError::Conflict(_) => "conflict",
+            AppError::RateLimited(_) => "rate_limited",
+            AppError::Unavailable(_) => "unavailable",
+            AppError::Database(_) => "database_error",
+            AppError::Redis(_) => "cache_error",
+            AppError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Message safe to show clients; server-side details stay in the logs
+    pub fn public_message(&self) -> String {
+        match self {
+            AppError::NotFound(msg)
+            | AppError::BadRequest(msg)
+            | AppError::Forbidden(msg)
+            | AppError::GameError(msg)
+            | AppError::Conflict(msg)
+            | AppError::Unavailable(msg) => msg.clone(),
+            AppError::Validation(errors) => errors.to_string(),
+            AppError::Unauthorized => "Unauthorized".into(),
+            AppError::RateLimited(secs) => format!("Too many requests, retry in {}s", secs),
+            AppError::Database(_) => "Database error".into(),
+            AppError::Redis(_) => "Cache error".into(),
+            AppError::Internal(_) => "Internal error".into(),
+        }
+    }
+
+    /// `public_message` in a reader's language
+    pub fn localized_message(&self, locale: Locale) -> String {
+        match self {
+            AppError::Validation(errors) => errors.localized(locale).to_string(),
+            _ => i18n::error_message(locale, &self.public_message()),
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, message) = match &self {
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
-            AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".into()),
-            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
-            AppError::GameError(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg.clone()),
+        let status = match &self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::BadRequest(_) | AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::GameError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
             AppError::Database(e) => {
                 tracing::error!("Database error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Database error".into())
+                if self.is_retryable() {
+                    StatusCode::SERVICE_UNAVAILABLE
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
             }
             AppError::Redis(e) => {
                 tracing::error!("Redis error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Cache error".into())
+                if self.is_retryable() {
+                    StatusCode::SERVICE_UNAVAILABLE
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
             }
             AppError::Internal(e) => {
                 tracing::error!("Internal error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal error".into())
+                StatusCode::INTERNAL_SERVER_ERROR
             }
         };
 
-        (status, Json(json!({ "error": message }))).into_response()
+        let mut response = (status, Json(self.body(Locale::En))).into_response();
+        if let Some(secs) = self.retry_after() {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response.extensions_mut().insert(Arc::new(self));
+        response
+    }
+}
+
+impl AppError {
+    /// Whether sending the same request again may succeed
+    ///
+    /// Lost races on a room, dropped or slow Redis and Postgres connections,
+    /// serialization failures, rate limits and a restarting server pass;
+    /// rule violations, bad input and missing permissions never do.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AppError::Conflict(_) | AppError::RateLimited(_) | AppError::Unavailable(_) => true,
+            AppError::Redis(e) => {
+                e.is_timeout()
+                    || e.is_connection_dropped()
+                    || e.is_connection_refusal()
+                    || e.is_io_error()
+            }
+            AppError::Database(e) => {
+                matches!(e, sqlx::Error::PoolTimedOut | sqlx::Error::Io(_))
+                    || crate::db::games::is_retryable(e)
+            }
+            _ => false,
+        }
+    }
+
+    /// Seconds to wait before retrying, for errors that may be retried
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            AppError::RateLimited(secs) => Some(*secs),
+            AppError::Conflict(_) => Some(0),
+            _ if self.is_retryable() => Some(1),
+            _ => None,
+        }
+    }
+
+    /// JSON body of the error's response
+    pub fn body(&self, locale: Locale) -> serde_json::Value {
+        let mut body = json!({
+            "error": self.localized_message(locale),
+            "retryable": self.is_retryable(),
+        });
+        if let AppError::Validation(errors) = self {
+            body["fields"] = json!(errors.localized(locale).0);
+        }
+        body
     }
 }
 
 pub type AppResult<T> = Result<T, AppError>;
+
+/// One problem with one field of a request body
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the field, e.g. `config.max_players`
+    pub field: String,
+    /// Stable code clients can match on, e.g. `out_of_range`
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Every problem found with a request body, so clients can point at all the
+/// offending inputs at once
+#[derive(Debug, Default)]
+pub struct FieldErrors(pub Vec<FieldError>);
+
+impl FieldErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(
+        &mut self,
+        field: impl Into<String>,
+        code: &'static str,
+        message: impl Into<String>,
+    ) {
+        self.0.push(FieldError {
+            field: field.into(),
+            code,
+            message: message.into(),
+        });
+    }
+
+    /// Take over the problems of a nested value, prefixing their fields
+    pub fn nest(&mut self, prefix: &str, errors: FieldErrors) {
+        self.0.extend(errors.0.into_iter().map(|error| FieldError {
+            field: format!("{}.{}", prefix, error.field),
+            ..error
+        }));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The same problems with their messages in a reader's language
+    pub fn localized(&self, locale: Locale) -> FieldErrors {
+        FieldErrors(
+            self.0
+                .iter()
+                .map(|error| FieldError {
+                    message: i18n::error_message(locale, &error.message),
+                    ..error.clone()
+                })
+                .collect(),
+        )
+    }
+
+    /// `Validation` if anything was found
+    pub fn into_result(self) -> AppResult<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(self))
+        }
+    }
+}
+
+impl std::fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<&str> = self.0.iter().map(|e| e.message.as_str()).collect();
+        f.write_str(&messages.join("; "))
+    }
+}
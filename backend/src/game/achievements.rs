@@ -0,0 +1,83 @@
+//! Milestones unlocked once per account
+//!
+//! They are worked out from a finished game's tallies when it is archived,
+//! so only registered users collect them. Each one is stored the first time
+//! it is earned; earning it again changes nothing.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::state::{GameState, Player};
+
+/// Rent a player has to collect over one game for `RentBaron`
+pub const RENT_BARON_THRESHOLD: i64 = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    /// Won a game
+    FirstWin,
+    /// Completed a color set
+    FirstMonopoly,
+    /// Won a game without building a hotel
+    WonWithoutHotels,
+    /// Bankrupted two players in one game
+    DoubleKnockout,
+    /// Collected `RENT_BARON_THRESHOLD` in rent in one game
+    RentBaron,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [
+        Achievement::FirstWin,
+        Achievement::FirstMonopoly,
+        Achievement::WonWithoutHotels,
+        Achievement::DoubleKnockout,
+        Achievement::RentBaron,
+    ];
+
+    /// Name as stored and sent to clients
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::FirstWin => "first_win",
+            Achievement::FirstMonopoly => "first_monopoly",
+            Achievement::WonWithoutHotels => "won_without_hotels",
+            Achievement::DoubleKnockout => "double_knockout",
+            Achievement::RentBaron => "rent_baron",
+        }
+    }
+
+    /// Whether a player earned this in a finished game
+    fn earned_by(self, player: &Player, won: bool) -> bool {
+        let tally = &player.tally;
+        match self {
+            Achievement::FirstWin => won,
+            Achievement::FirstMonopoly => tally.sets_completed > 0,
+            Achievement::WonWithoutHotels => won && tally.hotels_built == 0,
+            Achievement::DoubleKnockout => tally.players_bankrupted >= 2,
+            Achievement::RentBaron => tally.rent_collected >= RENT_BARON_THRESHOLD,
+        }
+    }
+}
+
+impl FromStr for Achievement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        Achievement::ALL
+            .into_iter()
+            .find(|achievement| achievement.name() == s)
+            .ok_or_else(|| format!("Unknown achievement {}", s))
+    }
+}
+
+/// Achievements a player earned in a finished game, unlocked before or not
+pub fn earned(game: &GameState, player: &Player) -> Vec<Achievement> {
+    let won = game.winner() == Some(player.id);
+    Achievement::ALL
+        .into_iter()
+        .filter(|achievement| achievement.earned_by(player, won))
+        .collect()
+}
@@ -1,4 +1,4 @@
-use super::GameState;
+use super::{GameState, LogLine};
 use uuid::Uuid;
 
 pub struct BankruptcyHandler;
@@ -25,7 +25,14 @@ impl BankruptcyHandler {
             "Unknown".to_string()
         };
 
-        game.log(format!("Player {} has gone BANKRUPT!", player_name));
+        game.log(LogLine::new("bankrupt").arg("name", player_name));
+        // Whatever they still owed goes with them
+        if game.debt.as_ref().is_some_and(|d| d.player_id == debtor_id) {
+            game.debt = None;
+        }
+        if !game.eliminated.contains(&debtor_id) {
+            game.eliminated.push(debtor_id);
+        }
 
         // 2. Identify assets (properties)
         let mut debtor_properties: Vec<u8> = Vec::new();
@@ -39,9 +46,10 @@ impl BankruptcyHandler {
         if let Some(creditor) = creditor_id {
             // Transfer to creditor
             // Log first
-            if let Some(creditor_player) = game.get_player(creditor) {
-                let msg = format!("All assets transferred to {}.", creditor_player.name);
-                game.log(msg);
+            if let Some(creditor_player) = game.get_player_mut(creditor) {
+                creditor_player.tally.players_bankrupted += 1;
+                let line = LogLine::new("assets_to_creditor").arg("name", &creditor_player.name);
+                game.log(line);
             }
 
             for idx in debtor_properties {
@@ -69,7 +77,7 @@ impl BankruptcyHandler {
             }
         } else {
             // Debt to Bank -> Reset properties (Auction in real rules, Reset for MVP)
-            game.log("Assets returned to the Bank.".to_string());
+            game.log(LogLine::new("assets_to_bank"));
             for idx in debtor_properties {
                 if let Some(prop) = game.properties.get_mut(&idx) {
                     prop.owner = None;
@@ -1,9 +1,19 @@
 //! Board definition - 40 tiles with properties based on world cities
+//!
+//! The tiles below are the built-in version of the board. Revised versions
+//! are stored in Postgres and installed at startup, along with the card
+//! decks of every version; the built-in one has none.
 
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::cards::{Card, CardEffect, Deck};
 
 /// Type of tile on the board
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub enum TileType {
     Go,
     Property,
@@ -18,7 +28,7 @@ pub enum TileType {
 }
 
 /// Color group for properties
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
 pub enum ColorGroup {
     Brown,
     LightBlue,
@@ -33,6 +43,19 @@ pub enum ColorGroup {
 }
 
 impl ColorGroup {
+    pub const ALL: [ColorGroup; 10] = [
+        ColorGroup::Brown,
+        ColorGroup::LightBlue,
+        ColorGroup::Pink,
+        ColorGroup::Orange,
+        ColorGroup::Red,
+        ColorGroup::Yellow,
+        ColorGroup::Green,
+        ColorGroup::DarkBlue,
+        ColorGroup::Railroad,
+        ColorGroup::Utility,
+    ];
+
     pub fn color_hex(&self) -> &'static str {
         match self {
             ColorGroup::Brown => "#8B4513",
@@ -59,7 +82,7 @@ impl ColorGroup {
 }
 
 /// A tile on the board
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Tile {
     pub index: u8,
     pub name: String,
@@ -89,6 +112,7 @@ impl Tile {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn property(
         index: u8,
         name: &str,
@@ -234,8 +258,14 @@ impl Tile {
     }
 }
 
+/// Id the board is served under
+pub const BOARD_ID: &str = "world";
+
+/// Display name of the board
+pub const BOARD_NAME: &str = "World Cities";
+
 /// The complete game board - 40 tiles based on Richup.io world cities
-pub static BOARD: once_cell::sync::Lazy<Vec<Tile>> = once_cell::sync::Lazy::new(|| {
+pub static BOARD: Lazy<Vec<Tile>> = Lazy::new(|| {
     vec![
         // === BOTTOM ROW (0-10) ===
         Tile::go(),
@@ -490,12 +520,166 @@ pub static BOARD: once_cell::sync::Lazy<Vec<Tile>> = once_cell::sync::Lazy::new(
     ]
 });
 
-/// Get a tile by index
-pub fn get_tile(idx: u8) -> Option<&'static Tile> {
-    BOARD.get(idx as usize)
+/// Version of the built-in board, as seeded into the database
+pub const BUILTIN_VERSION: i32 = 1;
+
+/// One version of a board's tiles and card decks
+///
+/// Games stay on the version they were created with, so a board can be
+/// revised without touching games already underway.
+#[derive(Debug, Clone)]
+pub struct BoardDef {
+    pub id: String,
+    pub name: String,
+    pub version: i32,
+    pub tiles: Vec<Tile>,
+    pub chance: Vec<Card>,
+    pub community_chest: Vec<Card>,
+}
+
+impl BoardDef {
+    /// Get a tile by index
+    pub fn tile(&self, idx: u8) -> Option<&Tile> {
+        self.tiles.get(idx as usize)
+    }
+
+    /// Cards of one of the decks
+    pub fn deck(&self, deck: Deck) -> &[Card] {
+        match deck {
+            Deck::Chance => &self.chance,
+            Deck::CommunityChest => &self.community_chest,
+        }
+    }
+
+    /// Check a definition against the built-in layout
+    ///
+    /// The engine knows where the corners, taxes and card tiles are, so
+    /// versions may change names, prices, rents and groups but not the kind
+    /// of tile on each square.
+    fn validate(&self) -> Result<(), String> {
+        if self.tiles.len() != BOARD.len() {
+            return Err(format!(
+                "has {} tiles, not {}",
+                self.tiles.len(),
+                BOARD.len()
+            ));
+        }
+        for (idx, (tile, builtin)) in self.tiles.iter().zip(BOARD.iter()).enumerate() {
+            if tile.index as usize != idx {
+                return Err(format!("tile {} is numbered {}", idx, tile.index));
+            }
+            if tile.tile_type != builtin.tile_type {
+                return Err(format!(
+                    "tile {} is a {:?}, not a {:?}",
+                    idx, tile.tile_type, builtin.tile_type
+                ));
+            }
+        }
+        for card in self.chance.iter().chain(&self.community_chest) {
+            card.validate()?;
+            // Drawing again on arrival could go on forever
+            if let CardEffect::AdvanceTo { tile } = card.effect {
+                if matches!(
+                    BOARD[tile as usize].tile_type,
+                    TileType::Chance | TileType::CommunityChest
+                ) {
+                    return Err(format!("card \"{}\" moves to a card tile", card.text));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+static BUILTIN: Lazy<BoardDef> = Lazy::new(|| BoardDef {
+    id: BOARD_ID.into(),
+    name: BOARD_NAME.into(),
+    version: BUILTIN_VERSION,
+    tiles: BOARD.clone(),
+    chance: Vec::new(),
+    community_chest: Vec::new(),
+});
+
+/// Definitions in effect; leaked when installed so games can hold on to
+/// tiles without copying them, which is fine as they only load at startup
+static DEFINITIONS: Lazy<RwLock<Vec<&'static BoardDef>>> =
+    Lazy::new(|| RwLock::new(vec![&*BUILTIN]));
+
+/// Add stored definitions, skipping invalid ones and versions already
+/// known; returns how many were added
+///
+/// The stored copy of the built-in version takes its place, bringing the
+/// card decks the built-in one lacks.
+pub fn install(defs: Vec<BoardDef>) -> usize {
+    let mut definitions = DEFINITIONS.write().unwrap_or_else(|e| e.into_inner());
+    let mut installed = 0;
+
+    for def in defs {
+        if let Err(e) = def.validate() {
+            tracing::warn!("Skipping board {} version {}: {}", def.id, def.version, e);
+            continue;
+        }
+        let known = definitions
+            .iter()
+            .position(|d| d.id == def.id && d.version == def.version);
+        if known.is_some_and(|idx| !std::ptr::eq(definitions[idx], &*BUILTIN)) {
+            continue;
+        }
+        let def = Box::leak(Box::new(def));
+        match known {
+            Some(idx) => definitions[idx] = def,
+            None => definitions.push(def),
+        }
+        installed += 1;
+    }
+
+    installed
+}
+
+/// A specific version of a board
+pub fn definition(id: &str, version: i32) -> Option<&'static BoardDef> {
+    let definitions = DEFINITIONS.read().unwrap_or_else(|e| e.into_inner());
+    definitions
+        .iter()
+        .find(|d| d.id == id && d.version == version)
+        .copied()
+}
+
+/// Newest version of a board
+pub fn latest(id: &str) -> Option<&'static BoardDef> {
+    let definitions = DEFINITIONS.read().unwrap_or_else(|e| e.into_inner());
+    definitions
+        .iter()
+        .filter(|d| d.id == id)
+        .max_by_key(|d| d.version)
+        .copied()
+}
+
+/// Newest version of every board, by id
+pub fn latest_boards() -> Vec<&'static BoardDef> {
+    let definitions = DEFINITIONS.read().unwrap_or_else(|e| e.into_inner());
+    let mut boards: Vec<&'static BoardDef> = Vec::new();
+    for def in definitions.iter() {
+        match boards.iter_mut().find(|b| b.id == def.id) {
+            Some(board) if board.version < def.version => *board = def,
+            Some(_) => {}
+            None => boards.push(def),
+        }
+    }
+    boards.sort_by(|a, b| a.id.cmp(&b.id));
+    boards
 }
 
-/// Get all tiles in a color group
-pub fn get_group_tiles(group: ColorGroup) -> Vec<&'static Tile> {
-    BOARD.iter().filter(|t| t.group == Some(group)).collect()
+/// Board new games are played on
+pub fn default_board() -> &'static BoardDef {
+    latest(BOARD_ID).unwrap_or(&BUILTIN)
+}
+
+/// Board a new game picking `id` is played on, the default one when it
+/// picks none; `None` for an id this server does not know
+pub fn for_new_game(id: Option<&str>) -> Option<&'static BoardDef> {
+    match id {
+        Some(id) => latest(id),
+        None => Some(default_board()),
+    }
 }
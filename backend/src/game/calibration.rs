@@ -0,0 +1,218 @@
+//! Self-play calibration of bot difficulties
+//!
+//! Each candidate parameter set takes one seat at a table of baseline bots,
+//! rotating through the seats over a fixed run of seeds so every candidate
+//! faces the same dice. Candidates are then ranked by win rate and the
+//! weakest, median and strongest sets become Easy, Normal and Hard.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::simulation::{self, SeatSetup, SimulationParams, SimulationResult};
+use super::state::GameConfig;
+use crate::bot::{BotDifficulty, BotParams, BotPersonality};
+
+/// Seats at every calibration table
+pub const TABLE_SIZE: u8 = 4;
+
+/// Turns before a calibration game is decided on net worth
+pub const DEFAULT_MAX_TURNS: u32 = 300;
+
+const SPEND_SCALES: [f32; 3] = [0.7, 1.0, 1.3];
+const BID_SCALES: [f32; 3] = [0.8, 1.0, 1.2];
+const RESERVE_SCALES: [f32; 3] = [0.5, 1.0, 2.0];
+
+const PERSONALITIES: [BotPersonality; 3] = [
+    BotPersonality::Aggressive,
+    BotPersonality::Balanced,
+    BotPersonality::Conservative,
+];
+
+/// Settings for a calibration run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationParams {
+    /// Seed of the first game; game `i` of every candidate uses `seed + i`
+    pub seed: u64,
+    pub games_per_candidate: u32,
+    pub max_turns: u32,
+}
+
+/// How one parameter set fared under one personality
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateResult {
+    pub personality: BotPersonality,
+    pub params: BotParams,
+    pub games: u32,
+    pub wins: u32,
+    pub win_rate: f64,
+}
+
+/// Parameter set picked for a difficulty
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TunedParams {
+    pub difficulty: BotDifficulty,
+    pub params: BotParams,
+    /// Win rate against the baseline table, averaged over personalities
+    pub win_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationReport {
+    pub candidates: Vec<CandidateResult>,
+    pub tuned: Vec<TunedParams>,
+}
+
+/// Every parameter set the calibration tries
+pub fn grid() -> Vec<BotParams> {
+    let mut grid = Vec::new();
+    for &spend_scale in &SPEND_SCALES {
+        for &bid_scale in &BID_SCALES {
+            for &reserve_scale in &RESERVE_SCALES {
+                grid.push(BotParams {
+                    spend_scale,
+                    bid_scale,
+                    reserve_scale,
+                });
+            }
+        }
+    }
+    grid
+}
+
+/// Every (personality, parameters) pair the calibration plays
+pub fn candidates() -> Vec<(BotPersonality, BotParams)> {
+    PERSONALITIES
+        .iter()
+        .flat_map(|&personality| grid().into_iter().map(move |p| (personality, p)))
+        .collect()
+}
+
+/// Run the whole grid and pick parameters for each difficulty
+pub fn calibrate(params: &CalibrationParams) -> CalibrationReport {
+    let candidates: Vec<CandidateResult> = candidates()
+        .into_iter()
+        .map(|(personality, candidate)| run_candidate(params, personality, candidate))
+        .collect();
+    let tuned = tune(&candidates);
+
+    CalibrationReport { candidates, tuned }
+}
+
+/// Play one candidate against baseline bots
+///
+/// Opponents use the built-in parameters rather than whatever is tuned at
+/// the time, so runs stay comparable with each other.
+pub fn run_candidate(
+    params: &CalibrationParams,
+    personality: BotPersonality,
+    candidate: BotParams,
+) -> CandidateResult {
+    let config = GameConfig {
+        max_players: TABLE_SIZE,
+        ..GameConfig::default()
+    };
+
+    let mut wins = 0;
+    for i in 0..params.games_per_candidate {
+        let seat = (i % TABLE_SIZE as u32) as usize;
+        let seats = (0..TABLE_SIZE as usize)
+            .map(|s| {
+                if s == seat {
+                    SeatSetup {
+                        personality: Some(personality),
+                        params: Some(candidate),
+                    }
+                } else {
+                    SeatSetup {
+                        personality: None,
+                        params: Some(BotParams::default()),
+                    }
+                }
+            })
+            .collect();
+
+        let result = simulation::run(&SimulationParams {
+            seed: params.seed.wrapping_add(i as u64),
+            bots: TABLE_SIZE,
+            config: config.clone(),
+            max_turns: params.max_turns,
+            record_replay: false,
+            difficulties: Vec::new(),
+            seats,
+        });
+
+        let candidate_id = result.players.get(seat).map(|p| p.id);
+        if candidate_id.is_some() && table_winner(&result) == candidate_id {
+            wins += 1;
+        }
+    }
+
+    CandidateResult {
+        personality,
+        params: candidate,
+        games: params.games_per_candidate,
+        wins,
+        win_rate: if params.games_per_candidate == 0 {
+            0.0
+        } else {
+            wins as f64 / params.games_per_candidate as f64
+        },
+    }
+}
+
+/// The last player standing, or the richest survivor when the turn cap hit
+fn table_winner(result: &SimulationResult) -> Option<Uuid> {
+    if result.error.is_some() {
+        return None;
+    }
+
+    result.winner.or_else(|| {
+        result
+            .players
+            .iter()
+            .filter(|p| !p.is_bankrupt)
+            .max_by_key(|p| p.net_worth)
+            .map(|p| p.id)
+    })
+}
+
+/// Rank parameter sets by win rate across personalities and map them onto
+/// difficulties
+///
+/// Hard keeps its rollouts on top of the strongest set.
+pub fn tune(candidates: &[CandidateResult]) -> Vec<TunedParams> {
+    let mut ranked: Vec<(BotParams, f64)> = Vec::new();
+    for params in grid() {
+        let rates: Vec<f64> = candidates
+            .iter()
+            .filter(|c| c.params == params)
+            .map(|c| c.win_rate)
+            .collect();
+        if rates.is_empty() {
+            continue;
+        }
+        ranked.push((params, rates.iter().sum::<f64>() / rates.len() as f64));
+    }
+
+    if ranked.is_empty() {
+        return Vec::new();
+    }
+
+    // Stable sort keeps grid order among ties, so results are reproducible
+    ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let picks = [
+        (BotDifficulty::Easy, 0),
+        (BotDifficulty::Normal, ranked.len() / 2),
+        (BotDifficulty::Hard, ranked.len() - 1),
+    ];
+
+    picks
+        .into_iter()
+        .map(|(difficulty, idx)| TunedParams {
+            difficulty,
+            params: ranked[idx].0,
+            win_rate: ranked[idx].1,
+        })
+        .collect()
+}
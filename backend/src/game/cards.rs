@@ -0,0 +1,124 @@
+//! Surprise and Treasure card decks
+//!
+//! Decks belong to a board version and are stored in Postgres next to its
+//! tiles, so a game draws from the cards of the version it started on. Each
+//! game keeps its own shuffled order; a drawn card goes to the bottom.
+
+use std::collections::VecDeque;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::board::BoardDef;
+
+/// The two decks, drawn from the Surprise and Treasure tiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Deck {
+    Chance,
+    CommunityChest,
+}
+
+/// What a card does to the player who draws it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CardEffect {
+    /// The bank pays the player
+    Collect {
+        amount: i32,
+    },
+    /// The player pays the bank
+    Pay {
+        amount: i32,
+    },
+    /// Every other player still in the game pays the player
+    CollectFromEach {
+        amount: i32,
+    },
+    /// The player pays every other player still in the game
+    PayEach {
+        amount: i32,
+    },
+    /// Move forward to a tile, collecting the GO salary on the way past
+    AdvanceTo {
+        tile: u8,
+    },
+    /// Move back a number of tiles
+    MoveBack {
+        spaces: u8,
+    },
+    GoToJail,
+    GetOutOfJail,
+}
+
+/// A card of a deck
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Card {
+    pub text: String,
+    pub effect: CardEffect,
+}
+
+impl Card {
+    /// Check the card can be played on a 40-tile board
+    pub(super) fn validate(&self) -> Result<(), String> {
+        match self.effect {
+            CardEffect::Collect { amount }
+            | CardEffect::Pay { amount }
+            | CardEffect::CollectFromEach { amount }
+            | CardEffect::PayEach { amount }
+                if amount < 0 =>
+            {
+                Err(format!("card \"{}\" has a negative amount", self.text))
+            }
+            CardEffect::AdvanceTo { tile } if tile >= 40 => {
+                Err(format!("card \"{}\" moves to tile {}", self.text, tile))
+            }
+            CardEffect::MoveBack { spaces } if spaces >= 40 => {
+                Err(format!("card \"{}\" moves back {}", self.text, spaces))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Order the cards of each deck come up in, by index into the board's deck
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeckOrder {
+    pub chance: VecDeque<usize>,
+    pub community_chest: VecDeque<usize>,
+}
+
+impl DeckOrder {
+    /// Both decks of `board`, shuffled
+    pub fn shuffled(board: &BoardDef, rng: &mut impl Rng) -> Self {
+        let mut shuffle = |len: usize| {
+            let mut order: Vec<usize> = (0..len).collect();
+            order.shuffle(rng);
+            order.into()
+        };
+        Self {
+            chance: shuffle(board.chance.len()),
+            community_chest: shuffle(board.community_chest.len()),
+        }
+    }
+
+    /// Draw the top card of a deck and put it at the bottom; `None` when the
+    /// board has no cards for it
+    ///
+    /// Games that started before decks were dealt draw them in stored order.
+    pub fn draw<'a>(&mut self, board: &'a BoardDef, deck: Deck) -> Option<&'a Card> {
+        let cards = board.deck(deck);
+        let order = match deck {
+            Deck::Chance => &mut self.chance,
+            Deck::CommunityChest => &mut self.community_chest,
+        };
+        if order.is_empty() {
+            order.extend(0..cards.len());
+        }
+
+        let idx = order.pop_front()?;
+        order.push_back(idx);
+        cards.get(idx)
+    }
+}
@@ -0,0 +1,216 @@
+//! Daily and weekly challenges
+//!
+//! The server keeps a pool of challenges for each period and rotates
+//! through it: every day and every week gets a few of them, stored in
+//! Postgres the first time anyone asks. Registered players make progress
+//! when a game they played is archived, and claim the reward once they
+//! reach the target.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::board::BOARD_ID;
+use super::state::{GameState, Player};
+
+/// Challenges running at once in each period
+pub const DAILY_SLOTS: usize = 2;
+pub const WEEKLY_SLOTS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Daily,
+    Weekly,
+}
+
+impl Period {
+    pub const ALL: [Period; 2] = [Period::Daily, Period::Weekly];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+        }
+    }
+
+    /// Start and end of the period `now` falls in; days start at midnight
+    /// UTC and weeks on Monday
+    pub fn bounds(self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let today = now.date_naive();
+        let (start, length) = match self {
+            Period::Daily => (today, Duration::days(1)),
+            Period::Weekly => (
+                today - Duration::days(today.weekday().num_days_from_monday().into()),
+                Duration::weeks(1),
+            ),
+        };
+        let start = start.and_time(NaiveTime::MIN).and_utc();
+        (start, start + length)
+    }
+
+    /// Challenges of the period starting at `start`, one per slot
+    pub fn rotation(self, start: DateTime<Utc>) -> Vec<&'static Template> {
+        let (pool, slots, length) = match self {
+            Period::Daily => (DAILY, DAILY_SLOTS, 1),
+            Period::Weekly => (WEEKLY, WEEKLY_SLOTS, 7),
+        };
+        let index = (start.timestamp() / 86_400 / length) as usize;
+        (0..slots)
+            .map(|slot| &pool[(index * slots + slot) % pool.len()])
+            .collect()
+    }
+}
+
+/// What a challenge counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Goal {
+    PlayGames,
+    WinGames,
+    CollectRent,
+    BuyProperties,
+    CompleteSets,
+    BuildHotels,
+}
+
+impl Goal {
+    pub const ALL: [Goal; 6] = [
+        Goal::PlayGames,
+        Goal::WinGames,
+        Goal::CollectRent,
+        Goal::BuyProperties,
+        Goal::CompleteSets,
+        Goal::BuildHotels,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Goal::PlayGames => "play_games",
+            Goal::WinGames => "win_games",
+            Goal::CollectRent => "collect_rent",
+            Goal::BuyProperties => "buy_properties",
+            Goal::CompleteSets => "complete_sets",
+            Goal::BuildHotels => "build_hotels",
+        }
+    }
+
+    /// How far a finished game takes a player towards this goal
+    pub fn progress(self, game: &GameState, player: &Player) -> i64 {
+        let tally = &player.tally;
+        match self {
+            Goal::PlayGames => 1,
+            Goal::WinGames => (game.winner() == Some(player.id)).into(),
+            Goal::CollectRent => tally.rent_collected,
+            Goal::BuyProperties => tally.properties_bought.into(),
+            Goal::CompleteSets => tally.sets_completed.into(),
+            Goal::BuildHotels => tally.hotels_built.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RewardKind {
+    /// Shown next to the player's name
+    Title,
+    /// Board, piece or profile decoration
+    Cosmetic,
+}
+
+impl RewardKind {
+    pub const ALL: [RewardKind; 2] = [RewardKind::Title, RewardKind::Cosmetic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RewardKind::Title => "title",
+            RewardKind::Cosmetic => "cosmetic",
+        }
+    }
+}
+
+/// What completing a challenge earns
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Reward {
+    pub kind: RewardKind,
+    pub id: String,
+}
+
+/// A challenge in the rotation
+#[derive(Debug)]
+pub struct Template {
+    pub goal: Goal,
+    pub target: i64,
+    /// Only games on this board count
+    pub board_id: Option<&'static str>,
+    pub reward_kind: RewardKind,
+    pub reward: &'static str,
+}
+
+impl Template {
+    const fn new(goal: Goal, target: i64, reward_kind: RewardKind, reward: &'static str) -> Self {
+        Self {
+            goal,
+            target,
+            board_id: None,
+            reward_kind,
+            reward,
+        }
+    }
+
+    const fn on_board(self, board_id: &'static str) -> Self {
+        Self {
+            board_id: Some(board_id),
+            ..self
+        }
+    }
+}
+
+const DAILY: &[Template] = &[
+    Template::new(Goal::PlayGames, 3, RewardKind::Title, "regular"),
+    Template::new(Goal::WinGames, 1, RewardKind::Cosmetic, "globe_badge").on_board(BOARD_ID),
+    Template::new(Goal::BuyProperties, 10, RewardKind::Cosmetic, "deed_frame"),
+    Template::new(Goal::CollectRent, 1500, RewardKind::Title, "landlord"),
+    Template::new(Goal::BuildHotels, 2, RewardKind::Cosmetic, "hotel_skyline"),
+];
+
+const WEEKLY: &[Template] = &[
+    Template::new(Goal::CollectRent, 3000, RewardKind::Title, "tycoon"),
+    Template::new(Goal::WinGames, 3, RewardKind::Title, "champion"),
+    Template::new(Goal::CompleteSets, 5, RewardKind::Cosmetic, "golden_board"),
+    Template::new(Goal::PlayGames, 10, RewardKind::Cosmetic, "veteran_badge"),
+];
+
+/// The variant of `all` whose `name` is `s`
+fn parse<T: Copy>(all: &[T], name: fn(T) -> &'static str, s: &str) -> Result<T, String> {
+    all.iter()
+        .copied()
+        .find(|value| name(*value) == s)
+        .ok_or_else(|| format!("Unknown value {}", s))
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        parse(&Period::ALL, Period::name, s)
+    }
+}
+
+impl FromStr for Goal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        parse(&Goal::ALL, Goal::name, s)
+    }
+}
+
+impl FromStr for RewardKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        parse(&RewardKind::ALL, RewardKind::name, s)
+    }
+}
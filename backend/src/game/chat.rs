@@ -0,0 +1,105 @@
+//! Room chat history
+//!
+//! The most recent messages of each room are kept in a Redis list next to the
+//! game, so reconnecting players can catch up. Every message is also queued
+//! for Postgres, which keeps it after the room is gone.
+
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::store;
+use crate::db;
+use crate::error::{AppError, AppResult};
+
+/// Messages kept per room
+pub const HISTORY_LEN: usize = 200;
+
+/// Messages sent to a client when it connects
+pub const BACKLOG_LEN: usize = 50;
+
+/// Same lifetime as the game itself
+const HISTORY_TTL_SECS: i64 = 86400;
+
+/// A chat message as stored and replayed
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ChatMessage {
+    /// Increases with every message in the room
+    pub id: u64,
+    pub from: Uuid,
+    pub from_name: String,
+    pub message: String,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Append a message to the room's history
+pub async fn record(
+    redis: &ConnectionManager,
+    room_id: &str,
+    from: Uuid,
+    from_name: &str,
+    message: &str,
+) -> AppResult<ChatMessage> {
+    let mut conn = redis.clone();
+    let seq_key = store::layout().chat_seq_key(room_id);
+    let key = store::layout().chat_key(room_id);
+
+    let id: u64 = conn.incr(&seq_key, 1).await?;
+    let entry = ChatMessage {
+        id,
+        from,
+        from_name: from_name.to_string(),
+        message: message.to_string(),
+        sent_at: Utc::now(),
+    };
+    let json = serde_json::to_string(&entry).map_err(|e| AppError::Internal(e.into()))?;
+
+    let _: () = redis::pipe()
+        .atomic()
+        .rpush(&key, json)
+        .ltrim(&key, -(HISTORY_LEN as isize), -1)
+        .expire(&key, HISTORY_TTL_SECS)
+        .expire(&seq_key, HISTORY_TTL_SECS)
+        .query_async(&mut conn)
+        .await?;
+    db::chat::record(room_id, &entry);
+
+    Ok(entry)
+}
+
+/// Drop a room's history from Redis; Postgres keeps its copy
+pub async fn delete(redis: &ConnectionManager, room_id: &str) -> AppResult<()> {
+    let mut conn = redis.clone();
+    let _: () = conn
+        .del(&[
+            store::layout().chat_key(room_id),
+            store::layout().chat_seq_key(room_id),
+        ])
+        .await?;
+    Ok(())
+}
+
+/// Up to `limit` of the latest messages older than `before`, oldest first
+pub async fn history(
+    redis: &ConnectionManager,
+    room_id: &str,
+    before: Option<u64>,
+    limit: usize,
+) -> AppResult<Vec<ChatMessage>> {
+    let mut conn = redis.clone();
+    let key = store::layout().chat_key(room_id);
+
+    let raw: Vec<String> = conn.lrange(&key, 0, -1).await?;
+    let mut messages: Vec<ChatMessage> = raw
+        .iter()
+        .filter_map(|json| serde_json::from_str(json).ok())
+        .filter(|m: &ChatMessage| before.is_none_or(|b| m.id < b))
+        .collect();
+
+    let skip = messages.len().saturating_sub(limit);
+    messages.drain(..skip);
+    Ok(messages)
+}
@@ -0,0 +1,46 @@
+//! Administrator commands that change a running game
+//!
+//! They get games unstuck or put right what a bug got wrong, outside the
+//! rules players play by. Each one is checked against the game before it is
+//! applied, written to the audit log and announced to the room with a log
+//! line.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Largest balance change a single command makes, either way
+pub const MAX_BALANCE_ADJUSTMENT: i32 = 1_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminCommand {
+    /// Add to or take from a player's balance, which cannot go below zero
+    AdjustBalance { player_id: Uuid, amount: i32 },
+    /// End the current turn, whatever it is waiting for
+    EndTurn,
+    /// Close the running auction with no winner
+    CancelAuction,
+    /// Let a player out of jail for free
+    ReleaseFromJail { player_id: Uuid },
+}
+
+impl AdminCommand {
+    /// Name as recorded in the audit log
+    pub fn name(&self) -> &'static str {
+        match self {
+            AdminCommand::AdjustBalance { .. } => "adjust_balance",
+            AdminCommand::EndTurn => "end_turn",
+            AdminCommand::CancelAuction => "cancel_auction",
+            AdminCommand::ReleaseFromJail { .. } => "release_from_jail",
+        }
+    }
+
+    /// Player the command is about, if it names one
+    pub fn target(&self) -> Option<Uuid> {
+        match *self {
+            AdminCommand::AdjustBalance { player_id, .. }
+            | AdminCommand::ReleaseFromJail { player_id } => Some(player_id),
+            AdminCommand::EndTurn | AdminCommand::CancelAuction => None,
+        }
+    }
+}
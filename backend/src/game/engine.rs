@@ -3,29 +3,43 @@
 //! Simplified version that avoids borrow checker complexity by cloning state
 //! where necessary for clarity and correctness.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use once_cell::sync::Lazy;
 use rand::Rng;
 use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
-use tokio::sync::RwLock;
+use tracing::Instrument;
 use uuid::Uuid;
 
 use super::bankruptcy::BankruptcyHandler;
-use super::board::{get_tile, ColorGroup, TileType, BOARD};
-use super::events::ServerEvent;
+use super::board::{ColorGroup, TileType};
+use super::cards::{CardEffect, Deck, DeckOrder};
+use super::chat;
+use super::commands::{AdminCommand, MAX_BALANCE_ADJUSTMENT};
+use super::events::{ClientEvent, ServerEvent};
+use super::integrity::MarkKind;
+use super::moderation;
+use super::permissions::{pending_actions, validate_action};
 use super::state::*;
+use super::store;
+use super::summary::{self, GameSummary};
 use super::trade::TradeHandler;
-use crate::error::{AppError, AppResult};
+use crate::bot::{BotAI, BotDifficulty, BotPersonality, ChatTrigger};
+use crate::db;
+use crate::error::{AppError, AppResult, FieldErrors};
+use crate::i18n::Locale;
 use crate::ws::Hub;
 
 /// Player colors for assignment
-const PLAYER_COLORS: &[&str] = &[
+pub(crate) const PLAYER_COLORS: &[&str] = &[
     "#FF5733", "#33FF57", "#3357FF", "#FF33F5", "#F5FF33", "#33FFF5", "#FF8C33", "#8C33FF",
 ];
 
 /// Bot name prefixes
-const BOT_NAMES: &[&str] = &[
+pub(crate) const BOT_NAMES: &[&str] = &[
     "Bot Alpha",
     "Bot Beta",
     "Bot Gamma",
@@ -36,35 +50,109 @@ const BOT_NAMES: &[&str] = &[
     "Bot Theta",
 ];
 
+/// Avatars bots can be given; the client maps each id to an image
+pub(crate) const BOT_AVATARS: &[&str] = &[
+    "robot",
+    "android",
+    "cyborg",
+    "satellite",
+    "rocket",
+    "chip",
+    "gear",
+    "drone",
+];
+
+/// Avatars people can pick for their profile; the client maps each id to an
+/// image
+pub(crate) const PLAYER_AVATARS: &[&str] = &[
+    "car", "hat", "dog", "cat", "boot", "ship", "iron", "thimble",
+];
+
+/// Playing pieces for the board, one per seat; the client maps each id to
+/// an image
+pub(crate) const PLAYER_PIECES: &[&str] = &[
+    "hat", "car", "ship", "dog", "cat", "boot", "iron", "thimble",
+];
+
+/// Longest display name accepted for any player
+pub(crate) const MAX_NAME_LEN: usize = 24;
+
+/// Price of leaving jail early, and of the forced bail
+const JAIL_BAIL: i32 = 50;
+
+/// Account and looks of a registered user taking a seat
+#[derive(Debug, Clone, Default)]
+pub struct SeatPrefs {
+    /// Statistics of the game are credited to this user
+    pub user_id: Option<Uuid>,
+    pub avatar: Option<String>,
+    /// Used unless someone in the room already has it
+    pub color: Option<String>,
+    /// Language the seat's game log and errors are shown in
+    pub locale: Locale,
+}
+
+/// Times a change is tried when other changes keep saving the room first
+const SAVE_ATTEMPTS: u32 = 3;
+
+/// Times a bot pass that failed on a retryable error is tried again, and
+/// the pause before the first retry, doubled each time
+const BOT_RETRIES: u32 = 3;
+const BOT_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Turn timers a player may let run out in a row before a bot takes over
+/// for them
+const AFK_TIMEOUTS: u8 = 2;
+
+/// Rooms with a running bot task, mapped to whether another pass was requested
+static BOT_TASKS: Lazy<Mutex<HashMap<String, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Rooms with a running turn timer, mapped to the generation of the latest
+/// one; older timers of the room stop when they wake
+static TURN_TIMERS: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static TURN_TIMER_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 pub struct GameEngine;
 
 impl GameEngine {
     /// Create a new game room
+    #[tracing::instrument(skip_all, fields(room_id, player_id))]
     pub async fn create_room(
         redis: &ConnectionManager,
+        hub: &Arc<Hub>,
         host_name: &str,
+        prefs: &SeatPrefs,
         config: GameConfig,
     ) -> AppResult<(String, Uuid)> {
         let room_id = generate_room_id();
         let player_id = Uuid::new_v4();
+        tracing::Span::current()
+            .record("room_id", room_id.as_str())
+            .record("player_id", tracing::field::display(player_id));
 
         let mut game = GameState::new(room_id.clone(), config);
+        let host_name = Self::validate_name(&game, host_name, "host_name")?;
 
-        let color = PLAYER_COLORS[0].to_string();
-        let player = Player::new(player_id, host_name.into(), color, true, false);
+        let player = Self::seat(&game, player_id, host_name.clone(), true, prefs);
         game.players.push(player);
-        game.log(format!("{} created the room", host_name));
+        game.log(LogLine::new("room_created").arg("name", &host_name));
+
+        Self::save_game(redis, &mut game).await?;
 
-        Self::save_game(redis, &game).await?;
+        // Nobody is connected yet, but every lobby change goes out as an event
+        hub.broadcast(&room_id, ServerEvent::GameState(Box::new(game)));
 
         Ok((room_id, player_id))
     }
 
     /// Join an existing room
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, player_id))]
     pub async fn join_room(
         redis: &ConnectionManager,
+        hub: &Arc<Hub>,
         room_id: &str,
         player_name: &str,
+        prefs: &SeatPrefs,
     ) -> AppResult<Uuid> {
         let mut game = Self::get_game(redis, room_id)
             .await?
@@ -78,24 +166,126 @@ impl GameEngine {
             return Err(AppError::BadRequest("Room is full".into()));
         }
 
+        let player_name = Self::validate_name(&game, player_name, "player_name")?;
+
+        if prefs.user_id.is_some() && game.players.iter().any(|p| p.user_id == prefs.user_id) {
+            return Err(AppError::BadRequest("Already in this room".into()));
+        }
+
         let player_id = Uuid::new_v4();
-        let color = PLAYER_COLORS[game.players.len() % PLAYER_COLORS.len()].to_string();
-        let player = Player::new(player_id, player_name.into(), color, false, false);
+        tracing::Span::current().record("player_id", tracing::field::display(player_id));
+        let player = Self::seat(&game, player_id, player_name.clone(), false, prefs);
 
-        game.log(format!("{} joined the game", player_name));
+        game.log(LogLine::new("joined").arg("name", &player_name));
         game.players.push(player);
+        let countdown = Self::arm_auto_start(&mut game);
 
-        Self::save_game(redis, &game).await?;
+        Self::save_game(redis, &mut game).await?;
+        Self::announce_join(hub, room_id, game, player_id).await;
+        if let Some((delay, starts_at)) = countdown {
+            Self::schedule_auto_start(redis, hub, room_id, delay, starts_at);
+        }
 
         Ok(player_id)
     }
 
-    /// Add a bot to the room
-    pub async fn add_bot(redis: &ConnectionManager, room_id: &str) -> AppResult<Uuid> {
+    /// Start the lobby countdown once a second human is in, if the room has
+    /// one; returns how long it runs and when it ends
+    fn arm_auto_start(game: &mut GameState) -> Option<(Duration, i64)> {
+        let delay = game.config.timers().auto_start()?;
+        let humans = game.players.iter().filter(|p| !p.is_bot).count();
+        if game.auto_start_at.is_some() || humans < 2 {
+            return None;
+        }
+
+        let starts_at = chrono::Utc::now().timestamp() + delay.as_secs() as i64;
+        game.auto_start_at = Some(starts_at);
+        game.log(LogLine::new("auto_start").arg("secs", delay.as_secs()));
+        Some((delay, starts_at))
+    }
+
+    /// Fill the lobby with bots and start once its countdown runs out
+    ///
+    /// Players who are not ready yet are started anyway. The countdown is
+    /// dropped when players leave, and the host may start earlier; the task
+    /// then finds nothing to do.
+    fn schedule_auto_start(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        delay: Duration,
+        starts_at: i64,
+    ) {
+        let redis = redis.clone();
+        let hub = hub.clone();
+        let room_id = room_id.to_string();
+        let span = tracing::info_span!("auto_start", room_id = %room_id);
+        let task = async move {
+            tokio::time::sleep(delay).await;
+            let result =
+                retry_on_conflict(|| Self::auto_start(&redis, &hub, &room_id, starts_at)).await;
+            if let Err(e) = result {
+                tracing::warn!("Auto-start failed in room {}: {}", room_id, e);
+            }
+        };
+        tokio::spawn(task.instrument(span));
+    }
+
+    async fn auto_start(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        starts_at: i64,
+    ) -> AppResult<()> {
+        let Some(mut game) = Self::get_game(redis, room_id).await? else {
+            return Ok(());
+        };
+        if game.phase != GamePhase::Lobby || game.auto_start_at != Some(starts_at) {
+            return Ok(());
+        }
+
+        let target = match game.config.auto_start_players {
+            0 => game.config.max_players,
+            n => n.min(game.config.max_players),
+        };
+        let mut joined = Vec::new();
+        while game.players.len() < target as usize {
+            let bot = Self::new_bot(&game, BotDifficulty::default(), None, None)?;
+            game.log(LogLine::new("joined").arg("name", &bot.name));
+            joined.push(ServerEvent::PlayerJoined {
+                player_id: bot.id,
+                name: bot.name.clone(),
+                is_bot: true,
+            });
+            game.players.push(bot);
+        }
+
+        Self::launch(redis, hub, room_id, game, joined).await
+    }
+
+    /// Add a bot to the room on behalf of its host
+    ///
+    /// Without a name or avatar the next unused one from the curated lists is
+    /// picked.
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, player_id))]
+    pub async fn add_bot(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        host_id: Uuid,
+        difficulty: BotDifficulty,
+        name: Option<&str>,
+        avatar: Option<&str>,
+    ) -> AppResult<Uuid> {
         let mut game = Self::get_game(redis, room_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
 
+        match game.get_player(host_id) {
+            Some(player) if player.is_host => {}
+            Some(_) => return Err(AppError::Forbidden("Only the host can add bots".into())),
+            None => return Err(AppError::Forbidden("Not a player in this game".into())),
+        }
         if game.phase != GamePhase::Lobby {
             return Err(AppError::BadRequest("Game already started".into()));
         }
@@ -104,395 +294,1302 @@ impl GameEngine {
             return Err(AppError::BadRequest("Room is full".into()));
         }
 
-        let bot_idx = game.players.iter().filter(|p| p.is_bot).count();
-        let player_id = Uuid::new_v4();
-        let color = PLAYER_COLORS[game.players.len() % PLAYER_COLORS.len()].to_string();
-        let name = BOT_NAMES[bot_idx % BOT_NAMES.len()].to_string();
-        let player = Player::new(player_id, name.clone(), color, false, true);
+        let player = Self::new_bot(&game, difficulty, name, avatar)?;
+        let player_id = player.id;
+        tracing::Span::current().record("player_id", tracing::field::display(player_id));
 
-        game.log(format!("{} joined the game", name));
+        game.log(LogLine::new("joined").arg("name", &player.name));
         game.players.push(player);
 
-        Self::save_game(redis, &game).await?;
+        Self::save_game(redis, &mut game).await?;
+        Self::announce_join(hub, room_id, game, player_id).await;
 
         Ok(player_id)
     }
 
-    /// Start the game
-    pub async fn start_game(
+    /// A bot for the next free seat, with unused looks where none are given
+    fn new_bot(
+        game: &GameState,
+        difficulty: BotDifficulty,
+        name: Option<&str>,
+        avatar: Option<&str>,
+    ) -> AppResult<Player> {
+        let name = match name {
+            Some(name) => Self::validate_name(game, name, "name")?,
+            None => Self::unused(game, BOT_NAMES, |p| p.name.as_str()),
+        };
+
+        let avatar = match avatar {
+            Some(avatar) if BOT_AVATARS.contains(&avatar) => avatar.to_string(),
+            Some(_) => return Err(AppError::BadRequest("Unknown avatar".into())),
+            None => Self::unused(game, BOT_AVATARS, |p| p.avatar.as_deref().unwrap_or("")),
+        };
+
+        let bot_idx = game.players.iter().filter(|p| p.is_bot).count();
+        let color = Self::unused(game, PLAYER_COLORS, |p| p.color.as_str());
+        let mut player = Player::new(Uuid::new_v4(), name, color, false, true);
+        player.avatar = Some(avatar);
+        player.piece = Some(Self::unused(game, PLAYER_PIECES, |p| {
+            p.piece.as_deref().unwrap_or("")
+        }));
+        player.bot_difficulty = difficulty;
+        player.bot_personality = BotPersonality::for_seat(bot_idx);
+        Ok(player)
+    }
+
+    /// Open a new lobby for the players of a finished game
+    ///
+    /// Everyone still seated at the end, bots included, sits down again
+    /// under the same id with the same looks, and the config carries over.
+    /// Asking again returns the lobby already opened. Returns the new room
+    /// and whether this call opened it.
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, player_id = %player_id))]
+    pub async fn rematch(
         redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
         room_id: &str,
-    ) -> AppResult<()> {
+        player_id: Uuid,
+    ) -> AppResult<(GameState, bool)> {
+        retry_on_conflict(|| Self::try_rematch(redis, room_id, player_id)).await
+    }
+
+    async fn try_rematch(
+        redis: &ConnectionManager,
+        room_id: &str,
+        player_id: Uuid,
+    ) -> AppResult<(GameState, bool)> {
         let mut game = Self::get_game(redis, room_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
 
-        if game.phase != GamePhase::Lobby {
-            return Err(AppError::BadRequest("Game already started".into()));
+        let seated = |id: Uuid| game.get_player(id).is_some() && !game.departed.contains(&id);
+        if !seated(player_id) {
+            return Err(AppError::Forbidden("Not a player in this game".into()));
         }
-
-        if game.players.len() < 2 {
-            return Err(AppError::BadRequest("Need at least 2 players".into()));
+        if game.phase != GamePhase::GameOver {
+            return Err(AppError::GameError("Game is not over yet".into()));
         }
-
-        // Set starting cash
-        let starting_cash = game.config.starting_cash;
-        for player in &mut game.players {
-            player.balance = starting_cash;
+        // An expired lobby is replaced by a new one
+        if let Some(next_id) = &game.rematch_room {
+            if let Some(next) = Self::get_game(redis, next_id).await? {
+                return Ok((next, false));
+            }
         }
 
-        // Randomize player order (scoped to avoid RNG across await)
-        let order = {
-            let mut rng = rand::thread_rng();
-            let mut order: Vec<Uuid> = game.players.iter().map(|p| p.id).collect();
-            for i in (1..order.len()).rev() {
-                let j = rng.gen_range(0..=i);
-                order.swap(i, j);
+        let host = game
+            .players
+            .iter()
+            .find(|p| p.is_host && seated(p.id))
+            .map_or(player_id, |p| p.id);
+        let mut next = GameState::new(generate_room_id(), game.config.clone());
+        for old in game.players.iter().filter(|p| seated(p.id)) {
+            let mut player = Player::new(
+                old.id,
+                old.name.clone(),
+                old.color.clone(),
+                old.id == host,
+                old.is_bot,
+            );
+            player.avatar = old.avatar.clone();
+            player.piece = old.piece.clone();
+            // Bots that took over a seat keep nothing of who held it
+            if !old.is_bot {
+                player.user_id = old.user_id;
+                player.locale = old.locale;
             }
-            order
-        };
-        game.turn_order = order.clone();
+            player.bot_difficulty = old.bot_difficulty;
+            player.bot_personality = old.bot_personality;
+            player.bot_params = old.bot_params;
+            next.players.push(player);
+        }
+        next.log(LogLine::new("rematch").arg("room", &game.id));
 
-        // Start first turn
-        let first_player = order[0];
-        game.turn = Some(TurnState::new(first_player));
-        game.phase = GamePhase::Playing;
-        game.log("Game started!".into());
+        // Saved first, so the old room never points at a lobby that is not
+        // there
+        Self::save_game(redis, &mut next).await?;
+        game.rematch_room = Some(next.id.clone());
+        Self::save_game(redis, &mut game).await?;
 
-        // Check if first player is a bot
-        let first_is_bot = game
-            .get_player(first_player)
-            .map(|p| p.is_bot)
-            .unwrap_or(false);
+        Ok((next, true))
+    }
 
-        Self::save_game(redis, &game).await?;
+    /// Close a room on an administrator's say
+    ///
+    /// Ending keeps the room around, read-only, for the close grace period
+    /// so players can look at the final board; deleting removes it with its
+    /// chat and disconnects everyone.
+    #[tracing::instrument(skip_all, fields(room_id = %room_id))]
+    pub async fn close_room(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        reason: String,
+        delete: bool,
+    ) -> AppResult<()> {
+        let mut game = Self::get_game(redis, room_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
 
-        // Broadcast game start
-        {
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(room_id, ServerEvent::GameState(game));
+        let mut events = Vec::new();
+        if !delete {
+            Self::wind_down(&mut game, &mut events);
+            game.log(LogLine::new("ended_by_admin").arg("reason", &reason));
+        }
+        events.push(ServerEvent::RoomClosed {
+            reason,
+            deleted: delete,
+        });
+        let journal = Self::journal_entries(&mut game, None, &events);
+
+        if delete {
+            db::archive::delete_snapshot(room_id).await?;
+            store::current(redis).delete(room_id).await?;
+            chat::delete(redis, room_id).await?;
+        } else {
+            Self::save_game(redis, &mut game).await?;
         }
 
-        // Note: Bot processing will be triggered by frontend polling or separate mechanism
-        // to avoid async recursion between end_turn and process_bot_turn
-        let _ = first_is_bot; // Acknowledge the variable
+        db::journal::record(journal);
+
+        if !delete {
+            hub.broadcast(room_id, ServerEvent::GameState(Box::new(game)));
+        }
+        for event in events {
+            hub.broadcast(room_id, event);
+        }
 
         Ok(())
     }
 
-    /// Handle a game event from a player
-    pub async fn handle_event(
+    /// Close a room on its host's say, keeping it read-only for the close
+    /// grace period
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, player_id = %player_id))]
+    pub async fn close_by_host(
         redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
+        hub: &Arc<Hub>,
         room_id: &str,
         player_id: Uuid,
-        event: super::events::ClientEvent,
     ) -> AppResult<()> {
-        use super::events::ClientEvent::*;
+        retry_on_conflict(|| Self::try_close_by_host(redis, hub, room_id, player_id)).await
+    }
 
+    async fn try_close_by_host(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        player_id: Uuid,
+    ) -> AppResult<()> {
         let mut game = Self::get_game(redis, room_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
 
-        // Verify it's this player's turn (for most actions)
-        let is_current_player = game
-            .turn
-            .as_ref()
-            .map(|t| t.player_id == player_id)
-            .unwrap_or(false);
-
-        match event {
-            RollDice => {
-                if !is_current_player {
-                    return Err(AppError::Forbidden("Not your turn".into()));
-                }
-                Self::roll_dice(redis, hub, room_id).await?;
-            }
-            BuyProperty => {
-                if !is_current_player {
-                    return Err(AppError::Forbidden("Not your turn".into()));
-                }
-                Self::buy_property(redis, hub, room_id).await?;
-            }
-            PassProperty => {
-                if !is_current_player {
-                    return Err(AppError::Forbidden("Not your turn".into()));
-                }
-                Self::start_auction(redis, hub, room_id).await?;
-            }
-            EndTurn => {
-                if !is_current_player {
-                    return Err(AppError::Forbidden("Not your turn".into()));
-                }
-                Self::end_turn(redis, hub, room_id).await?;
-            }
-            Bid { amount } => {
-                Self::place_bid(redis, hub, room_id, player_id, amount).await?;
-            }
-            PassBid => {
-                Self::pass_bid(redis, hub, room_id, player_id).await?;
-            }
-            PayJail => {
-                if !is_current_player {
-                    return Err(AppError::Forbidden("Not your turn".into()));
-                }
-                Self::pay_jail(redis, hub, room_id).await?;
-            }
-            Build { tile_idx } => {
-                Self::build_house(redis, hub, room_id, player_id, tile_idx).await?;
-            }
-            Mortgage { tile_idx } => {
-                Self::mortgage_property(redis, hub, room_id, player_id, tile_idx).await?;
-            }
-            Unmortgage { tile_idx } => {
-                Self::unmortgage_property(redis, hub, room_id, player_id, tile_idx).await?;
-            }
-            Chat { message } => {
-                let player_name = game
-                    .get_player(player_id)
-                    .map(|p| p.name.clone())
-                    .unwrap_or_else(|| "Unknown".into());
-
-                let hub_guard = hub.read().await;
-                hub_guard.broadcast(
-                    room_id,
-                    ServerEvent::Chat {
-                        from: player_id,
-                        from_name: player_name,
-                        message,
-                    },
-                );
-            }
-            TradeOffer { offer } => {
-                let trade = TradeHandler::create_offer(
-                    &mut game,
-                    player_id,
-                    offer.to_player,
-                    offer.offering,
-                    offer.requesting,
-                )
-                .map_err(AppError::GameError)?;
-
-                Self::save_game(redis, &game).await?;
-                let hub_guard = hub.read().await;
-                hub_guard.broadcast(room_id, ServerEvent::TradeProposed { trade });
-            }
-            TradeAccept { trade_id } => {
-                TradeHandler::accept_trade(&mut game, trade_id).map_err(AppError::GameError)?;
-                Self::save_game(redis, &game).await?;
-
-                let hub_guard = hub.read().await;
-                hub_guard.broadcast(
-                    room_id,
-                    ServerEvent::TradeResolved {
-                        trade_id,
-                        accepted: true,
-                    },
-                );
-                // Broadcast full state to sync property transfers
-                hub_guard.broadcast(room_id, ServerEvent::GameState(game.clone()));
-            }
-            TradeReject { trade_id } => {
-                TradeHandler::reject_trade(&mut game, trade_id).map_err(AppError::GameError)?;
-                Self::save_game(redis, &game).await?;
-                let hub_guard = hub.read().await;
-                hub_guard.broadcast(
-                    room_id,
-                    ServerEvent::TradeResolved {
-                        trade_id,
-                        accepted: false,
-                    },
-                );
-            }
-            _ => {
-                tracing::warn!("Unhandled event: {:?}", event);
+        let name = match game.get_player(player_id) {
+            Some(player) if player.is_host => player.name.clone(),
+            Some(_) => {
+                return Err(AppError::Forbidden(
+                    "Only the host can close the room".into(),
+                ))
             }
+            None => return Err(AppError::Forbidden("Not a player in this game".into())),
+        };
+        if game.closed_until.is_some() {
+            return Err(AppError::BadRequest("Room is already closed".into()));
         }
 
+        let mut events = Vec::new();
+        Self::wind_down(&mut game, &mut events);
+        game.log(LogLine::new("closed_by_host").arg("name", &name));
+        events.push(ServerEvent::RoomClosed {
+            reason: "Closed by the host".into(),
+            deleted: false,
+        });
+        let journal = Self::journal_entries(&mut game, Some(player_id), &events);
+        Self::save_game(redis, &mut game).await?;
+        db::journal::record(journal);
+
+        hub.broadcast(room_id, ServerEvent::GameState(Box::new(game)));
+        for event in events {
+            hub.broadcast(room_id, event);
+        }
         Ok(())
     }
 
-    /// Roll dice and move player
-    async fn roll_dice(
+    /// Set how long the room is kept after its last activity on its host's
+    /// say, or go back to the server's expiries with `None`
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, player_id = %player_id))]
+    pub async fn set_lifetime(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        player_id: Uuid,
+        lifetime_secs: Option<u64>,
+    ) -> AppResult<()> {
+        if lifetime_secs.is_some_and(|secs| !store::LIFETIME_SECS.contains(&secs)) {
+            let mut errors = FieldErrors::new();
+            errors.add(
+                "lifetime_secs",
+                "out_of_range",
+                format!(
+                    "lifetime_secs must be {} to {}",
+                    store::LIFETIME_SECS.start(),
+                    store::LIFETIME_SECS.end()
+                ),
+            );
+            errors.into_result()?;
+        }
+        retry_on_conflict(|| Self::try_set_lifetime(redis, hub, room_id, player_id, lifetime_secs))
+            .await
+    }
+
+    async fn try_set_lifetime(
         redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
+        hub: &Arc<Hub>,
         room_id: &str,
+        player_id: Uuid,
+        lifetime_secs: Option<u64>,
     ) -> AppResult<()> {
         let mut game = Self::get_game(redis, room_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
 
-        let turn = game
-            .turn
-            .as_mut()
-            .ok_or_else(|| AppError::GameError("No active turn".into()))?;
-
-        if turn.phase != TurnPhase::WaitingForRoll {
-            return Err(AppError::GameError("Cannot roll now".into()));
+        match game.get_player(player_id) {
+            Some(player) if player.is_host => {}
+            Some(_) => {
+                return Err(AppError::Forbidden(
+                    "Only the host can change the room's lifetime".into(),
+                ))
+            }
+            None => return Err(AppError::Forbidden("Not a player in this game".into())),
+        }
+        if game.closed_until.is_some() {
+            return Err(AppError::GameError("Room is closed".into()));
         }
 
-        // Roll dice (scoped to avoid RNG across await)
-        let (d1, d2) = {
-            let mut rng = rand::thread_rng();
-            (rng.gen_range(1..=6), rng.gen_range(1..=6))
+        // Saving applies the new expiry
+        game.lifetime_secs = lifetime_secs;
+        Self::save_game(redis, &mut game).await?;
+
+        hub.broadcast(room_id, ServerEvent::GameState(Box::new(game)));
+        Ok(())
+    }
+
+    /// Seconds until the room expires unless something happens in it,
+    /// `None` if it never does
+    pub async fn expires_in(redis: &ConnectionManager, room_id: &str) -> AppResult<Option<u64>> {
+        store::current(redis).ttl(room_id).await
+    }
+
+    /// Stop whatever the room was doing and start its close grace period
+    fn wind_down(game: &mut GameState, events: &mut Vec<ServerEvent>) {
+        game.phase = GamePhase::GameOver;
+        game.turn = None;
+        game.auction = None;
+        game.auto_start_at = None;
+        TradeHandler::expire(game, events);
+
+        let grace = store::layout().closed_grace_secs;
+        game.closed_until = Some(chrono::Utc::now().timestamp() + grace as i64);
+    }
+
+    /// Tell the lobby who just sat down, then send the updated room
+    async fn announce_join(hub: &Arc<Hub>, room_id: &str, game: GameState, player_id: Uuid) {
+        let Some(player) = game.get_player(player_id) else {
+            return;
+        };
+        let joined = ServerEvent::PlayerJoined {
+            player_id,
+            name: player.name.clone(),
+            is_bot: player.is_bot,
         };
-        let is_doubles = d1 == d2;
-        let dice_sum = d1 + d2;
 
-        turn.dice = Some((d1, d2));
-        turn.phase = TurnPhase::Moving;
+        hub.broadcast(room_id, joined);
+        hub.broadcast(room_id, ServerEvent::GameState(Box::new(game)));
+    }
 
-        if is_doubles {
-            turn.doubles_count += 1;
+    /// Check a display name against the room and return it trimmed;
+    /// problems are reported against `field` of the request
+    fn validate_name(game: &GameState, name: &str, field: &str) -> AppResult<String> {
+        let name = name.trim();
+
+        let mut errors = Self::check_name(name, field);
+        if errors.is_empty()
+            && game
+                .players
+                .iter()
+                .any(|p| p.name.eq_ignore_ascii_case(name))
+        {
+            errors.add(field, "taken", "Name is already taken");
         }
+        errors.into_result()?;
 
-        let player_id = turn.player_id;
-        let doubles_count = turn.doubles_count;
+        Ok(name.to_string())
+    }
 
-        // Broadcast dice result
-        {
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(
-                room_id,
-                ServerEvent::DiceResult {
-                    player_id,
-                    dice: (d1, d2),
-                    is_doubles,
-                },
+    /// Check a display name on its own, before any room is loaded
+    pub fn check_name(name: &str, field: &str) -> FieldErrors {
+        let name = name.trim();
+        let mut errors = FieldErrors::new();
+
+        if name.is_empty() {
+            errors.add(field, "required", "Name cannot be empty");
+        } else if name.chars().count() > MAX_NAME_LEN {
+            errors.add(
+                field,
+                "too_long",
+                format!("Name cannot be longer than {} characters", MAX_NAME_LEN),
             );
+        } else if name.chars().any(char::is_control) {
+            errors.add(field, "invalid_chars", "Name contains invalid characters");
         }
+        errors
+    }
 
-        // Check for 3 doubles = jail
-        if doubles_count >= 3 {
-            Self::send_to_jail(&mut game, player_id);
-            Self::save_game(redis, &game).await?;
+    /// A human player with their preferred looks where the room allows
+    fn seat(
+        game: &GameState,
+        player_id: Uuid,
+        name: String,
+        is_host: bool,
+        prefs: &SeatPrefs,
+    ) -> Player {
+        let color = prefs
+            .color
+            .as_deref()
+            .filter(|color| PLAYER_COLORS.contains(color))
+            .filter(|color| !game.players.iter().any(|p| p.color == *color))
+            .map(str::to_string)
+            .unwrap_or_else(|| Self::unused(game, PLAYER_COLORS, |p| p.color.as_str()));
+
+        let mut player = Player::new(player_id, name, color, is_host, false);
+        player.user_id = prefs.user_id;
+        player.locale = prefs.locale;
+        player.avatar = prefs
+            .avatar
+            .clone()
+            .filter(|avatar| PLAYER_AVATARS.contains(&avatar.as_str()));
+        // Profile avatars double as pieces where the two lists meet
+        player.piece = player
+            .avatar
+            .clone()
+            .filter(|avatar| PLAYER_PIECES.contains(&avatar.as_str()))
+            .filter(|avatar| {
+                !game
+                    .players
+                    .iter()
+                    .any(|p| p.piece.as_ref() == Some(avatar))
+            })
+            .or_else(|| {
+                Some(Self::unused(game, PLAYER_PIECES, |p| {
+                    p.piece.as_deref().unwrap_or("")
+                }))
+            });
+        player
+    }
 
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(room_id, ServerEvent::PlayerJailed { player_id });
-            return Ok(());
+    /// First entry of a curated list no player in the room is using yet
+    fn unused(game: &GameState, options: &[&str], field: impl Fn(&Player) -> &str) -> String {
+        options
+            .iter()
+            .find(|option| !game.players.iter().any(|p| field(p) == **option))
+            .unwrap_or(&options[game.players.len() % options.len()])
+            .to_string()
+    }
+
+    /// Start the game on behalf of its host
+    #[tracing::instrument(skip_all, fields(room_id = %room_id))]
+    pub async fn start_game(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        host_id: Uuid,
+    ) -> AppResult<()> {
+        let game = Self::get_game(redis, room_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+        match game.get_player(host_id) {
+            Some(player) if player.is_host => {}
+            Some(_) => {
+                return Err(AppError::Forbidden(
+                    "Only the host can start the game".into(),
+                ))
+            }
+            None => return Err(AppError::Forbidden("Not a player in this game".into())),
         }
 
-        // Get player data
-        let player_idx = game
+        // Bots are always ready, and the host says so by starting
+        let waiting: Vec<&str> = game
             .players
             .iter()
-            .position(|p| p.id == player_id)
-            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
-
-        let in_jail = game.players[player_idx].in_jail;
+            .filter(|p| !p.is_bot && !p.is_host && !p.ready)
+            .map(|p| p.name.as_str())
+            .collect();
+        if game.phase == GamePhase::Lobby && !waiting.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Waiting for {} to get ready",
+                waiting.join(", ")
+            )));
+        }
 
-        if in_jail {
-            if is_doubles {
-                // Freed by doubles
-                game.players[player_idx].in_jail = false;
-                game.players[player_idx].jail_turns = 0;
-                let name = game.players[player_idx].name.clone();
-                game.log(format!("{} rolled doubles and escaped jail!", name));
+        Self::launch(redis, hub, room_id, game, Vec::new()).await
+    }
 
-                let hub_guard = hub.read().await;
-                hub_guard.broadcast(
-                    room_id,
-                    ServerEvent::PlayerFreed {
-                        player_id,
-                        method: "dice".into(),
-                    },
-                );
-            } else {
-                game.players[player_idx].jail_turns += 1;
+    /// Set up a lobby for play, then save and announce it; `joined` goes
+    /// out first for seats filled on the way
+    async fn launch(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        mut game: GameState,
+        joined: Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        // Scoped to avoid holding the RNG across await
+        {
+            let mut rng = rand::thread_rng();
+            Self::setup_game(&mut game, &mut rng)?;
+        }
+        game.auto_start_at = None;
 
-                if game.players[player_idx].jail_turns >= 3 {
-                    // Forced to pay
-                    game.players[player_idx].balance -= 50;
-                    game.log(format!(
-                        "{} was forced to pay $50 bail",
-                        game.players[player_idx].name
-                    ));
-
-                    if BankruptcyHandler::is_bankrupt(&game, player_id) {
-                        BankruptcyHandler::handle_bankruptcy(&mut game, player_id, None); // Debt to bank
-                        let hub_guard = hub.read().await;
-                        hub_guard.broadcast(
-                            room_id,
-                            ServerEvent::Bankruptcy {
-                                player_id,
-                                creditor: None,
-                            },
-                        );
-                    }
+        // The opening position, for replays
+        let snapshot = [ServerEvent::GameState(Box::new(game.clone()))];
+        let journal = Self::journal_entries(&mut game, None, &snapshot);
+        Self::save_game(redis, &mut game).await?;
+        db::journal::record(journal);
 
-                    game.players[player_idx].in_jail = false;
-                    game.players[player_idx].jail_turns = 0;
-                } else {
-                    let name = game.players[player_idx].name.clone();
-                    game.log(format!("{} failed to roll doubles in jail", name));
-                    if let Some(t) = game.turn.as_mut() {
-                        t.phase = TurnPhase::TurnEnd;
-                        t.can_roll_again = false;
-                    }
-                    Self::save_game(redis, &game).await?;
+        // Broadcast game start
+        {
+            for event in joined {
+                hub.broadcast(room_id, event);
+            }
+            Self::remind_turns(hub, room_id, &game, &HashMap::new());
+            Self::arm_turn_timer(redis, hub, room_id, &game);
+            hub.broadcast(room_id, ServerEvent::GameState(Box::new(game)));
+        }
+
+        Self::spawn_bot_turn(redis, hub, room_id);
+
+        Ok(())
+    }
+
+    /// Move a lobby into play: starting cash, random order and first turn
+    pub(crate) fn setup_game(game: &mut GameState, rng: &mut impl Rng) -> AppResult<()> {
+        if game.phase != GamePhase::Lobby {
+            return Err(AppError::BadRequest("Game already started".into()));
+        }
+
+        if game.players.len() < 2 {
+            return Err(AppError::BadRequest("Need at least 2 players".into()));
+        }
+
+        // Set starting cash
+        let starting_cash = game.config.starting_cash;
+        for player in &mut game.players {
+            player.balance = starting_cash;
+        }
+
+        // Randomize player order
+        let mut order: Vec<Uuid> = game.players.iter().map(|p| p.id).collect();
+        for i in (1..order.len()).rev() {
+            let j = rng.gen_range(0..=i);
+            order.swap(i, j);
+        }
+        game.turn_order = order.clone();
+
+        // Start first turn
+        game.turn = Some(TurnState::new(order[0]));
+        game.phase = GamePhase::Playing;
+        game.started_at = Some(chrono::Utc::now().timestamp());
+        game.log(LogLine::new("game_started"));
+        game.decks = DeckOrder::shuffled(game.board(), rng);
+        Self::deal_properties(game, rng);
+
+        Ok(())
+    }
+
+    /// Deal `dealt_properties` random unowned properties to each player,
+    /// one round at a time in turn order, while any are left
+    fn deal_properties(game: &mut GameState, rng: &mut impl Rng) {
+        let per_player = game.config.dealt_properties as usize;
+        if per_player == 0 {
+            return;
+        }
+
+        // Sorted first, as map order would make seeded games differ
+        let mut deck: Vec<u8> = game
+            .properties
+            .iter()
+            .filter(|(_, prop)| prop.owner.is_none())
+            .map(|(&idx, _)| idx)
+            .collect();
+        deck.sort_unstable();
+        for i in (1..deck.len()).rev() {
+            let j = rng.gen_range(0..=i);
+            deck.swap(i, j);
+        }
+
+        let order = game.turn_order.clone();
+        let mut dealt: HashMap<Uuid, Vec<String>> = HashMap::new();
+        'deal: for _ in 0..per_player {
+            for &player_id in &order {
+                let Some(tile_idx) = deck.pop() else {
+                    break 'deal;
+                };
+                if let Some(prop) = game.properties.get_mut(&tile_idx) {
+                    prop.owner = Some(player_id);
+                }
+                let tile_name = game
+                    .tile(tile_idx)
+                    .map(|t| t.name.clone())
+                    .unwrap_or_default();
+                dealt.entry(player_id).or_default().push(tile_name);
+            }
+        }
+
+        for player_id in order {
+            let (Some(tiles), Some(player)) = (dealt.get(&player_id), game.get_player(player_id))
+            else {
+                continue;
+            };
+            let line = LogLine::new("dealt_properties")
+                .arg("name", &player.name)
+                .arg("tiles", tiles.join(", "));
+            game.log(line);
+        }
+    }
+
+    /// Handle a game event from a player
+    ///
+    /// When another action saves the room first, the event is checked and
+    /// applied again on the newer state, so it is either applied to the
+    /// latest state or rejected for it, never lost.
+    #[tracing::instrument(skip_all, fields(
+        room_id = %room_id,
+        player_id = %player_id,
+        event_type = %message.event.type_name().unwrap_or_default(),
+    ))]
+    pub async fn handle_event(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        player_id: Uuid,
+        message: super::events::ClientMessage,
+    ) -> AppResult<()> {
+        retry_on_conflict(|| Self::try_event(redis, hub, room_id, player_id, message.clone())).await
+    }
+
+    async fn try_event(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        player_id: Uuid,
+        message: super::events::ClientMessage,
+    ) -> AppResult<()> {
+        use super::events::ClientEvent::*;
+
+        let game = Self::get_game(redis, room_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+        let event = message.event;
+
+        // Reject turn-scoped actions issued for a turn that has already ended;
+        // clients that do not send the turn id are taken at their word
+        if event.is_turn_scoped() {
+            let current_turn_id = game.turn.as_ref().map(|t| t.id);
+            if message
+                .turn_id
+                .is_some_and(|id| Some(id) != current_turn_id)
+            {
+                return Err(AppError::GameError("Action is for a stale turn".into()));
+            }
+        }
+
+        // Chat never reaches `apply_event`, so check it here as well
+        validate_action(&game, player_id, &event)?;
+
+        match event {
+            Chat { message } => {
+                let player_name = game
+                    .get_player(player_id)
+                    .map(|p| p.name.clone())
+                    .unwrap_or_else(|| "Unknown".into());
+
+                if game.muted.contains(&player_id) {
+                    return Err(AppError::Forbidden("You are muted".into()));
+                }
+                moderation::check_cooldown(redis, room_id, player_id).await?;
+                // Chat saves nothing, but keeps the room alive all the same
+                store::current(redis)
+                    .touch(room_id, store::layout().ttl_for(&game))
+                    .await?;
+
+                let message = if game.config.allow_profanity {
+                    message
+                } else {
+                    moderation::filter(&message)
+                };
+                let event = ServerEvent::Chat {
+                    from: player_id,
+                    from_name: player_name.clone(),
+                    message: message.clone(),
+                };
+
+                // Echoed back as if sent, and kept out of the history the
+                // others read
+                if game.shadow_muted.contains(&player_id) {
+                    hub.send_to(room_id, player_id, event);
                     return Ok(());
                 }
+
+                chat::record(redis, room_id, player_id, &player_name, &message).await?;
+                hub.broadcast(room_id, event);
+            }
+            ShadowMute { player_id: target } => {
+                Self::apply_shadow_mute(redis, hub, game, Some(player_id), target, true).await?
+            }
+            ShadowUnmute { player_id: target } => {
+                Self::apply_shadow_mute(redis, hub, game, Some(player_id), target, false).await?
+            }
+            event => {
+                let mut game = game;
+                let returned = Self::mark_active(&mut game, player_id);
+                Self::execute(redis, hub, room_id, game, player_id, event).await?;
+                if returned {
+                    hub.broadcast(room_id, ServerEvent::PlayerReturned { player_id });
+                    if let Some(game) = Self::get_game(redis, room_id).await? {
+                        Self::arm_turn_timer(redis, hub, room_id, &game);
+                    }
+                }
+
+                // Hand over to bots if the action left them something to do
+                // (next turn, auction bids); the scheduler exits immediately
+                // otherwise
+                Self::spawn_bot_turn(redis, hub, room_id);
             }
         }
 
-        // Move player
-        let old_pos = game.players[player_idx].position;
-        let new_pos = (old_pos + dice_sum) % 40;
-        let passed_go = new_pos < old_pos && old_pos != 0;
+        Ok(())
+    }
 
-        game.players[player_idx].position = new_pos;
+    /// Apply an action to a loaded game, then persist and broadcast the result
+    async fn execute(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        mut game: GameState,
+        player_id: Uuid,
+        event: ClientEvent,
+    ) -> AppResult<()> {
+        let mut events = Vec::new();
+        // Names are taken now, as a kicked player is gone afterwards
+        let audit = Self::audit_entry(&game, player_id, &event);
+        let waiting = Self::waiting_on(&game);
+        let timed = Self::timed_turn(&game);
+        let debt_since = game.debt.as_ref().map(|d| d.since);
 
-        if passed_go {
-            game.players[player_idx].balance += 200;
-            let name = game.players[player_idx].name.clone();
-            game.log(format!("{} passed GO and collected $200", name));
+        // Scoped to avoid holding the RNG across await
+        {
+            let mut rng = rand::thread_rng();
+            Self::apply_event(&mut game, player_id, event, &mut rng, &mut events)?;
+        }
+
+        let journal = Self::journal_entries(&mut game, Some(player_id), &events);
+        Self::save_game(redis, &mut game).await?;
+        db::journal::record(journal);
+        if let Some(entry) = audit {
+            db::audit::record(entry);
+        }
+
+        // Bot banter is part of the room's history too
+        for event in &events {
+            if let ServerEvent::Chat {
+                from,
+                from_name,
+                message,
+            } = event
+            {
+                chat::record(redis, room_id, *from, from_name, message).await?;
+            }
+        }
+        let finished = events
+            .iter()
+            .any(|e| matches!(e, ServerEvent::GameOver { .. }));
+
+        {
+            for event in events {
+                hub.broadcast(room_id, event);
+            }
+        }
+        Self::remind_turns(hub, room_id, &game, &waiting);
+        if Self::timed_turn(&game) != timed {
+            Self::arm_turn_timer(redis, hub, room_id, &game);
         }
+        if let Some(debt) = game.debt.as_ref().filter(|d| Some(d.since) != debt_since) {
+            Self::schedule_debt_deadline(redis, hub, room_id, &game, debt);
+        }
+
+        if finished {
+            db::archive::archive_game(redis.clone(), hub.clone(), game);
+        }
+
+        Ok(())
+    }
+
+    /// Decisions the game waits for, by player
+    fn waiting_on(game: &GameState) -> HashMap<Uuid, Vec<String>> {
+        game.players
+            .iter()
+            .map(|p| (p.id, pending_actions(game, p.id)))
+            .filter(|(_, actions)| !actions.is_empty())
+            .collect()
+    }
+
+    /// Send `YourTurn` to each human the game started waiting on since
+    /// `before`
+    fn remind_turns(
+        hub: &Arc<Hub>,
+        room_id: &str,
+        game: &GameState,
+        before: &HashMap<Uuid, Vec<String>>,
+    ) {
+        let Some(turn) = &game.turn else {
+            return;
+        };
+        let now = chrono::Utc::now().timestamp();
+        let timers = game.config.timers();
 
-        // Broadcast movement
+        for player in game
+            .players
+            .iter()
+            .filter(|p| !p.is_bot && !p.bot_controlled)
         {
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(
+            let actions = pending_actions(game, player.id);
+            if actions.is_empty() || before.get(&player.id) == Some(&actions) {
+                continue;
+            }
+
+            let (since, limit) = match (&game.auction, &game.debt) {
+                (Some(auction), _) if actions.iter().any(|a| a == "BID") => {
+                    (auction.last_bid_at, timers.auction_secs)
+                }
+                (_, Some(debt))
+                    if timers.debt_secs > 0 && actions.iter().any(|a| a == "PAY_DEBT") =>
+                {
+                    (debt.since, timers.debt_secs)
+                }
+                _ => (turn.started_at, timers.turn_secs),
+            };
+            let remaining_secs =
+                (limit > 0).then(|| (since + i64::from(limit) - now).max(0) as u32);
+
+            hub.send_to(
                 room_id,
-                ServerEvent::PlayerMoved {
-                    player_id,
-                    from: old_pos,
-                    to: new_pos,
-                    passed_go,
+                player.id,
+                ServerEvent::YourTurn {
+                    turn_id: turn.id,
+                    actions,
+                    remaining_secs,
                 },
             );
         }
+    }
 
-        // Handle tile landing
-        let mut events = Vec::new();
-        Self::handle_tile_landing(&mut game, player_id, new_pos, &mut events)?;
+    /// Audit log entry for a host's moderation action, if `event` is one
+    fn audit_entry(
+        game: &GameState,
+        player_id: Uuid,
+        event: &ClientEvent,
+    ) -> Option<db::audit::AuditEntry> {
+        use db::audit::AuditAction;
+
+        let (action, target) = match *event {
+            ClientEvent::Kick { player_id } => (AuditAction::Kick, player_id),
+            ClientEvent::Mute { player_id } => (AuditAction::Mute, player_id),
+            ClientEvent::Unmute { player_id } => (AuditAction::Unmute, player_id),
+            _ => return None,
+        };
+        Some(Self::audit_action(game, Some(player_id), action, target))
+    }
 
-        // Update turn state
-        if is_doubles && !game.players[player_idx].in_jail {
-            if let Some(t) = game.turn.as_mut() {
-                t.can_roll_again = true;
+    /// Audit log entry for an action on `target` by a player, or by an
+    /// administrator when `actor` is `None`
+    fn audit_action(
+        game: &GameState,
+        actor: Option<Uuid>,
+        action: db::audit::AuditAction,
+        target: Uuid,
+    ) -> db::audit::AuditEntry {
+        let actor_player = actor.and_then(|id| game.get_player(id));
+        let target_player = game.get_player(target);
+
+        db::audit::AuditEntry {
+            action,
+            room_id: game.id.clone(),
+            actor_id: actor,
+            actor_user_id: actor_player.and_then(|p| p.user_id),
+            target_id: Some(target),
+            details: serde_json::json!({
+                "actor_name": actor_player.map(|p| p.name.clone()),
+                "target_name": target_player.map(|p| p.name.clone()),
+                "target_user_id": target_player.and_then(|p| p.user_id),
+            }),
+        }
+    }
+
+    /// Number events for the room's journal, to be queued once the game that
+    /// counts them is saved
+    fn journal_entries(
+        game: &mut GameState,
+        player_id: Option<Uuid>,
+        events: &[ServerEvent],
+    ) -> Vec<db::journal::JournalEntry> {
+        let at = chrono::Utc::now();
+        events
+            .iter()
+            .map(|event| {
+                game.journal_seq += 1;
+                db::journal::JournalEntry {
+                    room_id: game.id.clone(),
+                    seq: game.journal_seq,
+                    player_id,
+                    event: event.clone(),
+                    at,
+                }
+            })
+            .collect()
+    }
+
+    /// Apply a player action to the game state without any I/O
+    ///
+    /// Shared by live rooms and headless simulations; resulting broadcasts are
+    /// appended to `events` in order.
+    pub(crate) fn apply_event(
+        game: &mut GameState,
+        player_id: Uuid,
+        event: ClientEvent,
+        rng: &mut impl Rng,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        use super::events::ClientEvent::*;
+
+        validate_action(game, player_id, &event)?;
+
+        match event {
+            RollDice => {
+                let sides = game.config.dice_sides;
+                let dice = (0..game.config.dice_count)
+                    .map(|_| rng.gen_range(1..=sides))
+                    .collect();
+                Self::roll_dice(game, dice, events)?;
+            }
+            BuyProperty => Self::buy_property(game, events)?,
+            PassProperty => Self::start_auction(game, events)?,
+            EndTurn => Self::end_turn(game, events)?,
+            Bid { amount } => Self::place_bid(game, player_id, amount, events)?,
+            PassBid => Self::pass_bid(game, player_id, events)?,
+            PayJail => Self::pay_jail(game, events)?,
+            PayDebt => Self::pay_debt(game, player_id, events)?,
+            DeclareBankruptcy => Self::declare_bankruptcy(game, player_id, events)?,
+            UseCard => Self::use_jail_card(game, events)?,
+            Build { tile_idx } => Self::build_house(game, player_id, tile_idx, events)?,
+            Mortgage { tile_idx } => Self::mortgage_property(game, player_id, tile_idx, events)?,
+            Unmortgage { tile_idx } => {
+                Self::unmortgage_property(game, player_id, tile_idx, events)?
+            }
+            TradeOffer { offer } => {
+                let trade = TradeHandler::create_offer(
+                    game,
+                    player_id,
+                    offer.to_player,
+                    offer.offering,
+                    offer.requesting,
+                )
+                .map_err(AppError::GameError)?;
+
+                events.push(ServerEvent::TradeProposed { trade });
+            }
+            TradeAccept { trade_id } => {
+                let accepted = TradeHandler::accept_trade(game, trade_id, events)
+                    .map_err(AppError::GameError)?;
+
+                events.push(ServerEvent::TradeResolved { trade_id, accepted });
+                if accepted {
+                    // Broadcast full state to sync property transfers
+                    events.push(ServerEvent::GameState(Box::new(game.clone())));
+                }
+            }
+            TradeReject { trade_id } => {
+                TradeHandler::reject_trade(game, trade_id, events).map_err(AppError::GameError)?;
+
+                events.push(ServerEvent::TradeResolved {
+                    trade_id,
+                    accepted: false,
+                });
+            }
+            TradeCounter { trade_id, offer } => {
+                let trade = TradeHandler::counter_trade(
+                    game,
+                    player_id,
+                    trade_id,
+                    offer.offering,
+                    offer.requesting,
+                    events,
+                )
+                .map_err(AppError::GameError)?;
+
+                events.push(ServerEvent::TradeResolved {
+                    trade_id,
+                    accepted: false,
+                });
+                events.push(ServerEvent::TradeProposed { trade });
+            }
+            Resign => Self::remove_player(game, player_id, rng, events)?,
+            Kick { player_id: target } => {
+                if target == player_id {
+                    return Err(AppError::BadRequest("Use resign to leave the game".into()));
+                }
+                Self::remove_player(game, target, rng, events)?;
+            }
+            Mute { player_id: target } => Self::set_muted(game, player_id, target, true, events)?,
+            Unmute { player_id: target } => {
+                Self::set_muted(game, player_id, target, false, events)?
+            }
+            PickColor { color } => Self::pick_color(game, player_id, &color, events)?,
+            PickPiece { piece } => Self::pick_piece(game, player_id, &piece, events)?,
+            SetReady { ready } => {
+                if let Some(player) = game.get_player_mut(player_id) {
+                    player.ready = ready;
+                }
+                events.push(ServerEvent::PlayerReady { player_id, ready });
+                events.push(ServerEvent::GameState(Box::new(game.clone())));
+            }
+            event => {
+                tracing::warn!("Unhandled event: {:?}", event);
             }
         }
 
-        Self::save_game(redis, &game).await?;
+        Ok(())
+    }
+
+    /// Shadow-mute a player, or lift their shadow mute, on an
+    /// administrator's say
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, player_id = %target))]
+    pub async fn shadow_mute(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        target: Uuid,
+        muted: bool,
+    ) -> AppResult<()> {
+        retry_on_conflict(|| async {
+            let game = Self::get_game(redis, room_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+            Self::apply_shadow_mute(redis, hub, game, None, target, muted).await
+        })
+        .await
+    }
+
+    /// Update who is shadow-muted, by the host or by an administrator when
+    /// `actor` is `None`
+    ///
+    /// Nothing is logged or broadcast, so the target cannot tell; only the
+    /// host gets the updated state, and the audit log keeps the record.
+    async fn apply_shadow_mute(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        mut game: GameState,
+        actor: Option<Uuid>,
+        target: Uuid,
+        muted: bool,
+    ) -> AppResult<()> {
+        use db::audit::AuditAction;
+
+        if actor == Some(target) {
+            return Err(AppError::BadRequest("You cannot mute yourself".into()));
+        }
+        if game.get_player(target).is_none() {
+            return Err(AppError::NotFound("Player not found".into()));
+        }
+
+        game.shadow_muted.retain(|&id| id != target);
+        if muted {
+            game.shadow_muted.push(target);
+        }
+        Self::save_game(redis, &mut game).await?;
 
-        // Broadcast accumulated events (Bankruptcy etc)
+        let action = if muted {
+            AuditAction::ShadowMute
+        } else {
+            AuditAction::ShadowUnmute
+        };
+        db::audit::record(Self::audit_action(&game, actor, action, target));
+
+        for host in game.players.iter().filter(|p| p.is_host) {
+            hub.send_to(
+                &game.id,
+                host.id,
+                ServerEvent::GameState(Box::new(game.clone())),
+            );
+        }
+        Ok(())
+    }
+
+    /// Mute or unmute a player in chat on the host's say
+    fn set_muted(
+        game: &mut GameState,
+        host_id: Uuid,
+        target: Uuid,
+        muted: bool,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        if target == host_id {
+            return Err(AppError::BadRequest("You cannot mute yourself".into()));
+        }
+        let name = game
+            .get_player(target)
+            .map(|p| p.name.clone())
+            .ok_or_else(|| AppError::NotFound("Player not found".into()))?;
+
+        game.muted.retain(|&id| id != target);
+        if muted {
+            game.muted.push(target);
+            game.log(LogLine::new("muted").arg("name", name));
+        } else {
+            game.log(LogLine::new("unmuted").arg("name", name));
+        }
+
+        events.push(ServerEvent::PlayerMuted {
+            player_id: target,
+            muted,
+        });
+
+        Ok(())
+    }
+
+    /// Give a player a color from the palette that nobody else has
+    fn pick_color(
+        game: &mut GameState,
+        player_id: Uuid,
+        color: &str,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        let color = color.trim().to_uppercase();
+        if !PLAYER_COLORS.contains(&color.as_str()) {
+            return Err(AppError::BadRequest("Unknown color".into()));
+        }
+        if game
+            .players
+            .iter()
+            .any(|p| p.id != player_id && p.color == color)
         {
-            let hub_guard = hub.read().await;
-            for event in events {
-                hub_guard.broadcast(room_id, event);
+            return Err(AppError::GameError("Color is already taken".into()));
+        }
+
+        if let Some(player) = game.get_player_mut(player_id) {
+            player.color = color.clone();
+        }
+
+        events.push(ServerEvent::ColorPicked { player_id, color });
+        events.push(ServerEvent::GameState(Box::new(game.clone())));
+
+        Ok(())
+    }
+
+    /// Give a player a playing piece that nobody else has
+    fn pick_piece(
+        game: &mut GameState,
+        player_id: Uuid,
+        piece: &str,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        let piece = piece.trim().to_lowercase();
+        if !PLAYER_PIECES.contains(&piece.as_str()) {
+            return Err(AppError::BadRequest("Unknown piece".into()));
+        }
+        if game
+            .players
+            .iter()
+            .any(|p| p.id != player_id && p.piece.as_ref() == Some(&piece))
+        {
+            return Err(AppError::GameError("Piece is already taken".into()));
+        }
+
+        if let Some(player) = game.get_player_mut(player_id) {
+            player.piece = Some(piece.clone());
+        }
+
+        events.push(ServerEvent::PiecePicked { player_id, piece });
+        events.push(ServerEvent::GameState(Box::new(game.clone())));
+
+        Ok(())
+    }
+
+    /// Move the current player by a dice roll
+    fn roll_dice(
+        game: &mut GameState,
+        dice: Vec<u8>,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        let turn = game
+            .turn
+            .as_mut()
+            .ok_or_else(|| AppError::GameError("No active turn".into()))?;
+
+        if turn.phase != TurnPhase::WaitingForRoll {
+            return Err(AppError::GameError("Cannot roll now".into()));
+        }
+
+        let is_doubles = is_doubles(&dice);
+
+        turn.dice = Some(dice.clone());
+        turn.phase = TurnPhase::Moving;
+
+        if is_doubles {
+            turn.doubles_count += 1;
+        }
+
+        let player_id = turn.player_id;
+        let doubles_count = turn.doubles_count;
+
+        events.push(ServerEvent::DiceResult {
+            player_id,
+            dice,
+            is_doubles,
+        });
+
+        // Check for 3 doubles = jail
+        if doubles_count >= 3 && !game.config.no_jail_on_three_doubles {
+            Self::send_to_jail(game, player_id, events);
+            return Ok(());
+        }
+
+        // Get player data
+        let player_idx = game
+            .players
+            .iter()
+            .position(|p| p.id == player_id)
+            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+
+        let in_jail = game.players[player_idx].in_jail;
+
+        if in_jail {
+            if is_doubles {
+                // Freed by doubles
+                game.players[player_idx].in_jail = false;
+                game.players[player_idx].jail_turns = 0;
+                let name = game.players[player_idx].name.clone();
+                game.log(LogLine::new("jail_escaped").arg("name", &name));
+
+                events.push(ServerEvent::PlayerFreed {
+                    player_id,
+                    method: "dice".into(),
+                });
+            } else {
+                game.players[player_idx].jail_turns += 1;
+
+                if game.config.jail_forced_bail
+                    && game.players[player_idx].jail_turns >= game.config.max_jail_turns
+                {
+                    // Forced to pay; short of the bail, they stay in jail
+                    // until they raise it
+                    if game.players[player_idx].balance < JAIL_BAIL {
+                        Self::run_up_debt(game, player_id, JAIL_BAIL, events);
+                        return Ok(());
+                    }
+                    Self::pay_forced_bail(game, player_id);
+                } else {
+                    let name = game.players[player_idx].name.clone();
+                    game.log(LogLine::new("jail_stayed").arg("name", &name));
+                    if let Some(t) = game.turn.as_mut() {
+                        t.phase = TurnPhase::TurnEnd;
+                        t.can_roll_again = false;
+                    }
+                    return Ok(());
+                }
             }
         }
 
-        // Broadcast updated state
+        Self::move_by_roll(game, player_id, events)
+    }
+
+    /// Move the current player by the dice of their turn and play out the
+    /// tile they land on
+    fn move_by_roll(
+        game: &mut GameState,
+        player_id: Uuid,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        let dice = game
+            .turn
+            .as_ref()
+            .and_then(|t| t.dice.clone())
+            .ok_or_else(|| AppError::GameError("No dice rolled".into()))?;
+        let is_doubles = is_doubles(&dice);
+        let dice_sum: u8 = dice.iter().sum();
+
+        let player_idx = game
+            .players
+            .iter()
+            .position(|p| p.id == player_id)
+            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+
+        // Move player
+        let old_pos = game.players[player_idx].position;
+        let new_pos = (old_pos + dice_sum) % 40;
+        // Enough dice can go all the way round and land past the start
+        let passed_go = old_pos + dice_sum >= 40;
+        Self::move_player(game, player_id, new_pos, passed_go, events)?;
+
+        // Update turn state
+        if is_doubles && !game.players[player_idx].in_jail && !game.config.no_roll_again_on_doubles
         {
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(room_id, ServerEvent::GameState(game));
+            if let Some(t) = game.turn.as_mut() {
+                t.can_roll_again = true;
+            }
         }
 
+        // Broadcast updated state
+        events.push(ServerEvent::GameState(Box::new(game.clone())));
+
         Ok(())
     }
 
+    /// Put a player on `to`, paying the GO salary if they passed it, and play
+    /// out the tile
+    fn move_player(
+        game: &mut GameState,
+        player_id: Uuid,
+        to: u8,
+        passed_go: bool,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        let salary = game.inflated(GO_SALARY);
+        let player = game
+            .get_player_mut(player_id)
+            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+        let from = player.position;
+        player.position = to;
+
+        if passed_go {
+            player.balance += salary;
+            let name = player.name.clone();
+            game.log(
+                LogLine::new("passed_go")
+                    .arg("name", &name)
+                    .arg("amount", salary),
+            );
+        }
+
+        events.push(ServerEvent::PlayerMoved {
+            player_id,
+            from,
+            to,
+            passed_go,
+        });
+
+        Self::handle_tile_landing(game, player_id, to, events)
+    }
+
     /// Handle what happens when landing on a tile
     fn handle_tile_landing(
         game: &mut GameState,
@@ -500,7 +1597,9 @@ impl GameEngine {
         tile_idx: u8,
         events: &mut Vec<ServerEvent>,
     ) -> AppResult<()> {
-        let tile = get_tile(tile_idx).ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
+        let tile = game
+            .tile(tile_idx)
+            .ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
 
         match tile.tile_type {
             TileType::Go => {
@@ -539,12 +1638,18 @@ impl GameEngine {
 
                             if !owner_in_jail || collect_in_jail {
                                 let rent = Self::calculate_rent(game, tile_idx);
+                                if let Some(p) = game.get_player_mut(player_id) {
+                                    p.tally.rent_paid += rent as i64;
+                                }
+                                if let Some(p) = game.get_player_mut(owner_id) {
+                                    p.tally.rent_collected += rent as i64;
+                                }
                                 Self::transfer_money(
                                     game,
                                     player_id,
                                     owner_id,
                                     rent as i32,
-                                    &format!("rent on {}", tile.name),
+                                    LogLine::new("paid_rent").arg("tile", &tile.name),
                                     events,
                                 );
                             }
@@ -557,46 +1662,29 @@ impl GameEngine {
                 }
             }
             TileType::Tax => {
-                let player_idx = game.players.iter().position(|p| p.id == player_id);
-                if let Some(idx) = player_idx {
-                    let tax = tile.rent_base as i32;
-                    game.players[idx].balance -= tax;
-
+                if let Some(name) = game.get_player(player_id).map(|p| p.name.clone()) {
+                    let tax = if game.config.inflation_taxes {
+                        game.inflated(tile.rent_base as i32)
+                    } else {
+                        tile.rent_base as i32
+                    };
                     if game.config.free_parking_jackpot {
                         game.pot_money += tax;
                     }
 
-                    let name = game.players[idx].name.clone();
-                    game.log(format!("{} paid ${} tax", name, tax));
-
-                    if BankruptcyHandler::is_bankrupt(game, player_id) {
-                        BankruptcyHandler::handle_bankruptcy(game, player_id, None);
-                        events.push(ServerEvent::Bankruptcy {
-                            player_id,
-                            creditor: None,
-                        });
-                    }
+                    let line = LogLine::new("paid_tax")
+                        .arg("name", &name)
+                        .arg("amount", tax);
+                    Self::charge_bank(game, player_id, tax, line, events);
                 }
 
                 if let Some(t) = game.turn.as_mut() {
                     t.phase = TurnPhase::TurnEnd;
                 }
             }
-            TileType::Chance => {
-                if let Some(p) = game.get_player(player_id) {
-                    game.log(format!("{} drew a Surprise card", p.name));
-                }
-                if let Some(t) = game.turn.as_mut() {
-                    t.phase = TurnPhase::TurnEnd;
-                }
-            }
+            TileType::Chance => Self::draw_card(game, player_id, Deck::Chance, events)?,
             TileType::CommunityChest => {
-                if let Some(p) = game.get_player(player_id) {
-                    game.log(format!("{} drew a Treasure card", p.name));
-                }
-                if let Some(t) = game.turn.as_mut() {
-                    t.phase = TurnPhase::TurnEnd;
-                }
+                Self::draw_card(game, player_id, Deck::CommunityChest, events)?
             }
             TileType::FreeParking => {
                 if game.config.free_parking_jackpot && game.pot_money > 0 {
@@ -604,7 +1692,11 @@ impl GameEngine {
                     if let Some(idx) = game.players.iter().position(|p| p.id == player_id) {
                         game.players[idx].balance += pot;
                         let name = game.players[idx].name.clone();
-                        game.log(format!("{} collected ${} from Free Parking!", name, pot));
+                        game.log(
+                            LogLine::new("free_parking")
+                                .arg("name", &name)
+                                .arg("amount", pot),
+                        );
                     }
                     game.pot_money = 0;
                 }
@@ -618,29 +1710,339 @@ impl GameEngine {
                 }
             }
             TileType::GoToJail => {
-                Self::send_to_jail(game, player_id);
+                Self::send_to_jail(game, player_id, events);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draw the top card of a deck and play it
+    ///
+    /// Boards without cards for the deck only note the draw.
+    fn draw_card(
+        game: &mut GameState,
+        player_id: Uuid,
+        deck: Deck,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        if let Some(t) = game.turn.as_mut() {
+            t.phase = TurnPhase::TurnEnd;
+        }
+        let Some(name) = game.get_player(player_id).map(|p| p.name.clone()) else {
+            return Ok(());
+        };
+
+        let board = game.board();
+        let Some(card) = game.decks.draw(board, deck) else {
+            let key = match deck {
+                Deck::Chance => "drew_surprise",
+                Deck::CommunityChest => "drew_treasure",
+            };
+            game.log(LogLine::new(key).arg("name", &name));
+            return Ok(());
+        };
+
+        let key = match deck {
+            Deck::Chance => "drew_surprise_card",
+            Deck::CommunityChest => "drew_treasure_card",
+        };
+        game.log(LogLine::new(key).arg("name", &name).arg("text", &card.text));
+        events.push(ServerEvent::CardDrawn {
+            player_id,
+            card_type: match deck {
+                Deck::Chance => "chance",
+                Deck::CommunityChest => "community_chest",
+            }
+            .into(),
+            description: card.text.clone(),
+        });
+
+        let others: Vec<Uuid> = game
+            .players
+            .iter()
+            .filter(|p| p.id != player_id && !p.is_bankrupt)
+            .map(|p| p.id)
+            .collect();
+        let position = game.get_player(player_id).map(|p| p.position).unwrap_or(0);
+
+        match card.effect {
+            CardEffect::Collect { amount } => {
+                if let Some(p) = game.get_player_mut(player_id) {
+                    p.balance += amount;
+                }
+                game.log(
+                    LogLine::new("card_collected")
+                        .arg("name", &name)
+                        .arg("amount", amount),
+                );
+            }
+            CardEffect::Pay { amount } => {
+                if game.config.free_parking_jackpot {
+                    game.pot_money += amount;
+                }
+                let line = LogLine::new("card_paid")
+                    .arg("name", &name)
+                    .arg("amount", amount);
+                Self::charge_bank(game, player_id, amount, line, events);
+            }
+            CardEffect::CollectFromEach { amount } => {
+                for other in others {
+                    let line = LogLine::new("card_payment");
+                    Self::transfer_money(game, other, player_id, amount, line, events);
+                }
+            }
+            CardEffect::PayEach { amount } => {
+                for other in others {
+                    // Whatever is left went to the first player they could not pay
+                    if game.get_player(player_id).is_some_and(|p| p.is_bankrupt) {
+                        break;
+                    }
+                    let line = LogLine::new("card_payment");
+                    Self::transfer_money(game, player_id, other, amount, line, events);
+                }
+            }
+            CardEffect::AdvanceTo { tile } => {
+                Self::move_player(game, player_id, tile, tile < position, events)?;
+            }
+            CardEffect::MoveBack { spaces } => {
+                let to = (position + 40 - spaces) % 40;
+                Self::move_player(game, player_id, to, false, events)?;
+            }
+            CardEffect::GoToJail => Self::send_to_jail(game, player_id, events),
+            CardEffect::GetOutOfJail => {
+                if let Some(p) = game.get_player_mut(player_id) {
+                    p.get_out_cards = p.get_out_cards.saturating_add(1);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send a player to jail (internal helper)
+    fn send_to_jail(game: &mut GameState, player_id: Uuid, events: &mut Vec<ServerEvent>) {
+        if let Some(idx) = game.players.iter().position(|p| p.id == player_id) {
+            game.players[idx].position = 10;
+            game.players[idx].in_jail = true;
+            game.players[idx].jail_turns = 0;
+
+            let name = game.players[idx].name.clone();
+            game.log(LogLine::new("sent_to_jail").arg("name", &name));
+        }
+
+        if let Some(t) = game.turn.as_mut() {
+            t.phase = TurnPhase::TurnEnd;
+            t.can_roll_again = false;
+            t.doubles_count = 0;
+        }
+
+        events.push(ServerEvent::PlayerJailed { player_id });
+        Self::bot_chat(game, player_id, ChatTrigger::Jailed, events);
+    }
+
+    /// Let a bot comment on what just happened, if the room allows it
+    fn bot_chat(
+        game: &GameState,
+        player_id: Uuid,
+        trigger: ChatTrigger,
+        events: &mut Vec<ServerEvent>,
+    ) {
+        if !game.config.bot_chat {
+            return;
+        }
+
+        // Bots standing in for absent humans stay quiet
+        let Some(bot) = game.get_player(player_id).filter(|p| p.is_bot) else {
+            return;
+        };
+
+        events.push(ServerEvent::Chat {
+            from: bot.id,
+            from_name: bot.name.clone(),
+            message: trigger
+                .line(game.version as usize + bot.position as usize)
+                .into(),
+        });
+    }
+
+    /// Count a tile a player just acquired; returns whether it completed a
+    /// color set
+    fn acquired(game: &mut GameState, player_id: Uuid, tile_idx: u8) -> bool {
+        let completed = game
+            .tile(tile_idx)
+            .and_then(|tile| tile.group)
+            .is_some_and(|group| Self::player_has_full_set(game, player_id, group));
+        if completed {
+            if let Some(player) = game.get_player_mut(player_id) {
+                player.tally.sets_completed += 1;
             }
         }
-
-        Ok(())
+        completed
+    }
+
+    /// Take money from a player for the bank
+    ///
+    /// A player who cannot pay goes bankrupt to the bank. Returns whether
+    /// the player is still in the game.
+    fn charge_bank(
+        game: &mut GameState,
+        player_id: Uuid,
+        amount: i32,
+        line: LogLine,
+        events: &mut Vec<ServerEvent>,
+    ) -> bool {
+        let Some(player) = game.get_player_mut(player_id) else {
+            return false;
+        };
+        player.balance -= amount;
+        player.tally.paid(amount);
+        game.log(line);
+
+        if BankruptcyHandler::is_bankrupt(game, player_id) {
+            BankruptcyHandler::handle_bankruptcy(game, player_id, None);
+            events.push(ServerEvent::Bankruptcy {
+                player_id,
+                creditor: None,
+            });
+            return false;
+        }
+        true
+    }
+
+    /// Leave a player owing `amount` they cannot pay yet
+    ///
+    /// Their turn waits in the bankruptcy phase while they mortgage or trade
+    /// to raise it; they pay it off with `PayDebt` or give up with
+    /// `DeclareBankruptcy`.
+    fn run_up_debt(
+        game: &mut GameState,
+        player_id: Uuid,
+        amount: i32,
+        events: &mut Vec<ServerEvent>,
+    ) {
+        game.debt = Some(Debt {
+            player_id,
+            amount,
+            since: chrono::Utc::now().timestamp(),
+        });
+        if let Some(t) = game.turn.as_mut() {
+            t.phase = TurnPhase::Bankruptcy;
+            t.can_roll_again = false;
+        }
+
+        let name = game
+            .get_player(player_id)
+            .map(|p| p.name.clone())
+            .unwrap_or_default();
+        game.log(
+            LogLine::new("in_debt")
+                .arg("name", &name)
+                .arg("amount", amount),
+        );
+
+        events.push(ServerEvent::GameState(Box::new(game.clone())));
+    }
+
+    /// Pay off the current player's debt once they have raised it
+    ///
+    /// The debt is the bail forced after their last jail turn, so paying it
+    /// frees them and they move by the roll that ran it up.
+    fn pay_debt(
+        game: &mut GameState,
+        player_id: Uuid,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        let amount = game
+            .debt
+            .as_ref()
+            .filter(|d| d.player_id == player_id)
+            .map(|d| d.amount)
+            .ok_or_else(|| AppError::GameError("No debt to pay".into()))?;
+
+        let balance = game.get_player(player_id).map(|p| p.balance).unwrap_or(0);
+        if balance < amount {
+            return Err(AppError::GameError("Not enough money".into()));
+        }
+
+        game.debt = None;
+        Self::pay_forced_bail(game, player_id);
+        events.push(ServerEvent::PlayerFreed {
+            player_id,
+            method: "paid".into(),
+        });
+
+        // Their turn's clock starts over for the rest of the turn
+        if let Some(t) = game.turn.as_mut() {
+            t.phase = TurnPhase::Moving;
+            t.started_at = chrono::Utc::now().timestamp();
+        }
+        Self::move_by_roll(game, player_id, events)
     }
 
-    /// Send a player to jail (internal helper)
-    fn send_to_jail(game: &mut GameState, player_id: Uuid) {
-        if let Some(idx) = game.players.iter().position(|p| p.id == player_id) {
-            game.players[idx].position = 10;
-            game.players[idx].in_jail = true;
-            game.players[idx].jail_turns = 0;
-
-            let name = game.players[idx].name.clone();
-            game.log(format!("{} was sent to jail!", name));
+    /// Give up a debt that cannot be raised, going bankrupt to the bank
+    fn declare_bankruptcy(
+        game: &mut GameState,
+        player_id: Uuid,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        if game
+            .debt
+            .as_ref()
+            .filter(|d| d.player_id == player_id)
+            .is_none()
+        {
+            return Err(AppError::GameError("No debt to give up".into()));
         }
 
+        BankruptcyHandler::handle_bankruptcy(game, player_id, None);
+        events.push(ServerEvent::Bankruptcy {
+            player_id,
+            creditor: None,
+        });
         if let Some(t) = game.turn.as_mut() {
             t.phase = TurnPhase::TurnEnd;
-            t.can_roll_again = false;
-            t.doubles_count = 0;
         }
+        events.push(ServerEvent::GameState(Box::new(game.clone())));
+
+        Ok(())
+    }
+
+    /// Bankrupt a player whose debt deadline passed and hand the turn on
+    fn default_on_debt(game: &mut GameState, events: &mut Vec<ServerEvent>) -> AppResult<()> {
+        let Some(debt) = game.debt.clone() else {
+            return Ok(());
+        };
+        let name = game
+            .get_player(debt.player_id)
+            .map(|p| p.name.clone())
+            .unwrap_or_default();
+        game.log(
+            LogLine::new("debt_expired")
+                .arg("name", &name)
+                .arg("amount", debt.amount),
+        );
+
+        Self::declare_bankruptcy(game, debt.player_id, events)?;
+        Self::release_departed(game, debt.player_id, events)
+    }
+
+    /// Take the bail forced after the last jail turn and free the player
+    fn pay_forced_bail(game: &mut GameState, player_id: Uuid) {
+        let Some(player) = game.get_player_mut(player_id) else {
+            return;
+        };
+        player.balance -= JAIL_BAIL;
+        player.tally.paid(JAIL_BAIL);
+        player.in_jail = false;
+        player.jail_turns = 0;
+
+        let name = player.name.clone();
+        game.log(
+            LogLine::new("jail_forced_bail")
+                .arg("name", &name)
+                .arg("amount", JAIL_BAIL),
+        );
     }
 
     /// Transfer money between players
@@ -649,7 +2051,7 @@ impl GameEngine {
         from: Uuid,
         to: Uuid,
         amount: i32,
-        reason: &str,
+        line: LogLine,
         events: &mut Vec<ServerEvent>,
     ) {
         let from_idx = game.players.iter().position(|p| p.id == from);
@@ -660,12 +2062,14 @@ impl GameEngine {
             let to_name = game.players[ti].name.clone();
 
             game.players[fi].balance -= amount;
+            game.players[fi].tally.paid(amount);
             game.players[ti].balance += amount;
 
-            game.log(format!(
-                "{} paid ${} to {} for {}",
-                from_name, amount, to_name, reason
-            ));
+            game.log(
+                line.arg("from", from_name)
+                    .arg("to", to_name)
+                    .arg("amount", amount),
+            );
 
             if BankruptcyHandler::is_bankrupt(game, from) {
                 BankruptcyHandler::handle_bankruptcy(game, from, Some(to));
@@ -678,8 +2082,8 @@ impl GameEngine {
     }
 
     /// Calculate rent for a property
-    fn calculate_rent(game: &GameState, tile_idx: u8) -> u32 {
-        let tile = match get_tile(tile_idx) {
+    pub(crate) fn calculate_rent(game: &GameState, tile_idx: u8) -> u32 {
+        let tile = match game.tile(tile_idx) {
             Some(t) => t,
             None => return 0,
         };
@@ -724,7 +2128,8 @@ impl GameEngine {
                     .iter()
                     .filter(|(idx, state)| {
                         state.owner == Some(owner_id)
-                            && get_tile(**idx)
+                            && game
+                                .tile(**idx)
                                 .map(|t| t.tile_type == TileType::Railroad)
                                 .unwrap_or(false)
                     })
@@ -741,7 +2146,8 @@ impl GameEngine {
                     .iter()
                     .filter(|(idx, state)| {
                         state.owner == Some(owner_id)
-                            && get_tile(**idx)
+                            && game
+                                .tile(**idx)
                                 .map(|t| t.tile_type == TileType::Utility)
                                 .unwrap_or(false)
                     })
@@ -750,57 +2156,371 @@ impl GameEngine {
                 let multiplier = if util_count >= 2 { 10 } else { 4 };
                 let dice_sum = game.turn.as_ref().map(|t| t.dice_sum() as u32).unwrap_or(7);
 
-                dice_sum * multiplier
-            }
-            _ => 0,
+                // Scaled so utilities earn on average what they do with two
+                // six-sided dice, whose mean roll is 7
+                let mean_x2 = game.config.dice_count as u32 * (game.config.dice_sides as u32 + 1);
+                (dice_sum * multiplier * 14 + mean_x2 / 2) / mean_x2
+            }
+            _ => 0,
+        }
+    }
+
+    /// Check if player owns all properties in a color group
+    pub(crate) fn player_has_full_set(
+        game: &GameState,
+        player_id: Uuid,
+        group: ColorGroup,
+    ) -> bool {
+        let group_tiles: Vec<u8> = game
+            .board()
+            .tiles
+            .iter()
+            .filter(|t| t.group == Some(group))
+            .map(|t| t.index)
+            .collect();
+
+        group_tiles.iter().all(|idx| {
+            game.properties
+                .get(idx)
+                .map(|p| p.owner == Some(player_id))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Buy the property the current player is on
+    fn buy_property(game: &mut GameState, events: &mut Vec<ServerEvent>) -> AppResult<()> {
+        let (player_id, position) = {
+            let turn = game
+                .turn
+                .as_ref()
+                .ok_or_else(|| AppError::GameError("No active turn".into()))?;
+
+            if turn.phase != TurnPhase::BuyDecision {
+                return Err(AppError::GameError("Cannot buy now".into()));
+            }
+
+            (
+                turn.player_id,
+                game.get_player(turn.player_id)
+                    .map(|p| p.position)
+                    .unwrap_or(0),
+            )
+        };
+
+        let tile = game
+            .tile(position)
+            .ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
+
+        let player_idx = game
+            .players
+            .iter()
+            .position(|p| p.id == player_id)
+            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+
+        if game.players[player_idx].balance < tile.price as i32 {
+            return Err(AppError::GameError("Not enough money".into()));
+        }
+
+        // Deduct and assign
+        game.players[player_idx].balance -= tile.price as i32;
+        game.players[player_idx].tally.properties_bought += 1;
+        let player_name = game.players[player_idx].name.clone();
+
+        if let Some(prop) = game.properties.get_mut(&position) {
+            prop.owner = Some(player_id);
+        }
+
+        game.log(
+            LogLine::new("bought")
+                .arg("name", &player_name)
+                .arg("tile", &tile.name)
+                .arg("amount", tile.price),
+        );
+
+        if let Some(t) = game.turn.as_mut() {
+            t.phase = TurnPhase::TurnEnd;
+        }
+
+        events.push(ServerEvent::PropertyBought {
+            tile_idx: position,
+            player_id,
+            price: tile.price,
+        });
+
+        if Self::acquired(game, player_id, position) {
+            Self::bot_chat(game, player_id, ChatTrigger::CompletedSet, events);
+        }
+
+        Ok(())
+    }
+
+    /// Start an auction for the current property
+    fn start_auction(game: &mut GameState, events: &mut Vec<ServerEvent>) -> AppResult<()> {
+        let position = {
+            let turn = game
+                .turn
+                .as_ref()
+                .ok_or_else(|| AppError::GameError("No active turn".into()))?;
+
+            if turn.phase != TurnPhase::BuyDecision {
+                return Err(AppError::GameError("Cannot start auction now".into()));
+            }
+
+            game.get_player(turn.player_id)
+                .map(|p| p.position)
+                .unwrap_or(0)
+        };
+
+        if !game.config.auction_on_decline {
+            if let Some(t) = game.turn.as_mut() {
+                t.phase = TurnPhase::TurnEnd;
+            }
+            return Ok(());
+        }
+
+        Self::open_auction(game, AuctionState::new(position), events);
+        Ok(())
+    }
+
+    /// Put the next queued tile of a departed player up for auction, unless
+    /// an auction is already running
+    fn start_queued_auction(game: &mut GameState, events: &mut Vec<ServerEvent>) {
+        if game.auction.is_some() || game.phase != GamePhase::Playing {
+            return;
+        }
+        let Some(resume_phase) = game.turn.as_ref().map(|t| t.phase) else {
+            return;
+        };
+
+        // Someone may have landed on a queued tile and bought it meanwhile
+        while !game.auction_queue.is_empty() {
+            let tile_idx = game.auction_queue.remove(0);
+            if game
+                .properties
+                .get(&tile_idx)
+                .is_some_and(|p| p.owner.is_none())
+            {
+                let auction = AuctionState {
+                    resume_phase: Some(resume_phase),
+                    ..AuctionState::new(tile_idx)
+                };
+                Self::open_auction(game, auction, events);
+                return;
+            }
+        }
+    }
+
+    fn open_auction(game: &mut GameState, auction: AuctionState, events: &mut Vec<ServerEvent>) {
+        let tile_idx = auction.tile_idx;
+        game.auction = Some(auction);
+
+        if let Some(t) = game.turn.as_mut() {
+            t.phase = TurnPhase::Auction;
+        }
+
+        let tile_name = game
+            .tile(tile_idx)
+            .map(|t| t.name.clone())
+            .unwrap_or_default();
+        game.log(LogLine::new("auction_started").arg("tile", &tile_name));
+
+        events.push(ServerEvent::AuctionStart {
+            tile_idx,
+            starting_price: 0,
+        });
+    }
+
+    /// Place a bid in the current auction
+    fn place_bid(
+        game: &mut GameState,
+        player_id: Uuid,
+        amount: u32,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        if game.auction.is_none() {
+            return Err(AppError::GameError("No auction in progress".into()));
+        }
+
+        let player_balance = game.get_player(player_id).map(|p| p.balance).unwrap_or(0);
+
+        if player_balance < amount as i32 {
+            return Err(AppError::GameError("Not enough money".into()));
+        }
+
+        let current_bid = game.auction.as_ref().map(|a| a.current_bid).unwrap_or(0);
+
+        if amount <= current_bid {
+            return Err(AppError::GameError("Bid must be higher".into()));
+        }
+
+        if let Some(auction) = game.auction.as_mut() {
+            auction.current_bid = amount;
+            auction.highest_bidder = Some(player_id);
+            auction.last_bid_at = chrono::Utc::now().timestamp();
+        }
+
+        events.push(ServerEvent::BidPlaced { player_id, amount });
+
+        Ok(())
+    }
+
+    /// Pass on the current auction
+    fn pass_bid(
+        game: &mut GameState,
+        player_id: Uuid,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        let auction = game
+            .auction
+            .as_mut()
+            .ok_or_else(|| AppError::GameError("No auction in progress".into()))?;
+
+        if !auction.passed_players.contains(&player_id) {
+            auction.passed_players.push(player_id);
+        }
+
+        events.push(ServerEvent::BidPassed { player_id });
+
+        // Check if auction should end
+        let active_count = game.players.iter().filter(|p| !p.is_bankrupt).count();
+
+        let passed_count = game
+            .auction
+            .as_ref()
+            .map(|a| a.passed_players.len())
+            .unwrap_or(0);
+
+        if passed_count >= active_count - 1 || passed_count >= active_count {
+            Self::end_auction(game, events);
+        }
+
+        Ok(())
+    }
+
+    /// End the current auction
+    fn end_auction(game: &mut GameState, events: &mut Vec<ServerEvent>) {
+        let auction = match game.auction.take() {
+            Some(a) => a,
+            None => return,
+        };
+
+        let tile_idx = auction.tile_idx;
+        let tile_name = game
+            .tile(tile_idx)
+            .map(|t| t.name.clone())
+            .unwrap_or_default();
+
+        if let Some(winner_id) = auction.highest_bidder {
+            let amount = auction.current_bid;
+
+            if let Some(idx) = game.players.iter().position(|p| p.id == winner_id) {
+                game.players[idx].balance -= amount as i32;
+                game.players[idx].tally.properties_bought += 1;
+                let winner_name = game.players[idx].name.clone();
+
+                if let Some(prop) = game.properties.get_mut(&tile_idx) {
+                    prop.owner = Some(winner_id);
+                }
+
+                game.log(
+                    LogLine::new("auction_won")
+                        .arg("name", &winner_name)
+                        .arg("tile", &tile_name)
+                        .arg("amount", amount),
+                );
+            }
+
+            events.push(ServerEvent::AuctionEnd {
+                tile_idx,
+                winner: Some(winner_id),
+                amount,
+            });
+
+            let trigger = if Self::acquired(game, winner_id, tile_idx) {
+                ChatTrigger::CompletedSet
+            } else {
+                ChatTrigger::WonAuction
+            };
+            Self::bot_chat(game, winner_id, trigger, events);
+        } else {
+            game.log(LogLine::new("auction_no_bids").arg("tile", &tile_name));
+
+            events.push(ServerEvent::AuctionEnd {
+                tile_idx,
+                winner: None,
+                amount: 0,
+            });
+        }
+
+        if let Some(t) = game.turn.as_mut() {
+            t.phase = auction.resume_phase.unwrap_or(TurnPhase::TurnEnd);
+        }
+
+        // The player whose turn it is may have left during the auction
+        if game.current_player().is_some_and(|p| p.is_bankrupt) {
+            if let Some(t) = game.turn.as_mut() {
+                t.can_roll_again = false;
+            }
+            if let Err(e) = Self::end_turn(game, events) {
+                tracing::warn!("Failed to end turn after auction: {}", e);
+            }
+        }
+
+        Self::start_queued_auction(game, events);
+    }
+
+    /// Pay to get out of jail
+    fn pay_jail(game: &mut GameState, events: &mut Vec<ServerEvent>) -> AppResult<()> {
+        let player_id = game
+            .turn
+            .as_ref()
+            .map(|t| t.player_id)
+            .ok_or_else(|| AppError::GameError("No active turn".into()))?;
+
+        let player_idx = game
+            .players
+            .iter()
+            .position(|p| p.id == player_id)
+            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+
+        if !game.players[player_idx].in_jail {
+            return Err(AppError::GameError("Not in jail".into()));
         }
-    }
 
-    /// Check if player owns all properties in a color group
-    fn player_has_full_set(game: &GameState, player_id: Uuid, group: ColorGroup) -> bool {
-        let group_tiles: Vec<u8> = BOARD
-            .iter()
-            .filter(|t| t.group == Some(group))
-            .map(|t| t.index)
-            .collect();
+        if game.players[player_idx].balance < JAIL_BAIL {
+            return Err(AppError::GameError("Not enough money".into()));
+        }
 
-        group_tiles.iter().all(|idx| {
-            game.properties
-                .get(idx)
-                .map(|p| p.owner == Some(player_id))
-                .unwrap_or(false)
-        })
-    }
+        game.players[player_idx].balance -= JAIL_BAIL;
+        game.players[player_idx].in_jail = false;
+        game.players[player_idx].jail_turns = 0;
 
-    /// Buy the property the current player is on
-    async fn buy_property(
-        redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
-        room_id: &str,
-    ) -> AppResult<()> {
-        let mut game = Self::get_game(redis, room_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+        let name = game.players[player_idx].name.clone();
+        game.log(
+            LogLine::new("paid_bail")
+                .arg("name", &name)
+                .arg("amount", JAIL_BAIL),
+        );
 
-        let (player_id, position) = {
-            let turn = game
-                .turn
-                .as_ref()
-                .ok_or_else(|| AppError::GameError("No active turn".into()))?;
+        if let Some(t) = game.turn.as_mut() {
+            t.phase = TurnPhase::WaitingForRoll;
+        }
 
-            if turn.phase != TurnPhase::BuyDecision {
-                return Err(AppError::GameError("Cannot buy now".into()));
-            }
+        events.push(ServerEvent::PlayerFreed {
+            player_id,
+            method: "paid".into(),
+        });
 
-            (
-                turn.player_id,
-                game.get_player(turn.player_id)
-                    .map(|p| p.position)
-                    .unwrap_or(0),
-            )
-        };
+        Ok(())
+    }
 
-        let tile = get_tile(position).ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
+    /// Use a get out of jail free card
+    fn use_jail_card(game: &mut GameState, events: &mut Vec<ServerEvent>) -> AppResult<()> {
+        let player_id = game
+            .turn
+            .as_ref()
+            .map(|t| t.player_id)
+            .ok_or_else(|| AppError::GameError("No active turn".into()))?;
 
         let player_idx = game
             .players
@@ -808,258 +2528,510 @@ impl GameEngine {
             .position(|p| p.id == player_id)
             .ok_or_else(|| AppError::GameError("Player not found".into()))?;
 
-        if game.players[player_idx].balance < tile.price as i32 {
-            return Err(AppError::GameError("Not enough money".into()));
+        if !game.players[player_idx].in_jail {
+            return Err(AppError::GameError("Not in jail".into()));
         }
 
-        // Deduct and assign
-        game.players[player_idx].balance -= tile.price as i32;
-        let player_name = game.players[player_idx].name.clone();
-
-        if let Some(prop) = game.properties.get_mut(&position) {
-            prop.owner = Some(player_id);
+        if game.players[player_idx].get_out_cards == 0 {
+            return Err(AppError::GameError("No get out of jail cards".into()));
         }
 
-        game.log(format!(
-            "{} bought {} for ${}",
-            player_name, tile.name, tile.price
-        ));
+        game.players[player_idx].get_out_cards -= 1;
+        game.players[player_idx].in_jail = false;
+        game.players[player_idx].jail_turns = 0;
+
+        let name = game.players[player_idx].name.clone();
+        game.log(LogLine::new("used_jail_card").arg("name", &name));
 
         if let Some(t) = game.turn.as_mut() {
-            t.phase = TurnPhase::TurnEnd;
+            t.phase = TurnPhase::WaitingForRoll;
         }
 
-        Self::save_game(redis, &game).await?;
-
-        let hub_guard = hub.read().await;
-        hub_guard.broadcast(
-            room_id,
-            ServerEvent::PropertyBought {
-                tile_idx: position,
-                player_id,
-                price: tile.price,
-            },
-        );
+        events.push(ServerEvent::PlayerFreed {
+            player_id,
+            method: "card".into(),
+        });
 
         Ok(())
     }
 
-    /// Start an auction for the current property
-    async fn start_auction(
-        redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
-        room_id: &str,
-    ) -> AppResult<()> {
-        let mut game = Self::get_game(redis, room_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
-
-        let position = {
-            let turn = game
+    /// End the current turn
+    fn end_turn(game: &mut GameState, events: &mut Vec<ServerEvent>) -> AppResult<()> {
+        let can_roll_again = !game.config.no_roll_again_on_doubles
+            && game
                 .turn
                 .as_ref()
-                .ok_or_else(|| AppError::GameError("No active turn".into()))?;
+                .map(|t| t.can_roll_again)
+                .unwrap_or(false);
 
-            if turn.phase != TurnPhase::BuyDecision {
-                return Err(AppError::GameError("Cannot start auction now".into()));
+        if can_roll_again {
+            if let Some(t) = game.turn.as_mut() {
+                t.phase = TurnPhase::WaitingForRoll;
+                t.can_roll_again = false;
             }
+            return Ok(());
+        }
 
-            game.get_player(turn.player_id)
-                .map(|p| p.position)
-                .unwrap_or(0)
-        };
+        // Move to next player
+        let next_player_id = game
+            .next_player_id()
+            .ok_or_else(|| AppError::GameError("No next player".into()))?;
 
-        if !game.config.auction_on_decline {
-            if let Some(t) = game.turn.as_mut() {
-                t.phase = TurnPhase::TurnEnd;
+        let seat = |id| game.turn_order.iter().position(|&p| p == id);
+        let current_seat = game.turn.as_ref().and_then(|t| seat(t.player_id));
+        if current_seat.is_some_and(|current| seat(next_player_id) <= Some(current)) {
+            Self::complete_round(game);
+            if Self::check_round_limit(game, events) {
+                return Ok(());
             }
-            Self::save_game(redis, &game).await?;
-            return Ok(());
         }
 
-        game.auction = Some(AuctionState::new(position));
+        let next_turn = TurnState::new(next_player_id);
+        let next_turn_id = next_turn.id;
+        game.turn = Some(next_turn);
+        if let Some(next) = game.get_player_mut(next_player_id).filter(|p| p.in_jail) {
+            next.tally.turns_in_jail += 1;
+        }
 
-        if let Some(t) = game.turn.as_mut() {
-            t.phase = TurnPhase::Auction;
+        if Self::check_game_over(game, events) {
+            return Ok(());
         }
 
-        let tile_name = get_tile(position)
-            .map(|t| t.name.clone())
+        let next_name = game
+            .get_player(next_player_id)
+            .map(|p| p.name.clone())
             .unwrap_or_default();
-        game.log(format!("Auction started for {}", tile_name));
 
-        Self::save_game(redis, &game).await?;
+        game.log(LogLine::new("turn").arg("name", &next_name));
 
-        let hub_guard = hub.read().await;
-        hub_guard.broadcast(
-            room_id,
-            ServerEvent::AuctionStart {
-                tile_idx: position,
-                starting_price: 0,
-            },
-        );
+        events.push(ServerEvent::TurnChanged {
+            player_id: next_player_id,
+            turn_id: next_turn_id,
+        });
 
         Ok(())
     }
 
-    /// Place a bid in the current auction
-    async fn place_bid(
-        redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
-        room_id: &str,
+    /// Count a finished round and apply inflation when one is due
+    fn complete_round(game: &mut GameState) {
+        game.round += 1;
+        game.timeline.push(summary::sample(game));
+
+        let every = game.config.inflation_rounds;
+        if every > 0 && game.round.is_multiple_of(every) {
+            let line = LogLine::new("inflation").arg("amount", game.inflated(GO_SALARY));
+            game.log(line);
+        }
+    }
+
+    /// End the game once its last round is played, won by the richest
+    /// player still standing
+    fn check_round_limit(game: &mut GameState, events: &mut Vec<ServerEvent>) -> bool {
+        let limit = game.config.round_limit;
+        if limit == 0 || game.round < limit {
+            return false;
+        }
+
+        game.log(LogLine::new("round_limit").arg("rounds", limit));
+        let winner = game.standings().first().copied();
+        Self::finish_game(game, winner, events);
+        true
+    }
+
+    /// End the game once a single player is left standing
+    fn check_game_over(game: &mut GameState, events: &mut Vec<ServerEvent>) -> bool {
+        if game.active_player_count() > 1 {
+            return false;
+        }
+
+        let winner = game.players.iter().find(|p| !p.is_bankrupt).map(|p| p.id);
+        Self::finish_game(game, winner, events);
+        true
+    }
+
+    fn finish_game(game: &mut GameState, winner: Option<Uuid>, events: &mut Vec<ServerEvent>) {
+        game.phase = GamePhase::GameOver;
+        game.auction_queue.clear();
+        TradeHandler::expire(game, events);
+        game.timeline.push(summary::sample(game));
+
+        if let Some(winner_id) = winner {
+            let winner_name = game
+                .get_player(winner_id)
+                .map(|p| p.name.clone())
+                .unwrap_or_default();
+
+            game.log(LogLine::new("won").arg("name", &winner_name));
+
+            events.push(ServerEvent::GameOver { winner: winner_id });
+        }
+
+        events.push(ServerEvent::GameSummary {
+            summary: GameSummary::of(game),
+        });
+    }
+
+    /// Remove a player who resigned or was kicked
+    ///
+    /// In the lobby the seat is simply freed. Mid-game the seat goes to a new
+    /// bot when `bot_replaces_leavers` is set; otherwise the player's assets
+    /// return to the bank as if they went bankrupt.
+    fn remove_player(
+        game: &mut GameState,
         player_id: Uuid,
-        amount: u32,
+        rng: &mut impl Rng,
+        events: &mut Vec<ServerEvent>,
     ) -> AppResult<()> {
-        let mut game = Self::get_game(redis, room_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
-
-        let player_balance = game.get_player(player_id).map(|p| p.balance).unwrap_or(0);
+        let player = game
+            .get_player(player_id)
+            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
 
-        if player_balance < amount as i32 {
-            return Err(AppError::GameError("Not enough money".into()));
+        if player.is_bankrupt {
+            return Err(AppError::GameError("Player already left the game".into()));
         }
 
-        let current_bid = game.auction.as_ref().map(|a| a.current_bid).unwrap_or(0);
+        let name = player.name.clone();
+        let was_host = player.is_host;
 
-        if amount <= current_bid {
-            return Err(AppError::GameError("Bid must be higher".into()));
+        // Offers to or from the departing player are void
+        if game
+            .active_trade
+            .as_ref()
+            .is_some_and(|t| t.involves(player_id))
+        {
+            TradeHandler::expire(game, events);
         }
 
-        if let Some(auction) = game.auction.as_mut() {
-            auction.current_bid = amount;
-            auction.highest_bidder = Some(player_id);
+        let replaced_by = match game.phase {
+            GamePhase::Lobby => {
+                game.players.retain(|p| p.id != player_id);
+                game.log(LogLine::new("left").arg("name", &name));
+                if game.players.iter().filter(|p| !p.is_bot).count() < 2 {
+                    game.auto_start_at = None;
+                }
+                None
+            }
+            GamePhase::Playing if game.config.bot_replaces_leavers => {
+                let bot_id = uuid::Builder::from_random_bytes(rng.gen()).into_uuid();
+                let bot_name = Self::unused(game, BOT_NAMES, |p| p.name.as_str());
+                let avatar = Self::unused(game, BOT_AVATARS, |p| p.avatar.as_deref().unwrap_or(""));
+
+                Self::reassign_seat(game, player_id, bot_id);
+                if let Some(bot) = game.get_player_mut(bot_id) {
+                    bot.name = bot_name.clone();
+                    bot.avatar = Some(avatar);
+                    bot.is_bot = true;
+                    bot.bot_controlled = false;
+                    bot.is_host = false;
+                    // The game is no longer theirs to be credited with
+                    bot.user_id = None;
+                    bot.locale = Locale::default();
+                }
+
+                game.log(
+                    LogLine::new("left_bot_took_seat")
+                        .arg("name", &name)
+                        .arg("bot", &bot_name),
+                );
+                Some(bot_id)
+            }
+            GamePhase::Playing => {
+                let mut forfeited: Vec<u8> = game
+                    .properties
+                    .iter()
+                    .filter(|(_, prop)| prop.owner == Some(player_id))
+                    .map(|(&idx, _)| idx)
+                    .collect();
+                forfeited.sort_unstable();
+
+                BankruptcyHandler::handle_bankruptcy(game, player_id, None);
+                if let Some(p) = game.get_player_mut(player_id) {
+                    p.is_host = false;
+                }
+                game.departed.push(player_id);
+                game.log(LogLine::new("left").arg("name", &name));
+
+                // Started by `release_departed` once the turn is settled
+                if game.config.auction_forfeited_properties && !forfeited.is_empty() {
+                    game.log(
+                        LogLine::new("forfeited_to_auction")
+                            .arg("name", &name)
+                            .arg("count", forfeited.len()),
+                    );
+                    game.auction_queue.extend(forfeited);
+                }
+                None
+            }
+            _ => return Err(AppError::BadRequest("Game is not in progress".into())),
+        };
+
+        if was_host {
+            if let Some(p) = game
+                .players
+                .iter_mut()
+                .find(|p| !p.is_bot && !p.is_bankrupt && p.id != player_id)
+            {
+                p.is_host = true;
+            }
         }
 
-        Self::save_game(redis, &game).await?;
+        events.push(ServerEvent::PlayerLeft {
+            player_id,
+            replaced_by,
+        });
 
-        let hub_guard = hub.read().await;
-        hub_guard.broadcast(room_id, ServerEvent::BidPlaced { player_id, amount });
+        match replaced_by {
+            // Ids changed throughout the state, so resync everyone
+            Some(_) => events.push(ServerEvent::GameState(Box::new(game.clone()))),
+            None if game.phase == GamePhase::Playing => {
+                Self::release_departed(game, player_id, events)?
+            }
+            None => {}
+        }
 
         Ok(())
     }
 
-    /// Pass on the current auction
-    async fn pass_bid(
-        redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
-        room_id: &str,
+    /// Move a seat to a new player id, keeping assets and turn position
+    fn reassign_seat(game: &mut GameState, from: Uuid, to: Uuid) {
+        if let Some(p) = game.get_player_mut(from) {
+            p.id = to;
+        }
+
+        for id in game.turn_order.iter_mut().filter(|id| **id == from) {
+            *id = to;
+        }
+
+        if let Some(turn) = game.turn.as_mut().filter(|t| t.player_id == from) {
+            turn.player_id = to;
+        }
+
+        for prop in game.properties.values_mut() {
+            if prop.owner == Some(from) {
+                prop.owner = Some(to);
+            }
+        }
+
+        if let Some(debt) = game.debt.as_mut().filter(|d| d.player_id == from) {
+            debt.player_id = to;
+        }
+
+        if let Some(auction) = game.auction.as_mut() {
+            if auction.highest_bidder == Some(from) {
+                auction.highest_bidder = Some(to);
+            }
+            for id in auction.passed_players.iter_mut().filter(|id| **id == from) {
+                *id = to;
+            }
+        }
+    }
+
+    /// Make sure a liquidated player no longer holds up the game
+    fn release_departed(
+        game: &mut GameState,
         player_id: Uuid,
+        events: &mut Vec<ServerEvent>,
     ) -> AppResult<()> {
-        let mut game = Self::get_game(redis, room_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+        if Self::check_game_over(game, events) {
+            game.auction = None;
+            return Ok(());
+        }
 
+        // Their bid is void and they no longer count towards ending the auction
         if let Some(auction) = game.auction.as_mut() {
-            if !auction.passed_players.contains(&player_id) {
-                auction.passed_players.push(player_id);
+            if auction.highest_bidder == Some(player_id) {
+                auction.highest_bidder = None;
+                auction.current_bid = 0;
             }
+            auction.passed_players.retain(|id| *id != player_id);
+
+            let active_count = game.active_player_count();
+            let passed_count = game
+                .auction
+                .as_ref()
+                .map(|a| a.passed_players.len())
+                .unwrap_or(0);
+            if passed_count + 1 >= active_count {
+                Self::end_auction(game, events);
+            }
+        }
+
+        let is_their_turn = game
+            .turn
+            .as_ref()
+            .map(|t| t.player_id == player_id)
+            .unwrap_or(false);
+
+        // An auction still running on their turn hands the turn on when it ends
+        if is_their_turn && game.auction.is_none() {
+            if let Some(t) = game.turn.as_mut() {
+                t.can_roll_again = false;
+            }
+            Self::end_turn(game, events)?;
+        }
+
+        Self::start_queued_auction(game, events);
+        Ok(())
+    }
+
+    /// Build a house on a property
+    fn build_house(
+        game: &mut GameState,
+        player_id: Uuid,
+        tile_idx: u8,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<()> {
+        let tile = game
+            .tile(tile_idx)
+            .ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
+
+        if tile.tile_type != TileType::Property {
+            return Err(AppError::GameError("Cannot build on this tile".into()));
+        }
+
+        let group = tile
+            .group
+            .ok_or_else(|| AppError::GameError("No color group".into()))?;
+
+        if !Self::player_has_full_set(game, player_id, group) {
+            return Err(AppError::GameError("Must own full color set".into()));
+        }
+
+        let player_idx = game
+            .players
+            .iter()
+            .position(|p| p.id == player_id)
+            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+
+        if game.players[player_idx].balance < tile.build_cost as i32 {
+            return Err(AppError::GameError("Not enough money".into()));
         }
 
-        {
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(room_id, ServerEvent::BidPassed { player_id });
+        let current_houses = game
+            .properties
+            .get(&tile_idx)
+            .map(|p| p.houses)
+            .unwrap_or(0);
+
+        if current_houses >= 5 {
+            return Err(AppError::GameError("Already at max buildings".into()));
         }
 
-        // Check if auction should end
-        let active_count = game.players.iter().filter(|p| !p.is_bankrupt).count();
+        // Build; a hotel stands for five houses whatever it took to get there
+        let houses = if current_houses >= game.config.houses_per_hotel {
+            5
+        } else {
+            current_houses + 1
+        };
+        game.players[player_idx].balance -= tile.build_cost as i32;
 
-        let passed_count = game
-            .auction
-            .as_ref()
-            .map(|a| a.passed_players.len())
-            .unwrap_or(0);
+        if let Some(prop) = game.properties.get_mut(&tile_idx) {
+            prop.houses = houses;
+        }
 
-        if passed_count >= active_count - 1 || passed_count >= active_count {
-            Self::end_auction(redis, hub, room_id, &mut game).await?;
+        let key = if houses == 5 {
+            game.players[player_idx].tally.hotels_built += 1;
+            "built_hotel"
         } else {
-            Self::save_game(redis, &game).await?;
-        }
+            "built_house"
+        };
+        let player_name = game.players[player_idx].name.clone();
+        game.log(
+            LogLine::new(key)
+                .arg("name", &player_name)
+                .arg("tile", &tile.name),
+        );
+
+        events.push(ServerEvent::BuildingBuilt {
+            tile_idx,
+            player_id,
+            houses,
+        });
 
         Ok(())
     }
 
-    /// End the current auction
-    async fn end_auction(
-        redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
-        room_id: &str,
+    /// Mortgage a property
+    fn mortgage_property(
         game: &mut GameState,
+        player_id: Uuid,
+        tile_idx: u8,
+        events: &mut Vec<ServerEvent>,
     ) -> AppResult<()> {
-        let auction = match game.auction.take() {
-            Some(a) => a,
-            None => return Ok(()),
-        };
+        let tile = game
+            .tile(tile_idx)
+            .ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
 
-        let tile_idx = auction.tile_idx;
-        let tile_name = get_tile(tile_idx)
-            .map(|t| t.name.clone())
-            .unwrap_or_default();
+        let prop_state = game
+            .properties
+            .get(&tile_idx)
+            .ok_or_else(|| AppError::GameError("Not a property".into()))?;
 
-        if let Some(winner_id) = auction.highest_bidder {
-            let amount = auction.current_bid;
+        if prop_state.owner != Some(player_id) {
+            return Err(AppError::GameError("You don't own this property".into()));
+        }
 
-            if let Some(idx) = game.players.iter().position(|p| p.id == winner_id) {
-                game.players[idx].balance -= amount as i32;
-                let winner_name = game.players[idx].name.clone();
+        if prop_state.is_mortgaged {
+            return Err(AppError::GameError("Already mortgaged".into()));
+        }
 
-                if let Some(prop) = game.properties.get_mut(&tile_idx) {
-                    prop.owner = Some(winner_id);
-                }
+        if prop_state.houses > 0 {
+            return Err(AppError::GameError("Must sell buildings first".into()));
+        }
 
-                game.log(format!(
-                    "{} won {} at auction for ${}",
-                    winner_name, tile_name, amount
-                ));
-            }
+        let player_idx = game
+            .players
+            .iter()
+            .position(|p| p.id == player_id)
+            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
 
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(
-                room_id,
-                ServerEvent::AuctionEnd {
-                    tile_idx,
-                    winner: Some(winner_id),
-                    amount,
-                },
-            );
-        } else {
-            game.log(format!("Auction for {} ended with no bids", tile_name));
+        game.players[player_idx].balance += tile.mortgage_value as i32;
+        let player_name = game.players[player_idx].name.clone();
 
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(
-                room_id,
-                ServerEvent::AuctionEnd {
-                    tile_idx,
-                    winner: None,
-                    amount: 0,
-                },
-            );
+        if let Some(prop) = game.properties.get_mut(&tile_idx) {
+            prop.is_mortgaged = true;
         }
 
-        if let Some(t) = game.turn.as_mut() {
-            t.phase = TurnPhase::TurnEnd;
-        }
+        game.log(
+            LogLine::new("mortgaged")
+                .arg("name", &player_name)
+                .arg("tile", &tile.name)
+                .arg("amount", tile.mortgage_value),
+        );
 
-        Self::save_game(redis, game).await?;
+        events.push(ServerEvent::PropertyMortgaged {
+            tile_idx,
+            player_id,
+        });
 
         Ok(())
     }
 
-    /// Pay to get out of jail
-    async fn pay_jail(
-        redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
-        room_id: &str,
+    /// Unmortgage a property
+    fn unmortgage_property(
+        game: &mut GameState,
+        player_id: Uuid,
+        tile_idx: u8,
+        events: &mut Vec<ServerEvent>,
     ) -> AppResult<()> {
-        let mut game = Self::get_game(redis, room_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+        let tile = game
+            .tile(tile_idx)
+            .ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
 
-        let player_id = game
-            .turn
-            .as_ref()
-            .map(|t| t.player_id)
-            .ok_or_else(|| AppError::GameError("No active turn".into()))?;
+        let prop_state = game
+            .properties
+            .get(&tile_idx)
+            .ok_or_else(|| AppError::GameError("Not a property".into()))?;
+
+        if prop_state.owner != Some(player_id) {
+            return Err(AppError::GameError("You don't own this property".into()));
+        }
+
+        if !prop_state.is_mortgaged {
+            return Err(AppError::GameError("Not mortgaged".into()));
+        }
+
+        let unmortgage_cost = (tile.mortgage_value as f32 * 1.1) as i32;
 
         let player_idx = game
             .players
@@ -1067,441 +3039,828 @@ impl GameEngine {
             .position(|p| p.id == player_id)
             .ok_or_else(|| AppError::GameError("Player not found".into()))?;
 
-        if !game.players[player_idx].in_jail {
-            return Err(AppError::GameError("Not in jail".into()));
-        }
-
-        if game.players[player_idx].balance < 50 {
+        if game.players[player_idx].balance < unmortgage_cost {
             return Err(AppError::GameError("Not enough money".into()));
         }
 
-        game.players[player_idx].balance -= 50;
-        game.players[player_idx].in_jail = false;
-        game.players[player_idx].jail_turns = 0;
-
-        let name = game.players[player_idx].name.clone();
-        game.log(format!("{} paid $50 to get out of jail", name));
+        game.players[player_idx].balance -= unmortgage_cost;
+        let player_name = game.players[player_idx].name.clone();
 
-        if let Some(t) = game.turn.as_mut() {
-            t.phase = TurnPhase::WaitingForRoll;
+        if let Some(prop) = game.properties.get_mut(&tile_idx) {
+            prop.is_mortgaged = false;
         }
 
-        Self::save_game(redis, &game).await?;
-
-        let hub_guard = hub.read().await;
-        hub_guard.broadcast(
-            room_id,
-            ServerEvent::PlayerFreed {
-                player_id,
-                method: "paid".into(),
-            },
+        game.log(
+            LogLine::new("unmortgaged")
+                .arg("name", &player_name)
+                .arg("tile", &tile.name)
+                .arg("amount", unmortgage_cost),
         );
 
+        events.push(ServerEvent::PropertyUnmortgaged {
+            tile_idx,
+            player_id,
+        });
+
         Ok(())
     }
 
-    /// End the current turn
-    async fn end_turn(
+    /// Schedule bot processing for a room in the background
+    ///
+    /// At most one bot task runs per room; a request made while one is running
+    /// makes that task take another pass before it exits.
+    pub fn spawn_bot_turn(redis: &ConnectionManager, hub: &Arc<Hub>, room_id: &str) {
+        {
+            let mut tasks = BOT_TASKS.lock().unwrap();
+            if let Some(rerun) = tasks.get_mut(room_id) {
+                *rerun = true;
+                return;
+            }
+            tasks.insert(room_id.to_string(), false);
+        }
+
+        let redis = redis.clone();
+        let hub = hub.clone();
+        let room_id = room_id.to_string();
+        let span = tracing::info_span!("bot_turn", room_id = %room_id);
+        let task = async move {
+            let mut retries = 0;
+            loop {
+                match Self::process_bot_turn(&redis, &hub, &room_id).await {
+                    // Otherwise the bots would wait for the next human action
+                    Err(e) if e.is_retryable() && retries < BOT_RETRIES => {
+                        tracing::debug!("Bot processing in room {} will retry: {}", room_id, e);
+                        tokio::time::sleep(BOT_RETRY_DELAY * 2u32.pow(retries)).await;
+                        retries += 1;
+                        continue;
+                    }
+                    Err(e) => tracing::warn!("Bot processing failed in room {}: {}", room_id, e),
+                    Ok(()) => {}
+                }
+                retries = 0;
+
+                let mut tasks = BOT_TASKS.lock().unwrap();
+                match tasks.get_mut(&room_id) {
+                    Some(rerun) if *rerun => *rerun = false,
+                    _ => {
+                        tasks.remove(&room_id);
+                        break;
+                    }
+                }
+            }
+        };
+        tokio::spawn(task.instrument(span));
+    }
+
+    /// Whether a bot task is currently running for a room
+    pub fn bot_task_running(room_id: &str) -> bool {
+        BOT_TASKS.lock().unwrap().contains_key(room_id)
+    }
+
+    /// Process bot turns (iterative to avoid async recursion)
+    ///
+    /// Keeps going through consecutive bot turns and returns once a human has
+    /// to act, so turn changes never need to call back into the scheduler.
+    async fn process_bot_turn(
         redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
+        hub: &Arc<Hub>,
         room_id: &str,
     ) -> AppResult<()> {
-        let mut game = Self::get_game(redis, room_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
-
-        let can_roll_again = game
-            .turn
-            .as_ref()
-            .map(|t| t.can_roll_again)
-            .unwrap_or(false);
+        // Use a loop instead of recursion to avoid Box::pin complexity
+        loop {
+            let game = match Self::get_game(redis, room_id).await? {
+                Some(g) => g,
+                None => return Err(AppError::NotFound("Room not found".into())),
+            };
 
-        if can_roll_again {
-            if let Some(t) = game.turn.as_mut() {
-                t.phase = TurnPhase::WaitingForRoll;
-                t.can_roll_again = false;
-            }
-            Self::save_game(redis, &game).await?;
+            // Peek with the cheap policy to pick a pause for the kind of
+            // decision coming up
+            let upcoming = match BotAI::policy_action(&game, Player::controlled_by_bot) {
+                Some((_, action)) => action,
+                None => return Ok(()), // Waiting on a human
+            };
+            let delay = game
+                .config
+                .bot_pace
+                .think_time(&upcoming, &mut rand::thread_rng())
+                .min(game.config.timers().bot_delay_max());
+
+            // Humans may act during the pause, so decide on fresh state
+            let game = if delay.is_zero() {
+                game
+            } else {
+                tokio::time::sleep(delay).await;
+                match Self::get_game(redis, room_id).await? {
+                    Some(g) => g,
+                    None => return Err(AppError::NotFound("Room not found".into())),
+                }
+            };
 
-            // Note: Bot processing deferred to avoid async recursion
-            return Ok(());
-        }
+            // Hard bots run rollouts, keep them off the async workers
+            let snapshot = game.clone();
+            let decision = tokio::task::spawn_blocking(move || BotAI::next_action(&snapshot))
+                .await
+                .map_err(|e| AppError::Internal(e.into()))?;
 
-        // Move to next player
-        let next_player_id = game
-            .next_player_id()
-            .ok_or_else(|| AppError::GameError("No next player".into()))?;
+            let (player_id, action) = match decision {
+                Some(a) => a,
+                None => return Ok(()), // Waiting on a human
+            };
 
-        game.turn = Some(TurnState::new(next_player_id));
+            let span = tracing::info_span!(
+                "bot_action",
+                player_id = %player_id,
+                event_type = %action.type_name().unwrap_or_default(),
+            );
 
-        // Check for game over
-        if game.active_player_count() <= 1 {
-            game.phase = GamePhase::GameOver;
-            let winner_id = game
-                .players
-                .iter()
-                .find(|p| !p.is_bankrupt)
-                .map(|p| p.id)
-                .unwrap();
+            if game.config.bot_debug {
+                let reason = BotAI::explain(&game, player_id, &action);
+                span.in_scope(|| {
+                    tracing::debug!("Bot {} in room {}: {}", player_id, room_id, reason)
+                });
 
-            let winner_name = game
-                .get_player(winner_id)
-                .map(|p| p.name.clone())
-                .unwrap_or_default();
+                hub.broadcast(
+                    room_id,
+                    ServerEvent::BotDebug {
+                        player_id,
+                        action: action.clone(),
+                        reason,
+                    },
+                );
+            }
 
-            game.log(format!("{} wins the game!", winner_name));
+            match Self::execute(redis, hub, room_id, game, player_id, action)
+                .instrument(span)
+                .await
+            {
+                // Someone acted while the bot was deciding; decide again
+                Err(AppError::Conflict(_)) => continue,
+                result => result?,
+            }
+        }
+    }
 
-            Self::save_game(redis, &game).await?;
+    /// Hand a disconnected human over to bot control until they return
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, player_id = %player_id))]
+    pub async fn take_over_player(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        player_id: Uuid,
+    ) -> AppResult<()> {
+        retry_on_conflict(|| Self::try_take_over(redis, hub, room_id, player_id)).await
+    }
 
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(room_id, ServerEvent::GameOver { winner: winner_id });
+    async fn try_take_over(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        player_id: Uuid,
+    ) -> AppResult<()> {
+        let mut game = Self::get_game(redis, room_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
 
+        if game.phase != GamePhase::Playing {
             return Ok(());
         }
 
-        let next_name = game
-            .get_player(next_player_id)
-            .map(|p| p.name.clone())
-            .unwrap_or_default();
-        let is_next_bot = game
-            .get_player(next_player_id)
-            .map(|p| p.is_bot)
-            .unwrap_or(false);
+        let player = game
+            .get_player_mut(player_id)
+            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+
+        if player.controlled_by_bot() || player.is_bankrupt {
+            return Ok(());
+        }
 
-        game.log(format!("{}'s turn", next_name));
+        player.bot_controlled = true;
+        let name = player.name.clone();
+        game.log(LogLine::new("away").arg("name", &name));
 
-        Self::save_game(redis, &game).await?;
+        Self::save_game(redis, &mut game).await?;
 
         {
-            let hub_guard = hub.read().await;
-            hub_guard.broadcast(
-                room_id,
-                ServerEvent::TurnChanged {
-                    player_id: next_player_id,
-                },
-            );
+            hub.broadcast(room_id, ServerEvent::BotTakeover { player_id });
         }
 
-        // Note: Bot processing will be triggered by frontend polling or separate mechanism
-        // to avoid async recursion between end_turn and process_bot_turn
-        let _ = is_next_bot; // Acknowledge the variable
+        Self::spawn_bot_turn(redis, hub, room_id);
 
         Ok(())
     }
 
-    /// Build a house on a property
-    async fn build_house(
+    /// Give a returning player back control from the bot
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, player_id = %player_id))]
+    pub async fn return_player(
         redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
+        hub: &Arc<Hub>,
         room_id: &str,
         player_id: Uuid,
-        tile_idx: u8,
     ) -> AppResult<()> {
-        let mut game = Self::get_game(redis, room_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+        retry_on_conflict(|| Self::try_return(redis, hub, room_id, player_id)).await
+    }
+
+    async fn try_return(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        player_id: Uuid,
+    ) -> AppResult<()> {
+        let mut game = match Self::get_game(redis, room_id).await? {
+            Some(g) => g,
+            None => return Ok(()),
+        };
 
-        let tile = get_tile(tile_idx).ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
+        let player = match game.get_player_mut(player_id) {
+            Some(p) if p.bot_controlled => p,
+            _ => return Ok(()),
+        };
 
-        if tile.tile_type != TileType::Property {
-            return Err(AppError::GameError("Cannot build on this tile".into()));
+        player.bot_controlled = false;
+        player.missed_turn_timers = 0;
+        let name = player.name.clone();
+        game.log(LogLine::new("back").arg("name", &name));
+        // A turn the bot left unfinished starts over for its owner
+        if let Some(turn) = game.turn.as_mut().filter(|t| t.player_id == player_id) {
+            turn.started_at = chrono::Utc::now().timestamp();
         }
 
-        let group = tile
-            .group
-            .ok_or_else(|| AppError::GameError("No color group".into()))?;
+        Self::save_game(redis, &mut game).await?;
 
-        if !Self::player_has_full_set(&game, player_id, group) {
-            return Err(AppError::GameError("Must own full color set".into()));
-        }
+        hub.broadcast(room_id, ServerEvent::PlayerReturned { player_id });
+        Self::remind_turns(hub, room_id, &game, &HashMap::new());
+        Self::arm_turn_timer(redis, hub, room_id, &game);
 
-        let player_idx = game
-            .players
-            .iter()
-            .position(|p| p.id == player_id)
-            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+        Ok(())
+    }
 
-        if game.players[player_idx].balance < tile.build_cost as i32 {
-            return Err(AppError::GameError("Not enough money".into()));
+    /// Turn that runs against the clock: the current one, while a human in
+    /// control of their seat holds it and the room has a turn limit
+    ///
+    /// A debt with a deadline of its own stops the turn's clock until it is
+    /// settled.
+    fn timed_turn(game: &GameState) -> Option<Uuid> {
+        let timers = game.config.timers();
+        if game.phase != GamePhase::Playing
+            || timers.turn().is_none()
+            || (game.debt.is_some() && timers.debt().is_some())
+        {
+            return None;
         }
+        let turn = game.turn.as_ref()?;
+        game.get_player(turn.player_id)
+            .filter(|p| !p.controlled_by_bot() && !p.is_bankrupt)
+            .map(|_| turn.id)
+    }
 
-        let current_houses = game
-            .properties
-            .get(&tile_idx)
-            .map(|p| p.houses)
-            .unwrap_or(0);
+    /// Time the current turn, if it is a timed one
+    ///
+    /// The timer only ever acts on the turn it was armed for, and arming
+    /// another one in the room retires it.
+    fn arm_turn_timer(redis: &ConnectionManager, hub: &Arc<Hub>, room_id: &str, game: &GameState) {
+        let (Some(turn_id), Some(turn)) = (Self::timed_turn(game), &game.turn) else {
+            return;
+        };
+        let limit = game.config.timers().turn().unwrap_or_default();
+        let mut wait = limit.saturating_sub(Self::turn_elapsed(turn));
+
+        let generation = TURN_TIMER_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+        TURN_TIMERS
+            .lock()
+            .unwrap()
+            .insert(room_id.to_string(), generation);
+
+        let redis = redis.clone();
+        let hub = hub.clone();
+        let room_id = room_id.to_string();
+        let span = tracing::info_span!("turn_timer", room_id = %room_id, turn_id = %turn_id);
+        let task = async move {
+            loop {
+                tokio::time::sleep(wait).await;
+                if TURN_TIMERS.lock().unwrap().get(&room_id) != Some(&generation) {
+                    return;
+                }
+                let result =
+                    retry_on_conflict(|| Self::check_turn_timer(&redis, &hub, &room_id, turn_id))
+                        .await;
+                match result {
+                    Ok(Some(next)) => wait = next,
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::warn!("Turn timer failed in room {}: {}", room_id, e);
+                        break;
+                    }
+                }
+            }
 
-        if current_houses >= 5 {
-            return Err(AppError::GameError("Already at max buildings".into()));
-        }
+            let mut timers = TURN_TIMERS.lock().unwrap();
+            if timers.get(&room_id) == Some(&generation) {
+                timers.remove(&room_id);
+            }
+        };
+        tokio::spawn(task.instrument(span));
+    }
 
-        // Build
-        game.players[player_idx].balance -= tile.build_cost as i32;
+    /// Time since a turn began, or its timer last started over
+    fn turn_elapsed(turn: &TurnState) -> Duration {
+        let secs = chrono::Utc::now().timestamp() - turn.started_at;
+        Duration::from_secs(secs.max(0) as u64)
+    }
 
-        if let Some(prop) = game.properties.get_mut(&tile_idx) {
-            prop.houses += 1;
+    /// Strike the player whose turn timer ran out; returns how long until
+    /// the timer should be checked again, if it still runs
+    async fn check_turn_timer(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        turn_id: Uuid,
+    ) -> AppResult<Option<Duration>> {
+        let Some(mut game) = Self::get_game(redis, room_id).await? else {
+            return Ok(None);
+        };
+        let Some(turn) = game
+            .turn
+            .as_ref()
+            .filter(|_| Self::timed_turn(&game) == Some(turn_id))
+        else {
+            return Ok(None);
+        };
+        let limit = game.config.timers().turn().unwrap_or_default();
+        let elapsed = Self::turn_elapsed(turn);
+        if elapsed < limit {
+            return Ok(Some(limit - elapsed));
+        }
+        // Others hold the game up (an auction, say), not the turn's owner
+        if pending_actions(&game, turn.player_id).is_empty() {
+            return Ok(Some(limit));
         }
 
-        let houses = current_houses + 1;
-        let building_type = if houses == 5 { "hotel" } else { "house" };
-        let player_name = game.players[player_idx].name.clone();
-        game.log(format!(
-            "{} built a {} on {}",
-            player_name, building_type, tile.name
-        ));
-
-        Self::save_game(redis, &game).await?;
+        let mut events = Vec::new();
+        let taken_over = Self::time_out_turn(&mut game, &mut events);
+        Self::save_game(redis, &mut game).await?;
 
-        let hub_guard = hub.read().await;
-        hub_guard.broadcast(
-            room_id,
-            ServerEvent::BuildingBuilt {
-                tile_idx,
-                player_id,
-                houses,
-            },
-        );
+        for event in events {
+            hub.broadcast(room_id, event);
+        }
+        if taken_over {
+            Self::spawn_bot_turn(redis, hub, room_id);
+            return Ok(None);
+        }
+        Self::remind_turns(hub, room_id, &game, &HashMap::new());
+        Ok(Some(limit))
+    }
 
-        Ok(())
+    /// Bankrupt the player in `debt` unless they settle it before the room's
+    /// debt timeout, if it has one
+    ///
+    /// The task only acts on the debt it was scheduled for; once that is
+    /// paid or given up it finds nothing to do.
+    fn schedule_debt_deadline(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        game: &GameState,
+        debt: &Debt,
+    ) {
+        let Some(limit) = game.config.timers().debt() else {
+            return;
+        };
+        let elapsed = (chrono::Utc::now().timestamp() - debt.since).max(0) as u64;
+        let delay = limit.saturating_sub(Duration::from_secs(elapsed));
+
+        let redis = redis.clone();
+        let hub = hub.clone();
+        let room_id = room_id.to_string();
+        let (player_id, since) = (debt.player_id, debt.since);
+        let span = tracing::info_span!("debt_timer", room_id = %room_id, player_id = %player_id);
+        let task = async move {
+            tokio::time::sleep(delay).await;
+            let result =
+                retry_on_conflict(|| Self::expire_debt(&redis, &hub, &room_id, player_id, since))
+                    .await;
+            if let Err(e) = result {
+                tracing::warn!("Debt timer failed in room {}: {}", room_id, e);
+            }
+        };
+        tokio::spawn(task.instrument(span));
     }
 
-    /// Mortgage a property
-    async fn mortgage_property(
+    async fn expire_debt(
         redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
+        hub: &Arc<Hub>,
         room_id: &str,
         player_id: Uuid,
-        tile_idx: u8,
+        since: i64,
     ) -> AppResult<()> {
-        let mut game = Self::get_game(redis, room_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+        let Some(mut game) = Self::get_game(redis, room_id).await? else {
+            return Ok(());
+        };
+        let still_owed = game
+            .debt
+            .as_ref()
+            .is_some_and(|d| d.player_id == player_id && d.since == since);
+        if game.phase != GamePhase::Playing || !still_owed {
+            return Ok(());
+        }
 
-        let tile = get_tile(tile_idx).ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
+        let waiting = Self::waiting_on(&game);
+        let timed = Self::timed_turn(&game);
+        let mut events = Vec::new();
+        Self::default_on_debt(&mut game, &mut events)?;
 
-        let prop_state = game
-            .properties
-            .get(&tile_idx)
-            .ok_or_else(|| AppError::GameError("Not a property".into()))?;
+        let journal = Self::journal_entries(&mut game, None, &events);
+        Self::save_game(redis, &mut game).await?;
+        db::journal::record(journal);
 
-        if prop_state.owner != Some(player_id) {
-            return Err(AppError::GameError("You don't own this property".into()));
+        let finished = events
+            .iter()
+            .any(|e| matches!(e, ServerEvent::GameOver { .. }));
+        for event in events {
+            hub.broadcast(room_id, event);
         }
-
-        if prop_state.is_mortgaged {
-            return Err(AppError::GameError("Already mortgaged".into()));
+        Self::remind_turns(hub, room_id, &game, &waiting);
+        if Self::timed_turn(&game) != timed {
+            Self::arm_turn_timer(redis, hub, room_id, &game);
         }
 
-        if prop_state.houses > 0 {
-            return Err(AppError::GameError("Must sell buildings first".into()));
+        if finished {
+            db::archive::archive_game(redis.clone(), hub.clone(), game);
+        } else {
+            Self::spawn_bot_turn(redis, hub, room_id);
         }
 
-        let player_idx = game
-            .players
-            .iter()
-            .position(|p| p.id == player_id)
-            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+        Ok(())
+    }
 
-        game.players[player_idx].balance += tile.mortgage_value as i32;
-        let player_name = game.players[player_idx].name.clone();
+    /// Strike the holder of a turn whose timer ran out, handing their seat
+    /// to a bot once they let it run out `AFK_TIMEOUTS` times in a row;
+    /// returns whether the bot took over
+    ///
+    /// Short of that, the timer starts over for them.
+    fn time_out_turn(game: &mut GameState, events: &mut Vec<ServerEvent>) -> bool {
+        let Some(turn) = game.turn.as_mut() else {
+            return false;
+        };
+        let player_id = turn.player_id;
+        turn.started_at = chrono::Utc::now().timestamp();
+        let Some(player) = game.get_player_mut(player_id) else {
+            return false;
+        };
 
-        if let Some(prop) = game.properties.get_mut(&tile_idx) {
-            prop.is_mortgaged = true;
+        player.missed_turn_timers = player.missed_turn_timers.saturating_add(1);
+        let name = player.name.clone();
+        if player.missed_turn_timers < AFK_TIMEOUTS {
+            game.log(LogLine::new("turn_timed_out").arg("name", &name));
+            return false;
         }
 
-        game.log(format!(
-            "{} mortgaged {} for ${}",
-            player_name, tile.name, tile.mortgage_value
-        ));
-
-        Self::save_game(redis, &game).await?;
+        player.bot_controlled = true;
+        game.log(LogLine::new("away").arg("name", &name));
+        events.push(ServerEvent::BotTakeover { player_id });
+        true
+    }
 
-        let hub_guard = hub.read().await;
-        hub_guard.broadcast(
-            room_id,
-            ServerEvent::PropertyMortgaged {
-                tile_idx,
-                player_id,
-            },
-        );
+    /// Note that a player acted, clearing their run of timed out turns and
+    /// taking their seat back from a bot standing in for them; returns
+    /// whether they took it back
+    fn mark_active(game: &mut GameState, player_id: Uuid) -> bool {
+        let Some(player) = game.get_player_mut(player_id) else {
+            return false;
+        };
+        player.missed_turn_timers = 0;
+        if !player.bot_controlled {
+            return false;
+        }
 
-        Ok(())
+        player.bot_controlled = false;
+        let name = player.name.clone();
+        game.log(LogLine::new("back").arg("name", &name));
+        if let Some(turn) = game.turn.as_mut().filter(|t| t.player_id == player_id) {
+            turn.started_at = chrono::Utc::now().timestamp();
+        }
+        true
     }
 
-    /// Unmortgage a property
-    async fn unmortgage_property(
+    /// Note the IP and fingerprint hashes a player connected to a ranked
+    /// room with, flagging the room when another seat left the same
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, player_id = %player_id))]
+    pub async fn record_client(
         redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
         room_id: &str,
         player_id: Uuid,
-        tile_idx: u8,
+        marks: &[(MarkKind, String)],
     ) -> AppResult<()> {
-        let mut game = Self::get_game(redis, room_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
-
-        let tile = get_tile(tile_idx).ok_or_else(|| AppError::GameError("Invalid tile".into()))?;
-
-        let prop_state = game
-            .properties
-            .get(&tile_idx)
-            .ok_or_else(|| AppError::GameError("Not a property".into()))?;
+        retry_on_conflict(|| Self::try_record_client(redis, room_id, player_id, marks)).await
+    }
 
-        if prop_state.owner != Some(player_id) {
-            return Err(AppError::GameError("You don't own this property".into()));
+    async fn try_record_client(
+        redis: &ConnectionManager,
+        room_id: &str,
+        player_id: Uuid,
+        marks: &[(MarkKind, String)],
+    ) -> AppResult<()> {
+        let mut game = match Self::get_game(redis, room_id).await? {
+            Some(g) if g.config.ranked && g.phase != GamePhase::GameOver => g,
+            _ => return Ok(()),
+        };
+        match game.get_player(player_id) {
+            Some(player) if !player.is_bot => {}
+            _ => return Ok(()),
         }
 
-        if !prop_state.is_mortgaged {
-            return Err(AppError::GameError("Not mortgaged".into()));
+        let flags = game.integrity.flags.clone();
+        let mut changed = false;
+        for (kind, hash) in marks {
+            changed |= game.integrity.record(player_id, *kind, hash.clone());
+        }
+        if !changed {
+            return Ok(());
+        }
+        for flag in game.integrity.flags.iter().filter(|f| !flags.contains(f)) {
+            tracing::warn!(
+                "Ranked room {} flagged: players {:?} share an {:?}",
+                room_id,
+                flag.players,
+                flag.kind
+            );
         }
 
-        let unmortgage_cost = (tile.mortgage_value as f32 * 1.1) as i32;
+        Self::save_game(redis, &mut game).await
+    }
 
-        let player_idx = game
-            .players
-            .iter()
-            .position(|p| p.id == player_id)
-            .ok_or_else(|| AppError::GameError("Player not found".into()))?;
+    // === Storage ===
 
-        if game.players[player_idx].balance < unmortgage_cost {
-            return Err(AppError::GameError("Not enough money".into()));
+    /// Get game state from the store
+    pub async fn get_game(
+        redis: &ConnectionManager,
+        room_id: &str,
+    ) -> AppResult<Option<GameState>> {
+        match store::current(redis).get(room_id).await? {
+            Some(game) => Ok(Some(game)),
+            None => Self::restore_game(redis, room_id).await,
         }
+    }
 
-        game.players[player_idx].balance -= unmortgage_cost;
-        let player_name = game.players[player_idx].name.clone();
-
-        if let Some(prop) = game.properties.get_mut(&tile_idx) {
-            prop.is_mortgaged = false;
+    /// Bring back an idle room that expired from Redis from its snapshot
+    async fn restore_game(
+        redis: &ConnectionManager,
+        room_id: &str,
+    ) -> AppResult<Option<GameState>> {
+        let Some(mut game) = db::archive::restore_snapshot(room_id).await? else {
+            return Ok(None);
+        };
+        // Lobbies are meant to expire; older snapshots may still hold some
+        if matches!(game.phase, GamePhase::Lobby | GamePhase::GameOver) {
+            return Ok(None);
         }
 
-        game.log(format!(
-            "{} unmortgaged {} for ${}",
-            player_name, tile.name, unmortgage_cost
-        ));
+        Self::save_game(redis, &mut game).await?;
+        tracing::info!("Restored room {} from its snapshot", room_id);
+        Ok(Some(game))
+    }
 
-        Self::save_game(redis, &game).await?;
+    /// Every room still held in the store
+    pub async fn list_games(redis: &ConnectionManager) -> AppResult<Vec<GameState>> {
+        let store = store::current(redis);
+        let room_ids = store.room_ids().await?;
 
-        let hub_guard = hub.read().await;
-        hub_guard.broadcast(
-            room_id,
-            ServerEvent::PropertyUnmortgaged {
-                tile_idx,
-                player_id,
-            },
-        );
+        let mut games = Vec::with_capacity(room_ids.len());
+        for room_id in room_ids {
+            // Rooms may expire between listing and reading
+            if let Some(game) = store.get(&room_id).await? {
+                games.push(game);
+            }
+        }
 
-        Ok(())
+        Ok(games)
     }
 
-    /// Process a bot's turn (iterative to avoid async recursion)
-    #[allow(dead_code)]
-    async fn process_bot_turn(
+    /// Apply an administrator's command to a running game, then persist and
+    /// broadcast the result
+    #[tracing::instrument(skip_all, fields(room_id = %room_id, command = command.name()))]
+    pub async fn admin_command(
         redis: &ConnectionManager,
-        hub: &Arc<RwLock<Hub>>,
+        hub: &Arc<Hub>,
         room_id: &str,
+        command: AdminCommand,
     ) -> AppResult<()> {
-        // Use a loop instead of recursion to avoid Box::pin complexity
-        loop {
-            // Small delay for realism
-            tokio::time::sleep(tokio::time::Duration::from_millis(800)).await;
-
-            let game = match Self::get_game(redis, room_id).await? {
-                Some(g) => g,
-                None => return Err(AppError::NotFound("Room not found".into())),
-            };
+        retry_on_conflict(|| Self::try_admin_command(redis, hub, room_id, &command)).await
+    }
 
-            let turn = match game.turn.as_ref() {
-                Some(t) => t,
-                None => return Ok(()), // No active turn, exit
-            };
+    async fn try_admin_command(
+        redis: &ConnectionManager,
+        hub: &Arc<Hub>,
+        room_id: &str,
+        command: &AdminCommand,
+    ) -> AppResult<()> {
+        let mut game = Self::get_game(redis, room_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+        if game.phase != GamePhase::Playing {
+            return Err(AppError::GameError("Game is not in progress".into()));
+        }
 
-            // Check if current player is still a bot
-            let is_bot = game
-                .get_player(turn.player_id)
-                .map(|p| p.is_bot)
-                .unwrap_or(false);
+        let waiting = Self::waiting_on(&game);
+        let timed = Self::timed_turn(&game);
+        let mut events = Vec::new();
+        let target = Self::apply_command(&mut game, command, &mut events)?;
+        events.push(ServerEvent::GameState(Box::new(game.clone())));
+
+        let journal = Self::journal_entries(&mut game, None, &events);
+        Self::save_game(redis, &mut game).await?;
+        db::journal::record(journal);
+
+        let target_player = target.and_then(|id| game.get_player(id));
+        db::audit::record(db::audit::AuditEntry {
+            action: db::audit::AuditAction::Command,
+            room_id: room_id.to_string(),
+            actor_id: None,
+            actor_user_id: None,
+            target_id: target,
+            details: serde_json::json!({
+                "command": command,
+                "target_name": target_player.map(|p| p.name.clone()),
+                "target_user_id": target_player.and_then(|p| p.user_id),
+            }),
+        });
+
+        let finished = events
+            .iter()
+            .any(|e| matches!(e, ServerEvent::GameOver { .. }));
+        for event in events {
+            hub.broadcast(room_id, event);
+        }
+        Self::remind_turns(hub, room_id, &game, &waiting);
+        if Self::timed_turn(&game) != timed {
+            Self::arm_turn_timer(redis, hub, room_id, &game);
+        }
 
-            if !is_bot {
-                return Ok(()); // Not a bot's turn anymore
-            }
+        if finished {
+            db::archive::archive_game(redis.clone(), hub.clone(), game);
+        } else {
+            Self::spawn_bot_turn(redis, hub, room_id);
+        }
+        Ok(())
+    }
 
-            match turn.phase {
-                TurnPhase::WaitingForRoll => {
-                    Self::roll_dice(redis, hub, room_id).await?;
-                    // Continue loop to handle next phase
-                }
-                TurnPhase::BuyDecision => {
-                    let player_id = turn.player_id;
-                    let position = game.get_player(player_id).map(|p| p.position).unwrap_or(0);
-                    let balance = game.get_player(player_id).map(|p| p.balance).unwrap_or(0);
-
-                    if let Some(tile) = get_tile(position) {
-                        // Simple bot logic: buy if we have more than 40% extra
-                        if balance as u32 > tile.price + (tile.price * 4 / 10) {
-                            Self::buy_property(redis, hub, room_id).await?;
-                        } else {
-                            Self::start_auction(redis, hub, room_id).await?;
-                        }
-                    }
-                    // Continue loop to handle TurnEnd
-                }
-                TurnPhase::TurnEnd => {
-                    Self::end_turn(redis, hub, room_id).await?;
-                    return Ok(()); // end_turn will call process_bot_turn if needed
+    /// Apply an administrator's command without any I/O, returning the
+    /// player it was about
+    fn apply_command(
+        game: &mut GameState,
+        command: &AdminCommand,
+        events: &mut Vec<ServerEvent>,
+    ) -> AppResult<Option<Uuid>> {
+        match *command {
+            AdminCommand::AdjustBalance { player_id, amount } => {
+                if amount == 0 || amount.unsigned_abs() > MAX_BALANCE_ADJUSTMENT as u32 {
+                    return Err(AppError::BadRequest(format!(
+                        "Amount must be between -{0} and {0}, and not zero",
+                        MAX_BALANCE_ADJUSTMENT
+                    )));
                 }
-                TurnPhase::Auction => {
-                    // Bot should bid or pass
-                    let player_id = turn.player_id;
-                    if let Some(auction) = &game.auction {
-                        if !auction.passed_players.contains(&player_id) {
-                            // Simple: just pass for now
-                            Self::pass_bid(redis, hub, room_id, player_id).await?;
-                        }
-                    }
-                    return Ok(()); // Auction handled
+                let player = game
+                    .get_player_mut(player_id)
+                    .filter(|p| !p.is_bankrupt)
+                    .ok_or_else(|| AppError::NotFound("Player not found".into()))?;
+                let balance = player
+                    .balance
+                    .checked_add(amount)
+                    .filter(|&balance| balance >= 0)
+                    .ok_or_else(|| AppError::BadRequest("Balance cannot go below zero".into()))?;
+                player.balance = balance;
+                let name = player.name.clone();
+
+                game.log(
+                    LogLine::new("admin_balance")
+                        .arg("name", &name)
+                        .arg("amount", amount),
+                );
+                Ok(Some(player_id))
+            }
+            AdminCommand::EndTurn => {
+                if game.auction.is_some() {
+                    return Err(AppError::GameError("Cancel the auction first".into()));
                 }
-                _ => {
-                    // For other phases, wait
-                    return Ok(());
+                let player_id = game
+                    .turn
+                    .as_mut()
+                    .map(|turn| {
+                        turn.can_roll_again = false;
+                        turn.player_id
+                    })
+                    .ok_or_else(|| AppError::GameError("No active turn".into()))?;
+                let name = game
+                    .get_player(player_id)
+                    .map(|p| p.name.clone())
+                    .unwrap_or_default();
+
+                game.log(LogLine::new("admin_end_turn").arg("name", &name));
+                Self::end_turn(game, events)?;
+                Ok(Some(player_id))
+            }
+            AdminCommand::CancelAuction => {
+                let auction = game
+                    .auction
+                    .take()
+                    .ok_or_else(|| AppError::GameError("No auction in progress".into()))?;
+                let tile_name = game
+                    .tile(auction.tile_idx)
+                    .map(|t| t.name.clone())
+                    .unwrap_or_default();
+
+                game.log(LogLine::new("admin_cancel_auction").arg("tile", &tile_name));
+                events.push(ServerEvent::AuctionEnd {
+                    tile_idx: auction.tile_idx,
+                    winner: None,
+                    amount: 0,
+                });
+                if let Some(t) = game.turn.as_mut() {
+                    t.phase = auction.resume_phase.unwrap_or(TurnPhase::TurnEnd);
                 }
+                Self::start_queued_auction(game, events);
+                Ok(None)
             }
+            AdminCommand::ReleaseFromJail { player_id } => {
+                let player = game
+                    .get_player_mut(player_id)
+                    .ok_or_else(|| AppError::NotFound("Player not found".into()))?;
+                if !player.in_jail {
+                    return Err(AppError::GameError("Player is not in jail".into()));
+                }
+                player.in_jail = false;
+                player.jail_turns = 0;
+                let name = player.name.clone();
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+                game.log(LogLine::new("admin_release_jail").arg("name", &name));
+                Ok(Some(player_id))
+            }
         }
     }
 
-    // === Redis Storage ===
-
-    /// Get game state from Redis
-    pub async fn get_game(
+    /// Replace a room's state with an exported one, creating the room if
+    /// needed, and push it to everyone in the room
+    ///
+    /// The state takes the room's id and lands over whatever version is
+    /// stored. Its log lines are not appended to the room's log again.
+    #[tracing::instrument(skip_all, fields(room_id = %room_id))]
+    pub async fn import_game(
         redis: &ConnectionManager,
+        hub: &Arc<Hub>,
         room_id: &str,
-    ) -> AppResult<Option<GameState>> {
-        let mut conn = redis.clone();
-        let key = format!("game:{}", room_id);
+        game: GameState,
+    ) -> AppResult<GameState> {
+        let game = retry_on_conflict(|| Self::try_import(redis, room_id, game.clone())).await?;
 
-        let data: Option<String> = conn.get(&key).await?;
+        hub.broadcast(room_id, ServerEvent::GameState(Box::new(game.clone())));
+        Self::spawn_bot_turn(redis, hub, room_id);
+        Ok(game)
+    }
 
-        match data {
-            Some(json) => {
-                let game: GameState =
-                    serde_json::from_str(&json).map_err(|e| AppError::Internal(e.into()))?;
-                Ok(Some(game))
-            }
-            None => Ok(None),
-        }
+    async fn try_import(
+        redis: &ConnectionManager,
+        room_id: &str,
+        mut game: GameState,
+    ) -> AppResult<GameState> {
+        let stored = Self::get_game(redis, room_id).await?;
+
+        game.id = room_id.to_string();
+        game.version = stored.map_or(0, |stored| stored.version);
+        game.logs.clear();
+        game.logs_stored = 0;
+        game.log(LogLine::new("imported_by_admin"));
+
+        Self::save_game(redis, &mut game).await?;
+        Ok(game)
     }
 
-    /// Save game state to Redis
-    pub async fn save_game(redis: &ConnectionManager, game: &GameState) -> AppResult<()> {
-        let mut conn = redis.clone();
-        let key = format!("game:{}", game.id);
-        let json = serde_json::to_string(game).map_err(|e| AppError::Internal(e.into()))?;
+    /// A room's last `limit` log lines, oldest first
+    pub async fn get_logs(
+        redis: &ConnectionManager,
+        room_id: &str,
+        limit: usize,
+    ) -> AppResult<Vec<LogLine>> {
+        store::current(redis).logs(room_id, limit).await
+    }
 
-        // Store with 24 hour expiry
-        let _: () = conn.set_ex(&key, json, 86400).await?;
+    /// Save game state to the store, bumping its version
+    ///
+    /// Fails with a conflict if the room was saved since `game` was loaded.
+    pub async fn save_game(redis: &ConnectionManager, game: &mut GameState) -> AppResult<()> {
+        store::current(redis).save(game).await
+    }
+}
 
-        Ok(())
+/// Run a load, change and save step again while other saves get in first
+async fn retry_on_conflict<T, F, Fut>(mut step: F) -> AppResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = AppResult<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match step().await {
+            Err(AppError::Conflict(_)) if attempt < SAVE_ATTEMPTS => attempt += 1,
+            result => return result,
+        }
     }
 }
 
@@ -1517,3 +3876,225 @@ fn generate_room_id() -> String {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A timed game in play with the first of two humans on turn, waiting
+    /// to roll
+    fn game() -> (GameState, [Uuid; 2]) {
+        let mut config = GameConfig::default();
+        config.timers.turn_secs = Some(60);
+        let mut game = GameState::new("TEST".into(), config);
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+        for (i, &id) in ids.iter().enumerate() {
+            game.players.push(Player::new(
+                id,
+                format!("P{}", i),
+                "#fff".into(),
+                i == 0,
+                false,
+            ));
+        }
+        game.phase = GamePhase::Playing;
+        game.turn = Some(TurnState::new(ids[0]));
+        (game, ids)
+    }
+
+    #[test]
+    fn repeated_turn_timeouts_hand_the_seat_to_a_bot() {
+        let (mut game, ids) = game();
+        let turn_id = game.turn.as_ref().map(|t| t.id);
+        let mut events = Vec::new();
+
+        assert_eq!(GameEngine::timed_turn(&game), turn_id);
+        assert!(!GameEngine::time_out_turn(&mut game, &mut events));
+        assert!(events.is_empty());
+        assert_eq!(game.players[0].missed_turn_timers, 1);
+        assert!(!game.players[0].bot_controlled);
+
+        assert!(GameEngine::time_out_turn(&mut game, &mut events));
+        assert!(game.players[0].bot_controlled);
+        assert!(matches!(
+            events.as_slice(),
+            [ServerEvent::BotTakeover { player_id }] if *player_id == ids[0]
+        ));
+        assert_eq!(GameEngine::timed_turn(&game), None);
+    }
+
+    #[test]
+    fn acting_clears_timeouts_and_takes_the_seat_back() {
+        let (mut game, ids) = game();
+        let mut events = Vec::new();
+
+        GameEngine::time_out_turn(&mut game, &mut events);
+        assert!(!GameEngine::mark_active(&mut game, ids[0]));
+        assert_eq!(game.players[0].missed_turn_timers, 0);
+
+        // The run starts over, so one more timeout is only a strike
+        assert!(!GameEngine::time_out_turn(&mut game, &mut events));
+        assert!(GameEngine::time_out_turn(&mut game, &mut events));
+
+        assert!(GameEngine::mark_active(&mut game, ids[0]));
+        assert!(!game.players[0].bot_controlled);
+        assert_eq!(game.players[0].missed_turn_timers, 0);
+        assert_eq!(
+            GameEngine::timed_turn(&game),
+            game.turn.as_ref().map(|t| t.id)
+        );
+    }
+
+    /// The first player on their last jail turn with less than the bail,
+    /// owning one property to mortgage
+    fn short_of_bail() -> (GameState, [Uuid; 2]) {
+        let (mut game, ids) = game();
+        let max_jail_turns = game.config.max_jail_turns;
+        let player = &mut game.players[0];
+        player.in_jail = true;
+        player.position = 10;
+        player.jail_turns = max_jail_turns - 1;
+        player.balance = 20;
+        if let Some(prop) = game.properties.get_mut(&1) {
+            prop.owner = Some(ids[0]);
+        }
+
+        let mut events = Vec::new();
+        GameEngine::roll_dice(&mut game, vec![1, 2], &mut events).unwrap();
+        (game, ids)
+    }
+
+    #[test]
+    fn a_bail_they_cannot_afford_is_raised_then_paid() {
+        let (mut game, ids) = short_of_bail();
+        assert!(!game.players[0].is_bankrupt);
+        assert!(game.players[0].in_jail);
+        assert_eq!(
+            game.debt.as_ref().map(|d| (d.player_id, d.amount)),
+            Some((ids[0], JAIL_BAIL))
+        );
+        assert_eq!(
+            pending_actions(&game, ids[0]),
+            ["PAY_DEBT", "DECLARE_BANKRUPTCY"]
+        );
+
+        let mut rng = rand::thread_rng();
+        let mut events = Vec::new();
+        let mut play = |game: &mut GameState, event| {
+            GameEngine::apply_event(game, ids[0], event, &mut rng, &mut events)
+        };
+        assert!(play(&mut game, ClientEvent::PayDebt).is_err());
+        play(&mut game, ClientEvent::Mortgage { tile_idx: 1 }).unwrap();
+        play(&mut game, ClientEvent::PayDebt).unwrap();
+
+        let mortgage = game.tile(1).map(|t| t.mortgage_value as i32).unwrap();
+        assert!(game.debt.is_none());
+        assert!(!game.players[0].in_jail);
+        assert_eq!(game.players[0].position, 13);
+        assert_eq!(game.players[0].balance, 20 + mortgage - JAIL_BAIL);
+    }
+
+    #[test]
+    fn a_debt_given_up_is_a_bankruptcy() {
+        let (mut game, ids) = short_of_bail();
+        let mut events = Vec::new();
+        GameEngine::apply_event(
+            &mut game,
+            ids[0],
+            ClientEvent::DeclareBankruptcy,
+            &mut rand::thread_rng(),
+            &mut events,
+        )
+        .unwrap();
+
+        assert!(game.players[0].is_bankrupt);
+        assert!(game.debt.is_none());
+        assert_eq!(game.properties.get(&1).and_then(|p| p.owner), None);
+        assert_eq!(
+            game.turn.as_ref().map(|t| t.phase),
+            Some(TurnPhase::TurnEnd)
+        );
+    }
+
+    #[test]
+    fn a_bot_winning_in_a_leavers_seat_credits_no_one() {
+        let (mut game, ids) = game();
+        game.config.bot_replaces_leavers = true;
+        let user_id = Uuid::new_v4();
+        game.players[0].user_id = Some(user_id);
+        game.players[0].locale = Locale::Es;
+
+        let mut events = Vec::new();
+        GameEngine::remove_player(&mut game, ids[0], &mut rand::thread_rng(), &mut events).unwrap();
+        let bot_id = game.players[0].id;
+        assert!(game.players[0].is_bot);
+        assert_eq!(game.players[0].user_id, None);
+        assert_eq!(game.players[0].locale, Locale::default());
+
+        BankruptcyHandler::handle_bankruptcy(&mut game, ids[1], Some(bot_id));
+        game.phase = GamePhase::GameOver;
+        assert_eq!(game.winner(), Some(bot_id));
+        assert!(game.registered_players().is_empty());
+    }
+
+    #[test]
+    fn cards_come_from_the_games_board_version() {
+        use crate::game::board::{self, BoardDef, BOARD};
+        use crate::game::cards::Card;
+
+        let card = |effect| Card {
+            text: "test".into(),
+            effect,
+        };
+        let def = |version, chance| BoardDef {
+            id: "cards".into(),
+            name: "Cards".into(),
+            version,
+            tiles: BOARD.clone(),
+            chance: vec![card(chance)],
+            community_chest: vec![card(CardEffect::GoToJail)],
+        };
+        assert_eq!(
+            board::install(vec![
+                def(1, CardEffect::Collect { amount: 100 }),
+                def(2, CardEffect::AdvanceTo { tile: 22 }),
+            ]),
+            1,
+            "a card sending players to another card tile is refused"
+        );
+
+        let (mut game, ids) = game();
+        game.board_id = "cards".into();
+        game.board_version = 1;
+        let balance = game.players[0].balance;
+        let mut events = Vec::new();
+
+        GameEngine::move_player(&mut game, ids[0], 7, false, &mut events).unwrap();
+        assert_eq!(game.players[0].balance, balance + 100);
+        assert!(events.iter().any(
+            |e| matches!(e, ServerEvent::CardDrawn { card_type, .. } if card_type == "chance")
+        ));
+
+        GameEngine::move_player(&mut game, ids[0], 17, false, &mut events).unwrap();
+        assert!(game.players[0].in_jail);
+        assert_eq!(game.players[0].position, 10);
+    }
+
+    #[test]
+    fn a_timed_debt_stops_the_turn_clock_and_bankrupts_when_it_runs_out() {
+        let (mut game, ids) = short_of_bail();
+        game.config.timers.debt_secs = Some(60);
+        assert_eq!(GameEngine::timed_turn(&game), None);
+
+        let mut events = Vec::new();
+        GameEngine::default_on_debt(&mut game, &mut events).unwrap();
+        assert!(game.players[0].is_bankrupt);
+        assert!(game.debt.is_none());
+        // The other player is the last one standing
+        assert_eq!(game.phase, GamePhase::GameOver);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ServerEvent::Bankruptcy { player_id, creditor: None } if *player_id == ids[0]
+        )));
+    }
+}
@@ -1,9 +1,16 @@
 //! Client and Server events for WebSocket communication
 
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{GameState, TradeOffer};
+use super::achievements::Achievement;
+use super::chat::ChatMessage;
+use super::summary::GameSummary;
+use super::{GameState, TradeOffer, TradeRecord, TradeStatus};
+use crate::i18n::Locale;
+use crate::ws::EncodedFrames;
 
 /// Events sent from client to server
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +40,12 @@ pub enum ClientEvent {
     /// Use get out of jail free card
     UseCard,
 
+    /// Pay off a debt once enough money is raised
+    PayDebt,
+
+    /// Give up a debt that cannot be raised and go bankrupt
+    DeclareBankruptcy,
+
     /// Build a house/hotel on property
     Build { tile_idx: u8 },
 
@@ -59,19 +72,127 @@ pub enum ClientEvent {
 
     /// Send chat message
     Chat { message: String },
+
+    /// Acknowledge receipt of events up to a sequence number
+    Ack { seq: u64 },
+
+    /// Leave the game for good
+    Resign,
+
+    /// Remove another player from the game (host only)
+    Kick { player_id: Uuid },
+
+    /// Silence a player in chat (host only)
+    Mute { player_id: Uuid },
+
+    /// Let a muted player chat again (host only)
+    Unmute { player_id: Uuid },
+
+    /// Keep a player's chat from everyone but themselves, without telling
+    /// them (host only)
+    ShadowMute { player_id: Uuid },
+
+    /// Let a shadow-muted player's chat through again (host only)
+    ShadowUnmute { player_id: Uuid },
+
+    /// Take a color no other player has, in the lobby
+    PickColor { color: String },
+
+    /// Take a playing piece no other player has, in the lobby
+    PickPiece { piece: String },
+
+    /// Tell the lobby whether you are ready to start
+    SetReady { ready: bool },
+}
+
+impl ClientEvent {
+    /// Wire name of the event, as sent in its `type` field
+    pub fn type_name(&self) -> Option<String> {
+        serde_json::to_value(self)
+            .ok()?
+            .get("type")?
+            .as_str()
+            .map(str::to_string)
+    }
+
+    /// Actions that only the current player may take during their own turn
+    pub fn is_turn_scoped(&self) -> bool {
+        matches!(
+            self,
+            ClientEvent::RollDice
+                | ClientEvent::BuyProperty
+                | ClientEvent::PassProperty
+                | ClientEvent::EndTurn
+                | ClientEvent::PayJail
+                | ClientEvent::UseCard
+                | ClientEvent::PayDebt
+                | ClientEvent::DeclareBankruptcy
+        )
+    }
+
+    /// Building, selling buildings and (un)mortgaging
+    pub fn is_property_management(&self) -> bool {
+        matches!(
+            self,
+            ClientEvent::Build { .. }
+                | ClientEvent::SellBuilding { .. }
+                | ClientEvent::Mortgage { .. }
+                | ClientEvent::Unmortgage { .. }
+        )
+    }
+}
+
+/// Envelope for client events as received over the wire
+///
+/// Turn-scoped actions should carry the id of the turn they were issued for,
+/// so a late action (e.g. a slow `END_TURN`) cannot land on the following
+/// turn. Without one the action applies to whatever turn is current.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientMessage {
+    #[serde(default)]
+    pub turn_id: Option<Uuid>,
+    /// Picked by the client and echoed in the `ERROR` if the event is
+    /// rejected, so it can undo just that optimistic update
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(flatten)]
+    pub event: ClientEvent,
+}
+
+/// Longest `request_id` echoed back; longer ones are ignored
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+impl ClientMessage {
+    /// The client's request id, if it is short enough to echo
+    pub fn request_id(&self) -> Option<String> {
+        self.request_id
+            .clone()
+            .filter(|id| id.len() <= MAX_REQUEST_ID_LEN)
+    }
 }
 
 /// Events sent from server to clients
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ServerEvent {
+    /// First message on every connection
+    Welcome {
+        /// Protocol version the server will speak on this connection
+        protocol_version: u32,
+        /// Newest protocol version the server knows
+        latest_protocol_version: u32,
+        /// `None` for spectators
+        player_id: Option<Uuid>,
+    },
+
     /// Full game state update
-    GameState(GameState),
+    GameState(Box<GameState>),
 
     /// Dice roll result
     DiceResult {
         player_id: Uuid,
-        dice: (u8, u8),
+        /// One entry per die, as many as the room's `dice_count`
+        dice: Vec<u8>,
         is_doubles: bool,
     },
 
@@ -139,12 +260,19 @@ pub enum ServerEvent {
     /// Game ended
     GameOver { winner: Uuid },
 
+    /// Counters and timeline of the game, sent once it ends
+    GameSummary { summary: GameSummary },
+
     /// Trade proposed
     TradeProposed { trade: TradeOffer },
 
     /// Trade resolved
     TradeResolved { trade_id: Uuid, accepted: bool },
 
+    /// Offer settled and added to the trade history; only the two sides
+    /// hear of offers that were not accepted
+    TradeConcluded { trade: TradeRecord },
+
     /// Building constructed
     BuildingBuilt {
         tile_idx: u8,
@@ -172,12 +300,218 @@ pub enum ServerEvent {
         message: String,
     },
 
+    /// The host muted or unmuted a player
+    PlayerMuted { player_id: Uuid, muted: bool },
+
+    /// A player changed color in the lobby; the updated room follows
+    ColorPicked { player_id: Uuid, color: String },
+
+    /// A player changed piece in the lobby; the updated room follows
+    PiecePicked { player_id: Uuid, piece: String },
+
+    /// A player said whether they are ready; the updated room follows
+    PlayerReady { player_id: Uuid, ready: bool },
+
+    /// Recent chat, sent when a client connects
+    ChatHistory { messages: Vec<ChatMessage> },
+
+    /// Chat between spectators, never shown to players
+    SpectatorChat { from_name: String, message: String },
+
     /// Log message
     Log { message: String },
 
-    /// Error message (sent to specific player)
-    Error { message: String },
+    /// A player's action was rejected (sent to that player only)
+    Error {
+        /// Stable code such as `forbidden` or `game_error`
+        code: String,
+        message: String,
+        /// Type of the client event that failed, e.g. `ROLL_DICE`
+        event: Option<String>,
+        /// `request_id` the client sent with that event
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+        /// Whether sending the event again may succeed, e.g. after losing a
+        /// race with another player's action
+        #[serde(default)]
+        retryable: bool,
+    },
+
+    /// A player or bot took a seat in the lobby; a fresh `GameState`
+    /// follows
+    PlayerJoined {
+        player_id: Uuid,
+        name: String,
+        is_bot: bool,
+    },
+
+    /// A player opened a connection to the room
+    PlayerConnected {
+        player_id: Uuid,
+        /// They had dropped and came back
+        reconnected: bool,
+    },
+
+    /// A player's last connection closed or stopped answering pings
+    PlayerDisconnected {
+        player_id: Uuid,
+        /// Seconds they have to reconnect before a bot takes over, if one will
+        reconnect_grace_secs: Option<u64>,
+    },
+
+    /// A disconnected player is now played by a bot
+    BotTakeover { player_id: Uuid },
+
+    /// A player reconnected and took back control from the bot
+    PlayerReturned { player_id: Uuid },
+
+    /// Why a bot took its next action (rooms with `bot_debug` only)
+    BotDebug {
+        player_id: Uuid,
+        action: ClientEvent,
+        reason: String,
+    },
+
+    /// The players of this finished game have a new lobby; players get
+    /// their `token` for it, spectators only the room
+    Rematch {
+        room_id: String,
+        token: Option<String>,
+    },
+
+    /// A player resigned or was kicked; `replaced_by` is the bot now in
+    /// their seat, if any
+    PlayerLeft {
+        player_id: Uuid,
+        replaced_by: Option<Uuid>,
+    },
 
     /// Turn changed
-    TurnChanged { player_id: Uuid },
+    TurnChanged { player_id: Uuid, turn_id: Uuid },
+
+    /// The game waits on this player's decision, sent only to them when it
+    /// starts waiting: their turn began, a property or an auction came up,
+    /// or the turn is theirs to end. `remaining_secs` is left on the turn or
+    /// auction timer, when the room has one
+    YourTurn {
+        turn_id: Uuid,
+        actions: Vec<String>,
+        remaining_secs: Option<u32>,
+    },
+
+    /// The host or an administrator closed the room, which stays read-only
+    /// until it is deleted, or an administrator removed it altogether and is
+    /// closing every connection to it
+    RoomClosed { reason: String, deleted: bool },
+
+    /// An administrator banned the connected user, until `expires_at` if
+    /// set; sent on every connection the user has open, which then closes
+    Banned {
+        reason: Option<String>,
+        expires_at: Option<i64>,
+    },
+
+    /// This server is shutting down and closing every connection; the room
+    /// carries on, so reconnect after the delay
+    ServerRestarting { reconnect_after_ms: u64 },
+
+    /// A friend invited the connected user to another room; sent on every
+    /// connection the user has open
+    Invited {
+        invite_id: Uuid,
+        room_id: String,
+        from_user_id: Uuid,
+        from_name: String,
+    },
+
+    /// The connected user unlocked an achievement in a game that just ended;
+    /// sent on every connection the user has open
+    AchievementUnlocked {
+        achievement: Achievement,
+        room_id: String,
+    },
+}
+
+impl ServerEvent {
+    /// The event as `viewer` may see it, or `None` if it is not for them
+    pub fn view_for(&self, viewer: Uuid) -> Option<ServerEvent> {
+        match self {
+            ServerEvent::GameState(game) => {
+                Some(ServerEvent::GameState(Box::new(game.view_for(viewer))))
+            }
+            ServerEvent::TradeProposed { trade } if !trade.involves(viewer) => None,
+            ServerEvent::TradeConcluded { trade } if !trade.is_visible_to(viewer) => None,
+            other => Some(other.clone()),
+        }
+    }
+
+    /// The event with its text in `locale`
+    pub fn localized(self, locale: Locale) -> ServerEvent {
+        match self {
+            ServerEvent::GameState(mut game) => {
+                game.localize(locale);
+                ServerEvent::GameState(game)
+            }
+            other => other,
+        }
+    }
+
+    /// Whether the event carries text that reads differently per language
+    pub fn is_localized(&self) -> bool {
+        matches!(self, ServerEvent::GameState(game) if !game.logs.is_empty())
+    }
+
+    /// Whether `viewer` sees the event differently from someone outside the
+    /// game, so their copy cannot be shared with the rest of the room
+    pub fn differs_for(&self, viewer: Uuid) -> bool {
+        match self {
+            ServerEvent::GameState(game) => game.differs_for(viewer),
+            ServerEvent::TradeProposed { trade } => trade.involves(viewer),
+            ServerEvent::TradeConcluded { trade } => {
+                trade.offer.status != TradeStatus::Accepted && trade.offer.involves(viewer)
+            }
+            _ => false,
+        }
+    }
+
+    /// WebSocket close code to send once this event is out, if the
+    /// connection ends with it
+    pub fn close_code(&self) -> Option<u16> {
+        match self {
+            ServerEvent::RoomClosed { deleted: true, .. } => Some(1000),
+            // Policy Violation
+            ServerEvent::Banned { .. } => Some(1008),
+            // Service Restart
+            ServerEvent::ServerRestarting { .. } => Some(1012),
+            _ => None,
+        }
+    }
+}
+
+/// Envelope for server events as sent over the wire
+///
+/// `seq` increases with every event sent in the room; clients acknowledge it
+/// with `ACK` so hosts can see who is lagging behind.
+///
+/// The event sits behind an `Arc` so a broadcast hands every connection the
+/// same copy instead of cloning the game state for each of them. Clones also
+/// share the encoded frames, so however many connections a message goes to
+/// it is serialized at most once per encoding.
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerMessage {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: Arc<ServerEvent>,
+    #[serde(skip)]
+    pub(crate) frames: Arc<EncodedFrames>,
+}
+
+impl ServerMessage {
+    pub fn new(seq: u64, event: impl Into<Arc<ServerEvent>>) -> Self {
+        Self {
+            seq,
+            event: event.into(),
+            frames: Arc::default(),
+        }
+    }
 }
@@ -0,0 +1,104 @@
+//! Optional house rules a room may turn on
+//!
+//! Operators can switch any of them off for the whole server with
+//! `DISABLED_HOUSE_RULES`; rooms asking for a disabled rule are refused
+//! rather than quietly played without it.
+
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+
+use super::state::GameConfig;
+use crate::error::FieldErrors;
+
+/// A `GameConfig` option that is off unless a room asks for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HouseRule {
+    FreeParkingJackpot,
+    CollectRentInJail,
+    HideJailCards,
+    BotReplacesLeavers,
+    BotChat,
+    BotDebug,
+    AllowProfanity,
+    ManageOnOwnTurnOnly,
+    NoRollAgainOnDoubles,
+    NoJailOnThreeDoubles,
+}
+
+impl HouseRule {
+    pub const ALL: [HouseRule; 10] = [
+        HouseRule::FreeParkingJackpot,
+        HouseRule::CollectRentInJail,
+        HouseRule::HideJailCards,
+        HouseRule::BotReplacesLeavers,
+        HouseRule::BotChat,
+        HouseRule::BotDebug,
+        HouseRule::AllowProfanity,
+        HouseRule::ManageOnOwnTurnOnly,
+        HouseRule::NoRollAgainOnDoubles,
+        HouseRule::NoJailOnThreeDoubles,
+    ];
+
+    /// Name of the `GameConfig` field, also used in `DISABLED_HOUSE_RULES`
+    pub fn name(self) -> &'static str {
+        match self {
+            HouseRule::FreeParkingJackpot => "free_parking_jackpot",
+            HouseRule::CollectRentInJail => "collect_rent_in_jail",
+            HouseRule::HideJailCards => "hide_jail_cards",
+            HouseRule::BotReplacesLeavers => "bot_replaces_leavers",
+            HouseRule::BotChat => "bot_chat",
+            HouseRule::BotDebug => "bot_debug",
+            HouseRule::AllowProfanity => "allow_profanity",
+            HouseRule::ManageOnOwnTurnOnly => "manage_on_own_turn_only",
+            HouseRule::NoRollAgainOnDoubles => "no_roll_again_on_doubles",
+            HouseRule::NoJailOnThreeDoubles => "no_jail_on_three_doubles",
+        }
+    }
+
+    /// Whether a room's config turns this rule on
+    pub fn enabled_in(self, config: &GameConfig) -> bool {
+        match self {
+            HouseRule::FreeParkingJackpot => config.free_parking_jackpot,
+            HouseRule::CollectRentInJail => config.collect_rent_in_jail,
+            HouseRule::HideJailCards => config.hide_jail_cards,
+            HouseRule::BotReplacesLeavers => config.bot_replaces_leavers,
+            HouseRule::BotChat => config.bot_chat,
+            HouseRule::BotDebug => config.bot_debug,
+            HouseRule::AllowProfanity => config.allow_profanity,
+            HouseRule::ManageOnOwnTurnOnly => config.manage_on_own_turn_only,
+            HouseRule::NoRollAgainOnDoubles => config.no_roll_again_on_doubles,
+            HouseRule::NoJailOnThreeDoubles => config.no_jail_on_three_doubles,
+        }
+    }
+}
+
+impl Serialize for HouseRule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl FromStr for HouseRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        HouseRule::ALL
+            .into_iter()
+            .find(|rule| rule.name() == s)
+            .ok_or_else(|| format!("Unknown house rule {}", s))
+    }
+}
+
+/// Refuse a room config that turns on a rule the server has disabled
+pub fn check(config: &GameConfig, disabled: &[HouseRule]) -> FieldErrors {
+    let mut errors = FieldErrors::new();
+    for rule in disabled.iter().filter(|rule| rule.enabled_in(config)) {
+        errors.add(
+            rule.name(),
+            "disabled",
+            format!("House rule {} is disabled on this server", rule.name()),
+        );
+    }
+    errors
+}
@@ -0,0 +1,101 @@
+//! Shared-device checks for ranked rooms
+//!
+//! Every connection to a ranked room leaves salted hashes of its IP address
+//! and of the fingerprint its client sends. Two seats leaving the same hash
+//! flag the room, and a flagged game is stored as unrated. Hashes and flags
+//! are kept from players; admins see them.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Marks kept per player, so a player hopping networks cannot grow the
+/// state without bound
+const MAX_MARKS_PER_PLAYER: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkKind {
+    Ip,
+    Fingerprint,
+}
+
+/// A hash a player's connection left
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientMark {
+    pub player_id: Uuid,
+    pub kind: MarkKind,
+    pub hash: String,
+}
+
+/// Seats that connected with the same IP address or fingerprint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityFlag {
+    pub kind: MarkKind,
+    pub hash: String,
+    pub players: Vec<Uuid>,
+    /// Unix time the second seat showed up
+    pub flagged_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Integrity {
+    #[serde(default)]
+    pub marks: Vec<ClientMark>,
+    #[serde(default)]
+    pub flags: Vec<IntegrityFlag>,
+}
+
+impl Integrity {
+    /// Note a mark a player's connection left; returns whether it is new
+    pub fn record(&mut self, player_id: Uuid, kind: MarkKind, hash: String) -> bool {
+        let own = || self.marks.iter().filter(|m| m.player_id == player_id);
+        if own().any(|m| m.kind == kind && m.hash == hash) || own().count() >= MAX_MARKS_PER_PLAYER
+        {
+            return false;
+        }
+
+        let sharing: Vec<Uuid> = self
+            .marks
+            .iter()
+            .filter(|m| m.kind == kind && m.hash == hash)
+            .map(|m| m.player_id)
+            .collect();
+        if !sharing.is_empty() {
+            match self
+                .flags
+                .iter_mut()
+                .find(|f| f.kind == kind && f.hash == hash)
+            {
+                Some(flag) => flag.players.push(player_id),
+                None => self.flags.push(IntegrityFlag {
+                    kind,
+                    hash: hash.clone(),
+                    players: sharing.into_iter().chain([player_id]).collect(),
+                    flagged_at: chrono::Utc::now().timestamp(),
+                }),
+            }
+        }
+
+        self.marks.push(ClientMark {
+            player_id,
+            kind,
+            hash,
+        });
+        true
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        !self.flags.is_empty()
+    }
+}
+
+/// Salted hash of an IP address or fingerprint, so the raw values are never
+/// stored
+pub fn hash(secret: &str, kind: MarkKind, value: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(secret.as_bytes());
+    hasher.update([kind as u8]);
+    hasher.update(value.as_bytes());
+    hex::encode(&hasher.finalize()[..16])
+}
@@ -0,0 +1,52 @@
+//! Closed rooms
+//!
+//! A room closed by its host or ended by an administrator stays in Redis,
+//! read-only, so players can still look at the final board and chat. Once
+//! its `closed_until` has passed, a background job deletes it and
+//! disconnects whoever is still watching.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use redis::aio::ConnectionManager;
+
+use super::GameEngine;
+use crate::error::{AppError, AppResult};
+use crate::ws::Hub;
+
+/// How often closed rooms are checked
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Start deleting closed rooms in the background once their grace period
+/// runs out
+pub fn spawn(redis: ConnectionManager, hub: Arc<Hub>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            match sweep(&redis, &hub).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Deleted {} closed rooms", n),
+                Err(e) => tracing::warn!("Deleting closed rooms failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Delete every closed room past its grace period, returning how many
+async fn sweep(redis: &ConnectionManager, hub: &Arc<Hub>) -> AppResult<usize> {
+    let now = chrono::Utc::now().timestamp();
+    let mut deleted = 0;
+    for game in GameEngine::list_games(redis).await? {
+        if game.closed_until.is_none_or(|until| until > now) {
+            continue;
+        }
+        match GameEngine::close_room(redis, hub, &game.id, "Room closed".into(), true).await {
+            Ok(()) => deleted += 1,
+            // Another instance got to it first
+            Err(AppError::NotFound(_)) => {}
+            Err(e) => tracing::warn!("Failed to delete closed room {}: {}", game.id, e),
+        }
+    }
+    Ok(deleted)
+}
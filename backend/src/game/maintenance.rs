@@ -0,0 +1,245 @@
+//! Abandoned rooms and stray keys
+//!
+//! Expiries alone let a room linger as long as someone keeps chatting in its
+//! lobby, and leave chat and log keys behind when a room key goes first. A
+//! background job clears out:
+//!
+//! - started games nobody has touched for hours and nobody is connected to
+//!   on this instance, snapshotted to Postgres first so they can still be
+//!   resumed
+//! - lobbies that never started, once they are older than their limit
+//! - chat and log keys whose room is gone
+//!
+//! Counts of the last sweep and since startup are kept for operators.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use super::store;
+use super::{chat, GameEngine, GamePhase, LOG_LEN};
+use crate::db;
+use crate::error::{AppError, AppResult};
+use crate::ws::Hub;
+
+/// How often rooms and keys are checked
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// When rooms count as abandoned and which keys are checked
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Started games untouched this long are archived; `None` keeps them
+    pub abandoned_after: Option<Duration>,
+    /// Lobbies that have not started this long after being created are
+    /// deleted; `None` keeps them
+    pub lobby_max_age: Option<Duration>,
+    /// Look for log keys without a room; only meaningful with rooms in Redis
+    pub orphaned_logs: bool,
+    /// Look for chat keys without a room; only meaningful with rooms in Redis
+    pub orphaned_chat: bool,
+}
+
+/// What one sweep, or every sweep so far, cleared out
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Cleaned {
+    /// Started games snapshotted and dropped from Redis
+    pub abandoned_rooms: u64,
+    pub stale_lobbies: u64,
+    pub orphaned_keys: u64,
+}
+
+impl Cleaned {
+    fn is_empty(&self) -> bool {
+        self.abandoned_rooms == 0 && self.stale_lobbies == 0 && self.orphaned_keys == 0
+    }
+
+    fn add(&mut self, other: Cleaned) {
+        self.abandoned_rooms += other.abandoned_rooms;
+        self.stale_lobbies += other.stale_lobbies;
+        self.orphaned_keys += other.orphaned_keys;
+    }
+}
+
+/// Maintenance counts on this instance, as shown to operators
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MaintenanceStats {
+    /// Unix time the last sweep finished
+    pub last_run_at: Option<i64>,
+    pub last_run: Cleaned,
+    /// Since the instance started
+    pub total: Cleaned,
+    /// Sweeps that stopped on an error
+    pub failed_runs: u64,
+}
+
+static STATS: Lazy<Mutex<MaintenanceStats>> = Lazy::new(Default::default);
+
+/// Maintenance counts so far
+pub fn stats() -> MaintenanceStats {
+    STATS.lock().unwrap().clone()
+}
+
+/// Start clearing out abandoned rooms and stray keys in the background
+pub fn spawn(redis: ConnectionManager, pool: PgPool, hub: Arc<Hub>, limits: Limits) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let result = sweep(&redis, &pool, &hub, &limits).await;
+
+            let mut stats = STATS.lock().unwrap();
+            match result {
+                Ok(cleaned) => {
+                    if !cleaned.is_empty() {
+                        tracing::info!(
+                            abandoned_rooms = cleaned.abandoned_rooms,
+                            stale_lobbies = cleaned.stale_lobbies,
+                            orphaned_keys = cleaned.orphaned_keys,
+                            "Cleaned up rooms and keys"
+                        );
+                    }
+                    stats.last_run_at = Some(chrono::Utc::now().timestamp());
+                    stats.last_run = cleaned;
+                    stats.total.add(cleaned);
+                }
+                Err(e) => {
+                    tracing::warn!("Cleaning up rooms and keys failed: {}", e);
+                    stats.failed_runs += 1;
+                }
+            }
+        }
+    });
+}
+
+async fn sweep(
+    redis: &ConnectionManager,
+    pool: &PgPool,
+    hub: &Arc<Hub>,
+    limits: &Limits,
+) -> AppResult<Cleaned> {
+    let mut cleaned = Cleaned::default();
+    if limits.abandoned_after.is_some() || limits.lobby_max_age.is_some() {
+        sweep_rooms(redis, pool, hub, limits, &mut cleaned).await?;
+    }
+    if limits.orphaned_logs || limits.orphaned_chat {
+        cleaned.orphaned_keys = sweep_keys(redis, limits).await?;
+    }
+    Ok(cleaned)
+}
+
+async fn sweep_rooms(
+    redis: &ConnectionManager,
+    pool: &PgPool,
+    hub: &Arc<Hub>,
+    limits: &Limits,
+    cleaned: &mut Cleaned,
+) -> AppResult<()> {
+    let layout = store::layout();
+    let store = store::current(redis);
+    let now = chrono::Utc::now().timestamp();
+
+    for room_id in store.room_ids().await? {
+        let Some(mut game) = store.get(&room_id).await? else {
+            continue;
+        };
+
+        match game.phase {
+            // A host who chose the lifetime of their lobby wants it kept
+            GamePhase::Lobby if game.lifetime_secs.is_none() => {
+                let Some((max_age, created_at)) = limits.lobby_max_age.zip(game.created_at) else {
+                    continue;
+                };
+                if now - created_at < max_age.as_secs() as i64 {
+                    continue;
+                }
+                match GameEngine::close_room(
+                    redis,
+                    hub,
+                    &room_id,
+                    "Lobby never started".into(),
+                    true,
+                )
+                .await
+                {
+                    Ok(()) => cleaned.stale_lobbies += 1,
+                    // Another instance got to it first
+                    Err(AppError::NotFound(_)) => {}
+                    Err(e) => tracing::warn!("Failed to delete stale lobby {}: {}", room_id, e),
+                }
+            }
+            GamePhase::Lobby | GamePhase::GameOver => {}
+            _ => {
+                let Some(abandoned_after) = limits.abandoned_after else {
+                    continue;
+                };
+                if !hub.connections(&room_id).is_empty() || game.closed_until.is_some() {
+                    continue;
+                }
+                // Saves and chat renew the expiry, so the TTL tells how long
+                // the room has been left alone
+                let Some(ttl) = store.ttl(&room_id).await? else {
+                    continue;
+                };
+                let idle = layout.ttl_for(&game).saturating_sub(ttl);
+                if idle < abandoned_after.as_secs() {
+                    continue;
+                }
+
+                game.logs = GameEngine::get_logs(redis, &room_id, LOG_LEN).await?;
+                db::snapshots::save(pool, &game).await?;
+                store.delete(&room_id).await?;
+                chat::delete(redis, &room_id).await?;
+                cleaned.abandoned_rooms += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Delete chat and log keys whose room key is gone, returning how many
+async fn sweep_keys(redis: &ConnectionManager, limits: &Limits) -> AppResult<u64> {
+    let layout = store::layout();
+    let mut conn = redis.clone();
+
+    let mut keys: Vec<(String, String)> = Vec::new();
+    if limits.orphaned_logs {
+        for key in scan(&mut conn, &layout.log_pattern()).await? {
+            if let Some(room_id) = layout.log_room_id(&key) {
+                keys.push((room_id.to_string(), key));
+            }
+        }
+    }
+    if limits.orphaned_chat {
+        for pattern in layout.chat_patterns() {
+            for key in scan(&mut conn, &pattern).await? {
+                if let Some(room_id) = layout.chat_room_id(&key) {
+                    keys.push((room_id.to_string(), key));
+                }
+            }
+        }
+    }
+
+    let mut deleted = 0;
+    for (room_id, key) in keys {
+        if conn.exists(layout.game_key(&room_id)).await? {
+            continue;
+        }
+        let removed: u64 = conn.del(&key).await?;
+        deleted += removed;
+    }
+    Ok(deleted)
+}
+
+async fn scan(conn: &mut ConnectionManager, pattern: &str) -> AppResult<Vec<String>> {
+    let mut iter = conn.scan_match::<_, String>(pattern).await?;
+    let mut keys = Vec::new();
+    while let Some(key) = iter.next_item().await {
+        keys.push(key);
+    }
+    Ok(keys)
+}
@@ -1,13 +1,34 @@
 //! Game module - Core game engine and state machine
 
+pub mod achievements;
 pub mod bankruptcy;
 pub mod board;
+pub mod calibration;
+pub mod cards;
+pub mod challenges;
+pub mod chat;
+pub mod commands;
 mod engine;
 mod events;
+pub mod house_rules;
+pub mod integrity;
+pub mod lifecycle;
+pub mod maintenance;
+pub mod moderation;
+pub mod permissions;
+pub mod presets;
+pub mod simulation;
+pub mod snapshots;
 pub mod state;
+pub mod store;
+pub mod summary;
+pub mod timers;
 pub mod trade;
 
 pub use board::BOARD;
-pub use engine::GameEngine;
-pub use events::{ClientEvent, ServerEvent};
+pub use engine::{GameEngine, SeatPrefs};
+pub(crate) use engine::{MAX_NAME_LEN, PLAYER_AVATARS, PLAYER_COLORS, PLAYER_PIECES};
+pub use events::{ClientEvent, ClientMessage, ServerEvent, ServerMessage};
+pub use house_rules::HouseRule;
 pub use state::*;
+pub use timers::{GameTimers, TimerOverrides};
@@ -0,0 +1,87 @@
+//! Chat moderation: word filter and per-player cooldowns
+
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use redis::aio::ConnectionManager;
+use uuid::Uuid;
+
+use super::store;
+use crate::error::{AppError, AppResult};
+
+/// Shortest gap between two messages from the same player
+const CHAT_COOLDOWN_MS: u64 = 1500;
+
+/// Filtered when no list is configured
+const DEFAULT_BLOCKLIST: &[&str] = &[
+    "fuck", "fucking", "shit", "bitch", "cunt", "asshole", "bastard", "dick", "wanker",
+];
+
+/// Words masked in chat, lowercase
+static BLOCKLIST: Lazy<RwLock<HashSet<String>>> =
+    Lazy::new(|| RwLock::new(DEFAULT_BLOCKLIST.iter().map(|w| w.to_string()).collect()));
+
+/// Replace the filtered words
+pub fn set_blocklist(words: &[String]) {
+    let words = words
+        .iter()
+        .map(|w| w.trim().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect();
+    *BLOCKLIST.write().unwrap_or_else(|e| e.into_inner()) = words;
+}
+
+/// Mask filtered words with asterisks, matching whole words case-insensitively
+pub fn filter(message: &str) -> String {
+    let blocklist = BLOCKLIST.read().unwrap_or_else(|e| e.into_inner());
+    let mut out = String::with_capacity(message.len());
+    let mut word = String::new();
+
+    let flush = |word: &mut String, out: &mut String| {
+        if blocklist.contains(&word.to_lowercase()) {
+            out.extend(word.chars().map(|_| '*'));
+        } else {
+            out.push_str(word);
+        }
+        word.clear();
+    };
+
+    for c in message.chars() {
+        if c.is_alphanumeric() {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut word, &mut out);
+
+    out
+}
+
+/// Claim the player's chat slot, failing if they spoke too recently
+///
+/// Kept in Redis so the cooldown holds across instances.
+pub async fn check_cooldown(
+    redis: &ConnectionManager,
+    room_id: &str,
+    player_id: Uuid,
+) -> AppResult<()> {
+    let mut conn = redis.clone();
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(store::layout().chat_cooldown_key(room_id, player_id))
+        .arg(1)
+        .arg("NX")
+        .arg("PX")
+        .arg(CHAT_COOLDOWN_MS)
+        .query_async(&mut conn)
+        .await?;
+
+    match claimed {
+        Some(_) => Ok(()),
+        None => Err(AppError::BadRequest(
+            "You are sending messages too quickly".into(),
+        )),
+    }
+}
@@ -0,0 +1,414 @@
+//! Who may send which action, and when
+//!
+//! Every action passes `validate_action` before the engine applies it,
+//! whether it comes from a player's socket, a bot, a simulation or a
+//! rollout. This only decides whether the action is allowed at this point
+//! of the game; the handlers still check its details, such as whether the
+//! player can afford it or owns the tile.
+
+use uuid::Uuid;
+
+use super::events::ClientEvent;
+use super::state::{GamePhase, GameState, TurnPhase};
+use crate::error::{AppError, AppResult};
+
+/// Check that a player may send an action in the game's current phase
+///
+/// - Once the room is closed, only acks are accepted.
+/// - Chat and acks are open to every seated player at any time.
+/// - Kicking, muting and unmuting, shadow mutes included, are for the host,
+///   kicking only before the game is over.
+/// - Resigning is open until the game is over.
+/// - Picking a color or a piece and getting ready are only for the lobby.
+/// - Everything else needs a game in progress and a player still in it.
+///   Rolling, paying bail and using a jail card wait for the roll, buying or
+///   passing a property wait for the buy decision, and ending the turn waits
+///   for the end of the turn, all for the current player only; a player who
+///   went bankrupt on their own turn may still end it.
+/// - Bids and passes need an auction, and are open to every player.
+/// - Building, selling buildings, (un)mortgaging and trading are open to
+///   every player, unless `manage_on_own_turn_only` limits property
+///   management to the current player outside auctions.
+pub fn validate_action(game: &GameState, player_id: Uuid, event: &ClientEvent) -> AppResult<()> {
+    use ClientEvent::*;
+
+    // Players who left (or whose seat went to a bot) can no longer act
+    let player = game
+        .get_player(player_id)
+        .ok_or_else(|| AppError::Forbidden("Not a player in this game".into()))?;
+
+    if game.closed_until.is_some() && !matches!(event, Ack { .. }) {
+        return Err(AppError::GameError("Room is closed".into()));
+    }
+
+    match event {
+        Chat { .. } | Ack { .. } => return Ok(()),
+        Kick { .. } => {
+            if !player.is_host {
+                return Err(AppError::Forbidden("Only the host can kick players".into()));
+            }
+            return ensure_not_over(game);
+        }
+        Mute { .. } | Unmute { .. } | ShadowMute { .. } | ShadowUnmute { .. } => {
+            if !player.is_host {
+                return Err(AppError::Forbidden("Only the host can mute players".into()));
+            }
+            return Ok(());
+        }
+        Resign => return ensure_not_over(game),
+        PickColor { .. } | PickPiece { .. } | SetReady { .. } => {
+            if game.phase != GamePhase::Lobby {
+                return Err(AppError::GameError("Game already started".into()));
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    if game.phase != GamePhase::Playing {
+        return Err(AppError::GameError("Game is not in progress".into()));
+    }
+    let turn = game
+        .turn
+        .as_ref()
+        .ok_or_else(|| AppError::GameError("No active turn".into()))?;
+    let is_current_player = turn.player_id == player_id;
+
+    if player.is_bankrupt && !(is_current_player && matches!(event, EndTurn)) {
+        return Err(AppError::Forbidden("Bankrupt players can only chat".into()));
+    }
+
+    match event {
+        RollDice | PayJail | UseCard | PayDebt | DeclareBankruptcy | BuyProperty | PassProperty
+        | EndTurn => {
+            if !is_current_player {
+                return Err(AppError::Forbidden("Not your turn".into()));
+            }
+            let (phase, message) = match event {
+                RollDice => (TurnPhase::WaitingForRoll, "Cannot roll now"),
+                PayJail | UseCard => (TurnPhase::WaitingForRoll, "Cannot leave jail now"),
+                PayDebt | DeclareBankruptcy => (TurnPhase::Bankruptcy, "No debt to settle"),
+                BuyProperty => (TurnPhase::BuyDecision, "Cannot buy now"),
+                PassProperty => (TurnPhase::BuyDecision, "Cannot start auction now"),
+                _ => (TurnPhase::TurnEnd, "Cannot end turn now"),
+            };
+            if turn.phase != phase {
+                return Err(AppError::GameError(message.into()));
+            }
+        }
+        Bid { .. } | PassBid if game.auction.is_none() => {
+            return Err(AppError::GameError("No auction in progress".into()));
+        }
+        event if event.is_property_management() && game.config.manage_on_own_turn_only => {
+            if !is_current_player {
+                return Err(AppError::Forbidden(
+                    "Properties can only be managed on your own turn".into(),
+                ));
+            }
+            if game.auction.is_some() {
+                return Err(AppError::GameError(
+                    "Properties cannot be managed during an auction".into(),
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Decisions the game is waiting for from a player: rolling or leaving
+/// jail, settling a debt, buying, bidding and ending the turn, by event type
+///
+/// Empty while the game does not wait on them. Actions open at any time,
+/// such as building or trading, are left out.
+pub fn pending_actions(game: &GameState, player_id: Uuid) -> Vec<String> {
+    use ClientEvent::*;
+
+    [
+        RollDice,
+        PayJail,
+        UseCard,
+        PayDebt,
+        DeclareBankruptcy,
+        BuyProperty,
+        PassProperty,
+        Bid { amount: 0 },
+        PassBid,
+        EndTurn,
+    ]
+    .into_iter()
+    .filter(|event| validate_action(game, player_id, event).is_ok())
+    .filter_map(|event| event.type_name())
+    .collect()
+}
+
+fn ensure_not_over(game: &GameState) -> AppResult<()> {
+    if game.phase == GamePhase::GameOver {
+        return Err(AppError::GameError("Game is over".into()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::{
+        AuctionState, GameConfig, Player, TradeAssets, TradeOffer as Offer, TradeStatus, TurnState,
+    };
+
+    /// The host on turn, a second player waiting and a bankrupt third
+    fn game(phase: GamePhase, turn_phase: TurnPhase) -> (GameState, [Uuid; 3]) {
+        let mut game = GameState::new("TEST".into(), GameConfig::default());
+        let ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        for (i, &id) in ids.iter().enumerate() {
+            game.players.push(Player::new(
+                id,
+                format!("P{}", i),
+                "#fff".into(),
+                i == 0,
+                false,
+            ));
+        }
+        game.players[2].is_bankrupt = true;
+        game.phase = phase;
+        if phase == GamePhase::Playing {
+            let mut turn = TurnState::new(ids[0]);
+            turn.phase = turn_phase;
+            game.turn = Some(turn);
+            if turn_phase == TurnPhase::Auction {
+                game.auction = Some(AuctionState::new(1));
+            }
+        }
+        (game, ids)
+    }
+
+    fn all_events(other: Uuid) -> Vec<ClientEvent> {
+        use ClientEvent::*;
+        let offer = Offer {
+            id: Uuid::new_v4(),
+            from_player: Uuid::nil(),
+            to_player: other,
+            offering: TradeAssets::default(),
+            requesting: TradeAssets::default(),
+            status: TradeStatus::Pending,
+            counter_to: None,
+        };
+        vec![
+            RollDice,
+            BuyProperty,
+            PassProperty,
+            EndTurn,
+            Bid { amount: 10 },
+            PassBid,
+            PayJail,
+            UseCard,
+            PayDebt,
+            DeclareBankruptcy,
+            Build { tile_idx: 1 },
+            SellBuilding { tile_idx: 1 },
+            Mortgage { tile_idx: 1 },
+            Unmortgage { tile_idx: 1 },
+            TradeOffer {
+                offer: offer.clone(),
+            },
+            TradeAccept { trade_id: offer.id },
+            TradeReject { trade_id: offer.id },
+            TradeCounter {
+                trade_id: offer.id,
+                offer,
+            },
+            Chat {
+                message: "hi".into(),
+            },
+            Ack { seq: 1 },
+            Resign,
+            Kick { player_id: other },
+            Mute { player_id: other },
+            Unmute { player_id: other },
+            ShadowMute { player_id: other },
+            ShadowUnmute { player_id: other },
+            PickColor {
+                color: "#FF5733".into(),
+            },
+            PickPiece {
+                piece: "hat".into(),
+            },
+            SetReady { ready: true },
+        ]
+    }
+
+    /// Names of the events a player may send
+    fn allowed(game: &GameState, player_id: Uuid, other: Uuid) -> Vec<String> {
+        all_events(other)
+            .into_iter()
+            .filter(|event| validate_action(game, player_id, event).is_ok())
+            .filter_map(|event| event.type_name())
+            .collect()
+    }
+
+    const SOCIAL: [&str; 2] = ["CHAT", "ACK"];
+    const MODERATION: [&str; 6] = [
+        "RESIGN",
+        "KICK",
+        "MUTE",
+        "UNMUTE",
+        "SHADOW_MUTE",
+        "SHADOW_UNMUTE",
+    ];
+    const ANY_TIME: [&str; 8] = [
+        "BUILD",
+        "SELL_BUILDING",
+        "MORTGAGE",
+        "UNMORTGAGE",
+        "TRADE_OFFER",
+        "TRADE_ACCEPT",
+        "TRADE_REJECT",
+        "TRADE_COUNTER",
+    ];
+
+    fn expect(lists: &[&[&str]]) -> Vec<String> {
+        let mut names: Vec<String> = lists.concat().into_iter().map(String::from).collect();
+        names.sort();
+        names
+    }
+
+    fn sorted(mut names: Vec<String>) -> Vec<String> {
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn outsiders_can_do_nothing() {
+        let (game, ids) = game(GamePhase::Playing, TurnPhase::WaitingForRoll);
+        assert!(allowed(&game, Uuid::new_v4(), ids[1]).is_empty());
+    }
+
+    #[test]
+    fn lobby_and_game_over() {
+        for phase in [GamePhase::Lobby, GamePhase::RollingOrder] {
+            let (game, ids) = game(phase, TurnPhase::WaitingForRoll);
+            let colors: &[&str] = if phase == GamePhase::Lobby {
+                &["PICK_COLOR", "PICK_PIECE", "SET_READY"]
+            } else {
+                &[]
+            };
+            assert_eq!(
+                sorted(allowed(&game, ids[0], ids[1])),
+                expect(&[&SOCIAL, &MODERATION, colors])
+            );
+            assert_eq!(
+                sorted(allowed(&game, ids[1], ids[0])),
+                expect(&[&SOCIAL, &["RESIGN"], colors])
+            );
+        }
+
+        let (game, ids) = game(GamePhase::GameOver, TurnPhase::WaitingForRoll);
+        assert_eq!(
+            sorted(allowed(&game, ids[0], ids[1])),
+            expect(&[&SOCIAL, &["MUTE", "UNMUTE", "SHADOW_MUTE", "SHADOW_UNMUTE"]])
+        );
+        assert_eq!(sorted(allowed(&game, ids[1], ids[0])), expect(&[&SOCIAL]));
+    }
+
+    #[test]
+    fn closed_rooms_are_read_only() {
+        let (mut game, ids) = game(GamePhase::GameOver, TurnPhase::WaitingForRoll);
+        game.closed_until = Some(0);
+        assert_eq!(allowed(&game, ids[0], ids[1]), vec!["ACK".to_string()]);
+        assert_eq!(allowed(&game, ids[1], ids[0]), vec!["ACK".to_string()]);
+    }
+
+    #[test]
+    fn turn_phases() {
+        let cases: [(TurnPhase, &[&str]); 7] = [
+            (
+                TurnPhase::WaitingForRoll,
+                &["ROLL_DICE", "PAY_JAIL", "USE_CARD"],
+            ),
+            (TurnPhase::Moving, &[]),
+            (TurnPhase::BuyDecision, &["BUY_PROPERTY", "PASS_PROPERTY"]),
+            (TurnPhase::Auction, &["BID", "PASS_BID"]),
+            (TurnPhase::PayingRent, &[]),
+            (TurnPhase::Bankruptcy, &["PAY_DEBT", "DECLARE_BANKRUPTCY"]),
+            (TurnPhase::TurnEnd, &["END_TURN"]),
+        ];
+
+        for (phase, current_only) in cases {
+            let (game, ids) = game(GamePhase::Playing, phase);
+            let bids: &[&str] = if phase == TurnPhase::Auction {
+                &["BID", "PASS_BID"]
+            } else {
+                &[]
+            };
+
+            assert_eq!(
+                sorted(allowed(&game, ids[0], ids[1])),
+                expect(&[&SOCIAL, &MODERATION, &ANY_TIME, current_only]),
+                "current player in {:?}",
+                phase
+            );
+            assert_eq!(
+                sorted(allowed(&game, ids[1], ids[0])),
+                expect(&[&SOCIAL, &["RESIGN"], &ANY_TIME, bids]),
+                "other player in {:?}",
+                phase
+            );
+        }
+    }
+
+    #[test]
+    fn bankrupt_players_only_chat() {
+        let (mut game, ids) = game(GamePhase::Playing, TurnPhase::Auction);
+        assert_eq!(
+            sorted(allowed(&game, ids[2], ids[0])),
+            expect(&[&SOCIAL, &["RESIGN"]])
+        );
+
+        // Going bankrupt on your own turn still lets you hand it on
+        game.auction = None;
+        game.turn = Some(TurnState {
+            phase: TurnPhase::TurnEnd,
+            ..TurnState::new(ids[2])
+        });
+        assert_eq!(
+            sorted(allowed(&game, ids[2], ids[0])),
+            expect(&[&SOCIAL, &["RESIGN", "END_TURN"]])
+        );
+    }
+
+    #[test]
+    fn pending_decisions() {
+        let (buying, ids) = game(GamePhase::Playing, TurnPhase::BuyDecision);
+        assert_eq!(
+            pending_actions(&buying, ids[0]),
+            ["BUY_PROPERTY", "PASS_PROPERTY"]
+        );
+        assert!(pending_actions(&buying, ids[1]).is_empty());
+
+        let (auction, ids) = game(GamePhase::Playing, TurnPhase::Auction);
+        for id in &ids[..2] {
+            assert_eq!(pending_actions(&auction, *id), ["BID", "PASS_BID"]);
+        }
+        assert!(pending_actions(&auction, ids[2]).is_empty());
+    }
+
+    #[test]
+    fn managing_on_own_turn_only() {
+        let management = ["BUILD", "SELL_BUILDING", "MORTGAGE", "UNMORTGAGE"];
+
+        for phase in [TurnPhase::TurnEnd, TurnPhase::Auction] {
+            let (mut game, ids) = game(GamePhase::Playing, phase);
+            game.config.manage_on_own_turn_only = true;
+
+            let current = allowed(&game, ids[0], ids[1]);
+            let other = allowed(&game, ids[1], ids[0]);
+            for name in management {
+                let name = name.to_string();
+                assert_eq!(current.contains(&name), phase != TurnPhase::Auction);
+                assert!(!other.contains(&name));
+            }
+            assert!(other.contains(&"TRADE_OFFER".to_string()));
+        }
+    }
+}
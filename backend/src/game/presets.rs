@@ -0,0 +1,63 @@
+//! Named bundles of room options
+//!
+//! A preset sets how long a game runs: starting cash, properties dealt at
+//! the start, houses needed for a hotel, the turn time limit and the round
+//! limit. Rooms created with one keep every other option they ask for.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::state::GameConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// The standard game, played until one player is left
+    Classic,
+    /// About an hour: a few properties dealt and a round limit
+    Short,
+    /// Quick games with less cash, cheap hotels and tight turns
+    Blitz,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Classic, Preset::Short, Preset::Blitz];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Classic => "Classic",
+            Preset::Short => "Short",
+            Preset::Blitz => "Blitz",
+        }
+    }
+
+    /// Set the options this preset bundles, leaving the rest of `config`
+    pub fn apply(self, config: &mut GameConfig) {
+        let classic = GameConfig::default();
+        let (starting_cash, dealt_properties, houses_per_hotel, turn_secs, round_limit) = match self
+        {
+            Preset::Classic => (
+                classic.starting_cash,
+                classic.dealt_properties,
+                classic.houses_per_hotel,
+                None,
+                classic.round_limit,
+            ),
+            Preset::Short => (1500, 2, 3, Some(90), 40),
+            Preset::Blitz => (1000, 3, 2, Some(30), 20),
+        };
+
+        config.starting_cash = starting_cash;
+        config.dealt_properties = dealt_properties;
+        config.houses_per_hotel = houses_per_hotel;
+        config.timers.turn_secs = turn_secs;
+        config.round_limit = round_limit;
+    }
+
+    /// The default config with this preset applied
+    pub fn config(self) -> GameConfig {
+        let mut config = GameConfig::default();
+        self.apply(&mut config);
+        config
+    }
+}
@@ -0,0 +1,393 @@
+//! Headless bot-vs-bot games
+//!
+//! Runs the same rules as live rooms, without Redis or WebSockets, so bot
+//! changes can be measured offline. Games are seeded and reproducible.
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::board::{self, BoardDef, TileType};
+use super::engine::{GameEngine, BOT_NAMES, PLAYER_COLORS, PLAYER_PIECES};
+use super::events::{ClientEvent, ServerEvent};
+use super::state::{GameConfig, GamePhase, GameState, Player};
+use crate::bot::{BotAI, BotDifficulty, BotParams, BotPersonality};
+
+/// Default cap on turns before a game is called unfinished
+pub const DEFAULT_MAX_TURNS: u32 = 1000;
+
+/// Actions allowed per turn before a game is assumed stuck
+const MAX_ACTIONS_PER_TURN: u32 = 64;
+
+/// Parameters for a single simulated game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationParams {
+    pub seed: u64,
+    pub bots: u8,
+    pub config: GameConfig,
+    pub max_turns: u32,
+    pub record_replay: bool,
+    /// Difficulty per seat; seats past the end play `Normal`
+    #[serde(default)]
+    pub difficulties: Vec<BotDifficulty>,
+    /// Per-seat overrides for experiments; seats past the end keep defaults
+    #[serde(default)]
+    pub seats: Vec<SeatSetup>,
+}
+
+/// Overrides for one simulated seat
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeatSetup {
+    pub personality: Option<BotPersonality>,
+    pub params: Option<BotParams>,
+}
+
+/// One action taken during a simulated game
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStep {
+    pub player_id: Uuid,
+    pub event: ClientEvent,
+}
+
+/// What pushed a player into bankruptcy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BankruptcyCause {
+    Rent,
+    Tax,
+    Bail,
+    Other,
+}
+
+/// Final standing of a simulated player
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedPlayer {
+    pub id: Uuid,
+    pub name: String,
+    pub difficulty: BotDifficulty,
+    pub personality: BotPersonality,
+    pub balance: i32,
+    pub net_worth: i32,
+    pub properties: usize,
+    pub is_bankrupt: bool,
+    pub bankruptcy_cause: Option<BankruptcyCause>,
+}
+
+/// Outcome of a simulated game
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationResult {
+    pub seed: u64,
+    pub turns: u32,
+    pub finished: bool,
+    pub winner: Option<Uuid>,
+    /// Rule error that stopped the game early, if any
+    pub error: Option<String>,
+    pub players: Vec<SimulatedPlayer>,
+    /// Board and version the game was played on
+    pub board_id: String,
+    pub board_version: i32,
+    /// Times a token ended a move on each tile, indexed by tile
+    pub tile_landings: Vec<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay: Option<Vec<ReplayStep>>,
+}
+
+/// Play a full bot-only game
+pub fn run(params: &SimulationParams) -> SimulationResult {
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut game = GameState::new(format!("SIM-{}", params.seed), params.config.clone());
+
+    for i in 0..params.bots as usize {
+        // Ids come from the seeded RNG so replays are identical across runs
+        let id = uuid::Builder::from_random_bytes(rng.gen()).into_uuid();
+        let name = format!("{} (Bot)", BOT_NAMES[i % BOT_NAMES.len()]);
+        let color = PLAYER_COLORS[i % PLAYER_COLORS.len()].to_string();
+        let mut player = Player::new(id, name, color, i == 0, true);
+        player.piece = Some(PLAYER_PIECES[i % PLAYER_PIECES.len()].to_string());
+        player.bot_difficulty = params.difficulties.get(i).copied().unwrap_or_default();
+        player.bot_personality = BotPersonality::for_seat(i);
+        if let Some(seat) = params.seats.get(i) {
+            if let Some(personality) = seat.personality {
+                player.bot_personality = personality;
+            }
+            player.bot_params = seat.params;
+        }
+        game.players.push(player);
+    }
+
+    let mut replay = params.record_replay.then(Vec::new);
+    let board = game.board();
+    let mut tile_landings = vec![0; board.tiles.len()];
+    let mut causes: HashMap<Uuid, BankruptcyCause> = HashMap::new();
+    let mut turns = 0;
+    let mut error = None;
+
+    if let Err(e) = GameEngine::setup_game(&mut game, &mut rng) {
+        error = Some(e.to_string());
+    }
+
+    let mut actions_this_turn = 0;
+    while error.is_none() && game.phase == GamePhase::Playing && turns < params.max_turns {
+        let Some((player_id, event)) = BotAI::next_action(&game) else {
+            error = Some("No bot action available".into());
+            break;
+        };
+
+        if let Some(replay) = replay.as_mut() {
+            replay.push(ReplayStep {
+                player_id,
+                event: event.clone(),
+            });
+        }
+
+        // Positions as each event happened, to tell what a bankruptcy was for
+        let mut positions: HashMap<Uuid, u8> =
+            game.players.iter().map(|p| (p.id, p.position)).collect();
+
+        let mut events = Vec::new();
+        if let Err(e) = GameEngine::apply_event(&mut game, player_id, event, &mut rng, &mut events)
+        {
+            error = Some(e.to_string());
+            break;
+        }
+
+        let mut turn_over = false;
+        for event in &events {
+            match event {
+                ServerEvent::PlayerMoved { player_id, to, .. } => {
+                    positions.insert(*player_id, *to);
+                    if let Some(count) = tile_landings.get_mut(*to as usize) {
+                        *count += 1;
+                    }
+                }
+                ServerEvent::Bankruptcy {
+                    player_id,
+                    creditor,
+                } => {
+                    let position = positions.get(player_id).copied();
+                    causes.insert(*player_id, bankruptcy_cause(board, position, *creditor));
+                }
+                ServerEvent::TurnChanged { .. } | ServerEvent::GameOver { .. } => {
+                    turn_over = true;
+                }
+                _ => {}
+            }
+        }
+
+        if turn_over {
+            turns += 1;
+            actions_this_turn = 0;
+        } else {
+            actions_this_turn += 1;
+            if actions_this_turn > MAX_ACTIONS_PER_TURN {
+                error = Some("Turn did not end".into());
+            }
+        }
+    }
+
+    let finished = game.phase == GamePhase::GameOver;
+    let winner = if finished {
+        game.players.iter().find(|p| !p.is_bankrupt).map(|p| p.id)
+    } else {
+        None
+    };
+
+    let players = game
+        .players
+        .iter()
+        .map(|p| SimulatedPlayer {
+            id: p.id,
+            name: p.name.clone(),
+            difficulty: p.bot_difficulty,
+            personality: p.bot_personality,
+            balance: p.balance,
+            net_worth: game.net_worth(p.id),
+            properties: game
+                .properties
+                .values()
+                .filter(|prop| prop.owner == Some(p.id))
+                .count(),
+            is_bankrupt: p.is_bankrupt,
+            bankruptcy_cause: causes.get(&p.id).copied(),
+        })
+        .collect();
+
+    SimulationResult {
+        seed: params.seed,
+        turns,
+        finished,
+        winner,
+        error,
+        players,
+        board_id: game.board_id.clone(),
+        board_version: game.board_version,
+        tile_landings,
+        replay,
+    }
+}
+
+/// Work out what a bankruptcy was for from where the player stood
+fn bankruptcy_cause(
+    board: &BoardDef,
+    position: Option<u8>,
+    creditor: Option<Uuid>,
+) -> BankruptcyCause {
+    if creditor.is_some() {
+        return BankruptcyCause::Rent;
+    }
+
+    let tile_type = position
+        .and_then(|idx| board.tile(idx))
+        .map(|t| t.tile_type);
+
+    match tile_type {
+        Some(TileType::Tax) => BankruptcyCause::Tax,
+        // Forced bail is charged before the token leaves jail
+        Some(TileType::Jail) => BankruptcyCause::Bail,
+        _ => BankruptcyCause::Other,
+    }
+}
+
+/// Aggregate numbers over a batch of simulated games
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulationStats {
+    pub games: u32,
+    pub finished: u32,
+    pub errored: u32,
+    pub average_turns: f64,
+    /// Length of games that reached a winner
+    pub finished_turns: Option<TurnRange>,
+    pub personalities: Vec<PersonalityStats>,
+    pub tiles: Vec<TileStats>,
+    pub bankruptcies: Vec<BankruptcyStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnRange {
+    pub min: u32,
+    pub max: u32,
+    pub average: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonalityStats {
+    pub personality: BotPersonality,
+    pub seats: u32,
+    pub wins: u32,
+    pub win_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileStats {
+    pub tile_idx: u8,
+    pub name: String,
+    pub landings: u32,
+    /// Share of all landings in the batch
+    pub frequency: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankruptcyStats {
+    pub cause: String,
+    pub count: u32,
+}
+
+/// Summarize a batch of results
+pub fn aggregate(results: &[SimulationResult]) -> SimulationStats {
+    let games = results.len() as u32;
+    if games == 0 {
+        return SimulationStats::default();
+    }
+
+    let finished: Vec<&SimulationResult> = results.iter().filter(|r| r.finished).collect();
+    let total_turns: u64 = results.iter().map(|r| r.turns as u64).sum();
+
+    let finished_turns = (!finished.is_empty()).then(|| TurnRange {
+        min: finished.iter().map(|r| r.turns).min().unwrap_or(0),
+        max: finished.iter().map(|r| r.turns).max().unwrap_or(0),
+        average: finished.iter().map(|r| r.turns as f64).sum::<f64>() / finished.len() as f64,
+    });
+
+    let mut personalities: Vec<PersonalityStats> = Vec::new();
+    for result in results {
+        for player in &result.players {
+            let idx = match personalities
+                .iter()
+                .position(|s| s.personality == player.personality)
+            {
+                Some(i) => i,
+                None => {
+                    personalities.push(PersonalityStats {
+                        personality: player.personality,
+                        seats: 0,
+                        wins: 0,
+                        win_rate: 0.0,
+                    });
+                    personalities.len() - 1
+                }
+            };
+            personalities[idx].seats += 1;
+            if result.winner == Some(player.id) {
+                personalities[idx].wins += 1;
+            }
+        }
+    }
+    for stats in &mut personalities {
+        stats.win_rate = stats.wins as f64 / stats.seats as f64;
+    }
+
+    // A batch is played on one board
+    let board = board::definition(&results[0].board_id, results[0].board_version)
+        .unwrap_or_else(board::default_board);
+    let mut landings = vec![0u32; board.tiles.len()];
+    for result in results {
+        for (total, count) in landings.iter_mut().zip(&result.tile_landings) {
+            *total += count;
+        }
+    }
+    let total_landings: u32 = landings.iter().sum();
+    let tiles = board
+        .tiles
+        .iter()
+        .zip(&landings)
+        .map(|(tile, &count)| TileStats {
+            tile_idx: tile.index,
+            name: tile.name.clone(),
+            landings: count,
+            frequency: if total_landings == 0 {
+                0.0
+            } else {
+                count as f64 / total_landings as f64
+            },
+        })
+        .collect();
+
+    let mut causes: HashMap<BankruptcyCause, u32> = HashMap::new();
+    for player in results.iter().flat_map(|r| &r.players) {
+        if let Some(cause) = player.bankruptcy_cause {
+            *causes.entry(cause).or_default() += 1;
+        }
+    }
+    let mut bankruptcies: Vec<BankruptcyStats> = causes
+        .into_iter()
+        .map(|(cause, count)| BankruptcyStats {
+            cause: format!("{:?}", cause).to_lowercase(),
+            count,
+        })
+        .collect();
+    bankruptcies.sort_by(|a, b| b.count.cmp(&a.count).then(a.cause.cmp(&b.cause)));
+
+    SimulationStats {
+        games,
+        finished: finished.len() as u32,
+        errored: results.iter().filter(|r| r.error.is_some()).count() as u32,
+        average_turns: total_turns as f64 / games as f64,
+        finished_turns,
+        personalities,
+        tiles,
+        bankruptcies,
+    }
+}
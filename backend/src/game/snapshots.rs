@@ -0,0 +1,94 @@
+//! Snapshots of idle games
+//!
+//! Started rooms live in Redis for `GAME_TTL_SECS` after their last change.
+//! Casual games can sit untouched for longer than that, so a background job
+//! copies rooms that have gone quiet into Postgres, and `GameEngine::get_game`
+//! brings them back when someone returns after the Redis key expired.
+//! Lobbies are left to expire: nobody is waiting on a game that never began.
+
+use std::time::Duration;
+
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use sqlx::PgPool;
+
+use super::store;
+use super::{GameEngine, GamePhase, LOG_LEN};
+use crate::db;
+use crate::error::AppResult;
+
+/// How often rooms are checked
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Rooms untouched for this long get a snapshot
+const IDLE_SECS: i64 = 30 * 60;
+
+/// Snapshots nobody came back to are dropped after a month
+const SNAPSHOT_RETENTION_DAYS: i32 = 30;
+
+/// Start snapshotting idle rooms in the background
+pub fn spawn(redis: ConnectionManager, pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            match sweep(&redis, &pool).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Snapshotted {} idle rooms", n),
+                Err(e) => tracing::warn!("Snapshotting idle rooms failed: {}", e),
+            }
+            match db::snapshots::purge_older_than(&pool, SNAPSHOT_RETENTION_DAYS).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Dropped {} stale snapshots", n),
+                Err(e) => tracing::warn!("Dropping stale snapshots failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Snapshot every in-progress room that has gone quiet, returning how many
+/// changed since their last snapshot
+async fn sweep(redis: &ConnectionManager, pool: &PgPool) -> AppResult<usize> {
+    let layout = store::layout();
+    let mut conn = redis.clone();
+    let keys: Vec<String> = {
+        let mut iter = conn.scan_match::<_, String>(layout.game_pattern()).await?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        keys
+    };
+
+    let mut saved = 0;
+    for key in keys {
+        let Some(room_id) = layout.room_id(&key) else {
+            continue;
+        };
+
+        // Every save resets the expiry, so the TTL tells how long a room
+        // has been left alone. Rooms with a lifetime of their own can only
+        // be told apart once loaded.
+        let ttl: i64 = conn.ttl(&key).await?;
+        if ttl < 0 {
+            continue;
+        }
+
+        let Some(mut game) = GameEngine::get_game(redis, room_id).await? else {
+            continue;
+        };
+        if matches!(game.phase, GamePhase::Lobby | GamePhase::GameOver)
+            || (layout.ttl_for(&game) as i64) - ttl < IDLE_SECS
+        {
+            continue;
+        }
+        // The log lives apart from the state; the snapshot keeps both, and
+        // restoring saves the lines back into the room's log
+        game.logs = GameEngine::get_logs(redis, room_id, LOG_LEN).await?;
+        if db::snapshots::save(pool, &game).await? {
+            saved += 1;
+        }
+    }
+
+    Ok(saved)
+}
@@ -1,19 +1,149 @@
 //! Game state types and structures
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use super::board::{self, BoardDef, Tile};
+use super::cards::DeckOrder;
+use super::integrity::Integrity;
+use super::summary::RoundSample;
+use super::timers::{GameTimers, TimerOverrides};
+use crate::bot::{BotDifficulty, BotPace, BotParams, BotPersonality};
+use crate::error::FieldErrors;
+use crate::i18n::{self, Locale};
+
+/// Log lines kept per room
+pub const LOG_LEN: usize = 100;
+
+/// Seats a room may have; the upper end is one per player color
+pub const MAX_PLAYERS: RangeInclusive<u8> = 2..=8;
+
+/// Cash each player may start with
+pub const STARTING_CASH: RangeInclusive<i32> = 100..=100_000;
+
+/// Failed rolls a room may allow in jail before the forced bail
+pub const MAX_JAIL_TURNS: RangeInclusive<u8> = 1..=10;
+
+/// Properties a room may deal to each player at the start
+pub const DEALT_PROPERTIES: RangeInclusive<u8> = 0..=10;
+
+/// Houses a room may ask for before the next build makes a hotel
+pub const HOUSES_PER_HOTEL: RangeInclusive<u8> = 1..=4;
+
+/// Rounds a room may cap its game at; 0 plays until one player is left
+pub const ROUND_LIMIT: RangeInclusive<u32> = 0..=500;
+
+/// Dice a room may roll each turn
+pub const DICE_COUNT: RangeInclusive<u8> = 1..=4;
+
+/// Sides each die may have
+pub const DICE_SIDES: RangeInclusive<u8> = 4..=12;
+
+/// Paid for passing GO, before inflation
+pub const GO_SALARY: i32 = 200;
+
+/// Rounds between inflation raises a room may ask for; 0 turns it off
+pub const INFLATION_ROUNDS: RangeInclusive<u32> = 0..=100;
+
+/// Size of each inflation raise, in percent of the base amount
+pub const INFLATION_PERCENT: RangeInclusive<u32> = 1..=100;
+
 /// Game configuration options
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct GameConfig {
     pub max_players: u8,
     pub starting_cash: i32,
     pub free_parking_jackpot: bool,
     pub auction_on_decline: bool,
+    /// Auction the properties of a player who resigns or is kicked, one
+    /// after another, instead of leaving them with the bank
+    #[serde(default = "default_auction_forfeited_properties")]
+    pub auction_forfeited_properties: bool,
     pub collect_rent_in_jail: bool,
     pub even_build_rule: bool,
     pub double_rent_on_full_set: bool,
+    /// Hand the seat of a player who leaves mid-game to a bot instead of
+    /// returning their assets to the bank
+    #[serde(default)]
+    pub bot_replaces_leavers: bool,
+    /// Let bots post short chat messages about what happens to them
+    #[serde(default)]
+    pub bot_chat: bool,
+    #[serde(default)]
+    pub bot_pace: BotPace,
+    /// Broadcast the reasoning behind every bot decision
+    #[serde(default)]
+    pub bot_debug: bool,
+    /// House rule: players only see their own get-out-of-jail cards
+    #[serde(default)]
+    pub hide_jail_cards: bool,
+    /// Turn off the chat word filter
+    #[serde(default)]
+    pub allow_profanity: bool,
+    /// Building, selling buildings and (un)mortgaging only on your own
+    /// turn, and not while an auction runs
+    #[serde(default)]
+    pub manage_on_own_turn_only: bool,
+    /// Random unowned properties dealt to each player for free when the
+    /// game starts, as in the official short game; fewer when the board
+    /// runs out
+    #[serde(default)]
+    pub dealt_properties: u8,
+    /// Dice rolled each turn; doubles need every die to match, so a single
+    /// die never rolls them
+    #[serde(default = "default_dice_count")]
+    pub dice_count: u8,
+    #[serde(default = "default_dice_sides")]
+    pub dice_sides: u8,
+    /// Seats filled with bots when the lobby countdown of
+    /// `timers.auto_start_secs` runs out; 0 fills every seat
+    #[serde(default)]
+    pub auto_start_players: u8,
+    /// Houses a property needs before the next build makes it a hotel
+    #[serde(default = "default_houses_per_hotel")]
+    pub houses_per_hotel: u8,
+    /// End the game after this many rounds, won by the richest player
+    /// still standing; 0 plays until one player is left
+    #[serde(default)]
+    pub round_limit: u32,
+    /// Failed rolls in jail before `jail_forced_bail` applies
+    #[serde(default = "default_max_jail_turns")]
+    pub max_jail_turns: u8,
+    /// Pay bail after `max_jail_turns` failed rolls and move on, running up a
+    /// debt to raise it when short; when off, players stay in jail until they
+    /// roll doubles, pay or use a card
+    #[serde(default = "default_jail_forced_bail")]
+    pub jail_forced_bail: bool,
+    /// Raise the GO salary every this many rounds; 0 leaves it fixed
+    #[serde(default)]
+    pub inflation_rounds: u32,
+    /// Each raise adds this percentage of the base amount
+    #[serde(default = "default_inflation_percent")]
+    pub inflation_percent: u32,
+    /// Raise taxes along with the GO salary
+    #[serde(default)]
+    pub inflation_taxes: bool,
+    /// Doubles do not earn another roll
+    #[serde(default)]
+    pub no_roll_again_on_doubles: bool,
+    /// Rolling doubles three times in one turn does not send you to jail
+    #[serde(default)]
+    pub no_jail_on_three_doubles: bool,
+    /// Counts towards ratings: connections are checked for seats sharing a
+    /// device, and a game where they do is stored as unrated
+    #[serde(default)]
+    pub ranked: bool,
+    /// Time limits set for this room; the server's defaults fill the rest
+    /// when the room is created
+    #[serde(default)]
+    pub timers: TimerOverrides,
+    /// Board to play on, from `/api/boards`; the default board when unset.
+    /// The newest version is used and kept for the whole game.
+    #[serde(default)]
+    pub board_id: Option<String>,
 }
 
 impl Default for GameConfig {
@@ -23,10 +153,202 @@ impl Default for GameConfig {
             starting_cash: 1500,
             free_parking_jackpot: false,
             auction_on_decline: true,
+            auction_forfeited_properties: true,
             collect_rent_in_jail: false,
             even_build_rule: true,
             double_rent_on_full_set: true,
+            bot_replaces_leavers: false,
+            bot_chat: false,
+            bot_pace: BotPace::default(),
+            bot_debug: false,
+            hide_jail_cards: false,
+            allow_profanity: false,
+            manage_on_own_turn_only: false,
+            dealt_properties: 0,
+            dice_count: 2,
+            dice_sides: 6,
+            auto_start_players: 0,
+            houses_per_hotel: 4,
+            round_limit: 0,
+            max_jail_turns: 3,
+            jail_forced_bail: true,
+            inflation_rounds: 0,
+            inflation_percent: 10,
+            inflation_taxes: false,
+            no_roll_again_on_doubles: false,
+            no_jail_on_three_doubles: false,
+            ranked: false,
+            timers: TimerOverrides::default(),
+            board_id: None,
+        }
+    }
+}
+
+fn default_auction_forfeited_properties() -> bool {
+    true
+}
+
+fn default_dice_count() -> u8 {
+    2
+}
+
+fn default_dice_sides() -> u8 {
+    6
+}
+
+fn default_houses_per_hotel() -> u8 {
+    4
+}
+
+fn default_max_jail_turns() -> u8 {
+    3
+}
+
+fn default_jail_forced_bail() -> bool {
+    true
+}
+
+fn default_inflation_percent() -> u32 {
+    10
+}
+
+impl GameConfig {
+    /// Check the options a new room asks for; time limits are checked once
+    /// merged with the server's defaults
+    pub fn validate(&self) -> FieldErrors {
+        let mut errors = FieldErrors::new();
+        if !MAX_PLAYERS.contains(&self.max_players) {
+            errors.add(
+                "max_players",
+                "out_of_range",
+                format!(
+                    "max_players must be {} to {}",
+                    MAX_PLAYERS.start(),
+                    MAX_PLAYERS.end()
+                ),
+            );
+        }
+        if !STARTING_CASH.contains(&self.starting_cash) {
+            errors.add(
+                "starting_cash",
+                "out_of_range",
+                format!(
+                    "starting_cash must be {} to {}",
+                    STARTING_CASH.start(),
+                    STARTING_CASH.end()
+                ),
+            );
+        }
+        if !DEALT_PROPERTIES.contains(&self.dealt_properties) {
+            errors.add(
+                "dealt_properties",
+                "out_of_range",
+                format!(
+                    "dealt_properties must be {} to {}",
+                    DEALT_PROPERTIES.start(),
+                    DEALT_PROPERTIES.end()
+                ),
+            );
+        }
+        if !DICE_COUNT.contains(&self.dice_count) {
+            errors.add(
+                "dice_count",
+                "out_of_range",
+                format!(
+                    "dice_count must be {} to {}",
+                    DICE_COUNT.start(),
+                    DICE_COUNT.end()
+                ),
+            );
+        }
+        if !DICE_SIDES.contains(&self.dice_sides) {
+            errors.add(
+                "dice_sides",
+                "out_of_range",
+                format!(
+                    "dice_sides must be {} to {}",
+                    DICE_SIDES.start(),
+                    DICE_SIDES.end()
+                ),
+            );
+        }
+        if self.auto_start_players != 0
+            && !(MAX_PLAYERS.start()..=&self.max_players).contains(&&self.auto_start_players)
+        {
+            errors.add(
+                "auto_start_players",
+                "out_of_range",
+                format!(
+                    "auto_start_players must be 0 (every seat) or {} to {}",
+                    MAX_PLAYERS.start(),
+                    self.max_players
+                ),
+            );
+        }
+        if !HOUSES_PER_HOTEL.contains(&self.houses_per_hotel) {
+            errors.add(
+                "houses_per_hotel",
+                "out_of_range",
+                format!(
+                    "houses_per_hotel must be {} to {}",
+                    HOUSES_PER_HOTEL.start(),
+                    HOUSES_PER_HOTEL.end()
+                ),
+            );
         }
+        if !ROUND_LIMIT.contains(&self.round_limit) {
+            errors.add(
+                "round_limit",
+                "out_of_range",
+                format!(
+                    "round_limit must be {} to {}",
+                    ROUND_LIMIT.start(),
+                    ROUND_LIMIT.end()
+                ),
+            );
+        }
+        if !MAX_JAIL_TURNS.contains(&self.max_jail_turns) {
+            errors.add(
+                "max_jail_turns",
+                "out_of_range",
+                format!(
+                    "max_jail_turns must be {} to {}",
+                    MAX_JAIL_TURNS.start(),
+                    MAX_JAIL_TURNS.end()
+                ),
+            );
+        }
+        if !INFLATION_ROUNDS.contains(&self.inflation_rounds) {
+            errors.add(
+                "inflation_rounds",
+                "out_of_range",
+                format!(
+                    "inflation_rounds must be {} to {}",
+                    INFLATION_ROUNDS.start(),
+                    INFLATION_ROUNDS.end()
+                ),
+            );
+        }
+        if !INFLATION_PERCENT.contains(&self.inflation_percent) {
+            errors.add(
+                "inflation_percent",
+                "out_of_range",
+                format!(
+                    "inflation_percent must be {} to {}",
+                    INFLATION_PERCENT.start(),
+                    INFLATION_PERCENT.end()
+                ),
+            );
+        }
+        if board::for_new_game(self.board_id.as_deref()).is_none() {
+            errors.add("board_id", "unknown", "Unknown board");
+        }
+        errors
+    }
+
+    /// Time limits in effect for the room
+    pub fn timers(&self) -> GameTimers {
+        GameTimers::default().with(&self.timers)
     }
 }
 
@@ -67,30 +389,80 @@ pub enum TurnPhase {
 /// Turn state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TurnState {
+    /// Unique per turn; clients echo it on turn-scoped actions. Turns saved
+    /// before ids existed read as the nil id, the same on every load
+    #[serde(default)]
+    pub id: Uuid,
     pub player_id: Uuid,
-    pub dice: Option<(u8, u8)>,
+    /// Last roll, one entry per die
+    pub dice: Option<Vec<u8>>,
     pub doubles_count: u8,
     pub phase: TurnPhase,
     pub can_roll_again: bool,
+    /// Unix time the turn began, or its timer last started over, for the
+    /// turn timer
+    #[serde(default)]
+    pub started_at: i64,
 }
 
 impl TurnState {
     pub fn new(player_id: Uuid) -> Self {
         Self {
+            id: Uuid::new_v4(),
             player_id,
             dice: None,
             doubles_count: 0,
             phase: TurnPhase::WaitingForRoll,
             can_roll_again: false,
+            started_at: chrono::Utc::now().timestamp(),
         }
     }
 
     pub fn dice_sum(&self) -> u8 {
-        self.dice.map(|(a, b)| a + b).unwrap_or(0)
+        self.dice
+            .as_deref()
+            .map(|dice| dice.iter().sum())
+            .unwrap_or(0)
     }
 
     pub fn is_doubles(&self) -> bool {
-        self.dice.map(|(a, b)| a == b).unwrap_or(false)
+        self.dice.as_deref().is_some_and(is_doubles)
+    }
+}
+
+/// Whether every die shows the same face; one die is never doubles
+pub fn is_doubles(dice: &[u8]) -> bool {
+    dice.len() > 1 && dice.windows(2).all(|pair| pair[0] == pair[1])
+}
+
+/// Counters kept for a player over one game, added to their account's
+/// statistics when it ends
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerTally {
+    pub rent_collected: i64,
+    pub rent_paid: i64,
+    /// Bought outright or won at auction
+    pub properties_bought: u32,
+    /// Largest rent, tax or fine paid at once
+    #[serde(default)]
+    pub biggest_payment: i64,
+    /// Turns started in jail
+    #[serde(default)]
+    pub turns_in_jail: u32,
+    /// Color sets completed by a purchase or an auction
+    #[serde(default)]
+    pub sets_completed: u32,
+    #[serde(default)]
+    pub hotels_built: u32,
+    /// Players who went bankrupt owing this one
+    #[serde(default)]
+    pub players_bankrupted: u32,
+}
+
+impl PlayerTally {
+    /// Count a payment to another player or the bank
+    pub fn paid(&mut self, amount: i32) {
+        self.biggest_payment = self.biggest_payment.max(amount.into());
     }
 }
 
@@ -108,6 +480,38 @@ pub struct Player {
     pub is_bot: bool,
     pub is_bankrupt: bool,
     pub is_host: bool,
+    /// Avatar id from the curated list, if one was picked
+    #[serde(default)]
+    pub avatar: Option<String>,
+    /// Playing piece drawn on the board, one of a kind in the room
+    #[serde(default)]
+    pub piece: Option<String>,
+    /// Done picking looks in the lobby; the game starts once every human
+    /// but the host is ready
+    #[serde(default)]
+    pub ready: bool,
+    /// Human temporarily played by a bot while disconnected or away
+    #[serde(default)]
+    pub bot_controlled: bool,
+    /// Turn timers run out in a row on this player without them acting in
+    /// between; enough of them and a bot takes over
+    #[serde(default)]
+    pub missed_turn_timers: u8,
+    #[serde(default)]
+    pub bot_difficulty: BotDifficulty,
+    #[serde(default)]
+    pub bot_personality: BotPersonality,
+    /// Overrides the difficulty's parameters, for calibration seats
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot_params: Option<BotParams>,
+    /// Registered user playing this seat
+    #[serde(default)]
+    pub user_id: Option<Uuid>,
+    /// Language the player reads the game in
+    #[serde(default)]
+    pub locale: Locale,
+    #[serde(default)]
+    pub tally: PlayerTally,
 }
 
 impl Player {
@@ -124,28 +528,40 @@ impl Player {
             is_bot,
             is_bankrupt: false,
             is_host,
+            avatar: None,
+            piece: None,
+            ready: false,
+            bot_controlled: false,
+            missed_turn_timers: 0,
+            bot_difficulty: BotDifficulty::default(),
+            bot_personality: BotPersonality::default(),
+            bot_params: None,
+            user_id: None,
+            locale: Locale::default(),
+            tally: PlayerTally::default(),
         }
     }
+
+    /// Whether the bot scheduler should act for this player
+    pub fn controlled_by_bot(&self) -> bool {
+        self.is_bot || self.bot_controlled
+    }
+
+    /// Heuristic parameters the bot policy plays this seat with
+    pub fn policy_params(&self) -> BotParams {
+        self.bot_params
+            .unwrap_or_else(|| self.bot_difficulty.params())
+    }
 }
 
 /// State of a property on the board
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PropertyState {
     pub owner: Option<Uuid>,
     pub houses: u8, // 0-4 = houses, 5 = hotel
     pub is_mortgaged: bool,
 }
 
-impl Default for PropertyState {
-    fn default() -> Self {
-        Self {
-            owner: None,
-            houses: 0,
-            is_mortgaged: false,
-        }
-    }
-}
-
 /// Auction state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuctionState {
@@ -153,6 +569,27 @@ pub struct AuctionState {
     pub current_bid: u32,
     pub highest_bidder: Option<Uuid>,
     pub passed_players: Vec<Uuid>,
+    /// Phase to put the turn back in when the auction ends; `None` for an
+    /// auction of a declined property, which ends the turn
+    #[serde(default)]
+    pub resume_phase: Option<TurnPhase>,
+    /// Unix time of the last bid, or of the opening before any, for the
+    /// auction timer
+    #[serde(default)]
+    pub last_bid_at: i64,
+}
+
+/// A payment a player could not afford when it fell due
+///
+/// The player keeps their turn to mortgage or trade until they can pay it
+/// off, or gives up and goes bankrupt. Only the bail forced after the last
+/// jail turn runs one up; paying it frees them and moves them by their roll.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Debt {
+    pub player_id: Uuid,
+    pub amount: i32,
+    /// Unix time the debt was run up, for the debt timer
+    pub since: i64,
 }
 
 impl AuctionState {
@@ -162,12 +599,14 @@ impl AuctionState {
             current_bid: 0,
             highest_bidder: None,
             passed_players: Vec::new(),
+            resume_phase: None,
+            last_bid_at: chrono::Utc::now().timestamp(),
         }
     }
 }
 
 /// Trade offer between players
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TradeOffer {
     pub id: Uuid,
     pub from_player: Uuid,
@@ -175,31 +614,51 @@ pub struct TradeOffer {
     pub offering: TradeAssets,
     pub requesting: TradeAssets,
     pub status: TradeStatus,
+    /// Offer this one answered, if it is a counter-offer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counter_to: Option<Uuid>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl TradeOffer {
+    /// Whether the player is on either side of the offer
+    pub fn involves(&self, player_id: Uuid) -> bool {
+        self.from_player == player_id || self.to_player == player_id
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 pub struct TradeAssets {
     pub money: u32,
     pub properties: Vec<u8>,
     pub get_out_cards: u8,
 }
 
-impl Default for TradeAssets {
-    fn default() -> Self {
-        Self {
-            money: 0,
-            properties: Vec::new(),
-            get_out_cards: 0,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub enum TradeStatus {
     Pending,
     Accepted,
     Rejected,
     Countered,
+    /// Voided before an answer: a side left the game, could no longer pay
+    /// or the game ended
+    Expired,
+}
+
+/// An offer once it is settled, as kept in the game's trade history
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TradeRecord {
+    #[serde(flatten)]
+    pub offer: TradeOffer,
+    /// Unix time the offer was settled
+    pub concluded_at: i64,
+}
+
+impl TradeRecord {
+    /// Accepted trades changed the board for all to see; the rest stay
+    /// between the two sides, like the offer itself
+    pub fn is_visible_to(&self, viewer: Uuid) -> bool {
+        self.offer.status == TradeStatus::Accepted || self.offer.involves(viewer)
+    }
 }
 
 /// Complete game state
@@ -210,17 +669,100 @@ pub struct GameState {
     pub turn: Option<TurnState>,
     pub turn_order: Vec<Uuid>,
     pub current_turn_idx: usize,
+    /// Rounds completed: bumped each time the turn wraps around the turn
+    /// order
+    #[serde(default)]
+    pub round: u32,
     pub players: Vec<Player>,
     pub properties: HashMap<u8, PropertyState>,
     pub auction: Option<AuctionState>,
+    /// Tiles of departed players waiting for their auction, next first
+    #[serde(default)]
+    pub auction_queue: Vec<u8>,
+    /// Money the current player owes and has yet to raise
+    #[serde(default)]
+    pub debt: Option<Debt>,
+    /// Order the card decks come up in; hidden from players
+    #[serde(default)]
+    pub decks: DeckOrder,
     pub active_trade: Option<TradeOffer>,
     pub pot_money: i32, // Free parking jackpot
     pub config: GameConfig,
-    pub logs: Vec<String>,
+    /// Log lines added since the state was loaded; the room's full log is
+    /// stored apart from the state and read with `GameEngine::get_logs`
+    pub logs: Vec<LogLine>,
+    /// How many of `logs` the store already has
+    #[serde(skip)]
+    pub logs_stored: usize,
+    /// Players the host has muted in chat
+    #[serde(default)]
+    pub muted: Vec<Uuid>,
+    /// Players whose chat only they themselves see, set by the host or an
+    /// administrator; only the host's view lists them
+    #[serde(default)]
+    pub shadow_muted: Vec<Uuid>,
+    /// Unix time the room was created; unknown for rooms older than the
+    /// field
+    #[serde(default)]
+    pub created_at: Option<i64>,
+    /// Unix time the game left the lobby
+    #[serde(default)]
+    pub started_at: Option<i64>,
+    /// Unix time the lobby countdown runs out, while one is running
+    #[serde(default)]
+    pub auto_start_at: Option<i64>,
+    /// Unix time a closed room is deleted; until then it is read-only
+    #[serde(default)]
+    pub closed_until: Option<i64>,
+    /// Seconds the room is kept after its last activity, if the host chose;
+    /// the server's expiries apply otherwise
+    #[serde(default)]
+    pub lifetime_secs: Option<u64>,
+    /// Players in the order they went bankrupt
+    #[serde(default)]
+    pub eliminated: Vec<Uuid>,
+    /// Net worth and properties of each player after every round
+    #[serde(default)]
+    pub timeline: Vec<RoundSample>,
+    /// Players who resigned or were kicked after the game started
+    #[serde(default)]
+    pub departed: Vec<Uuid>,
+    /// Lobby opened for a rematch once the game was over
+    #[serde(default)]
+    pub rematch_room: Option<String>,
+    /// Every settled trade offer, oldest first
+    #[serde(default)]
+    pub trades: Vec<TradeRecord>,
+    /// Events written to the room's event journal so far
+    #[serde(default)]
+    pub journal_seq: u64,
+    /// Bumped by every save, to catch saves of a stale copy
+    #[serde(default)]
+    pub version: u64,
+    /// Board and version the game was created on
+    #[serde(default = "default_board_id")]
+    pub board_id: String,
+    #[serde(default = "default_board_version")]
+    pub board_version: i32,
+    /// Shared-device checks of a ranked room; only admins see them
+    #[serde(default)]
+    pub integrity: Integrity,
+}
+
+fn default_board_id() -> String {
+    board::BOARD_ID.into()
+}
+
+fn default_board_version() -> i32 {
+    board::BUILTIN_VERSION
 }
 
 impl GameState {
     pub fn new(id: String, config: GameConfig) -> Self {
+        // Callers check the id; an unknown one plays the default board
+        let board =
+            board::for_new_game(config.board_id.as_deref()).unwrap_or_else(board::default_board);
+
         // Initialize property states for ownable tiles
         let mut properties = HashMap::new();
         for idx in 0..40u8 {
@@ -237,16 +779,54 @@ impl GameState {
             turn: None,
             turn_order: Vec::new(),
             current_turn_idx: 0,
+            round: 0,
             players: Vec::new(),
             properties,
             auction: None,
+            auction_queue: Vec::new(),
+            debt: None,
+            decks: DeckOrder::default(),
             active_trade: None,
             pot_money: 0,
             config,
             logs: Vec::new(),
+            logs_stored: 0,
+            muted: Vec::new(),
+            shadow_muted: Vec::new(),
+            created_at: Some(chrono::Utc::now().timestamp()),
+            started_at: None,
+            auto_start_at: None,
+            closed_until: None,
+            lifetime_secs: None,
+            eliminated: Vec::new(),
+            departed: Vec::new(),
+            rematch_room: None,
+            timeline: Vec::new(),
+            trades: Vec::new(),
+            journal_seq: 0,
+            version: 0,
+            board_id: board.id.clone(),
+            board_version: board.version,
+            integrity: Integrity::default(),
         }
     }
 
+    /// Board version the game is played on
+    ///
+    /// Falls back to the newest version of the board when this instance has
+    /// not loaded the game's one, which only happens when a version was
+    /// stored after it started.
+    pub fn board(&self) -> &'static BoardDef {
+        board::definition(&self.board_id, self.board_version)
+            .or_else(|| board::latest(&self.board_id))
+            .unwrap_or_else(board::default_board)
+    }
+
+    /// Get a tile of the game's board by index
+    pub fn tile(&self, idx: u8) -> Option<&'static Tile> {
+        self.board().tile(idx)
+    }
+
     /// Get player by ID
     pub fn get_player(&self, id: Uuid) -> Option<&Player> {
         self.players.iter().find(|p| p.id == id)
@@ -294,21 +874,223 @@ impl GameState {
         Some(*active[next_idx])
     }
 
+    /// An amount raised by the room's inflation so far
+    pub fn inflated(&self, base: i32) -> i32 {
+        let raises = self
+            .round
+            .checked_div(self.config.inflation_rounds)
+            .unwrap_or(0);
+        let percent = 100 + i64::from(self.config.inflation_percent) * i64::from(raises);
+        (i64::from(base) * percent / 100).min(i64::from(i32::MAX)) as i32
+    }
+
     /// Count active (non-bankrupt) players
     pub fn active_player_count(&self) -> usize {
         self.players.iter().filter(|p| !p.is_bankrupt).count()
     }
 
+    /// Players from first to last place
+    ///
+    /// Those still standing rank by net worth, ahead of everyone who went
+    /// bankrupt; the later a player went bankrupt, the better they placed.
+    pub fn standings(&self) -> Vec<Uuid> {
+        let mut standing: Vec<&Player> = self.players.iter().filter(|p| !p.is_bankrupt).collect();
+        standing.sort_by_key(|p| std::cmp::Reverse(self.net_worth(p.id)));
+
+        let mut order: Vec<Uuid> = standing.iter().map(|p| p.id).collect();
+        order.extend(
+            self.eliminated
+                .iter()
+                .rev()
+                .filter(|id| self.get_player(**id).is_some()),
+        );
+        // Went bankrupt before elimination order was kept
+        for player in &self.players {
+            if !order.contains(&player.id) {
+                order.push(player.id);
+            }
+        }
+        order
+    }
+
+    /// Whether the game counts towards ratings: ranked, with no seats found
+    /// sharing a device
+    pub fn is_rated(&self) -> bool {
+        self.config.ranked && !self.integrity.is_flagged()
+    }
+
+    /// Seats credited to registered users, in user id order so concurrent
+    /// games lock their rows in the same order
+    ///
+    /// Bots are never credited, whoever held the seat before them.
+    pub fn registered_players(&self) -> Vec<(Uuid, &Player)> {
+        let mut players: Vec<_> = self
+            .players
+            .iter()
+            .filter(|p| !p.is_bot)
+            .filter_map(|p| Some((p.user_id?, p)))
+            .collect();
+        players.sort_by_key(|(user_id, _)| *user_id);
+        players
+    }
+
+    /// First in the standings, unless everyone went bankrupt
+    ///
+    /// Games cut short by a round limit end with several players standing.
+    pub fn winner(&self) -> Option<Uuid> {
+        self.standings()
+            .first()
+            .copied()
+            .filter(|id| self.get_player(*id).is_some_and(|p| !p.is_bankrupt))
+    }
+
+    /// Cash plus the value of owned property and buildings
+    pub fn net_worth(&self, player_id: Uuid) -> i32 {
+        let balance = self.get_player(player_id).map(|p| p.balance).unwrap_or(0);
+
+        let assets: u32 = self
+            .properties
+            .iter()
+            .filter(|(_, prop)| prop.owner == Some(player_id))
+            .filter_map(|(idx, prop)| {
+                let tile = self.tile(*idx)?;
+                let value = if prop.is_mortgaged {
+                    tile.mortgage_value
+                } else {
+                    tile.price
+                };
+                Some(value + prop.houses as u32 * tile.build_cost)
+            })
+            .sum();
+
+        balance + assets as i32
+    }
+
+    /// The state as `viewer` may see it
+    ///
+    /// Pending trades are only shown to the two players involved, and with
+    /// hidden jail cards other players' card counts read as zero.
+    pub fn view_for(&self, viewer: Uuid) -> GameState {
+        let mut view = self.clone();
+        view.integrity = Integrity::default();
+        view.decks = DeckOrder::default();
+        if !self.is_host(viewer) {
+            view.shadow_muted.clear();
+        }
+
+        if self.config.hide_jail_cards {
+            for player in view.players.iter_mut().filter(|p| p.id != viewer) {
+                player.get_out_cards = 0;
+            }
+        }
+
+        if view
+            .active_trade
+            .as_ref()
+            .is_some_and(|t| !t.involves(viewer))
+        {
+            view.active_trade = None;
+        }
+        view.trades.retain(|t| t.is_visible_to(viewer));
+
+        view
+    }
+
+    /// Whether `viewer`'s view differs from what a spectator sees
+    ///
+    /// Mirrors `view_for`: only trades they are part of, with hidden jail
+    /// cards their own cards, or for the host shadow-muted players set them
+    /// apart.
+    pub fn differs_for(&self, viewer: Uuid) -> bool {
+        let in_trade = self
+            .active_trade
+            .as_ref()
+            .is_some_and(|t| t.involves(viewer))
+            || self
+                .trades
+                .iter()
+                .any(|t| t.offer.status != TradeStatus::Accepted && t.offer.involves(viewer));
+        let holds_cards = self.config.hide_jail_cards
+            && self.get_player(viewer).is_some_and(|p| p.get_out_cards > 0);
+        let sees_shadow_muted = !self.shadow_muted.is_empty() && self.is_host(viewer);
+        in_trade || holds_cards || sees_shadow_muted
+    }
+
+    fn is_host(&self, player_id: Uuid) -> bool {
+        self.get_player(player_id).is_some_and(|p| p.is_host)
+    }
+
     /// Add log entry
-    pub fn log(&mut self, message: String) {
-        self.logs.push(message);
-        // Keep last 100 logs
-        if self.logs.len() > 100 {
+    pub fn log(&mut self, line: LogLine) {
+        self.logs.push(line);
+        if self.logs.len() > LOG_LEN {
             self.logs.remove(0);
+            self.logs_stored = self.logs_stored.saturating_sub(1);
+        }
+    }
+
+    /// Log lines the store has yet to append to the room's log
+    pub fn unstored_logs(&self) -> &[LogLine] {
+        &self.logs[self.logs_stored.min(self.logs.len())..]
+    }
+
+    /// Render the log lines for a reader of `locale`
+    pub fn localize(&mut self, locale: Locale) {
+        for line in &mut self.logs {
+            line.text = Some(line.render(locale));
         }
     }
 }
 
+/// A game log line: a catalog key and the values of its placeholders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogLine {
+    /// Key in the `i18n` catalog, e.g. `passed_go`
+    pub key: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub args: BTreeMap<String, String>,
+    /// The line in the reader's language, set when it is sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl LogLine {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            args: BTreeMap::new(),
+            text: None,
+        }
+    }
+
+    pub fn arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.args.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// The line in a language; lines logged before keys existed keep their
+    /// English text
+    pub fn render(&self, locale: Locale) -> String {
+        i18n::log_line(locale, &self.key, &self.args)
+            .or_else(|| self.text.clone())
+            .unwrap_or_else(|| self.key.clone())
+    }
+
+    /// Form kept in the store's log list
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Read a line back from the store's log list
+    pub fn decode(stored: &str) -> Self {
+        serde_json::from_str(stored).unwrap_or_else(|_| Self {
+            key: String::new(),
+            args: BTreeMap::new(),
+            text: Some(stored.to_string()),
+        })
+    }
+}
+
 /// Check if a tile can be owned
 fn is_ownable_tile(idx: u8) -> bool {
     // Corners: 0 (GO), 10 (Jail), 20 (Free Parking), 30 (Go to Jail)
@@ -0,0 +1,434 @@
+//! Where game states live between actions
+//!
+//! The engine loads a room, applies an action and saves it again. Saves are
+//! versioned: a save only lands if nobody saved the room since it was
+//! loaded, so two instances acting on the same room cannot silently undo
+//! each other.
+//!
+//! Rooms expire from Redis some time after their last save, sooner while
+//! still in the lobby so abandoned lobbies do not linger; every save renews
+//! the expiry, and so does activity that saves nothing, such as chat. A host
+//! may set their room's lifetime instead. Key prefix and expiries come from
+//! [`RedisLayout`].
+//!
+//! A room's log is kept apart from its state and capped at `LOG_LEN` lines,
+//! so the state read and written on every action stays small. Saving a room
+//! appends the lines logged since it was loaded. Redis is the store in
+//! production; [`MemoryStore`] keeps rooms
+//! in the process for tests and tools that run without Redis.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use axum::async_trait;
+use once_cell::sync::{Lazy, OnceCell};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use uuid::Uuid;
+
+use super::state::{GamePhase, GameState, LogLine, LOG_LEN};
+use crate::error::{AppError, AppResult};
+
+/// Lifetimes a host may give their room, in seconds: ten minutes to a week
+pub const LIFETIME_SECS: std::ops::RangeInclusive<u64> = 600..=7 * 86400;
+
+/// Where and for how long rooms are kept in Redis
+#[derive(Debug, Clone)]
+pub struct RedisLayout {
+    /// Put before every key and pub/sub channel the server uses
+    pub key_prefix: String,
+    /// Expiry of a started room, renewed by every save
+    pub game_ttl_secs: u64,
+    /// Expiry of a room still in its lobby, renewed the same way
+    pub lobby_ttl_secs: u64,
+    /// How long a closed room stays viewable before it is deleted
+    pub closed_grace_secs: u64,
+}
+
+impl Default for RedisLayout {
+    fn default() -> Self {
+        Self {
+            key_prefix: String::new(),
+            game_ttl_secs: 86400,
+            lobby_ttl_secs: 3600,
+            closed_grace_secs: 3600,
+        }
+    }
+}
+
+impl RedisLayout {
+    pub(crate) fn game_key(&self, room_id: &str) -> String {
+        format!("{}game:{}", self.key_prefix, room_id)
+    }
+
+    pub(crate) fn log_key(&self, room_id: &str) -> String {
+        format!("{}game_log:{}", self.key_prefix, room_id)
+    }
+
+    /// Pattern matching the state key of every room
+    pub(crate) fn game_pattern(&self) -> String {
+        format!("{}game:*", self.key_prefix)
+    }
+
+    /// Room a state key belongs to
+    pub(crate) fn room_id<'a>(&self, key: &'a str) -> Option<&'a str> {
+        key.strip_prefix(&self.key_prefix)?.strip_prefix("game:")
+    }
+
+    /// Pattern matching the log key of every room
+    pub(crate) fn log_pattern(&self) -> String {
+        format!("{}game_log:*", self.key_prefix)
+    }
+
+    /// Room a log key belongs to
+    pub(crate) fn log_room_id<'a>(&self, key: &'a str) -> Option<&'a str> {
+        key.strip_prefix(&self.key_prefix)?
+            .strip_prefix("game_log:")
+    }
+
+    pub(crate) fn chat_key(&self, room_id: &str) -> String {
+        format!("{}chat:{}", self.key_prefix, room_id)
+    }
+
+    pub(crate) fn chat_seq_key(&self, room_id: &str) -> String {
+        format!("{}chat_seq:{}", self.key_prefix, room_id)
+    }
+
+    /// Patterns matching the chat keys of every room; `chat:*` does not
+    /// match `chat_seq:*`
+    pub(crate) fn chat_patterns(&self) -> [String; 2] {
+        [
+            format!("{}chat:*", self.key_prefix),
+            format!("{}chat_seq:*", self.key_prefix),
+        ]
+    }
+
+    /// Room a chat key belongs to
+    pub(crate) fn chat_room_id<'a>(&self, key: &'a str) -> Option<&'a str> {
+        let key = key.strip_prefix(&self.key_prefix)?;
+        key.strip_prefix("chat:")
+            .or_else(|| key.strip_prefix("chat_seq:"))
+    }
+
+    pub(crate) fn chat_cooldown_key(&self, room_id: &str, player_id: Uuid) -> String {
+        format!("{}chat_cooldown:{}:{}", self.key_prefix, room_id, player_id)
+    }
+
+    /// Counter numbering a room's events across instances
+    pub(crate) fn room_seq_key(&self, room_id: &str) -> String {
+        format!("{}room_seq:{}", self.key_prefix, room_id)
+    }
+
+    /// Channels instances share room events and notifications on
+    pub(crate) fn channel_prefix(&self) -> String {
+        format!("{}hub:", self.key_prefix)
+    }
+
+    pub(crate) fn rate_limit_key(&self, group: &str, client: &str, window: u64) -> String {
+        format!(
+            "{}ratelimit:{}:{}:{}",
+            self.key_prefix, group, client, window
+        )
+    }
+
+    pub(crate) fn session_key(&self, session_id: Uuid) -> String {
+        format!("{}session:{}", self.key_prefix, session_id)
+    }
+
+    /// Session each player of a room took their seat under
+    pub(crate) fn room_sessions_key(&self, room_id: &str) -> String {
+        format!("{}room_sessions:{}", self.key_prefix, room_id)
+    }
+
+    pub(crate) fn oauth_state_key(&self, csrf: &str) -> String {
+        format!("{}oauth_state:{}", self.key_prefix, csrf)
+    }
+
+    pub(crate) fn simulation_key(&self, job_id: Uuid) -> String {
+        format!("{}simulation:{}", self.key_prefix, job_id)
+    }
+
+    /// How long a room is kept after its last save; a closed room at least
+    /// until it is due for deletion
+    pub(crate) fn ttl_for(&self, game: &GameState) -> u64 {
+        let ttl = match (game.lifetime_secs, game.phase) {
+            (Some(lifetime), _) => lifetime,
+            (None, GamePhase::Lobby) => self.lobby_ttl_secs,
+            (None, _) => self.game_ttl_secs,
+        };
+        match game.closed_until {
+            Some(until) => ttl.max((until - chrono::Utc::now().timestamp()).max(0) as u64),
+            None => ttl,
+        }
+    }
+}
+
+static LAYOUT: OnceCell<RedisLayout> = OnceCell::new();
+
+/// Set the Redis key prefix and expiries; called once at startup
+pub fn configure(layout: RedisLayout) {
+    if LAYOUT.set(layout).is_err() {
+        tracing::warn!("Redis layout already configured");
+    }
+}
+
+/// Key prefix and expiries of rooms in Redis, the defaults if unconfigured
+pub(crate) fn layout() -> &'static RedisLayout {
+    LAYOUT.get_or_init(RedisLayout::default)
+}
+
+#[async_trait]
+pub trait GameStore: Send + Sync {
+    async fn get(&self, room_id: &str) -> AppResult<Option<GameState>>;
+
+    /// Store `game` and bump its version, appending its new log lines
+    ///
+    /// Fails with [`AppError::Conflict`] if the stored room has moved past
+    /// the version `game` was loaded at. A room that is not stored yet is
+    /// always accepted.
+    async fn save(&self, game: &mut GameState) -> AppResult<()>;
+
+    async fn delete(&self, room_id: &str) -> AppResult<()>;
+
+    /// Renew the room's expiry to `ttl_secs` without saving it
+    async fn touch(&self, room_id: &str, ttl_secs: u64) -> AppResult<()>;
+
+    /// Seconds until the room expires, `None` if it never does
+    async fn ttl(&self, room_id: &str) -> AppResult<Option<u64>>;
+
+    /// The room's last `limit` log lines, oldest first
+    async fn logs(&self, room_id: &str, limit: usize) -> AppResult<Vec<LogLine>>;
+
+    /// Ids of every stored room
+    async fn room_ids(&self) -> AppResult<Vec<String>>;
+}
+
+static OVERRIDE: OnceCell<Arc<dyn GameStore>> = OnceCell::new();
+
+/// Run the engine on another store instead of Redis; called once at startup
+pub fn install(store: Arc<dyn GameStore>) {
+    if OVERRIDE.set(store).is_err() {
+        tracing::warn!("Game store already installed");
+    }
+}
+
+/// The store rooms are kept in
+pub(crate) fn current(redis: &ConnectionManager) -> Arc<dyn GameStore> {
+    match OVERRIDE.get() {
+        Some(store) => store.clone(),
+        None => Arc::new(RedisStore::new(redis.clone())),
+    }
+}
+
+fn conflict(room_id: &str) -> AppError {
+    AppError::Conflict(format!(
+        "Room {} changed in the meantime, try again",
+        room_id
+    ))
+}
+
+/// Rooms as JSON under `<prefix>game:<room id>` and their logs as lists
+/// under `<prefix>game_log:<room id>`, both expiring with the room
+#[derive(Clone)]
+pub struct RedisStore {
+    redis: ConnectionManager,
+}
+
+/// Writes the room only if the stored copy is still at the expected
+/// version, appending its new log lines in the same step
+static SAVE_IF_CURRENT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r"
+        local current = redis.call('GET', KEYS[1])
+        if current then
+            local version = cjson.decode(current).version or 0
+            if version ~= tonumber(ARGV[1]) then
+                return 0
+            end
+        end
+        redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+        if #ARGV > 4 then
+            redis.call('RPUSH', KEYS[2], unpack(ARGV, 5))
+            redis.call('LTRIM', KEYS[2], -tonumber(ARGV[4]), -1)
+        end
+        redis.call('EXPIRE', KEYS[2], ARGV[3])
+        return 1
+        ",
+    )
+});
+
+impl RedisStore {
+    pub fn new(redis: ConnectionManager) -> Self {
+        Self { redis }
+    }
+}
+
+#[async_trait]
+impl GameStore for RedisStore {
+    async fn get(&self, room_id: &str) -> AppResult<Option<GameState>> {
+        let mut conn = self.redis.clone();
+        let data: Option<String> = conn.get(layout().game_key(room_id)).await?;
+
+        data.map(|json| serde_json::from_str(&json).map_err(|e| AppError::Internal(e.into())))
+            .transpose()
+    }
+
+    async fn save(&self, game: &mut GameState) -> AppResult<()> {
+        let expected = game.version;
+        game.version += 1;
+
+        // The log goes to its own list, not into the state
+        let logs = std::mem::take(&mut game.logs);
+        let json = serde_json::to_string(game).map_err(|e| AppError::Internal(e.into()));
+        game.logs = logs;
+        let json = json?;
+
+        let layout = layout();
+        let mut conn = self.redis.clone();
+        let saved: i32 = SAVE_IF_CURRENT
+            .key(layout.game_key(&game.id))
+            .key(layout.log_key(&game.id))
+            .arg(expected)
+            .arg(json)
+            .arg(layout.ttl_for(game))
+            .arg(LOG_LEN)
+            .arg(
+                game.unstored_logs()
+                    .iter()
+                    .map(LogLine::encode)
+                    .collect::<Vec<_>>(),
+            )
+            .invoke_async(&mut conn)
+            .await?;
+
+        if saved == 0 {
+            game.version = expected;
+            return Err(conflict(&game.id));
+        }
+        game.logs_stored = game.logs.len();
+        Ok(())
+    }
+
+    async fn delete(&self, room_id: &str) -> AppResult<()> {
+        let mut conn = self.redis.clone();
+        let _: () = conn
+            .del(&[layout().game_key(room_id), layout().log_key(room_id)])
+            .await?;
+        Ok(())
+    }
+
+    async fn touch(&self, room_id: &str, ttl_secs: u64) -> AppResult<()> {
+        let layout = layout();
+        let mut conn = self.redis.clone();
+        let ttl_secs = ttl_secs.min(i64::MAX as u64) as i64;
+        let _: () = redis::pipe()
+            .expire(layout.game_key(room_id), ttl_secs)
+            .expire(layout.log_key(room_id), ttl_secs)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn ttl(&self, room_id: &str) -> AppResult<Option<u64>> {
+        let mut conn = self.redis.clone();
+        let ttl: i64 = conn.ttl(layout().game_key(room_id)).await?;
+        // -1 for no expiry, -2 for no key
+        Ok((ttl >= 0).then_some(ttl as u64))
+    }
+
+    async fn logs(&self, room_id: &str, limit: usize) -> AppResult<Vec<LogLine>> {
+        let mut conn = self.redis.clone();
+        let limit = limit.min(LOG_LEN) as isize;
+        let lines: Vec<String> = conn.lrange(layout().log_key(room_id), -limit, -1).await?;
+        Ok(lines.iter().map(|line| LogLine::decode(line)).collect())
+    }
+
+    async fn room_ids(&self) -> AppResult<Vec<String>> {
+        let layout = layout();
+        let mut conn = self.redis.clone();
+        let mut iter = conn.scan_match::<_, String>(layout.game_pattern()).await?;
+        let mut ids = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            if let Some(room_id) = layout.room_id(&key) {
+                ids.push(room_id.to_string());
+            }
+        }
+        Ok(ids)
+    }
+}
+
+/// Rooms held in the process; they never expire
+#[derive(Default)]
+pub struct MemoryStore {
+    games: Mutex<HashMap<String, GameState>>,
+    logs: Mutex<HashMap<String, VecDeque<LogLine>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl GameStore for MemoryStore {
+    async fn get(&self, room_id: &str) -> AppResult<Option<GameState>> {
+        let games = self.games.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(games.get(room_id).cloned())
+    }
+
+    async fn save(&self, game: &mut GameState) -> AppResult<()> {
+        let mut games = self.games.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(stored) = games.get(&game.id) {
+            if stored.version != game.version {
+                return Err(conflict(&game.id));
+            }
+        }
+
+        game.version += 1;
+        let mut stored = game.clone();
+        stored.logs.clear();
+        games.insert(game.id.clone(), stored);
+
+        let mut logs = self.logs.lock().unwrap_or_else(|e| e.into_inner());
+        let log = logs.entry(game.id.clone()).or_default();
+        log.extend(game.unstored_logs().iter().cloned());
+        while log.len() > LOG_LEN {
+            log.pop_front();
+        }
+        game.logs_stored = game.logs.len();
+        Ok(())
+    }
+
+    async fn delete(&self, room_id: &str) -> AppResult<()> {
+        let mut games = self.games.lock().unwrap_or_else(|e| e.into_inner());
+        games.remove(room_id);
+        let mut logs = self.logs.lock().unwrap_or_else(|e| e.into_inner());
+        logs.remove(room_id);
+        Ok(())
+    }
+
+    async fn touch(&self, _room_id: &str, _ttl_secs: u64) -> AppResult<()> {
+        Ok(())
+    }
+
+    async fn ttl(&self, _room_id: &str) -> AppResult<Option<u64>> {
+        Ok(None)
+    }
+
+    async fn logs(&self, room_id: &str, limit: usize) -> AppResult<Vec<LogLine>> {
+        let logs = self.logs.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(logs
+            .get(room_id)
+            .map(|log| {
+                let skip = log.len().saturating_sub(limit);
+                log.iter().skip(skip).cloned().collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn room_ids(&self) -> AppResult<Vec<String>> {
+        let games = self.games.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(games.keys().cloned().collect())
+    }
+}
@@ -0,0 +1,171 @@
+//! What happened over a game, shown to everyone once it ends
+//!
+//! Counters build up in each player's `PlayerTally` while the game runs,
+//! and every finished round adds a sample of where each player stands. The
+//! summary turns the samples into one series per player, compact enough to
+//! keep with the game record and draw as a wealth-over-time chart.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::state::GameState;
+
+/// Where each player stood at the end of a round
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundSample {
+    pub round: u32,
+    pub players: Vec<PlayerSample>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSample {
+    pub player_id: Uuid,
+    pub net_worth: i32,
+    /// Tiles owned, mortgaged ones included
+    pub properties: u32,
+}
+
+/// The game as a whole, sent and stored when it ends
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GameSummary {
+    pub winner: Option<Uuid>,
+    pub rounds: u32,
+    pub duration_secs: i64,
+    /// From first to last place
+    pub players: Vec<PlayerSummary>,
+    pub timeline: Timeline,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PlayerSummary {
+    pub player_id: Uuid,
+    pub name: String,
+    /// 1 for the winner
+    pub place: u32,
+    pub is_bot: bool,
+    pub is_bankrupt: bool,
+    pub balance: i32,
+    pub net_worth: i32,
+    pub properties: u32,
+    pub rent_collected: i64,
+    pub rent_paid: i64,
+    pub properties_bought: u32,
+    /// Largest rent, tax or fine paid at once
+    pub biggest_payment: i64,
+    pub turns_in_jail: u32,
+}
+
+/// Samples as one series per player
+///
+/// Entry `i` of every series belongs to `rounds[i]`; the last sample is the
+/// final position. A player's entries are `null` once they are out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct Timeline {
+    pub rounds: Vec<u32>,
+    pub players: Vec<PlayerSeries>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PlayerSeries {
+    pub player_id: Uuid,
+    pub net_worth: Vec<Option<i32>>,
+    pub properties: Vec<Option<u32>>,
+}
+
+impl Timeline {
+    /// Series for `players`, in that order
+    pub fn of(samples: &[RoundSample], players: &[Uuid]) -> Self {
+        let players = players
+            .iter()
+            .map(|&player_id| {
+                let entries: Vec<Option<&PlayerSample>> = samples
+                    .iter()
+                    .map(|sample| sample.players.iter().find(|p| p.player_id == player_id))
+                    .collect();
+                PlayerSeries {
+                    player_id,
+                    net_worth: entries.iter().map(|e| e.map(|p| p.net_worth)).collect(),
+                    properties: entries.iter().map(|e| e.map(|p| p.properties)).collect(),
+                }
+            })
+            .collect();
+
+        Self {
+            rounds: samples.iter().map(|sample| sample.round).collect(),
+            players,
+        }
+    }
+}
+
+/// Where every player still in the game stands now
+pub fn sample(game: &GameState) -> RoundSample {
+    let players = game
+        .players
+        .iter()
+        .filter(|p| !p.is_bankrupt)
+        .map(|p| PlayerSample {
+            player_id: p.id,
+            net_worth: game.net_worth(p.id),
+            properties: properties_of(game, p.id),
+        })
+        .collect();
+
+    RoundSample {
+        round: game.round,
+        players,
+    }
+}
+
+impl GameSummary {
+    pub fn of(game: &GameState) -> Self {
+        let standings = game.standings();
+        let winner = standings
+            .first()
+            .copied()
+            .filter(|id| game.get_player(*id).is_some_and(|p| !p.is_bankrupt));
+
+        let players = standings
+            .iter()
+            .enumerate()
+            .filter_map(|(place, id)| {
+                let p = game.get_player(*id)?;
+                Some(PlayerSummary {
+                    player_id: p.id,
+                    name: p.name.clone(),
+                    place: place as u32 + 1,
+                    is_bot: p.is_bot,
+                    is_bankrupt: p.is_bankrupt,
+                    balance: p.balance,
+                    net_worth: game.net_worth(p.id),
+                    properties: properties_of(game, p.id),
+                    rent_collected: p.tally.rent_collected,
+                    rent_paid: p.tally.rent_paid,
+                    properties_bought: p.tally.properties_bought,
+                    biggest_payment: p.tally.biggest_payment,
+                    turns_in_jail: p.tally.turns_in_jail,
+                })
+            })
+            .collect();
+
+        let duration_secs = game
+            .started_at
+            .map(|started| (chrono::Utc::now().timestamp() - started).max(0))
+            .unwrap_or(0);
+
+        Self {
+            winner,
+            rounds: game.round,
+            duration_secs,
+            players,
+            timeline: Timeline::of(&game.timeline, &standings),
+        }
+    }
+}
+
+fn properties_of(game: &GameState, player_id: Uuid) -> u32 {
+    game.properties
+        .values()
+        .filter(|prop| prop.owner == Some(player_id))
+        .count() as u32
+}
@@ -0,0 +1,180 @@
+//! Time limits of a room
+//!
+//! The server's defaults come from the environment; a room can override any
+//! of them when it is created. Both are checked against the same ranges so a
+//! room cannot stall or rush its players.
+
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::FieldErrors;
+
+const TURN_SECS: RangeInclusive<u32> = 15..=600;
+const AUCTION_SECS: RangeInclusive<u32> = 5..=120;
+const DEBT_SECS: RangeInclusive<u32> = 30..=600;
+const RECONNECT_GRACE_SECS: RangeInclusive<u32> = 5..=600;
+const BOT_DELAY_MAX_MS: RangeInclusive<u32> = 0..=10_000;
+const AUTO_START_SECS: RangeInclusive<u32> = 10..=600;
+
+/// Time limits in effect for a room
+///
+/// Turn, auction and debt limits of zero mean no limit, and a lobby
+/// countdown of zero leaves starting to the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct GameTimers {
+    /// Time a player has for their turn
+    pub turn_secs: u32,
+    /// Time an auction stays open after the last bid
+    pub auction_secs: u32,
+    /// Time a player in debt has to raise the money or go bankrupt
+    pub debt_secs: u32,
+    /// Time a player may stay disconnected before a bot takes over
+    pub reconnect_grace_secs: u32,
+    /// Longest a bot pauses before acting, whatever its pace
+    pub bot_delay_max_ms: u32,
+    /// Countdown from the second human joining the lobby until the empty
+    /// seats go to bots and the game starts
+    #[serde(default)]
+    pub auto_start_secs: u32,
+}
+
+impl Default for GameTimers {
+    fn default() -> Self {
+        Self {
+            turn_secs: 0,
+            auction_secs: 0,
+            debt_secs: 0,
+            reconnect_grace_secs: 60,
+            bot_delay_max_ms: 2000,
+            auto_start_secs: 0,
+        }
+    }
+}
+
+impl GameTimers {
+    /// Check every limit against its allowed range
+    pub fn validate(&self) -> FieldErrors {
+        let mut errors = FieldErrors::new();
+        check_optional(&mut errors, "turn_secs", self.turn_secs, TURN_SECS);
+        check_optional(&mut errors, "auction_secs", self.auction_secs, AUCTION_SECS);
+        check_optional(&mut errors, "debt_secs", self.debt_secs, DEBT_SECS);
+        check(
+            &mut errors,
+            "reconnect_grace_secs",
+            self.reconnect_grace_secs,
+            RECONNECT_GRACE_SECS,
+        );
+        check(
+            &mut errors,
+            "bot_delay_max_ms",
+            self.bot_delay_max_ms,
+            BOT_DELAY_MAX_MS,
+        );
+        check_optional(
+            &mut errors,
+            "auto_start_secs",
+            self.auto_start_secs,
+            AUTO_START_SECS,
+        );
+        errors
+    }
+
+    /// These limits with a room's overrides applied
+    pub fn with(&self, overrides: &TimerOverrides) -> Self {
+        Self {
+            turn_secs: overrides.turn_secs.unwrap_or(self.turn_secs),
+            auction_secs: overrides.auction_secs.unwrap_or(self.auction_secs),
+            debt_secs: overrides.debt_secs.unwrap_or(self.debt_secs),
+            reconnect_grace_secs: overrides
+                .reconnect_grace_secs
+                .unwrap_or(self.reconnect_grace_secs),
+            bot_delay_max_ms: overrides.bot_delay_max_ms.unwrap_or(self.bot_delay_max_ms),
+            auto_start_secs: overrides.auto_start_secs.unwrap_or(self.auto_start_secs),
+        }
+    }
+
+    pub fn turn(&self) -> Option<Duration> {
+        limit(self.turn_secs)
+    }
+
+    pub fn auction(&self) -> Option<Duration> {
+        limit(self.auction_secs)
+    }
+
+    pub fn debt(&self) -> Option<Duration> {
+        limit(self.debt_secs)
+    }
+
+    pub fn auto_start(&self) -> Option<Duration> {
+        limit(self.auto_start_secs)
+    }
+
+    pub fn reconnect_grace(&self) -> Duration {
+        Duration::from_secs(self.reconnect_grace_secs.into())
+    }
+
+    pub fn bot_delay_max(&self) -> Duration {
+        Duration::from_millis(self.bot_delay_max_ms.into())
+    }
+}
+
+/// Limits a room sets for itself; the rest come from the server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TimerOverrides {
+    pub turn_secs: Option<u32>,
+    pub auction_secs: Option<u32>,
+    pub debt_secs: Option<u32>,
+    pub reconnect_grace_secs: Option<u32>,
+    pub bot_delay_max_ms: Option<u32>,
+    #[serde(default)]
+    pub auto_start_secs: Option<u32>,
+}
+
+impl TimerOverrides {
+    /// Every limit set, so the room keeps them if server defaults change
+    pub fn pinned(timers: GameTimers) -> Self {
+        Self {
+            turn_secs: Some(timers.turn_secs),
+            auction_secs: Some(timers.auction_secs),
+            debt_secs: Some(timers.debt_secs),
+            reconnect_grace_secs: Some(timers.reconnect_grace_secs),
+            bot_delay_max_ms: Some(timers.bot_delay_max_ms),
+            auto_start_secs: Some(timers.auto_start_secs),
+        }
+    }
+}
+
+fn limit(secs: u32) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs.into()))
+}
+
+fn check(errors: &mut FieldErrors, name: &str, value: u32, range: RangeInclusive<u32>) {
+    if range.contains(&value) {
+        return;
+    }
+    errors.add(
+        name,
+        "out_of_range",
+        format!("{} must be {} to {}", name, range.start(), range.end()),
+    );
+}
+
+/// Like `check`, but zero for no limit is also fine
+fn check_optional(errors: &mut FieldErrors, name: &str, value: u32, range: RangeInclusive<u32>) {
+    if value == 0 || range.contains(&value) {
+        return;
+    }
+    errors.add(
+        name,
+        "out_of_range",
+        format!(
+            "{} must be 0 (no limit) or {} to {}",
+            name,
+            range.start(),
+            range.end()
+        ),
+    );
+}
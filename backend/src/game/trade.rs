@@ -1,4 +1,4 @@
-use super::{GameState, TradeAssets, TradeOffer, TradeStatus};
+use super::{GameState, LogLine, ServerEvent, TradeAssets, TradeOffer, TradeRecord, TradeStatus};
 use uuid::Uuid;
 
 pub struct TradeHandler;
@@ -39,6 +39,7 @@ impl TradeHandler {
             offering,
             requesting,
             status: TradeStatus::Pending,
+            counter_to: None,
         };
 
         game.active_trade = Some(offer.clone());
@@ -82,8 +83,57 @@ impl TradeHandler {
         true
     }
 
+    /// Answer the active offer with new terms going the other way
+    ///
+    /// Only the player the offer was made to can counter it. The original
+    /// is settled as countered and the counter-offer becomes the active one.
+    pub fn counter_trade(
+        game: &mut GameState,
+        player_id: Uuid,
+        trade_id: Uuid,
+        offering: TradeAssets,
+        requesting: TradeAssets,
+        events: &mut Vec<ServerEvent>,
+    ) -> Result<TradeOffer, String> {
+        let trade = match &game.active_trade {
+            Some(t) if t.id == trade_id && t.status == TradeStatus::Pending => t.clone(),
+            _ => return Err("Trade offer not found or expired.".to_string()),
+        };
+
+        if trade.to_player != player_id {
+            return Err("Only the player offered a trade can counter it.".to_string());
+        }
+        if !Self::validate_assets(game, player_id, &offering) {
+            return Err("You do not own all the offered assets.".to_string());
+        }
+        if !Self::validate_assets(game, trade.from_player, &requesting) {
+            return Err("Target player does not own all the requested assets.".to_string());
+        }
+
+        let counter = TradeOffer {
+            id: Uuid::new_v4(),
+            from_player: player_id,
+            to_player: trade.from_player,
+            offering,
+            requesting,
+            status: TradeStatus::Pending,
+            counter_to: Some(trade.id),
+        };
+
+        Self::conclude(game, trade, TradeStatus::Countered, events);
+        game.active_trade = Some(counter.clone());
+        Ok(counter)
+    }
+
     /// Accept the current active trade
-    pub fn accept_trade(game: &mut GameState, trade_id: Uuid) -> Result<(), String> {
+    ///
+    /// Returns whether the trade went through; an offer whose assets are no
+    /// longer all there expires instead.
+    pub fn accept_trade(
+        game: &mut GameState,
+        trade_id: Uuid,
+        events: &mut Vec<ServerEvent>,
+    ) -> Result<bool, String> {
         let trade = match &game.active_trade {
             Some(t) if t.id == trade_id => t.clone(),
             _ => return Err("Trade offer not found or expired.".to_string()),
@@ -94,23 +144,20 @@ impl TradeHandler {
         }
 
         // Re-validate ownership just in case state changed
-        if !Self::validate_assets(game, trade.from_player, &trade.offering) {
-            game.active_trade = None;
-            return Err("Offer side assets no longer available.".to_string());
-        }
-        if !Self::validate_assets(game, trade.to_player, &trade.requesting) {
-            game.active_trade = None;
-            return Err("Request side assets no longer available.".to_string());
+        if !Self::validate_assets(game, trade.from_player, &trade.offering)
+            || !Self::validate_assets(game, trade.to_player, &trade.requesting)
+        {
+            Self::expire(game, events);
+            return Ok(false);
         }
 
         // Execute Transfer
         Self::transfer_assets(game, trade.from_player, trade.to_player, &trade.offering);
         Self::transfer_assets(game, trade.to_player, trade.from_player, &trade.requesting);
 
-        game.active_trade = None;
-        game.log("Trade completed successfully.".to_string());
+        Self::conclude(game, trade, TradeStatus::Accepted, events);
 
-        Ok(())
+        Ok(true)
     }
 
     fn transfer_assets(game: &mut GameState, from: Uuid, to: Uuid, assets: &TradeAssets) {
@@ -143,18 +190,63 @@ impl TradeHandler {
     }
 
     /// Reject active trade
-    pub fn reject_trade(game: &mut GameState, trade_id: Uuid) -> Result<(), String> {
-        let valid = match &game.active_trade {
-            Some(t) => t.id == trade_id,
-            None => false,
-        };
+    pub fn reject_trade(
+        game: &mut GameState,
+        trade_id: Uuid,
+        events: &mut Vec<ServerEvent>,
+    ) -> Result<(), String> {
+        match &game.active_trade {
+            Some(t) if t.id == trade_id => {
+                let trade = t.clone();
+                Self::conclude(game, trade, TradeStatus::Rejected, events);
+                Ok(())
+            }
+            _ => Err("Trade not found.".to_string()),
+        }
+    }
 
-        if valid {
+    /// Void the active offer, if any, without an answer
+    pub fn expire(game: &mut GameState, events: &mut Vec<ServerEvent>) {
+        if let Some(trade) = game.active_trade.clone() {
+            Self::conclude(game, trade, TradeStatus::Expired, events);
+        }
+    }
+
+    /// Settle an offer: clear it, add it to the trade history and the game
+    /// log, and announce it
+    fn conclude(
+        game: &mut GameState,
+        mut trade: TradeOffer,
+        status: TradeStatus,
+        events: &mut Vec<ServerEvent>,
+    ) {
+        if game.active_trade.as_ref().is_some_and(|t| t.id == trade.id) {
             game.active_trade = None;
-            game.log("Trade offer rejected.".to_string());
-            Ok(())
-        } else {
-            Err("Trade not found.".to_string())
         }
+        trade.status = status;
+
+        let name = |id| {
+            game.get_player(id)
+                .map(|p| p.name.clone())
+                .unwrap_or_default()
+        };
+        let key = match status {
+            TradeStatus::Accepted => "trade_accepted",
+            TradeStatus::Rejected => "trade_declined",
+            TradeStatus::Countered => "trade_countered",
+            TradeStatus::Pending | TradeStatus::Expired => "trade_expired",
+        };
+        let line = LogLine::new(key)
+            .arg("from", name(trade.from_player))
+            .arg("to", name(trade.to_player))
+            .arg("trade_id", trade.id);
+        game.log(line);
+
+        let record = TradeRecord {
+            offer: trade,
+            concluded_at: chrono::Utc::now().timestamp(),
+        };
+        game.trades.push(record.clone());
+        events.push(ServerEvent::TradeConcluded { trade: record });
     }
 }
@@ -0,0 +1,290 @@
+//! gRPC API for programmatic clients
+//!
+//! Built with the `grpc` feature and served on `GRPC_PORT`, next to the HTTP
+//! server. Each call goes through the same handler as its REST endpoint, so
+//! validation, bans and tokens work alike, and the event stream is a hub
+//! connection like a WebSocket. Events travel as their WebSocket JSON; see
+//! `proto/mo_de.proto`.
+
+// tonic's `Status` is what every call returns as its error
+#![allow(clippy::result_large_err)]
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use futures::Stream;
+use serde::de::DeserializeOwned;
+use tonic::{metadata::MetadataMap, Request, Response, Status};
+
+use crate::api::{self, auth, handlers, AppState, ClientIp};
+use crate::error::AppError;
+use crate::game::ClientMessage;
+use crate::i18n::Locale;
+use crate::shutdown;
+use crate::ws::{self, Feed};
+
+pub mod proto {
+    tonic::include_proto!("mo_de.v1");
+}
+
+use proto::game_server::{Game, GameServer};
+
+/// Serve the gRPC API until the process exits
+///
+/// Event streams end with the others when the server drains, on the
+/// `SERVER_RESTARTING` every connection gets.
+pub async fn serve(addr: SocketAddr, state: AppState) -> anyhow::Result<()> {
+    tonic::transport::Server::builder()
+        .add_service(GameServer::new(GameService { state }))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+struct GameService {
+    state: AppState,
+}
+
+fn status(e: AppError) -> Status {
+    let message = e.localized_message(Locale::default());
+    match e {
+        AppError::NotFound(_) => Status::not_found(message),
+        AppError::BadRequest(_) | AppError::Validation(_) => Status::invalid_argument(message),
+        AppError::Unauthorized => Status::unauthenticated(message),
+        AppError::Forbidden(_) => Status::permission_denied(message),
+        AppError::GameError(_) => Status::failed_precondition(message),
+        AppError::Conflict(_) => Status::aborted(message),
+        AppError::RateLimited(_) => Status::resource_exhausted(message),
+        AppError::Unavailable(_) => Status::unavailable(message),
+        _ if e.is_retryable() => Status::unavailable(message),
+        _ => Status::internal(message),
+    }
+}
+
+/// A request body as its REST endpoint takes it
+fn body<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, Status> {
+    serde_json::from_value(value).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+/// JSON a client sent in a string field, `null` when empty
+fn json_field(field: &str, json: &str) -> Result<serde_json::Value, Status> {
+    if json.trim().is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+    serde_json::from_str(json)
+        .map_err(|e| Status::invalid_argument(format!("{} is not valid JSON: {}", field, e)))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, Status> {
+    serde_json::to_string(value).map_err(|e| Status::internal(e.to_string()))
+}
+
+impl GameService {
+    /// Caller's address, as the HTTP server would see it
+    fn client_ip<T>(&self, request: &Request<T>) -> ClientIp {
+        let headers = request.metadata().clone().into_headers();
+        ClientIp(api::client_ip(
+            &self.state.config,
+            &headers,
+            request.remote_addr(),
+        ))
+    }
+
+    /// Player whose token the call carries, checked against the room
+    async fn player(
+        &self,
+        metadata: &MetadataMap,
+        room_id: &str,
+    ) -> Result<auth::PlayerAuth, Status> {
+        let token = metadata
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| status(AppError::Unauthorized))?;
+        let player_id = auth::player_in_token(&self.state.config, token)
+            .ok_or_else(|| status(AppError::Unauthorized))?;
+        auth::verify_player_token(&self.state, token, room_id, player_id)
+            .await
+            .map_err(status)
+    }
+}
+
+#[tonic::async_trait]
+impl Game for GameService {
+    async fn create_room(
+        &self,
+        request: Request<proto::CreateRoomRequest>,
+    ) -> Result<Response<proto::Seat>, Status> {
+        shutdown::ensure_accepting().map_err(status)?;
+        let ip = self.client_ip(&request);
+        let headers = request.metadata().clone().into_headers();
+        let req = request.into_inner();
+        let body = body(serde_json::json!({
+            "host_name": req.host_name,
+            "config": json_field("config_json", &req.config_json)?,
+            "preset": req.preset,
+        }))?;
+
+        let Json(seat) =
+            handlers::create_room(State(self.state.clone()), None, ip, headers, Json(body))
+                .await
+                .map_err(status)?;
+        Ok(Response::new(proto::Seat {
+            room_id: seat.room_id,
+            player_id: seat.player_id.to_string(),
+            token: seat.token,
+        }))
+    }
+
+    async fn join_room(
+        &self,
+        request: Request<proto::JoinRoomRequest>,
+    ) -> Result<Response<proto::Seat>, Status> {
+        shutdown::ensure_accepting().map_err(status)?;
+        let ip = self.client_ip(&request);
+        let headers = request.metadata().clone().into_headers();
+        let req = request.into_inner();
+        let body = body(serde_json::json!({ "player_name": req.player_name }))?;
+
+        let Json(seat) = handlers::join_room(
+            State(self.state.clone()),
+            Path(req.room_id.clone()),
+            None,
+            ip,
+            headers,
+            Json(body),
+        )
+        .await
+        .map_err(status)?;
+        Ok(Response::new(proto::Seat {
+            room_id: req.room_id,
+            player_id: seat.player_id.to_string(),
+            token: seat.token,
+        }))
+    }
+
+    async fn add_bot(
+        &self,
+        request: Request<proto::AddBotRequest>,
+    ) -> Result<Response<proto::AddBotResponse>, Status> {
+        shutdown::ensure_accepting().map_err(status)?;
+        let room_id = request.get_ref().room_id.clone();
+        let auth = self.player(request.metadata(), &room_id).await?;
+        let req = request.into_inner();
+        let body = body(serde_json::json!({
+            "difficulty": req.difficulty,
+            "name": req.name,
+            "avatar": req.avatar,
+        }))?;
+
+        let Json(bot) =
+            handlers::add_bot(auth, State(self.state.clone()), Path(room_id), Json(body))
+                .await
+                .map_err(status)?;
+        Ok(Response::new(proto::AddBotResponse {
+            player_id: bot.player_id.to_string(),
+        }))
+    }
+
+    async fn start_game(
+        &self,
+        request: Request<proto::RoomRequest>,
+    ) -> Result<Response<proto::StartGameResponse>, Status> {
+        shutdown::ensure_accepting().map_err(status)?;
+        let room_id = request.get_ref().room_id.clone();
+        let auth = self.player(request.metadata(), &room_id).await?;
+
+        let Json(_) = handlers::start_game(auth, State(self.state.clone()), Path(room_id))
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::StartGameResponse {}))
+    }
+
+    async fn get_state(
+        &self,
+        request: Request<proto::RoomRequest>,
+    ) -> Result<Response<proto::GameState>, Status> {
+        let room_id = request.get_ref().room_id.clone();
+        let auth = self.player(request.metadata(), &room_id).await?;
+
+        let Json(state) = handlers::get_state(auth, State(self.state.clone()), Path(room_id))
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::GameState {
+            seq: state.seq,
+            state_json: to_json(&state.state)?,
+        }))
+    }
+
+    async fn submit_event(
+        &self,
+        request: Request<proto::ClientMessage>,
+    ) -> Result<Response<proto::ActionResult>, Status> {
+        let room_id = request.get_ref().room_id.clone();
+        let auth = self.player(request.metadata(), &room_id).await?;
+        let message: ClientMessage = serde_json::from_str(&request.get_ref().message_json)
+            .map_err(|e| Status::invalid_argument(format!("Unknown or malformed event: {}", e)))?;
+
+        let Json(result) = handlers::submit_action(
+            auth,
+            State(self.state.clone()),
+            Path(room_id),
+            Json(message),
+        )
+        .await
+        .map_err(status)?;
+        Ok(Response::new(proto::ActionResult {
+            event_type: result.event.unwrap_or_default(),
+            request_id: result.request_id,
+            seq: result.seq,
+            state_json: to_json(&result.state)?,
+        }))
+    }
+
+    type StreamEventsStream =
+        Pin<Box<dyn Stream<Item = Result<proto::ServerMessage, Status>> + Send + 'static>>;
+
+    async fn stream_events(
+        &self,
+        request: Request<proto::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        shutdown::ensure_accepting().map_err(status)?;
+        let ClientIp(ip) = self.client_ip(&request);
+        let room_id = request.get_ref().room_id.clone();
+        let player_id = self.player(request.metadata(), &room_id).await?.player_id;
+        let req = request.into_inner();
+        api::ensure_not_banned(&self.state, None, ip.as_deref())
+            .await
+            .map_err(status)?;
+        let version = ws::negotiate_version(req.version).map_err(status)?;
+        ws::record_client(&self.state, &room_id, player_id, ip.as_deref(), None).await;
+
+        let span = tracing::info_span!("grpc", room_id = %room_id, player_id = %player_id);
+        let feed = Feed::open(
+            self.state.clone(),
+            room_id,
+            player_id,
+            version,
+            req.after_seq,
+            span,
+        )
+        .await;
+
+        let stream = futures::stream::unfold(feed, |mut feed| async move {
+            let message = feed.next().await?;
+            let item = serde_json::to_value(&message)
+                .map(|value| proto::ServerMessage {
+                    seq: message.seq,
+                    r#type: value["type"].as_str().unwrap_or_default().to_string(),
+                    message_json: value.to_string(),
+                })
+                .map_err(|e| Status::internal(e.to_string()));
+            Some((item, feed))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
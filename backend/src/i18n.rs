@@ -0,0 +1,631 @@
+//! Translations of game logs and error messages
+//!
+//! Log lines are stored as a catalog key and the values of their
+//! placeholders, and rendered in each reader's language when they are sent.
+//! Errors keep their English messages in the code; the catalog maps those
+//! messages, with `{}` for the values formatted into them, to translations.
+//! Anything without a translation is shown in English.
+
+use std::collections::BTreeMap;
+
+use axum::http::{header::ACCEPT_LANGUAGE, HeaderMap};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A language the catalog has translations for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Es];
+
+    /// Position in `ALL`
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Supported language of a tag such as `es-MX`
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag.split(['-', '_']).next()?.trim();
+        if language.eq_ignore_ascii_case("en") {
+            Some(Locale::En)
+        } else if language.eq_ignore_ascii_case("es") {
+            Some(Locale::Es)
+        } else {
+            None
+        }
+    }
+
+    /// Most preferred supported language of an `Accept-Language` header
+    pub fn from_accept_language(headers: &HeaderMap) -> Option<Locale> {
+        let header = headers.get(ACCEPT_LANGUAGE)?.to_str().ok()?;
+        let mut best: Option<(f32, Locale)> = None;
+        for entry in header.split(',') {
+            let mut parts = entry.split(';');
+            let Some(locale) = parts.next().and_then(Locale::from_tag) else {
+                continue;
+            };
+            let quality = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse().ok())
+                .unwrap_or(1.0);
+            if quality > 0.0 && best.is_none_or(|(q, _)| quality > q) {
+                best = Some((quality, locale));
+            }
+        }
+        best.map(|(_, locale)| locale)
+    }
+
+    /// Language of a profile if set and supported, else the request's
+    pub fn negotiate(profile: Option<&str>, headers: &HeaderMap) -> Locale {
+        profile
+            .and_then(Locale::from_tag)
+            .or_else(|| Locale::from_accept_language(headers))
+            .unwrap_or_default()
+    }
+}
+
+/// Log lines by key: English, then Spanish
+const LOGS: &[(&str, &str, &str)] = &[
+    (
+        "room_created",
+        "{name} created the room",
+        "{name} creó la sala",
+    ),
+    (
+        "joined",
+        "{name} joined the game",
+        "{name} se unió a la partida",
+    ),
+    (
+        "ended_by_admin",
+        "The game was ended by an administrator: {reason}",
+        "Un administrador terminó la partida: {reason}",
+    ),
+    (
+        "admin_balance",
+        "An administrator adjusted {name}'s balance by ${amount}",
+        "Un administrador ajustó el saldo de {name} en ${amount}",
+    ),
+    (
+        "admin_end_turn",
+        "An administrator ended {name}'s turn",
+        "Un administrador terminó el turno de {name}",
+    ),
+    (
+        "admin_cancel_auction",
+        "An administrator cancelled the auction of {tile}",
+        "Un administrador canceló la subasta de {tile}",
+    ),
+    (
+        "admin_release_jail",
+        "An administrator released {name} from jail",
+        "Un administrador liberó a {name} de la cárcel",
+    ),
+    (
+        "closed_by_host",
+        "{name} closed the room",
+        "{name} cerró la sala",
+    ),
+    (
+        "imported_by_admin",
+        "An administrator replaced the game state",
+        "Un administrador reemplazó el estado de la partida",
+    ),
+    ("game_started", "Game started!", "¡Comenzó la partida!"),
+    (
+        "dealt_properties",
+        "{name} was dealt {tiles}",
+        "{name} recibió {tiles}",
+    ),
+    ("muted", "{name} was muted", "{name} fue silenciado"),
+    ("unmuted", "{name} was unmuted", "{name} ya puede hablar"),
+    (
+        "jail_escaped",
+        "{name} rolled doubles and escaped jail!",
+        "¡{name} sacó dobles y salió de la cárcel!",
+    ),
+    (
+        "jail_forced_bail",
+        "{name} was forced to pay ${amount} bail",
+        "{name} tuvo que pagar ${amount} de fianza",
+    ),
+    (
+        "in_debt",
+        "{name} owes ${amount} and must raise it",
+        "{name} debe ${amount} y tiene que reunirlos",
+    ),
+    (
+        "debt_expired",
+        "{name} ran out of time to raise ${amount}",
+        "{name} se quedó sin tiempo para reunir ${amount}",
+    ),
+    (
+        "jail_stayed",
+        "{name} failed to roll doubles in jail",
+        "{name} no sacó dobles en la cárcel",
+    ),
+    (
+        "passed_go",
+        "{name} passed GO and collected ${amount}",
+        "{name} pasó por la SALIDA y cobró ${amount}",
+    ),
+    (
+        "paid_tax",
+        "{name} paid ${amount} tax",
+        "{name} pagó ${amount} de impuestos",
+    ),
+    (
+        "drew_surprise",
+        "{name} drew a Surprise card",
+        "{name} sacó una carta de Sorpresa",
+    ),
+    (
+        "drew_treasure",
+        "{name} drew a Treasure card",
+        "{name} sacó una carta de Tesoro",
+    ),
+    (
+        "drew_surprise_card",
+        "{name} drew a Surprise card: {text}",
+        "{name} sacó una carta de Sorpresa: {text}",
+    ),
+    (
+        "drew_treasure_card",
+        "{name} drew a Treasure card: {text}",
+        "{name} sacó una carta de Tesoro: {text}",
+    ),
+    (
+        "card_collected",
+        "{name} collected ${amount}",
+        "{name} cobró ${amount}",
+    ),
+    (
+        "card_paid",
+        "{name} paid ${amount}",
+        "{name} pagó ${amount}",
+    ),
+    (
+        "card_payment",
+        "{from} paid {to} ${amount}",
+        "{from} pagó ${amount} a {to}",
+    ),
+    (
+        "free_parking",
+        "{name} collected ${amount} from Free Parking!",
+        "¡{name} cobró ${amount} del Estacionamiento Libre!",
+    ),
+    (
+        "sent_to_jail",
+        "{name} was sent to jail!",
+        "¡{name} fue a la cárcel!",
+    ),
+    (
+        "paid_rent",
+        "{from} paid ${amount} to {to} for rent on {tile}",
+        "{from} pagó ${amount} a {to} de alquiler por {tile}",
+    ),
+    (
+        "bought",
+        "{name} bought {tile} for ${amount}",
+        "{name} compró {tile} por ${amount}",
+    ),
+    (
+        "auction_started",
+        "Auction started for {tile}",
+        "Comenzó la subasta de {tile}",
+    ),
+    (
+        "auction_won",
+        "{name} won {tile} at auction for ${amount}",
+        "{name} ganó {tile} en subasta por ${amount}",
+    ),
+    (
+        "auction_no_bids",
+        "Auction for {tile} ended with no bids",
+        "La subasta de {tile} terminó sin pujas",
+    ),
+    (
+        "paid_bail",
+        "{name} paid ${amount} to get out of jail",
+        "{name} pagó ${amount} para salir de la cárcel",
+    ),
+    (
+        "used_jail_card",
+        "{name} used a card to get out of jail",
+        "{name} usó una carta para salir de la cárcel",
+    ),
+    ("turn", "{name}'s turn", "Turno de {name}"),
+    (
+        "auto_start",
+        "The game starts in {secs} seconds, with bots in the empty seats",
+        "La partida empieza en {secs} segundos, con bots en los asientos libres",
+    ),
+    (
+        "inflation",
+        "Inflation! Passing GO now pays ${amount}",
+        "¡Inflación! Pasar por la SALIDA ahora paga ${amount}",
+    ),
+    (
+        "round_limit",
+        "{rounds} rounds played, the richest player wins",
+        "Se jugaron {rounds} rondas, gana el jugador más rico",
+    ),
+    (
+        "rematch",
+        "Rematch of room {room}",
+        "Revancha de la sala {room}",
+    ),
+    ("won", "{name} wins the game!", "¡{name} gana la partida!"),
+    ("left", "{name} left the game", "{name} abandonó la partida"),
+    (
+        "left_bot_took_seat",
+        "{name} left the game, {bot} takes their seat",
+        "{name} abandonó la partida, {bot} ocupa su lugar",
+    ),
+    (
+        "built_house",
+        "{name} built a house on {tile}",
+        "{name} construyó una casa en {tile}",
+    ),
+    (
+        "built_hotel",
+        "{name} built a hotel on {tile}",
+        "{name} construyó un hotel en {tile}",
+    ),
+    (
+        "mortgaged",
+        "{name} mortgaged {tile} for ${amount}",
+        "{name} hipotecó {tile} por ${amount}",
+    ),
+    (
+        "unmortgaged",
+        "{name} unmortgaged {tile} for ${amount}",
+        "{name} levantó la hipoteca de {tile} por ${amount}",
+    ),
+    (
+        "away",
+        "{name} is away, a bot is playing for them",
+        "{name} está ausente, un bot juega en su lugar",
+    ),
+    ("back", "{name} is back", "{name} ha vuelto"),
+    (
+        "turn_timed_out",
+        "{name} ran out of time",
+        "A {name} se le acabó el tiempo",
+    ),
+    (
+        "bankrupt",
+        "Player {name} has gone BANKRUPT!",
+        "¡{name} está en BANCARROTA!",
+    ),
+    (
+        "assets_to_creditor",
+        "All assets transferred to {name}.",
+        "Todos los bienes pasan a {name}.",
+    ),
+    (
+        "forfeited_to_auction",
+        "{name}'s {count} properties go up for auction one by one",
+        "Las {count} propiedades de {name} salen a subasta una por una",
+    ),
+    (
+        "assets_to_bank",
+        "Assets returned to the Bank.",
+        "Los bienes vuelven a la Banca.",
+    ),
+    (
+        "trade_completed",
+        "Trade completed successfully.",
+        "Intercambio completado.",
+    ),
+    (
+        "trade_rejected",
+        "Trade offer rejected.",
+        "Oferta de intercambio rechazada.",
+    ),
+    (
+        "trade_accepted",
+        "{from} and {to} completed a trade",
+        "{from} y {to} completaron un intercambio",
+    ),
+    (
+        "trade_declined",
+        "{to} turned down a trade offer from {from}",
+        "{to} rechazó una oferta de intercambio de {from}",
+    ),
+    (
+        "trade_countered",
+        "{to} answered a trade offer from {from} with a counter-offer",
+        "{to} respondió a una oferta de intercambio de {from} con una contraoferta",
+    ),
+    (
+        "trade_expired",
+        "A trade offer from {from} to {to} expired",
+        "Venció una oferta de intercambio de {from} a {to}",
+    ),
+];
+
+/// Error messages: English, then Spanish; the first match wins, so more
+/// specific templates come first
+const ERRORS: &[(&str, &str)] = &[
+    ("Room not found", "Sala no encontrada"),
+    ("Player not found", "Jugador no encontrado"),
+    ("User not found", "Usuario no encontrado"),
+    ("Board not found", "Tablero no encontrado"),
+    ("Invite not found", "Invitación no encontrada"),
+    ("Challenge not found", "Desafío no encontrado"),
+    ("Reward already claimed", "La recompensa ya fue reclamada"),
+    (
+        "Challenge is not complete yet",
+        "El desafío todavía no está completo",
+    ),
+    (
+        "Invite not found or expired",
+        "Invitación no encontrada o vencida",
+    ),
+    ("Unauthorized", "No autorizado"),
+    ("Not enough money", "No tienes suficiente dinero"),
+    ("No active turn", "No hay un turno en curso"),
+    ("Not your turn", "No es tu turno"),
+    (
+        "Action is for a stale turn",
+        "La acción es de un turno anterior",
+    ),
+    ("Cannot roll now", "No puedes tirar ahora"),
+    (
+        "Cannot leave jail now",
+        "No puedes salir de la cárcel ahora",
+    ),
+    ("Cannot buy now", "No puedes comprar ahora"),
+    (
+        "Cannot start auction now",
+        "No puedes iniciar una subasta ahora",
+    ),
+    ("Cannot end turn now", "No puedes terminar el turno ahora"),
+    ("No auction in progress", "No hay una subasta en curso"),
+    ("Cancel the auction first", "Primero cancela la subasta"),
+    ("Room is closed", "La sala está cerrada"),
+    ("Room is already closed", "La sala ya está cerrada"),
+    (
+        "Only the host can close the room",
+        "Solo el anfitrión puede cerrar la sala",
+    ),
+    (
+        "Only the host can start the game",
+        "Solo el anfitrión puede empezar la partida",
+    ),
+    (
+        "Only the host can add bots",
+        "Solo el anfitrión puede añadir bots",
+    ),
+    (
+        "Only the host can change the room's lifetime",
+        "Solo el anfitrión puede cambiar la duración de la sala",
+    ),
+    ("Player is not in jail", "El jugador no está en la cárcel"),
+    (
+        "Balance cannot go below zero",
+        "El saldo no puede quedar por debajo de cero",
+    ),
+    (
+        "Amount must be between -{} and {}, and not zero",
+        "El monto debe estar entre -{} y {}, y no ser cero",
+    ),
+    ("Bid must be higher", "La puja debe ser más alta"),
+    ("Invalid tile", "Casilla no válida"),
+    ("Not a property", "No es una propiedad"),
+    ("You don't own this property", "Esta propiedad no es tuya"),
+    (
+        "Must own full color set",
+        "Necesitas todo el grupo de color",
+    ),
+    (
+        "Cannot build on this tile",
+        "No se puede construir en esta casilla",
+    ),
+    (
+        "Already at max buildings",
+        "Ya tiene el máximo de edificios",
+    ),
+    ("Must sell buildings first", "Primero vende los edificios"),
+    (
+        "Properties can only be managed on your own turn",
+        "Solo puedes gestionar propiedades en tu turno",
+    ),
+    (
+        "Properties cannot be managed during an auction",
+        "No puedes gestionar propiedades durante una subasta",
+    ),
+    ("Already mortgaged", "Ya está hipotecada"),
+    ("Not mortgaged", "No está hipotecada"),
+    ("Not in jail", "No estás en la cárcel"),
+    (
+        "No get out of jail cards",
+        "No tienes cartas para salir de la cárcel",
+    ),
+    ("Game already started", "La partida ya empezó"),
+    ("Game is not over yet", "La partida aún no terminó"),
+    ("Game is not in progress", "La partida no está en curso"),
+    ("Game is over", "La partida terminó"),
+    (
+        "Only the player offered a trade can counter it.",
+        "Solo quien recibió la oferta de intercambio puede contraofertar.",
+    ),
+    (
+        "Bankrupt players can only chat",
+        "Los jugadores en bancarrota solo pueden chatear",
+    ),
+    ("Room is full", "La sala está llena"),
+    (
+        "Need at least 2 players",
+        "Se necesitan al menos 2 jugadores",
+    ),
+    ("Already in this room", "Ya estás en esta sala"),
+    ("Not a player in this game", "No juegas en esta partida"),
+    (
+        "Player already left the game",
+        "El jugador ya dejó la partida",
+    ),
+    (
+        "Use resign to leave the game",
+        "Usa rendirse para dejar la partida",
+    ),
+    ("You are muted", "Estás silenciado"),
+    (
+        "You cannot mute yourself",
+        "No puedes silenciarte a ti mismo",
+    ),
+    (
+        "Only the host can mute players",
+        "Solo el anfitrión puede silenciar jugadores",
+    ),
+    (
+        "Only the host can kick players",
+        "Solo el anfitrión puede expulsar jugadores",
+    ),
+    ("Unknown avatar", "Avatar desconocido"),
+    ("Unknown color", "Color desconocido"),
+    ("Color is already taken", "Ese color ya está en uso"),
+    ("Unknown piece", "Ficha desconocida"),
+    ("Piece is already taken", "Esa ficha ya está en uso"),
+    (
+        "Waiting for {} to get ready",
+        "Esperando a que {} esté listo",
+    ),
+    ("Unknown board", "Tablero desconocido"),
+    ("Invalid locale", "Idioma no válido"),
+    ("A name is required", "Se necesita un nombre"),
+    ("Name cannot be empty", "El nombre no puede estar vacío"),
+    (
+        "Name cannot be longer than {} characters",
+        "El nombre no puede tener más de {} caracteres",
+    ),
+    (
+        "Name contains invalid characters",
+        "El nombre contiene caracteres no válidos",
+    ),
+    ("Name is already taken", "Ese nombre ya está en uso"),
+    ("Username is already taken", "Ese usuario ya existe"),
+    (
+        "{} must be 0 (no limit) or {} to {}",
+        "{} debe ser 0 (sin límite) o estar entre {} y {}",
+    ),
+    (
+        "{} must be 0 (every seat) or {} to {}",
+        "{} debe ser 0 (todos los asientos) o estar entre {} y {}",
+    ),
+    ("{} must be {} to {}", "{} debe estar entre {} y {}"),
+    (
+        "House rule {} is disabled on this server",
+        "La regla de la casa {} está desactivada en este servidor",
+    ),
+    (
+        "Server is restarting, try again shortly",
+        "El servidor se está reiniciando, inténtalo en un momento",
+    ),
+    (
+        "Too many requests, retry in {}s",
+        "Demasiadas solicitudes, reintenta en {}s",
+    ),
+    (
+        "You cannot befriend yourself",
+        "No puedes agregarte como amigo",
+    ),
+    (
+        "You can only invite friends",
+        "Solo puedes invitar a amigos",
+    ),
+    ("Not friends", "No son amigos"),
+    ("You are banned", "Tienes prohibido el acceso"),
+    (
+        "You cannot report yourself",
+        "No puedes denunciarte a ti mismo",
+    ),
+    ("Bots cannot be reported", "No se puede denunciar a un bot"),
+    (
+        "You already reported this player",
+        "Ya denunciaste a este jugador",
+    ),
+    ("Report not found", "Denuncia no encontrada"),
+    ("Report is already closed", "La denuncia ya está cerrada"),
+    (
+        "Acknowledgements are only sent over a WebSocket",
+        "Las confirmaciones solo se envían por WebSocket",
+    ),
+    (
+        "No pending friend request",
+        "No hay solicitud de amistad pendiente",
+    ),
+    (
+        "Sign-in expired, please start again",
+        "El inicio de sesión venció, vuelve a empezar",
+    ),
+    ("Internal error", "Error interno"),
+    ("Database error", "Error de base de datos"),
+    ("Cache error", "Error de caché"),
+];
+
+/// A log line in a language, if its key is in the catalog
+pub fn log_line(locale: Locale, key: &str, args: &BTreeMap<String, String>) -> Option<String> {
+    let (_, en, es) = LOGS.iter().find(|(k, _, _)| *k == key)?;
+    let template = match locale {
+        Locale::En => en,
+        Locale::Es => es,
+    };
+
+    let mut line = template.to_string();
+    for (name, value) in args {
+        line = line.replace(&format!("{{{}}}", name), value);
+    }
+    Some(line)
+}
+
+/// An error message in a language, the English one if it has no translation
+pub fn error_message(locale: Locale, message: &str) -> String {
+    if locale == Locale::En {
+        return message.to_string();
+    }
+    ERRORS
+        .iter()
+        .find_map(|(en, es)| {
+            let values = match_template(en, message)?;
+            let mut parts = es.split("{}");
+            let mut translated = parts.next().unwrap_or_default().to_string();
+            for (value, part) in values.into_iter().zip(parts) {
+                translated.push_str(value);
+                translated.push_str(part);
+            }
+            Some(translated)
+        })
+        .unwrap_or_else(|| message.to_string())
+}
+
+/// Values a message put in place of each `{}` of a template
+fn match_template<'a>(template: &str, message: &'a str) -> Option<Vec<&'a str>> {
+    let mut literals = template.split("{}");
+    let mut rest = message.strip_prefix(literals.next()?)?;
+    let literals: Vec<&str> = literals.collect();
+
+    let mut values = Vec::with_capacity(literals.len());
+    for (i, literal) in literals.iter().enumerate() {
+        let end = if i + 1 == literals.len() {
+            rest.strip_suffix(literal)?.len()
+        } else if literal.is_empty() {
+            return None;
+        } else {
+            rest.find(literal)?
+        };
+        if end == 0 {
+            return None;
+        }
+        values.push(&rest[..end]);
+        rest = &rest[end + literal.len()..];
+    }
+    rest.is_empty().then_some(values)
+}
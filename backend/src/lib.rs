@@ -20,3 +20,8 @@ pub mod ws;
 
 pub mod config;
 pub mod error;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod i18n;
+pub mod shutdown;
+pub mod tls;
@@ -1,59 +1,188 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::Router;
-use tokio::sync::RwLock;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use mo_de_backend::{api, config::Config, db, ws::Hub};
+use mo_de_backend::{
+    api,
+    config::{Config, GameStoreKind, LiveTunables, LogFormat, Profile, Tunables},
+    db, game, shutdown, tls, ws,
+};
+
+/// Longest requests still in flight may run once a TLS server stops
+const REQUEST_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "mo_de_backend=debug,tower_http=debug".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
     // Load config
     dotenvy::dotenv().ok();
     let config = Config::from_env()?;
+    let tunables = LiveTunables::new(Tunables::from_env()?);
+
+    // Initialize tracing; JSON lines carry the room and player of the spans
+    // they were logged in, so one game's logs can be filtered out
+    let json = config.log_format == LogFormat::Json;
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(&config.log_filter))
+        .with((!json).then(tracing_subscriber::fmt::layer))
+        .with(json.then(|| {
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+        }))
+        .init();
+
+    let problems = config.profile_problems(&tunables.current());
+    for problem in &problems {
+        tracing::warn!("{:?} profile: {}", config.profile, problem);
+    }
+    if config.profile == Profile::Prod && !problems.is_empty() {
+        anyhow::bail!("Refusing to start with unsafe settings in the prod profile");
+    }
+
+    // Rooms in the process instead of Redis; only for a single dev instance
+    if config.game_store == GameStoreKind::Memory {
+        game::store::install(Arc::new(game::store::MemoryStore::new()));
+    }
+
+    if let Some(words) = &config.chat_blocklist {
+        game::moderation::set_blocklist(words);
+    }
 
     // Initialize database
     let db_pool = db::create_pool(&config.database_url).await?;
-    
+
+    // Bots fall back to built-in parameters until a calibration is stored
+    match db::calibration::apply_stored_params(&db_pool).await {
+        Ok(0) => {}
+        Ok(n) => tracing::info!("Loaded calibrated parameters for {} bot difficulties", n),
+        Err(e) => tracing::warn!("Could not load calibrated bot parameters: {}", e),
+    }
+
+    // New rooms use the newest stored board and its card decks; older
+    // versions stay loaded for the games still playing on them
+    match db::boards::load_all(&db_pool).await {
+        Ok(defs) => match game::board::install(defs) {
+            0 => {}
+            n => tracing::info!("Loaded {} stored board versions", n),
+        },
+        Err(e) => tracing::warn!("Could not load stored boards: {}", e),
+    }
+
+    db::archive::init(db_pool.clone());
+    db::audit::init(db_pool.clone());
+    db::chat::init(db_pool.clone(), config.chat_retention_days);
+    db::journal::init(db_pool.clone());
+    game::store::configure(game::store::RedisLayout {
+        key_prefix: config.redis_key_prefix.clone(),
+        game_ttl_secs: config.game_ttl_secs,
+        lobby_ttl_secs: config.lobby_ttl_secs,
+        closed_grace_secs: config.room_close_grace_secs,
+    });
+
     // Initialize Redis
     let redis_client = redis::Client::open(config.redis_url.as_str())?;
-    let redis_conn = redis::aio::ConnectionManager::new(redis_client).await?;
+    let redis_conn = redis::aio::ConnectionManager::new(redis_client.clone()).await?;
+
+    // Initialize WebSocket hub, shared with other instances over Redis
+    let hub = ws::start_hub(redis_client, redis_conn.clone()).await?;
 
-    // Initialize WebSocket hub
-    let hub = Arc::new(RwLock::new(Hub::new()));
+    // Keep idle games past the Redis expiry
+    game::snapshots::spawn(redis_conn.clone(), db_pool.clone());
+    // Delete closed rooms once nobody needs to look at them any more
+    game::lifecycle::spawn(redis_conn.clone(), hub.clone());
+    // Clear out rooms everyone left and keys whose room is gone
+    let redis_rooms = config.game_store == GameStoreKind::Redis;
+    game::maintenance::spawn(
+        redis_conn.clone(),
+        db_pool.clone(),
+        hub.clone(),
+        game::maintenance::Limits {
+            abandoned_after: (config.abandoned_room_hours > 0)
+                .then(|| Duration::from_secs(config.abandoned_room_hours * 3600)),
+            lobby_max_age: (config.stale_lobby_mins > 0)
+                .then(|| Duration::from_secs(config.stale_lobby_mins * 60)),
+            orphaned_logs: redis_rooms,
+            orphaned_chat: redis_rooms,
+        },
+    );
+
+    // Rate limits, origins, house rules and room defaults can change without
+    // a restart
+    #[cfg(unix)]
+    tunables.reload_on_sighup()?;
 
     // Build application state
     let app_state = api::AppState {
         db: db_pool,
         redis: redis_conn,
-        hub,
+        hub: hub.clone(),
         config: config.clone(),
+        tunables: tunables.clone(),
     };
 
+    // Programmatic clients get the same rooms over gRPC
+    if let Some(port) = config.grpc_port {
+        #[cfg(feature = "grpc")]
+        {
+            let grpc_addr: SocketAddr = format!("{}:{}", config.host, port).parse()?;
+            tracing::info!("gRPC API starting on {}", grpc_addr);
+            let state = app_state.clone();
+            tokio::spawn(async move {
+                if let Err(e) = mo_de_backend::grpc::serve(grpc_addr, state).await {
+                    tracing::error!("gRPC server stopped: {}", e);
+                }
+            });
+        }
+        #[cfg(not(feature = "grpc"))]
+        tracing::warn!(
+            "GRPC_PORT is set to {} but this build has no gRPC API; build with --features grpc",
+            port
+        );
+    }
+
     // Build router
     let app = Router::new()
-        .merge(api::routes())
-        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any))
+        .merge(api::routes(&app_state))
+        .layer(api::cors_layer(&tunables))
         .layer(TraceLayer::new_for_http())
         .with_state(app_state);
 
     // Start server
     let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
-    tracing::info!("🎲 MO-DE server starting on {}", addr);
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    if let Some(files) = &config.tls {
+        let rustls = tls::load(files).await?;
+        tracing::info!("🎲 MO-DE server starting on {} (TLS)", addr);
+
+        let handle = axum_server::Handle::new();
+        tokio::spawn({
+            let handle = handle.clone();
+            async move {
+                shutdown::signal(hub).await;
+                handle.graceful_shutdown(Some(REQUEST_DRAIN_TIMEOUT));
+            }
+        });
+        axum_server::bind_rustls(addr, rustls)
+            .handle(handle)
+            .serve(app)
+            .await?;
+    } else {
+        tracing::info!("🎲 MO-DE server starting on {}", addr);
+
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown::signal(hub))
+            .await?;
+    }
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    shutdown::flush().await;
+    tracing::info!("MO-DE server stopped");
 
     Ok(())
 }
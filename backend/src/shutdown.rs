@@ -0,0 +1,118 @@
+//! Draining the server on SIGTERM or Ctrl-C
+//!
+//! Once a signal arrives, new rooms, seats and sockets are refused and the
+//! readiness probe fails so the load balancer stops sending traffic. Every
+//! connected client is told the server is restarting and when to reconnect,
+//! then its socket is closed with 1012 (service restart). Game states need no
+//! flushing: every action saves its room to Redis before it is broadcast, so
+//! the rooms carry on from any other instance. What is still queued for
+//! Postgres is written out before the process exits.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::db;
+use crate::error::{AppError, AppResult};
+use crate::game::ServerEvent;
+use crate::ws::Hub;
+
+/// How long clients are asked to wait before reconnecting
+const RECONNECT_AFTER: Duration = Duration::from_secs(2);
+
+/// Longest to wait for sockets to close after telling them to go
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest to wait for each Postgres writer to catch up
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often `signal` checks whether every socket is gone
+const DRAIN_POLL: Duration = Duration::from_millis(50);
+
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+/// Whether the server is shutting down
+pub fn is_draining() -> bool {
+    DRAINING.load(Ordering::Relaxed)
+}
+
+/// Refuse work that would outlive this instance once it is shutting down
+pub fn ensure_accepting() -> AppResult<()> {
+    if is_draining() {
+        return Err(AppError::Unavailable(
+            "Server is restarting, try again shortly".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Resolve once the server is asked to stop and its sockets are closed
+///
+/// Passed to `axum::serve`, which stops accepting connections when this
+/// returns and waits for requests still in flight.
+pub async fn signal(hub: Arc<Hub>) {
+    wait_for_signal().await;
+    DRAINING.store(true, Ordering::Relaxed);
+
+    tracing::info!(
+        "Shutting down, closing {} connections",
+        hub.local_connections()
+    );
+    hub.send_to_all_local(ServerEvent::ServerRestarting {
+        reconnect_after_ms: RECONNECT_AFTER.as_millis() as u64,
+    });
+
+    let deadline = tokio::time::Instant::now() + DRAIN_TIMEOUT;
+    while hub.local_connections() > 0 {
+        if tokio::time::Instant::now() >= deadline {
+            tracing::warn!(
+                "{} connections still open after {:?}, closing anyway",
+                hub.local_connections(),
+                DRAIN_TIMEOUT
+            );
+            break;
+        }
+        tokio::time::sleep(DRAIN_POLL).await;
+    }
+}
+
+/// Write out chat and journal entries still queued; call once the server
+/// has stopped
+pub async fn flush() {
+    if !db::chat::flush(FLUSH_TIMEOUT).await {
+        tracing::warn!("Chat writer did not catch up, some messages are lost");
+    }
+    if !db::journal::flush(FLUSH_TIMEOUT).await {
+        tracing::warn!("Journal writer did not catch up, some events are lost");
+    }
+}
+
+async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
@@ -0,0 +1,68 @@
+//! TLS termination for self-hosted deployments
+//!
+//! With `TLS_CERT_PATH` and `TLS_KEY_PATH` set, the server speaks HTTPS on
+//! its port, so WebSocket upgrades on it are WSS. Certificates are not
+//! requested here: an ACME client such as certbot renews the files, and the
+//! server picks up the new ones without a restart.
+
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+
+use crate::config::TlsFiles;
+
+/// How often the certificate files are checked for changes
+const RELOAD_POLL: Duration = Duration::from_secs(60);
+
+/// Load the certificate and key, and start reloading them when they change
+pub async fn load(files: &TlsFiles) -> Result<RustlsConfig> {
+    // Several rustls versions are linked in; pick the provider for ours
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let config = RustlsConfig::from_pem_file(&files.cert_path, &files.key_path)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to load TLS certificate {} and key {}",
+                files.cert_path.display(),
+                files.key_path.display()
+            )
+        })?;
+
+    tokio::spawn(watch(config.clone(), files.clone()));
+    Ok(config)
+}
+
+async fn watch(config: RustlsConfig, files: TlsFiles) {
+    let mut loaded = modified(&files).await;
+    loop {
+        tokio::time::sleep(RELOAD_POLL).await;
+
+        let current = modified(&files).await;
+        if current == loaded {
+            continue;
+        }
+        // Renewals write both files; keep the old pair if the new one does
+        // not load yet and try again on the next round
+        match config
+            .reload_from_pem_file(&files.cert_path, &files.key_path)
+            .await
+        {
+            Ok(()) => {
+                tracing::info!("Reloaded TLS certificate {}", files.cert_path.display());
+                loaded = current;
+            }
+            Err(e) => tracing::warn!("Failed to reload TLS certificate: {}", e),
+        }
+    }
+}
+
+/// Modification times of the certificate and key
+async fn modified(files: &TlsFiles) -> (Option<SystemTime>, Option<SystemTime>) {
+    async fn mtime(path: &Path) -> Option<SystemTime> {
+        tokio::fs::metadata(path).await.ok()?.modified().ok()
+    }
+    (mtime(&files.cert_path).await, mtime(&files.key_path).await)
+}
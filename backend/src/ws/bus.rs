@@ -0,0 +1,217 @@
+//! Redis pub/sub transport for hub events
+//!
+//! Every instance publishes room events to Redis and delivers whatever it
+//! receives to its own connections, so the players of one room may be
+//! connected to different instances. Sequence numbers come from a per-room
+//! Redis counter, so they agree across instances.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use super::hub::{Audience, Hub};
+use crate::game::{store, ServerEvent};
+
+/// Pause before resubscribing after the subscription drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Sequence counters outlive rooms by as long as rooms are kept
+const SEQ_TTL_SECS: u64 = 86400;
+
+/// Numbers and publishes an event in one step, so every subscriber sees
+/// events in sequence order
+static PUBLISH_EVENT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r"
+        local seq = redis.call('INCR', KEYS[1])
+        redis.call('EXPIRE', KEYS[1], ARGV[2])
+        redis.call('PUBLISH', KEYS[2], seq .. '|' .. ARGV[1])
+        return seq
+        ",
+    )
+});
+
+/// What instances tell each other about a room
+///
+/// On the wire a message is prefixed with its sequence number, `<seq>|<json>`,
+/// which is zero for anything but events for the room or its spectators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BusMessage {
+    /// An event for some or all of the room
+    Event {
+        room_id: String,
+        audience: Audience,
+        event: ServerEvent,
+    },
+    /// A player opened a connection on some instance
+    Connected { room_id: String, player_id: Uuid },
+    /// An event for a registered user, whatever rooms they are in
+    User { user_id: Uuid, event: ServerEvent },
+}
+
+impl BusMessage {
+    /// `hub:<room_id>`, or `hub:user:<user_id>` for notifications, after the
+    /// key prefix
+    fn channel(&self) -> String {
+        match self {
+            BusMessage::Event { room_id, .. } | BusMessage::Connected { room_id, .. } => {
+                format!("{}{}", store::layout().channel_prefix(), room_id)
+            }
+            BusMessage::User { user_id, .. } => {
+                format!("{}user:{}", store::layout().channel_prefix(), user_id)
+            }
+        }
+    }
+}
+
+/// Create a hub that shares rooms with other instances through Redis
+///
+/// Subscribes before returning, so a broken Redis fails startup instead of
+/// silently splitting rooms.
+pub async fn start_hub(
+    client: redis::Client,
+    redis: ConnectionManager,
+) -> redis::RedisResult<Arc<Hub>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let hub = Arc::new(Hub::with_bus(tx));
+
+    let pubsub = subscribe(&client).await?;
+    tokio::spawn(receive(client, pubsub, hub.clone()));
+    tokio::spawn(publish(redis, rx, hub.clone()));
+
+    Ok(hub)
+}
+
+/// Sequence number of the last event published for a room by any instance
+pub async fn room_seq(redis: &ConnectionManager, room_id: &str) -> redis::RedisResult<u64> {
+    let mut conn = redis.clone();
+    let seq: Option<u64> = conn.get(store::layout().room_seq_key(room_id)).await?;
+    Ok(seq.unwrap_or(0))
+}
+
+async fn subscribe(client: &redis::Client) -> redis::RedisResult<redis::aio::PubSub> {
+    let mut pubsub = client.get_async_connection().await?.into_pubsub();
+    pubsub
+        .psubscribe(format!("{}*", store::layout().channel_prefix()))
+        .await?;
+    Ok(pubsub)
+}
+
+/// Forward queued messages to Redis, delivering locally if Redis is down so
+/// players on this instance are not cut off
+async fn publish(
+    mut redis: ConnectionManager,
+    mut rx: mpsc::UnboundedReceiver<BusMessage>,
+    hub: Arc<Hub>,
+) {
+    while let Some(message) = rx.recv().await {
+        let channel = message.channel();
+        let json = match serde_json::to_string(&message) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!("Failed to encode hub message: {}", e);
+                continue;
+            }
+        };
+
+        let published: redis::RedisResult<()> = match &message {
+            BusMessage::Event {
+                room_id,
+                audience: Audience::Room | Audience::Spectators,
+                ..
+            } => PUBLISH_EVENT
+                .key(store::layout().room_seq_key(room_id))
+                .key(&channel)
+                .arg(&json)
+                .arg(SEQ_TTL_SECS)
+                .invoke_async::<_, u64>(&mut redis)
+                .await
+                .map(|_| ()),
+            BusMessage::Event { .. } | BusMessage::Connected { .. } | BusMessage::User { .. } => {
+                redis::cmd("PUBLISH")
+                    .arg(&channel)
+                    .arg(format!("0|{}", json))
+                    .query_async(&mut redis)
+                    .await
+            }
+        };
+
+        if let Err(e) = published {
+            tracing::warn!("Hub publish failed, delivering locally: {}", e);
+            match &message {
+                BusMessage::Event {
+                    room_id,
+                    audience,
+                    event,
+                } => {
+                    let seq = match audience {
+                        Audience::Player(_) => 0,
+                        Audience::Room | Audience::Spectators => hub.current_seq(room_id) + 1,
+                    };
+                    hub.deliver(room_id, *audience, seq, event);
+                }
+                BusMessage::User { user_id, event } => hub.deliver_to_user(*user_id, event),
+                BusMessage::Connected { .. } => {}
+            }
+        }
+    }
+}
+
+/// Deliver messages from every instance to local connections
+async fn receive(client: redis::Client, mut pubsub: redis::aio::PubSub, hub: Arc<Hub>) {
+    loop {
+        {
+            let mut messages = pubsub.on_message();
+            while let Some(msg) = messages.next().await {
+                let payload: String = match msg.get_payload() {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        tracing::warn!("Unreadable hub message: {}", e);
+                        continue;
+                    }
+                };
+                handle(&hub, &payload).await;
+            }
+        }
+
+        tracing::warn!("Hub subscription dropped, resubscribing");
+        pubsub = loop {
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            match subscribe(&client).await {
+                Ok(pubsub) => break pubsub,
+                Err(e) => tracing::warn!("Hub resubscribe failed: {}", e),
+            }
+        };
+    }
+}
+
+async fn handle(hub: &Arc<Hub>, payload: &str) {
+    let parsed = payload.split_once('|').and_then(|(seq, json)| {
+        Some((
+            seq.parse::<u64>().ok()?,
+            serde_json::from_str::<BusMessage>(json).ok()?,
+        ))
+    });
+    let Some((seq, message)) = parsed else {
+        tracing::warn!("Malformed hub message");
+        return;
+    };
+
+    match message {
+        BusMessage::Event {
+            room_id,
+            audience,
+            event,
+        } => hub.deliver(&room_id, audience, seq, &event),
+        BusMessage::Connected { room_id, player_id } => hub.mark_connected(&room_id, player_id),
+        BusMessage::User { user_id, event } => hub.deliver_to_user(user_id, &event),
+    }
+}
@@ -0,0 +1,109 @@
+//! Wire encodings for WebSocket frames
+//!
+//! JSON text frames are the default. Clients can ask for MessagePack binary
+//! frames at upgrade time, with `?encoding=msgpack` or the `msgpack`
+//! subprotocol. Maps keep their field names and ids stay strings, so both
+//! encodings carry the same shape.
+
+use axum::extract::ws::{CloseFrame, Message};
+use axum::http::HeaderValue;
+use once_cell::sync::OnceCell;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::game::{ClientMessage, ServerMessage};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Json,
+    Msgpack,
+}
+
+impl Encoding {
+    /// Subprotocols offered at upgrade, preferred first
+    pub const PROTOCOLS: [&'static str; 2] = ["msgpack", "json"];
+
+    /// Encoding for the subprotocol agreed on during the handshake
+    pub fn from_protocol(protocol: &HeaderValue) -> Option<Self> {
+        match protocol.to_str().ok()? {
+            "msgpack" => Some(Encoding::Msgpack),
+            "json" => Some(Encoding::Json),
+            _ => None,
+        }
+    }
+
+    /// Frame carrying a server message
+    ///
+    /// The first connection to send a message in an encoding serializes it;
+    /// every other one copies the cached bytes.
+    pub fn encode(&self, message: &ServerMessage) -> Option<Message> {
+        let frames = &message.frames;
+        let frame = match self {
+            Encoding::Json => json(message).map(Message::Text),
+            Encoding::Msgpack => frames
+                .msgpack
+                .get_or_init(|| to_msgpack(message))
+                .clone()
+                .map(Message::Binary),
+        };
+
+        if frame.is_none() {
+            tracing::error!("Failed to encode server message {}", message.seq);
+        }
+        frame
+    }
+}
+
+/// JSON text of a server message, serialized once like the frames
+pub(super) fn json(message: &ServerMessage) -> Option<String> {
+    message
+        .frames
+        .json
+        .get_or_init(|| serde_json::to_string(message).ok())
+        .clone()
+}
+
+/// Close frame ending a connection with `code`
+pub(super) fn close(code: u16) -> Message {
+    let reason = match code {
+        1012 => "Server restarting",
+        _ => "Room closed",
+    };
+    Message::Close(Some(CloseFrame {
+        code,
+        reason: reason.into(),
+    }))
+}
+
+/// A server message as already encoded, filled in on first send
+#[derive(Debug, Default)]
+pub struct EncodedFrames {
+    json: OnceCell<Option<String>>,
+    msgpack: OnceCell<Option<Vec<u8>>>,
+}
+
+/// Read a client message from a text (JSON) or binary (MessagePack) frame
+///
+/// Either encoding is accepted whatever was negotiated.
+pub fn decode(frame: &Message) -> Option<ClientMessage> {
+    match frame {
+        Message::Text(text) => serde_json::from_str(text).ok(),
+        Message::Binary(bytes) => from_msgpack(bytes),
+        _ => None,
+    }
+}
+
+fn to_msgpack<T: Serialize>(value: &T) -> Option<Vec<u8>> {
+    let mut buf = Vec::new();
+    let mut serializer = rmp_serde::Serializer::new(&mut buf)
+        .with_struct_map()
+        .with_human_readable();
+    value.serialize(&mut serializer).ok()?;
+    Some(buf)
+}
+
+fn from_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+    let mut deserializer = rmp_serde::Deserializer::new(bytes).with_human_readable();
+    T::deserialize(&mut deserializer).ok()
+}
@@ -0,0 +1,162 @@
+//! A player's connection to a room as a stream of messages
+//!
+//! Transports other than the game WebSocket, such as Server-Sent Events,
+//! join the hub through a feed: it says hello, catches the player up and
+//! then hands out the room's messages one at a time, with the same
+//! slow-client handling as a socket. Dropping it disconnects the player.
+
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tracing::Instrument;
+use uuid::Uuid;
+
+use super::hub::{self, ConnectionStats, SEND_QUEUE_CAPACITY};
+use crate::api::AppState;
+use crate::game::{chat, GameEngine, ServerEvent, ServerMessage};
+
+pub struct Feed {
+    state: AppState,
+    room_id: String,
+    player_id: Uuid,
+    conn_id: Uuid,
+    rx: mpsc::Receiver<ServerMessage>,
+    stats: Arc<ConnectionStats>,
+    /// Sent before anything from the queue
+    pending: VecDeque<ServerMessage>,
+    /// The last message closes the connection
+    closing: bool,
+    span: tracing::Span,
+}
+
+impl Feed {
+    /// Connect a player whose token has been checked
+    ///
+    /// The first message is `WELCOME`. With `after_seq`, the events since
+    /// then follow if this instance still has them all; otherwise, or
+    /// without it, the player's state and the chat so far.
+    pub async fn open(
+        state: AppState,
+        room_id: String,
+        player_id: Uuid,
+        version: u32,
+        after_seq: Option<u64>,
+        span: tracing::Span,
+    ) -> Self {
+        // Register connection in hub and let the room know
+        let (tx, rx) = mpsc::channel::<ServerMessage>(SEND_QUEUE_CAPACITY);
+        let stats = Arc::new(ConnectionStats::default());
+        let (user_id, locale) = hub::seat(&state, &room_id, player_id).await;
+        let reconnected = state.hub.disconnected_for(&room_id, player_id).is_some();
+        let (conn_id, missed) = state.hub.join_resumable(
+            &room_id,
+            player_id,
+            user_id,
+            locale,
+            tx,
+            stats.clone(),
+            after_seq,
+        );
+        state.hub.sync_seq(&state.redis, &room_id).await;
+        hub::connected(&state, &room_id, player_id, reconnected).await;
+
+        let mut pending = VecDeque::from([ServerMessage::new(
+            state.hub.current_seq(&room_id),
+            ServerEvent::Welcome {
+                protocol_version: version,
+                latest_protocol_version: super::PROTOCOL_VERSION,
+                player_id: Some(player_id),
+            },
+        )]);
+        match missed {
+            Some(missed) => pending.extend(missed),
+            None => {
+                if let Ok(Some(game)) = GameEngine::get_game(&state.redis, &room_id).await {
+                    pending.push_back(ServerMessage::new(
+                        state.hub.current_seq(&room_id),
+                        ServerEvent::GameState(Box::new(game.view_for(player_id))),
+                    ));
+                }
+                if let Ok(messages) =
+                    chat::history(&state.redis, &room_id, None, chat::BACKLOG_LEN).await
+                {
+                    pending.push_back(ServerMessage::new(
+                        state.hub.current_seq(&room_id),
+                        ServerEvent::ChatHistory { messages },
+                    ));
+                }
+            }
+        }
+
+        Self {
+            state,
+            room_id,
+            player_id,
+            conn_id,
+            rx,
+            stats,
+            pending,
+            closing: false,
+            span,
+        }
+    }
+
+    /// The next message for the player; `None` once the connection is over
+    pub async fn next(&mut self) -> Option<ServerMessage> {
+        let span = self.span.clone();
+        self.next_message().instrument(span).await
+    }
+
+    async fn next_message(&mut self) -> Option<ServerMessage> {
+        if let Some(message) = self.pending.pop_front() {
+            return Some(message);
+        }
+        if self.closing {
+            return None;
+        }
+
+        let message = tokio::select! {
+            message = self.rx.recv() => message?,
+            _ = self.stats.kick.notified() => {
+                tracing::info!(
+                    "Disconnecting slow client {} in room {}",
+                    self.player_id,
+                    self.room_id
+                );
+                return None;
+            }
+        };
+        self.closing = message.event.close_code().is_some();
+
+        // Events were dropped while the queue was full; once it has
+        // drained, one fresh state replaces them
+        if !self.closing && self.rx.is_empty() && self.stats.resync.swap(false, Ordering::Relaxed) {
+            if let Ok(Some(game)) = GameEngine::get_game(&self.state.redis, &self.room_id).await {
+                self.pending.push_back(ServerMessage::new(
+                    self.state.hub.current_seq(&self.room_id),
+                    ServerEvent::GameState(Box::new(game.view_for(self.player_id))),
+                ));
+            }
+        }
+        Some(message)
+    }
+}
+
+impl Drop for Feed {
+    fn drop(&mut self) {
+        tracing::debug!(
+            "Player {} disconnected from room {}",
+            self.player_id,
+            self.room_id
+        );
+        let disconnected = hub::disconnected(
+            self.state.clone(),
+            self.room_id.clone(),
+            self.player_id,
+            self.conn_id,
+        );
+        tokio::spawn(disconnected.instrument(self.span.clone()));
+    }
+}
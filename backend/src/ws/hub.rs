@@ -1,65 +1,641 @@
 //! WebSocket hub for managing connections and broadcasting
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use axum::extract::ws::{Message, WebSocket};
+use dashmap::DashMap;
 use futures::{SinkExt, StreamExt};
-use tokio::sync::mpsc;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::Notify;
+use tracing::Instrument;
 use uuid::Uuid;
 
+use super::bus::BusMessage;
+use super::codec::{self, Encoding};
 use crate::api::AppState;
-use crate::game::{ClientEvent, GameEngine, ServerEvent};
+use crate::error::AppError;
+use crate::game::{chat, ClientEvent, GameEngine, GamePhase, ServerEvent, ServerMessage};
+use crate::i18n::Locale;
+
+/// How often connections are pinged to measure latency
+const PING_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Silence after which a connection is assumed dead and reaped
+const PONG_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// Messages queued for a client before new ones are dropped
+pub(super) const SEND_QUEUE_CAPACITY: usize = 64;
+
+/// How long a client may keep its queue full before it is disconnected
+const SLOW_CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events kept per room for clients that resume after reconnecting
+const REPLAY_LEN: usize = 128;
+
+/// Live metrics for a connection, updated without locking the hub
+pub struct ConnectionStats {
+    /// Highest event sequence number the client has acknowledged
+    pub last_acked_seq: AtomicU64,
+    /// Round-trip time of the last ping, `u64::MAX` until the first pong
+    pub latency_ms: AtomicU64,
+    /// Unix millis of the last pong, or of connecting before the first one
+    last_pong_ms: AtomicU64,
+    /// Events dropped because the client's queue was full
+    pub dropped: AtomicU64,
+    /// Unix millis when the queue was first found full, zero while it drains
+    backlogged_since_ms: AtomicU64,
+    /// Send a fresh state once the queue drains, in place of dropped events
+    pub(super) resync: AtomicBool,
+    /// Woken when the client has been backlogged for too long
+    pub(super) kick: Notify,
+}
+
+impl Default for ConnectionStats {
+    fn default() -> Self {
+        Self {
+            last_acked_seq: AtomicU64::new(0),
+            latency_ms: AtomicU64::new(u64::MAX),
+            last_pong_ms: AtomicU64::new(chrono::Utc::now().timestamp_millis() as u64),
+            dropped: AtomicU64::new(0),
+            backlogged_since_ms: AtomicU64::new(0),
+            resync: AtomicBool::new(false),
+            kick: Notify::new(),
+        }
+    }
+}
+
+impl ConnectionStats {
+    /// Note a message that could not be queued
+    ///
+    /// Dropped state updates coalesce into the single state sent on resync;
+    /// a client that stays full past the timeout is disconnected.
+    fn overflowed(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        self.resync.store(true, Ordering::Relaxed);
+
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let since = match self.backlogged_since_ms.compare_exchange(
+            0,
+            now,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => now,
+            Err(since) => since,
+        };
+
+        if now.saturating_sub(since) >= SLOW_CLIENT_TIMEOUT.as_millis() as u64 {
+            self.kick.notify_one();
+        }
+    }
+}
 
 /// A connection to a single client
 pub struct Connection {
+    pub id: Uuid,
+    pub player_id: Uuid,
+    /// Registered user in the seat, who gets their notifications here
+    pub user_id: Option<Uuid>,
+    /// Language the player's log lines are rendered in
+    pub locale: Locale,
+    pub tx: mpsc::Sender<ServerMessage>,
+    pub connected_at: Instant,
+    pub stats: Arc<ConnectionStats>,
+}
+
+/// A read-only connection watching a room
+pub struct Spectator {
+    pub id: Uuid,
+    pub locale: Locale,
+    pub tx: mpsc::Sender<ServerMessage>,
+}
+
+/// Who an event is meant for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "player_id", rename_all = "snake_case")]
+pub enum Audience {
+    /// Players and spectators
+    Room,
+    Player(Uuid),
+    Spectators,
+}
+
+/// An event as it went out to a room, kept for resuming clients
+struct Delivered {
+    seq: u64,
+    audience: Audience,
+    event: Arc<ServerEvent>,
+}
+
+/// Connections of a single room
+#[derive(Default)]
+struct Room {
+    connections: Vec<Connection>,
+    spectators: Vec<Spectator>,
+    /// When each player's last connection dropped, cleared on reconnect or
+    /// once their grace period is over; the room is kept until then
+    disconnected: HashMap<Uuid, Instant>,
+    /// Sequence number of the last event sent in this room
+    seq: AtomicU64,
+    /// The latest events, once a resumable client has joined the room
+    recent: Option<Mutex<VecDeque<Delivered>>>,
+}
+
+impl Room {
+    fn next_seq(&self) -> u64 {
+        self.seq.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn is_empty(&self) -> bool {
+        self.connections.is_empty() && self.spectators.is_empty() && self.disconnected.is_empty()
+    }
+
+    /// Replace any connection of the same player
+    fn add(&mut self, conn: Connection) {
+        self.disconnected.remove(&conn.player_id);
+        self.connections.retain(|c| c.player_id != conn.player_id);
+        self.connections.push(conn);
+    }
+
+    /// Events after `after_seq` as the player sees them, or `None` when some
+    /// of them are no longer kept
+    fn missed_since(
+        &self,
+        after_seq: u64,
+        player_id: Uuid,
+        locale: Locale,
+    ) -> Option<Vec<ServerMessage>> {
+        let recent = self.recent.as_ref()?.lock().unwrap();
+        let seq = self.seq.load(Ordering::Relaxed);
+        if after_seq > seq
+            || (after_seq < seq && recent.front().is_none_or(|d| d.seq > after_seq + 1))
+        {
+            return None;
+        }
+        Some(
+            recent
+                .iter()
+                .filter(|d| d.seq > after_seq)
+                .filter(|d| match d.audience {
+                    Audience::Room => true,
+                    Audience::Player(id) => id == player_id,
+                    Audience::Spectators => false,
+                })
+                .filter_map(|d| {
+                    let view = d.event.view_for(player_id)?;
+                    Some(ServerMessage::new(d.seq, view.localized(locale)))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Point-in-time view of a connection for diagnostics
+#[derive(Debug, Clone)]
+pub struct ConnectionSnapshot {
     pub player_id: Uuid,
-    pub tx: mpsc::UnboundedSender<ServerEvent>,
+    pub connected_for: Duration,
+    pub latency_ms: Option<u64>,
+    pub last_acked_seq: u64,
+    /// Messages waiting to be written to the socket
+    pub queue_depth: usize,
+    pub dropped_events: u64,
 }
 
 /// Hub manages all active connections grouped by room
+///
+/// Rooms live in a sharded map, so joining or broadcasting in one room only
+/// locks the shard it falls in, and only while connections are added or the
+/// event is queued to them. Nothing is held across an await.
+///
+/// With a bus attached, events go through Redis and come back to every
+/// instance's hub, so a room can span instances. Without one they are
+/// delivered straight to local connections.
 pub struct Hub {
-    rooms: HashMap<String, Vec<Connection>>,
+    rooms: DashMap<String, Room>,
+    bus: Option<mpsc::UnboundedSender<BusMessage>>,
 }
 
 impl Hub {
     pub fn new() -> Self {
         Self {
-            rooms: HashMap::new(),
+            rooms: DashMap::new(),
+            bus: None,
+        }
+    }
+
+    /// Hub that publishes through the Redis bus
+    pub(super) fn with_bus(bus: mpsc::UnboundedSender<BusMessage>) -> Self {
+        Self {
+            bus: Some(bus),
+            ..Self::new()
+        }
+    }
+
+    /// Add a connection to a room, returning its connection id
+    pub fn join(
+        &self,
+        room_id: &str,
+        player_id: Uuid,
+        user_id: Option<Uuid>,
+        locale: Locale,
+        tx: mpsc::Sender<ServerMessage>,
+        stats: Arc<ConnectionStats>,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        self.rooms
+            .entry(room_id.to_string())
+            .or_default()
+            .add(Connection {
+                id,
+                player_id,
+                user_id,
+                locale,
+                tx,
+                connected_at: Instant::now(),
+                stats,
+            });
+        self.publish(BusMessage::Connected {
+            room_id: room_id.to_string(),
+            player_id,
+        });
+        id
+    }
+
+    /// Add a connection that may later pick up where it left off
+    ///
+    /// The room keeps its latest events from now on. With `after_seq`, the
+    /// events since then that the player missed come back along with the
+    /// connection id, or `None` when this instance no longer has them all
+    /// and a fresh state is needed instead. Events sent after joining go to
+    /// the queue, never to both.
+    #[allow(clippy::too_many_arguments)]
+    pub fn join_resumable(
+        &self,
+        room_id: &str,
+        player_id: Uuid,
+        user_id: Option<Uuid>,
+        locale: Locale,
+        tx: mpsc::Sender<ServerMessage>,
+        stats: Arc<ConnectionStats>,
+        after_seq: Option<u64>,
+    ) -> (Uuid, Option<Vec<ServerMessage>>) {
+        let id = Uuid::new_v4();
+        let missed = {
+            let mut room = self.rooms.entry(room_id.to_string()).or_default();
+            let missed =
+                after_seq.and_then(|after_seq| room.missed_since(after_seq, player_id, locale));
+            room.recent.get_or_insert_with(Default::default);
+            room.add(Connection {
+                id,
+                player_id,
+                user_id,
+                locale,
+                tx,
+                connected_at: Instant::now(),
+                stats,
+            });
+            missed
+        };
+        self.publish(BusMessage::Connected {
+            room_id: room_id.to_string(),
+            player_id,
+        });
+        (id, missed)
+    }
+
+    /// Forget a player's disconnect after they reconnected to another
+    /// instance
+    pub(super) fn mark_connected(&self, room_id: &str, player_id: Uuid) {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.disconnected.remove(&player_id);
         }
+        self.rooms.remove_if(room_id, |_, room| room.is_empty());
     }
 
-    /// Add a connection to a room
-    pub fn join(&mut self, room_id: &str, player_id: Uuid, tx: mpsc::UnboundedSender<ServerEvent>) {
-        let room = self.rooms.entry(room_id.to_string()).or_default();
-        // Remove any existing connection for this player
-        room.retain(|c| c.player_id != player_id);
-        room.push(Connection { player_id, tx });
+    /// Forget a player's disconnect once their grace period is over,
+    /// returning whether they were still away all that time
+    ///
+    /// A disconnect that happened later, after a reconnect, is kept for its
+    /// own grace period.
+    pub fn expire_disconnect(&self, room_id: &str, player_id: Uuid, grace: Duration) -> bool {
+        let still_away = {
+            let Some(mut room) = self.rooms.get_mut(room_id) else {
+                return false;
+            };
+            let still_away = room
+                .disconnected
+                .get(&player_id)
+                .is_some_and(|since| since.elapsed() >= grace);
+            if still_away {
+                room.disconnected.remove(&player_id);
+            }
+            still_away
+        };
+
+        self.rooms.remove_if(room_id, |_, room| room.is_empty());
+        still_away
     }
 
     /// Remove a connection from a room
-    pub fn leave(&mut self, room_id: &str, player_id: Uuid) {
-        if let Some(room) = self.rooms.get_mut(room_id) {
-            room.retain(|c| c.player_id != player_id);
-            if room.is_empty() {
-                self.rooms.remove(room_id);
+    ///
+    /// Only the given connection is removed, so a stale socket closing late
+    /// cannot drop the player's newer connection. Returns whether that was
+    /// the player's last connection; the room then stays until
+    /// `expire_disconnect`, so the disconnect can still be announced and
+    /// timed.
+    pub fn leave(&self, room_id: &str, player_id: Uuid, conn_id: Uuid) -> bool {
+        let went_offline = {
+            let Some(mut room) = self.rooms.get_mut(room_id) else {
+                return false;
+            };
+
+            let before = room.connections.len();
+            room.connections.retain(|c| c.id != conn_id);
+            let went_offline = room.connections.len() < before
+                && !room.connections.iter().any(|c| c.player_id == player_id);
+
+            if went_offline {
+                room.disconnected.insert(player_id, Instant::now());
             }
+            went_offline
+        };
+
+        self.rooms.remove_if(room_id, |_, room| room.is_empty());
+        went_offline
+    }
+
+    /// Add a spectator to a room, returning their id
+    pub fn join_spectator(
+        &self,
+        room_id: &str,
+        locale: Locale,
+        tx: mpsc::Sender<ServerMessage>,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        let mut room = self.rooms.entry(room_id.to_string()).or_default();
+        room.spectators.push(Spectator { id, locale, tx });
+        id
+    }
+
+    /// Remove a spectator from a room
+    pub fn leave_spectator(&self, room_id: &str, spectator_id: Uuid) {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.spectators.retain(|s| s.id != spectator_id);
         }
+        self.rooms.remove_if(room_id, |_, room| room.is_empty());
     }
 
-    /// Broadcast event to all players in a room
-    pub fn broadcast(&self, room_id: &str, event: ServerEvent) {
-        if let Some(room) = self.rooms.get(room_id) {
-            for conn in room {
-                let _ = conn.tx.send(event.clone());
+    /// Spectators watching a room through this instance
+    pub fn spectator_count(&self, room_id: &str) -> usize {
+        self.rooms
+            .get(room_id)
+            .map(|r| r.spectators.len())
+            .unwrap_or(0)
+    }
+
+    /// How long a player has been without a connection, if they dropped and
+    /// their reconnect grace has not run out yet
+    pub fn disconnected_for(&self, room_id: &str, player_id: Uuid) -> Option<Duration> {
+        self.rooms
+            .get(room_id)?
+            .disconnected
+            .get(&player_id)
+            .map(|since| since.elapsed())
+    }
+
+    /// Carry on numbering a room's events from the last number any instance
+    /// published for it, in case this instance has not seen them all
+    ///
+    /// Numbers live in Redis, so they survive the room being dropped here
+    /// and resuming clients are not thrown back to zero.
+    pub async fn sync_seq(&self, redis: &ConnectionManager, room_id: &str) {
+        if self.bus.is_none() {
+            return;
+        }
+        match super::room_seq(redis, room_id).await {
+            Ok(seq) => {
+                if let Some(room) = self.rooms.get(room_id) {
+                    room.seq.fetch_max(seq, Ordering::Relaxed);
+                }
             }
+            Err(e) => tracing::warn!("Failed to read sequence of room {}: {}", room_id, e),
         }
     }
 
+    /// Sequence number of the last event sent in a room
+    pub fn current_seq(&self, room_id: &str) -> u64 {
+        self.rooms
+            .get(room_id)
+            .map(|r| r.seq.load(Ordering::Relaxed))
+            .unwrap_or(0)
+    }
+
+    /// Snapshot of every live connection in a room
+    pub fn connections(&self, room_id: &str) -> Vec<ConnectionSnapshot> {
+        self.rooms
+            .get(room_id)
+            .map(|room| {
+                room.connections
+                    .iter()
+                    .map(|c| {
+                        let latency = c.stats.latency_ms.load(Ordering::Relaxed);
+                        ConnectionSnapshot {
+                            player_id: c.player_id,
+                            connected_for: c.connected_at.elapsed(),
+                            latency_ms: (latency != u64::MAX).then_some(latency),
+                            last_acked_seq: c.stats.last_acked_seq.load(Ordering::Relaxed),
+                            queue_depth: SEND_QUEUE_CAPACITY - c.tx.capacity(),
+                            dropped_events: c.stats.dropped.load(Ordering::Relaxed),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Broadcast event to all players in a room
+    ///
+    /// Each connection gets the event as its player may see it; players an
+    /// event is hidden from still use up its sequence number.
+    pub fn broadcast(&self, room_id: &str, event: ServerEvent) {
+        self.route(room_id, Audience::Room, event);
+    }
+
     /// Send event to a specific player
+    ///
+    /// Nobody else sees it, so it carries the room's current number without
+    /// using one up, or the others would find gaps in their sequence. Being
+    /// outside the sequence, it is not replayed to resuming clients either.
     pub fn send_to(&self, room_id: &str, player_id: Uuid, event: ServerEvent) {
-        if let Some(room) = self.rooms.get(room_id) {
-            if let Some(conn) = room.iter().find(|c| c.player_id == player_id) {
-                let _ = conn.tx.send(event);
+        self.route(room_id, Audience::Player(player_id), event);
+    }
+
+    /// Send event to the room's spectators only
+    pub fn broadcast_spectators(&self, room_id: &str, event: ServerEvent) {
+        self.route(room_id, Audience::Spectators, event);
+    }
+
+    /// Send event to every connection on this instance, whatever the room
+    ///
+    /// Other instances are not told, so this is for the instance itself
+    /// going away. Like notifications, it carries each room's current
+    /// number without using one up.
+    pub fn send_to_all_local(&self, event: ServerEvent) {
+        let event = Arc::new(event);
+        for room in self.rooms.iter() {
+            let message = ServerMessage::new(room.seq.load(Ordering::Relaxed), event.clone());
+            for conn in &room.connections {
+                let _ = conn.tx.try_send(message.clone());
+            }
+            for spectator in &room.spectators {
+                let _ = spectator.tx.try_send(message.clone());
+            }
+        }
+    }
+
+    /// Connections and spectators on this instance
+    pub fn local_connections(&self) -> usize {
+        self.rooms
+            .iter()
+            .map(|room| room.connections.len() + room.spectators.len())
+            .sum()
+    }
+
+    /// Send event to every connection of a registered user, on any instance
+    pub fn notify_user(&self, user_id: Uuid, event: ServerEvent) {
+        if self.bus.is_some() {
+            self.publish(BusMessage::User { user_id, event });
+        } else {
+            self.deliver_to_user(user_id, &event);
+        }
+    }
+
+    fn route(&self, room_id: &str, audience: Audience, event: ServerEvent) {
+        if self.bus.is_some() {
+            self.publish(BusMessage::Event {
+                room_id: room_id.to_string(),
+                audience,
+                event,
+            });
+        } else if let Some(seq) = self.rooms.get(room_id).map(|room| match audience {
+            Audience::Player(_) => room.seq.load(Ordering::Relaxed),
+            Audience::Room | Audience::Spectators => room.next_seq(),
+        }) {
+            self.deliver(room_id, audience, seq, &event);
+        }
+    }
+
+    fn publish(&self, message: BusMessage) {
+        if let Some(bus) = &self.bus {
+            let _ = bus.send(message);
+        }
+    }
+
+    /// Hand an event with an assigned sequence number to local connections
+    ///
+    /// Events for a single player have none of their own and go out with
+    /// the room's current number.
+    /// Everyone who sees the event as an outsider would shares one message,
+    /// encoded once however many of them there are; only players it reveals
+    /// more to get their own. Events with log lines are shared per language.
+    /// Spectators get the outsider's message; when their queue is full
+    /// events are simply dropped, as they only watch.
+    pub(super) fn deliver(&self, room_id: &str, audience: Audience, seq: u64, event: &ServerEvent) {
+        let Some(room) = self.rooms.get(room_id) else {
+            return;
+        };
+        let seq = match audience {
+            Audience::Player(_) => room.seq.load(Ordering::Relaxed),
+            Audience::Room | Audience::Spectators => {
+                room.seq.fetch_max(seq, Ordering::Relaxed);
+                seq
+            }
+        };
+        if let Some(recent) = room
+            .recent
+            .as_ref()
+            .filter(|_| !matches!(audience, Audience::Player(_)))
+        {
+            let mut recent = recent.lock().unwrap();
+            if recent.len() == REPLAY_LEN {
+                recent.pop_front();
+            }
+            recent.push_back(Delivered {
+                seq,
+                audience,
+                event: Arc::new(event.clone()),
+            });
+        }
+
+        // The nil id is never a player, so these are the public views, one
+        // per language if the event has text to translate
+        let localized = event.is_localized();
+        let mut public: [Option<Option<ServerMessage>>; Locale::ALL.len()] = Default::default();
+        let mut public_for = |locale: Locale| {
+            let locale = if localized { locale } else { Locale::default() };
+            public[locale.index()]
+                .get_or_insert_with(|| {
+                    event
+                        .view_for(Uuid::nil())
+                        .map(|view| ServerMessage::new(seq, view.localized(locale)))
+                })
+                .clone()
+        };
+
+        for conn in &room.connections {
+            match audience {
+                Audience::Room => {}
+                Audience::Player(id) if id == conn.player_id => {}
+                _ => continue,
+            }
+            let message = if event.differs_for(conn.player_id) {
+                event
+                    .view_for(conn.player_id)
+                    .map(|view| ServerMessage::new(seq, view.localized(conn.locale)))
+            } else {
+                public_for(conn.locale)
+            };
+            let Some(message) = message else {
+                continue;
+            };
+            match conn.tx.try_send(message) {
+                Ok(()) => conn.stats.backlogged_since_ms.store(0, Ordering::Relaxed),
+                Err(TrySendError::Full(_)) => conn.stats.overflowed(),
+                Err(TrySendError::Closed(_)) => {}
+            }
+        }
+
+        if matches!(audience, Audience::Room | Audience::Spectators) {
+            for spectator in &room.spectators {
+                if let Some(message) = public_for(spectator.locale) {
+                    let _ = spectator.tx.try_send(message);
+                }
+            }
+        }
+    }
+
+    /// Hand a user notification to the user's local connections
+    ///
+    /// Notifications are outside any room's sequence, so they carry the
+    /// room's current number without using one up.
+    pub(super) fn deliver_to_user(&self, user_id: Uuid, event: &ServerEvent) {
+        let event = Arc::new(event.clone());
+        for room in self.rooms.iter() {
+            let message = ServerMessage::new(room.seq.load(Ordering::Relaxed), event.clone());
+            for conn in room
+                .connections
+                .iter()
+                .filter(|c| c.user_id == Some(user_id))
+            {
+                if let Err(TrySendError::Full(_)) = conn.tx.try_send(message.clone()) {
+                    conn.stats.overflowed();
+                }
             }
         }
     }
@@ -72,67 +648,294 @@ impl Default for Hub {
 }
 
 /// Handle a single WebSocket connection
-pub async fn handle_socket(socket: WebSocket, state: AppState, room_id: String, player_id: Uuid) {
+///
+/// `requested` is an encoding asked for in the query string; otherwise the
+/// negotiated subprotocol decides, falling back to JSON. `version` is the
+/// protocol version agreed on at upgrade.
+pub async fn handle_socket(
+    socket: WebSocket,
+    state: AppState,
+    room_id: String,
+    player_id: Uuid,
+    requested: Option<Encoding>,
+    version: u32,
+) {
+    let encoding = requested
+        .or_else(|| socket.protocol().and_then(Encoding::from_protocol))
+        .unwrap_or_default();
     let (mut sender, mut receiver) = socket.split();
 
     // Create channel for sending messages to this client
-    let (tx, mut rx) = mpsc::unbounded_channel::<ServerEvent>();
+    let (tx, mut rx) = mpsc::channel::<ServerMessage>(SEND_QUEUE_CAPACITY);
+    let stats = Arc::new(ConnectionStats::default());
 
-    // Register connection in hub
-    {
-        let mut hub = state.hub.write().await;
-        hub.join(&room_id, player_id, tx);
+    // Register connection in hub and let the room know
+    let (user_id, locale) = seat(&state, &room_id, player_id).await;
+    let reconnected = state.hub.disconnected_for(&room_id, player_id).is_some();
+    let conn_id = state
+        .hub
+        .join(&room_id, player_id, user_id, locale, tx, stats.clone());
+    state.hub.sync_seq(&state.redis, &room_id).await;
+    connected(&state, &room_id, player_id, reconnected).await;
+
+    // Say hello before anything else; events queued since joining wait for
+    // the forwarding task below
+    let welcome = ServerMessage::new(
+        state.hub.current_seq(&room_id),
+        ServerEvent::Welcome {
+            protocol_version: version,
+            latest_protocol_version: super::PROTOCOL_VERSION,
+            player_id: Some(player_id),
+        },
+    );
+    if let Some(frame) = encoding.encode(&welcome) {
+        let _ = sender.send(frame).await;
     }
 
     // Send current game state on connect
     if let Ok(Some(game)) = GameEngine::get_game(&state.redis, &room_id).await {
-        let state_event = ServerEvent::GameState(game);
-        let msg = serde_json::to_string(&state_event).unwrap();
-        let _ = sender.send(Message::Text(msg.into())).await;
+        let state_event = ServerMessage::new(
+            state.hub.current_seq(&room_id),
+            ServerEvent::GameState(Box::new(game.view_for(player_id))),
+        );
+        if let Some(frame) = encoding.encode(&state_event) {
+            let _ = sender.send(frame).await;
+        }
     }
 
-    // Spawn task to forward messages from channel to WebSocket
-    let send_task = tokio::spawn(async move {
-        while let Some(event) = rx.recv().await {
-            let msg = serde_json::to_string(&event).unwrap();
-            if sender.send(Message::Text(msg.into())).await.is_err() {
+    // Catch up on the conversation so far
+    if let Ok(messages) = chat::history(&state.redis, &room_id, None, chat::BACKLOG_LEN).await {
+        let backlog = ServerMessage::new(
+            state.hub.current_seq(&room_id),
+            ServerEvent::ChatHistory { messages },
+        );
+        if let Some(frame) = encoding.encode(&backlog) {
+            let _ = sender.send(frame).await;
+        }
+    }
+
+    // Spawn task to forward messages from channel to WebSocket, pinging
+    // periodically so latency can be measured from the pongs
+    let send_state = state.clone();
+    let send_room_id = room_id.clone();
+    let send_stats = stats.clone();
+    let kick_stats = stats.clone();
+    let send = async move {
+        let mut ping = tokio::time::interval(PING_INTERVAL);
+        loop {
+            let mut close_code = None;
+            let msg = tokio::select! {
+                event = rx.recv() => match event {
+                    Some(event) => {
+                        close_code = event.event.close_code();
+                        match encoding.encode(&event) {
+                            Some(frame) => frame,
+                            None => continue,
+                        }
+                    }
+                    None => break,
+                },
+                _ = ping.tick() => {
+                    let sent_at = chrono::Utc::now().timestamp_millis();
+
+                    // Reap connections that stopped answering pings
+                    let last_pong = send_stats.last_pong_ms.load(Ordering::Relaxed);
+                    if (sent_at as u64).saturating_sub(last_pong) > PONG_TIMEOUT.as_millis() as u64 {
+                        tracing::debug!("Reaping silent connection of player {}", player_id);
+                        break;
+                    }
+
+                    Message::Ping(sent_at.to_be_bytes().to_vec())
+                }
+            };
+            if sender.send(msg).await.is_err() {
                 break;
             }
+            if let Some(code) = close_code {
+                let _ = sender.send(codec::close(code)).await;
+                break;
+            }
+
+            // Events were dropped while the queue was full; once it has
+            // drained, one fresh state replaces them
+            if rx.is_empty() && send_stats.resync.swap(false, Ordering::Relaxed) {
+                let Ok(Some(game)) = GameEngine::get_game(&send_state.redis, &send_room_id).await
+                else {
+                    continue;
+                };
+                let resync = ServerMessage::new(
+                    send_state.hub.current_seq(&send_room_id),
+                    ServerEvent::GameState(Box::new(game.view_for(player_id))),
+                );
+                if let Some(frame) = encoding.encode(&resync) {
+                    if sender.send(frame).await.is_err() {
+                        break;
+                    }
+                }
+            }
         }
-    });
+    };
+    let mut send_task = tokio::spawn(send.in_current_span());
 
     // Handle incoming messages
     let recv_state = state.clone();
     let recv_room_id = room_id.clone();
-    let recv_task = tokio::spawn(async move {
+    let recv = async move {
         while let Some(Ok(msg)) = receiver.next().await {
-            if let Message::Text(text) = msg {
-                if let Ok(event) = serde_json::from_str::<ClientEvent>(&text) {
-                    // Process the event through game engine
-                    let _ = GameEngine::handle_event(
-                        &recv_state.redis,
-                        &recv_state.hub,
+            if let Message::Pong(payload) = &msg {
+                if let Ok(bytes) = <[u8; 8]>::try_from(payload.as_slice()) {
+                    let sent_at = i64::from_be_bytes(bytes);
+                    let rtt = chrono::Utc::now().timestamp_millis() - sent_at;
+                    stats.latency_ms.store(rtt.max(0) as u64, Ordering::Relaxed);
+                    stats.last_pong_ms.store(
+                        chrono::Utc::now().timestamp_millis() as u64,
+                        Ordering::Relaxed,
+                    );
+                }
+                continue;
+            }
+            if let Some(message) = codec::decode(&msg) {
+                // Acks only feed connection diagnostics
+                if let ClientEvent::Ack { seq } = message.event {
+                    stats.last_acked_seq.fetch_max(seq, Ordering::Relaxed);
+                    continue;
+                }
+
+                // Process the event through game engine, telling the
+                // sender if it was rejected
+                let event_type = message.event.type_name();
+                let request_id = message.request_id();
+                if let Err(e) = GameEngine::handle_event(
+                    &recv_state.redis,
+                    &recv_state.hub,
+                    &recv_room_id,
+                    player_id,
+                    message,
+                )
+                .await
+                {
+                    match &e {
+                        AppError::Database(_) | AppError::Redis(_) | AppError::Internal(_) => {
+                            tracing::error!(
+                                "Failed to handle {:?} from player {}: {:?}",
+                                event_type,
+                                player_id,
+                                e
+                            )
+                        }
+                        _ => tracing::debug!(
+                            "Rejected {:?} from player {}: {}",
+                            event_type,
+                            player_id,
+                            e
+                        ),
+                    }
+                    recv_state.hub.send_to(
                         &recv_room_id,
                         player_id,
-                        event,
-                    )
-                    .await;
+                        ServerEvent::Error {
+                            code: e.code().into(),
+                            message: e.localized_message(locale),
+                            event: event_type,
+                            request_id,
+                            retryable: e.is_retryable(),
+                        },
+                    );
                 }
             }
         }
-    });
+    };
+    let mut recv_task = tokio::spawn(recv.in_current_span());
 
-    // Wait for either task to complete
+    // Wait for either task to complete, or cut off a client that has been
+    // too slow to keep up for too long
     tokio::select! {
-        _ = send_task => {},
-        _ = recv_task => {},
+        _ = &mut send_task => {},
+        _ = &mut recv_task => {},
+        _ = kick_stats.kick.notified() => {
+            tracing::info!("Disconnecting slow client {} in room {}", player_id, room_id);
+            send_task.abort();
+            recv_task.abort();
+        }
     }
 
-    // Remove connection from hub
-    {
-        let mut hub = state.hub.write().await;
-        hub.leave(&room_id, player_id);
+    tracing::debug!("Player {} disconnected from room {}", player_id, room_id);
+    disconnected(state, room_id, player_id, conn_id).await;
+}
+
+/// Account and language of a seat: notifications for the account come
+/// through the seat's connection too, and its log lines are in the seat's
+/// language
+pub(super) async fn seat(
+    state: &AppState,
+    room_id: &str,
+    player_id: Uuid,
+) -> (Option<Uuid>, Locale) {
+    match GameEngine::get_game(&state.redis, room_id).await {
+        Ok(Some(game)) => game
+            .get_player(player_id)
+            .map(|p| (p.user_id, p.locale))
+            .unwrap_or_default(),
+        _ => Default::default(),
     }
+}
 
-    tracing::debug!("Player {} disconnected from room {}", player_id, room_id);
+/// Let the room know a player connected, and give them their seat back if
+/// a bot was playing for them while away
+pub(super) async fn connected(state: &AppState, room_id: &str, player_id: Uuid, reconnected: bool) {
+    state.hub.broadcast(
+        room_id,
+        ServerEvent::PlayerConnected {
+            player_id,
+            reconnected,
+        },
+    );
+    if let Err(e) = GameEngine::return_player(&state.redis, &state.hub, room_id, player_id).await {
+        tracing::warn!("Failed to restore player {}: {}", player_id, e);
+    }
+}
+
+/// Remove a closed connection, and if it was the player's last, let the
+/// room know and have a bot play for them if they don't come back in time
+pub(super) async fn disconnected(state: AppState, room_id: String, player_id: Uuid, conn_id: Uuid) {
+    if !state.hub.leave(&room_id, player_id, conn_id) {
+        return;
+    }
+
+    let game = GameEngine::get_game(&state.redis, &room_id)
+        .await
+        .ok()
+        .flatten();
+    let grace = match &game {
+        Some(game) => game.config.timers().reconnect_grace(),
+        None => state.tunables.current().timers.reconnect_grace(),
+    };
+
+    // A bot only steps in for humans in a running game
+    let takes_over = matches!(
+        &game,
+        Some(game) if game.phase == GamePhase::Playing
+            && game.get_player(player_id).is_some_and(|p| !p.controlled_by_bot() && !p.is_bankrupt)
+    );
+    state.hub.broadcast(
+        &room_id,
+        ServerEvent::PlayerDisconnected {
+            player_id,
+            reconnect_grace_secs: takes_over.then_some(grace.as_secs()),
+        },
+    );
+
+    // Let a bot play for the player if they don't come back in time
+    let take_over = async move {
+        tokio::time::sleep(grace).await;
+
+        if state.hub.expire_disconnect(&room_id, player_id, grace) {
+            if let Err(e) =
+                GameEngine::take_over_player(&state.redis, &state.hub, &room_id, player_id).await
+            {
+                tracing::warn!("Bot takeover failed for player {}: {}", player_id, e);
+            }
+        }
+    };
+    tokio::spawn(take_over.in_current_span());
 }
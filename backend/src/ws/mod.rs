@@ -1,22 +1,202 @@
 //! WebSocket module for real-time game communication
 
+mod bus;
+mod codec;
+mod feed;
 mod hub;
+mod spectator;
+mod sse;
 
-pub use hub::Hub;
+pub use bus::{room_seq, start_hub};
+pub use codec::{EncodedFrames, Encoding};
+pub(crate) use feed::Feed;
+pub use hub::{ConnectionSnapshot, Hub};
+pub use sse::events_handler;
 
 use axum::{
-    extract::{Path, State, WebSocketUpgrade},
+    extract::{Path, Query, State, WebSocketUpgrade},
+    http::{header::AUTHORIZATION, HeaderMap},
     response::Response,
 };
+use serde::Deserialize;
+use tracing::Instrument;
 use uuid::Uuid;
 
-use crate::api::AppState;
+use crate::api::{self, auth, AppState, ClientIp};
+use crate::error::{AppError, AppResult};
+use crate::game::integrity::{self, MarkKind};
+use crate::game::GameEngine;
+use crate::i18n::Locale;
+use crate::shutdown;
+
+/// Version of the event schema spoken over the WebSocket
+///
+/// Bump it when events change in a way older clients would misread, and
+/// keep serving older versions down to `MIN_PROTOCOL_VERSION` for as long
+/// as they are supported.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest protocol version still served
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Pick the protocol version for a connection
+///
+/// Clients that send no version are assumed to speak the first one. A client
+/// newer than the server gets the newest version the server knows and learns
+/// so from `WELCOME`; one older than the oldest supported is turned away.
+pub(crate) fn negotiate_version(requested: Option<u32>) -> AppResult<u32> {
+    match requested.unwrap_or(1) {
+        v if v < MIN_PROTOCOL_VERSION => Err(AppError::BadRequest(format!(
+            "Protocol version {} is no longer supported; use {} to {}",
+            v, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+        ))),
+        v => Ok(v.min(PROTOCOL_VERSION)),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConnectQuery {
+    /// Player token from creating or joining the room
+    pub token: Option<String>,
+    /// `json` or `msgpack`; overrides the subprotocol
+    pub encoding: Option<Encoding>,
+    /// Protocol version the client speaks
+    pub version: Option<u32>,
+    /// Device fingerprint computed by the client; ranked rooms check it for
+    /// seats sharing a device
+    pub fingerprint: Option<String>,
+}
+
+/// Longest fingerprint taken from a client
+const MAX_FINGERPRINT_LEN: usize = 256;
 
 /// WebSocket handler - upgrades HTTP to WebSocket connection
+///
+/// The player token may be passed as `?token=` (browsers cannot set headers
+/// on a WebSocket) or as `Authorization: Bearer`. Frames are JSON unless
+/// MessagePack is requested with `?encoding=msgpack` or the `msgpack`
+/// subprotocol. `?version=` picks the protocol version, see
+/// `PROTOCOL_VERSION`.
 pub async fn handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
     Path((room_id, player_id)): Path<(String, Uuid)>,
-) -> Response {
-    ws.on_upgrade(move |socket| hub::handle_socket(socket, state, room_id, player_id))
+    Query(query): Query<ConnectQuery>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    shutdown::ensure_accepting()?;
+    api::check_origin(&state.tunables.current(), &headers)?;
+    let token = token(query.token.as_deref(), &headers)?;
+
+    // Banned users lost their sessions, and with them the tokens of their
+    // seats, so only the address is left to check
+    auth::verify_player_token(&state, token, &room_id, player_id).await?;
+    api::ensure_not_banned(&state, None, ip.as_deref()).await?;
+    let version = negotiate_version(query.version)?;
+    record_client(
+        &state,
+        &room_id,
+        player_id,
+        ip.as_deref(),
+        query.fingerprint.as_deref(),
+    )
+    .await;
+
+    let encoding = query.encoding;
+    let span = tracing::info_span!("ws", room_id = %room_id, player_id = %player_id);
+    Ok(ws.protocols(Encoding::PROTOCOLS).on_upgrade(move |socket| {
+        hub::handle_socket(socket, state, room_id, player_id, encoding, version).instrument(span)
+    }))
+}
+
+/// Player token from the query string, or else from `Authorization: Bearer`
+fn token<'a>(query: Option<&'a str>, headers: &'a HeaderMap) -> AppResult<&'a str> {
+    let bearer = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    query.or(bearer).ok_or(AppError::Unauthorized)
+}
+
+/// Note the address and device a player connects from, for the shared
+/// device checks of ranked rooms
+pub(crate) async fn record_client(
+    state: &AppState,
+    room_id: &str,
+    player_id: Uuid,
+    ip: Option<&str>,
+    fingerprint: Option<&str>,
+) {
+    let secret = &state.config.jwt_secret;
+    let fingerprint = fingerprint.filter(|f| !f.is_empty() && f.len() <= MAX_FINGERPRINT_LEN);
+    let marks: Vec<_> = [(MarkKind::Ip, ip), (MarkKind::Fingerprint, fingerprint)]
+        .into_iter()
+        .filter_map(|(kind, value)| Some((kind, integrity::hash(secret, kind, value?))))
+        .collect();
+    // A failed check must not keep the player out
+    if let Err(e) = GameEngine::record_client(&state.redis, room_id, player_id, &marks).await {
+        tracing::warn!("Failed to record client of room {}: {}", room_id, e);
+    }
+}
+
+/// Longest display name a spectator may pick
+const MAX_SPECTATOR_NAME_LEN: usize = 24;
+
+#[derive(Debug, Deserialize)]
+pub struct SpectateQuery {
+    /// Shown next to spectator chat messages
+    pub name: Option<String>,
+    /// Language of the game log, e.g. `es`; `Accept-Language` by default
+    pub locale: Option<String>,
+    /// `json` or `msgpack`; overrides the subprotocol
+    pub encoding: Option<Encoding>,
+    /// Protocol version the client speaks
+    pub version: Option<u32>,
+}
+
+/// Spectator WebSocket handler
+///
+/// Needs no token: spectators only ever see the public view of the room and
+/// cannot act in the game.
+pub async fn spectate_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Query(query): Query<SpectateQuery>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    shutdown::ensure_accepting()?;
+    api::check_origin(&state.tunables.current(), &headers)?;
+    api::ensure_not_banned(&state, None, ip.as_deref()).await?;
+    let name = match query.name.as_deref().map(str::trim) {
+        None | Some("") => "Spectator".to_string(),
+        Some(name)
+            if name.chars().count() > MAX_SPECTATOR_NAME_LEN
+                || name.chars().any(char::is_control) =>
+        {
+            return Err(AppError::BadRequest(format!(
+                "Spectator name must be at most {} printable characters",
+                MAX_SPECTATOR_NAME_LEN
+            )));
+        }
+        Some(name) => name.to_string(),
+    };
+    let version = negotiate_version(query.version)?;
+    let locale = Locale::negotiate(query.locale.as_deref(), &headers);
+
+    if GameEngine::get_game(&state.redis, &room_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFound("Room not found".into()));
+    }
+
+    let encoding = query.encoding;
+    let span = tracing::info_span!("spectate", room_id = %room_id, spectator = %name);
+    Ok(ws.protocols(Encoding::PROTOCOLS).on_upgrade(move |socket| {
+        spectator::handle_spectator(socket, state, room_id, name, locale, encoding, version)
+            .instrument(span)
+    }))
 }
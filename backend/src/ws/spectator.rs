@@ -0,0 +1,166 @@
+//! Read-only WebSocket connections for people watching a room
+//!
+//! Spectators get the public view of every room event and can chat among
+//! themselves; anything that would change the game is refused.
+
+use axum::extract::ws::WebSocket;
+use futures::{SinkExt, StreamExt};
+use tokio::sync::mpsc;
+use tracing::Instrument;
+use uuid::Uuid;
+
+use super::codec::{self, Encoding};
+use super::hub::SEND_QUEUE_CAPACITY;
+use crate::api::AppState;
+use crate::error::{AppError, AppResult};
+use crate::game::{chat, moderation, ClientEvent, GameEngine, ServerEvent, ServerMessage};
+use crate::i18n::Locale;
+
+/// Longest spectator chat message accepted
+const MAX_CHAT_LEN: usize = 200;
+
+/// Handle a single spectator connection
+pub async fn handle_spectator(
+    socket: WebSocket,
+    state: AppState,
+    room_id: String,
+    name: String,
+    locale: Locale,
+    requested: Option<Encoding>,
+    version: u32,
+) {
+    let encoding = requested
+        .or_else(|| socket.protocol().and_then(Encoding::from_protocol))
+        .unwrap_or_default();
+    let (mut sender, mut receiver) = socket.split();
+
+    let (tx, mut rx) = mpsc::channel::<ServerMessage>(SEND_QUEUE_CAPACITY);
+    let spectator_id = state.hub.join_spectator(&room_id, locale, tx.clone());
+    state.hub.sync_seq(&state.redis, &room_id).await;
+
+    // Say hello before anything else; events queued since joining wait for
+    // the forwarding task below
+    let welcome = ServerMessage::new(
+        state.hub.current_seq(&room_id),
+        ServerEvent::Welcome {
+            protocol_version: version,
+            latest_protocol_version: super::PROTOCOL_VERSION,
+            player_id: None,
+        },
+    );
+    if let Some(frame) = encoding.encode(&welcome) {
+        let _ = sender.send(frame).await;
+    }
+
+    // Send the public state on connect
+    if let Ok(Some(game)) = GameEngine::get_game(&state.redis, &room_id).await {
+        let state_event = ServerMessage::new(
+            state.hub.current_seq(&room_id),
+            ServerEvent::GameState(Box::new(game.view_for(Uuid::nil()))),
+        );
+        if let Some(frame) = encoding.encode(&state_event) {
+            let _ = sender.send(frame).await;
+        }
+    }
+
+    // Catch up on the conversation so far
+    if let Ok(messages) = chat::history(&state.redis, &room_id, None, chat::BACKLOG_LEN).await {
+        let backlog = ServerMessage::new(
+            state.hub.current_seq(&room_id),
+            ServerEvent::ChatHistory { messages },
+        );
+        if let Some(frame) = encoding.encode(&backlog) {
+            let _ = sender.send(frame).await;
+        }
+    }
+
+    let send_task = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let Some(frame) = encoding.encode(&message) else {
+                continue;
+            };
+            if sender.send(frame).await.is_err() {
+                break;
+            }
+            if let Some(code) = message.event.close_code() {
+                let _ = sender.send(codec::close(code)).await;
+                break;
+            }
+        }
+    });
+
+    let recv_state = state.clone();
+    let recv_room_id = room_id.clone();
+    let recv = async move {
+        while let Some(Ok(msg)) = receiver.next().await {
+            let Some(message) = codec::decode(&msg) else {
+                continue;
+            };
+
+            let event_name = message.event.type_name();
+            let request_id = message.request_id();
+            let result = match message.event {
+                ClientEvent::Ack { .. } => Ok(()),
+                ClientEvent::Chat { message } => {
+                    chat(&recv_state, &recv_room_id, spectator_id, &name, &message).await
+                }
+                _ => Err(AppError::Forbidden(
+                    "Spectators cannot take part in the game".into(),
+                )),
+            };
+
+            if let Err(e) = result {
+                let error = ServerEvent::Error {
+                    code: e.code().into(),
+                    message: e.localized_message(locale),
+                    event: event_name,
+                    request_id,
+                    retryable: e.is_retryable(),
+                };
+                let seq = recv_state.hub.current_seq(&recv_room_id);
+                let _ = tx.try_send(ServerMessage::new(seq, error));
+            }
+        }
+    };
+    let recv_task = tokio::spawn(recv.in_current_span());
+
+    tokio::select! {
+        _ = send_task => {},
+        _ = recv_task => {},
+    }
+
+    state.hub.leave_spectator(&room_id, spectator_id);
+
+    tracing::debug!("Spectator left room {}", room_id);
+}
+
+/// Pass a spectator's message on to the other spectators
+async fn chat(
+    state: &AppState,
+    room_id: &str,
+    spectator_id: Uuid,
+    name: &str,
+    message: &str,
+) -> AppResult<()> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Ok(());
+    }
+    if message.chars().count() > MAX_CHAT_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Messages cannot be longer than {} characters",
+            MAX_CHAT_LEN
+        )));
+    }
+    moderation::check_cooldown(&state.redis, room_id, spectator_id).await?;
+
+    state.hub.broadcast_spectators(
+        room_id,
+        ServerEvent::SpectatorChat {
+            from_name: name.to_string(),
+            message: moderation::filter(message),
+        },
+    );
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Server-Sent Events for clients that cannot open a WebSocket
+//!
+//! Some networks block WebSocket upgrades but let a long-lived HTTP response
+//! through. The stream carries the same `ServerMessage`s as the game
+//! WebSocket, each with its sequence number as the event id, while actions
+//! go through the REST endpoints. A client that reconnects with
+//! `Last-Event-ID` gets the events it missed when this instance still has
+//! them, and a fresh state otherwise.
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::Stream;
+use serde::Deserialize;
+
+use super::{codec, Feed};
+use crate::api::{self, auth, AppState, ClientIp};
+use crate::error::{AppError, AppResult};
+use crate::game::ServerEvent;
+use crate::shutdown;
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    /// Player token from creating or joining the room; `EventSource` cannot
+    /// set headers
+    pub token: Option<String>,
+    /// Protocol version the client speaks
+    pub version: Option<u32>,
+    /// Device fingerprint computed by the client; ranked rooms check it for
+    /// seats sharing a device
+    pub fingerprint: Option<String>,
+}
+
+/// Event stream of a room for one player
+///
+/// Takes the token like the game WebSocket, as `?token=` or
+/// `Authorization: Bearer`; the token names the player.
+pub async fn events_handler(
+    State(state): State<AppState>,
+    Path(room_id): Path<String>,
+    Query(query): Query<EventsQuery>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    shutdown::ensure_accepting()?;
+    api::check_origin(&state.tunables.current(), &headers)?;
+    let token = super::token(query.token.as_deref(), &headers)?;
+    let player_id = auth::player_in_token(&state.config, token).ok_or(AppError::Unauthorized)?;
+    auth::verify_player_token(&state, token, &room_id, player_id).await?;
+    api::ensure_not_banned(&state, None, ip.as_deref()).await?;
+    let version = super::negotiate_version(query.version)?;
+    super::record_client(
+        &state,
+        &room_id,
+        player_id,
+        ip.as_deref(),
+        query.fingerprint.as_deref(),
+    )
+    .await;
+
+    let after_seq = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok());
+    let span = tracing::info_span!("sse", room_id = %room_id, player_id = %player_id);
+    let feed = Feed::open(state, room_id, player_id, version, after_seq, span).await;
+
+    let stream = futures::stream::unfold(feed, |mut feed| async move {
+        loop {
+            let message = feed.next().await?;
+            let Some(json) = codec::json(&message) else {
+                tracing::error!("Failed to encode server message {}", message.seq);
+                continue;
+            };
+            // The welcome comes before any event a resuming client missed,
+            // so it must not move the client's position
+            let event = Event::default().data(json);
+            let event = match *message.event {
+                ServerEvent::Welcome { .. } => event,
+                _ => event.id(message.seq.to_string()),
+            };
+            return Some((Ok(event), feed));
+        }
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -19,7 +19,7 @@ export default function Home() {
     }
     setLoading(true);
     try {
-      const { room_id, player_id } = await api.createRoom(playerName);
+      const { room_id, player_id, token } = await api.createRoom(playerName);
       // Save info to sessionStorage or pass via query params
       // Since this is MVP, we use query params or localStorage. 
       // Ideally we'd use a store (Zustand/Context), but let's stick to simple URL params for now or sessionStorage.
@@ -27,6 +27,7 @@ export default function Home() {
       // Better: sessionStorage.
 
       sessionStorage.setItem(`player_id_${room_id}`, player_id);
+      sessionStorage.setItem(`token_${room_id}`, token);
       router.push(`/room/${room_id}`);
     } catch (err: any) {
       setError(err.message || 'Failed to create room');
@@ -46,8 +47,9 @@ export default function Home() {
     }
     setLoading(true);
     try {
-      const { player_id } = await api.joinRoom(roomId, playerName);
+      const { player_id, token } = await api.joinRoom(roomId, playerName);
       sessionStorage.setItem(`player_id_${roomId}`, player_id);
+      sessionStorage.setItem(`token_${roomId}`, token);
       router.push(`/room/${roomId}`);
     } catch (err: any) {
       setError(err.message || 'Failed to join room');
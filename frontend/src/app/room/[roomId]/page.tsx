@@ -25,6 +25,7 @@ export default function RoomPage() {
     const [loading, setLoading] = useState(true);
     const [error, setError] = useState<string | null>(null);
     const [playerId, setPlayerId] = useState<string | null>(null);
+    const [token, setToken] = useState<string>('');
 
     const { connect: connectWs, disconnect: disconnectWs, status: wsStatus } = useWsStore();
     const { gameState } = useGameStore();
@@ -32,14 +33,16 @@ export default function RoomPage() {
     useEffect(() => {
         // Check authentication
         const storedId = sessionStorage.getItem(`player_id_${roomId}`);
-        if (!storedId) {
+        const storedToken = sessionStorage.getItem(`token_${roomId}`);
+        if (!storedId || !storedToken) {
             router.push('/');
             return;
         }
         setPlayerId(storedId);
+        setToken(storedToken);
 
         // Connect WebSocket
-        connectWs(roomId, storedId);
+        connectWs(roomId, storedId, storedToken);
 
         // Initial fetch REST (for Lobby state before GameStart)
         fetchRoom();
@@ -64,7 +67,7 @@ export default function RoomPage() {
 
     const handleStartGame = async () => {
         try {
-            await api.startGame(roomId);
+            await api.startGame(roomId, token);
         } catch (err: any) {
             alert(err.message);
         }
@@ -72,7 +75,7 @@ export default function RoomPage() {
 
     const handleAddBot = async () => {
         try {
-            await api.addBot(roomId);
+            await api.addBot(roomId, token);
             fetchRoom(); // refresh list immediately
         } catch (err: any) {
             alert(err.message);
@@ -8,7 +8,7 @@ interface WsStore {
     socket: WebSocket | null;
     status: 'disconnected' | 'connecting' | 'connected' | 'error';
 
-    connect: (roomId: string, playerId: string) => void;
+    connect: (roomId: string, playerId: string, token: string) => void;
     disconnect: () => void;
     send: (event: ClientEvent) => void;
 }
@@ -17,14 +17,16 @@ export const useWsStore = create<WsStore>((set, get) => ({
     socket: null,
     status: 'disconnected',
 
-    connect: (roomId, playerId) => {
+    connect: (roomId, playerId, token) => {
         const { socket } = get();
         if (socket) {
             socket.close();
         }
 
         set({ status: 'connecting' });
-        const ws = new WebSocket(`${WS_URL}/ws/${roomId}/${playerId}`);
+        const ws = new WebSocket(
+            `${WS_URL}/ws/${roomId}/${playerId}?token=${encodeURIComponent(token)}`
+        );
 
         ws.onopen = () => {
             set({ status: 'connected' });
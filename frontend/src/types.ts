@@ -45,6 +45,7 @@ export interface CreateRoomRequest {
 export interface CreateRoomResponse {
     room_id: string;
     player_id: string;
+    token: string;
 }
 
 export interface JoinRoomRequest {
@@ -53,6 +54,11 @@ export interface JoinRoomRequest {
 
 export interface JoinRoomResponse {
     player_id: string;
+    token: string;
+}
+
+export interface AddBotResponse {
+    player_id: string;
 }
 
 // -- WebSocket Events --
@@ -66,6 +72,8 @@ export type ClientEvent =
     | { type: "PASS_BID" }
     | { type: "PAY_JAIL" }
     | { type: "USE_CARD" }
+    | { type: "PAY_DEBT" }
+    | { type: "DECLARE_BANKRUPTCY" }
     | { type: "BUILD"; tile_idx: number }
     | { type: "MORTGAGE"; tile_idx: number }
     | { type: "UNMORTGAGE"; tile_idx: number }
@@ -1,4 +1,5 @@
 import {
+    AddBotResponse,
     CreateRoomRequest,
     CreateRoomResponse,
     JoinRoomRequest,
@@ -35,17 +36,27 @@ class ApiClient {
         return res.json();
     }
 
-    async startGame(roomId: string): Promise<void> {
+    async startGame(roomId: string, token: string): Promise<void> {
         const res = await fetch(`${API_BASE_URL}/api/rooms/${roomId}/start`, {
             method: 'POST',
+            headers: { Authorization: `Bearer ${token}` },
         });
         if (!res.ok) throw new Error(await res.text());
     }
 
-    async addBot(roomId: string): Promise<JoinRoomResponse> {
+    async renewToken(roomId: string, token: string): Promise<JoinRoomResponse> {
+        const res = await fetch(`${API_BASE_URL}/api/rooms/${roomId}/token`, {
+            method: 'POST',
+            headers: { Authorization: `Bearer ${token}` },
+        });
+        if (!res.ok) throw new Error(await res.text());
+        return res.json();
+    }
+
+    async addBot(roomId: string, token: string): Promise<AddBotResponse> {
         const res = await fetch(`${API_BASE_URL}/api/rooms/${roomId}/bot`, {
             method: 'POST',
-            headers: { 'Content-Type': 'application/json' },
+            headers: { 'Content-Type': 'application/json', Authorization: `Bearer ${token}` },
             body: JSON.stringify({}),
         });
         if (!res.ok) throw new Error(await res.text());